@@ -6,9 +6,10 @@ use base58::ToBase58;
 use cosmwasm_std::entry_point;
 use cosmwasm_std::{
     to_json_binary, to_json_string, wasm_execute, Addr, Attribute, Binary, Coin, CosmosMsg, Deps,
-    DepsMut, Env, Event, MessageInfo, QueryRequest, Reply, Response, StdError, StdResult, SubMsg,
-    SubMsgResult, Uint128, Uint256, WasmMsg,
+    DepsMut, Env, Event, MessageInfo, Order, QueryRequest, Reply, Response, StdError, StdResult,
+    Storage, SubMsg, SubMsgResult, Uint128, Uint256, WasmMsg,
 };
+use cw_storage_plus::Bound;
 use ibc_union_msg::{
     module::IbcUnionMsg,
     msg::{MsgSendPacket, MsgWriteAcknowledgement},
@@ -25,14 +26,18 @@ use unionlabs::{
 
 use crate::{
     com::{
-        Ack, Batch, BatchAck, FungibleAssetOrder, FungibleAssetOrderAck, Instruction, Multiplex,
-        ZkgmPacket, ACK_ERR_ONLY_MAKER, FILL_TYPE_MARKETMAKER, FILL_TYPE_PROTOCOL, OP_BATCH,
-        OP_FUNGIBLE_ASSET_ORDER, OP_MULTIPLEX, TAG_ACK_FAILURE, TAG_ACK_SUCCESS, ZKGM_VERSION_0,
+        Ack, Batch, BatchAck, Forward, FungibleAssetOrder, FungibleAssetOrderAck, Instruction,
+        Multiplex, ZkgmPacket, ACK_ERR_ONLY_MAKER, FILL_TYPE_MARKETMAKER, FILL_TYPE_PROTOCOL,
+        OP_BATCH, OP_FORWARD, OP_FUNGIBLE_ASSET_ORDER, OP_MULTIPLEX, TAG_ACK_FAILURE,
+        TAG_ACK_SUCCESS, ZKGM_VERSION_0,
+    },
+    msg::{
+        ChannelBalance, ChannelBalancesResponse, EurekaMsg, ExecuteMsg, InitMsg,
+        PredictWrappedTokenResponse, QueryMsg, TokenOriginResponse,
     },
-    msg::{EurekaMsg, ExecuteMsg, InitMsg},
     state::{
         CHANNEL_BALANCE, CONFIG, EXECUTING_PACKET, EXECUTION_ACK, HASH_TO_FOREIGN_TOKEN,
-        TOKEN_MINTER, TOKEN_ORIGIN,
+        IN_FLIGHT_PACKET, TOKEN_MINTER, TOKEN_ORIGIN,
     },
     ContractError,
 };
@@ -45,6 +50,9 @@ pub const CREATE_DENOM_REPLY_ID: u64 = 0xdead;
 
 pub const ZKGM_TOKEN_MINTER_LABEL: &str = "zkgm-token-minter";
 
+pub const DEFAULT_QUERY_LIMIT: u32 = 30;
+pub const MAX_QUERY_LIMIT: u32 = 100;
+
 /// Instantiate `ucs03-zkgm`.
 ///
 /// This will instantiate the minter contract with the provided [`TokenMinterInitMsg`][crate::msg::TokenMinterInitMsg]. The admin of the minter contract is set to the instantiator of `ucs03-zkgm`, under the assumption that the caller will also set themselves as admin, as it is not possible for a contract to check the admin of itself during instantiation.
@@ -189,6 +197,21 @@ pub fn execute(
             timeout_timestamp,
             salt,
         ),
+        ExecuteMsg::Send {
+            channel_id,
+            timeout_height,
+            timeout_timestamp,
+            salt,
+            instruction,
+        } => send(
+            deps,
+            info,
+            channel_id,
+            timeout_height,
+            timeout_timestamp,
+            salt,
+            instruction,
+        ),
     }
 }
 
@@ -215,6 +238,20 @@ fn timeout_packet(
     packet: Packet,
     relayer: Addr,
 ) -> Result<Response, ContractError> {
+    if let Some(parent) = take_in_flight_packet(deps.storage, &packet)? {
+        // The forwarded packet timed out, the parent packet is failed such that the timeout is
+        // propagated back to the origin.
+        return Ok(Response::new().add_message(write_acknowledgement(
+            &CONFIG.load(deps.storage)?.ibc_host,
+            parent,
+            Ack {
+                tag: TAG_ACK_FAILURE,
+                inner_ack: Default::default(),
+            }
+            .abi_encode_params()
+            .into(),
+        )?));
+    }
     let zkgm_packet = ZkgmPacket::abi_decode_params(&packet.data, true)?;
     timeout_internal(
         deps,
@@ -281,6 +318,19 @@ fn timeout_internal(
                 Ok(Response::new())
             }
         }
+        OP_FORWARD => {
+            let forward = Forward::abi_decode_params(&instruction.operand, true)?;
+            timeout_internal(
+                deps,
+                env,
+                info,
+                packet,
+                relayer,
+                salt,
+                _path,
+                forward.instruction,
+            )
+        }
         _ => Err(ContractError::UnknownOpcode {
             opcode: instruction.opcode,
         }),
@@ -295,6 +345,15 @@ fn acknowledge_packet(
     relayer: Addr,
     ack: Bytes,
 ) -> Result<Response, ContractError> {
+    if let Some(parent) = take_in_flight_packet(deps.storage, &packet)? {
+        // The forwarded packet has been acknowledged, the acknowledgement is propagated verbatim
+        // to the parent packet.
+        return Ok(Response::new().add_message(write_acknowledgement(
+            &CONFIG.load(deps.storage)?.ibc_host,
+            parent,
+            ack,
+        )?));
+    }
     let zkgm_packet = ZkgmPacket::abi_decode_params(&packet.data, true)?;
     let ack = Ack::abi_decode_params(&ack, true)?;
     acknowledge_internal(
@@ -383,6 +442,23 @@ fn acknowledge_internal(
                 Ok(Response::new())
             }
         }
+        // The acknowledgement of the last hop is propagated verbatim along the path, hence the
+        // forwarded instruction is acknowledged as if it was sent directly.
+        OP_FORWARD => {
+            let forward = Forward::abi_decode_params(&instruction.operand, true)?;
+            acknowledge_internal(
+                deps,
+                env,
+                info,
+                packet,
+                relayer,
+                salt,
+                path,
+                forward.instruction,
+                successful,
+                ack,
+            )
+        }
         _ => Err(ContractError::UnknownOpcode {
             opcode: instruction.opcode,
         }),
@@ -407,8 +483,7 @@ fn refund(
     let base_denom =
         String::from_utf8(base_token.to_vec()).map_err(|_| ContractError::InvalidBaseToken)?;
     let mut messages = Vec::<CosmosMsg>::new();
-    // TODO: handle forward path
-    if order.base_token_path == source_channel.try_into().unwrap() {
+    if last_channel_from_path(order.base_token_path) == source_channel {
         messages.push(make_wasm_msg(
             WrappedTokenMsg::MintTokens {
                 denom: base_denom,
@@ -467,8 +542,7 @@ fn acknowledge_fungible_asset_order(
                         query_wrapped_token_identifier(deps.as_ref(), &minter, &order.base_token)?;
                     let base_denom = String::from_utf8(base_denom.to_vec())
                         .map_err(|_| ContractError::InvalidBaseToken)?;
-                    // TODO: handle forward path
-                    if order.base_token_path == packet.source_channel_id.try_into().unwrap() {
+                    if last_channel_from_path(order.base_token_path) == packet.source_channel_id {
                         messages.push(make_wasm_msg(
                             WrappedTokenMsg::MintTokens {
                                 denom: base_denom,
@@ -583,12 +657,128 @@ fn execute_internal(
                 multiplex,
             )
         }
+        OP_FORWARD => {
+            let forward = Forward::abi_decode_params(&instruction.operand, true)?;
+            execute_forward(
+                deps,
+                env,
+                info,
+                packet,
+                relayer,
+                relayer_msg,
+                salt,
+                path,
+                forward,
+            )
+        }
         _ => Err(ContractError::UnknownOpcode {
             opcode: instruction.opcode,
         }),
     }
 }
 
+/// Append `next_channel_id` as the next hop of the channel `path`. Each hop is stored in 32 bits,
+/// with the first hop in the least significant bits.
+pub fn update_channel_path(
+    path: alloy::primitives::U256,
+    next_channel_id: u32,
+) -> Result<alloy::primitives::U256, ContractError> {
+    if path == alloy::primitives::U256::ZERO {
+        return Ok(alloy::primitives::U256::from(next_channel_id));
+    }
+    let next_hop_index = (path.bit_len() - 1) / 32 + 1;
+    if next_hop_index > 7 {
+        return Err(ContractError::InvalidHops);
+    }
+    Ok((alloy::primitives::U256::from(next_channel_id) << (32 * next_hop_index)) | path)
+}
+
+/// The last hop of the channel `path`, or 0 if the path is empty.
+pub fn last_channel_from_path(path: alloy::primitives::U256) -> u32 {
+    if path == alloy::primitives::U256::ZERO {
+        return 0;
+    }
+    let current_hop_index = (path.bit_len() - 1) / 32;
+    u32::try_from(path >> (32 * current_hop_index)).expect("the last hop is 32 bits")
+}
+
+/// Remove the last hop of the channel `path`.
+pub fn pop_channel_from_path(path: alloy::primitives::U256) -> alloy::primitives::U256 {
+    if path == alloy::primitives::U256::ZERO {
+        return alloy::primitives::U256::ZERO;
+    }
+    let current_hop_index = (path.bit_len() - 1) / 32;
+    path & ((alloy::primitives::U256::from(1) << (32 * current_hop_index))
+        - alloy::primitives::U256::from(1))
+}
+
+/// Reverse the hops of the channel `path`.
+pub fn reverse_channel_path(
+    mut path: alloy::primitives::U256,
+) -> Result<alloy::primitives::U256, ContractError> {
+    let mut reversed_path = alloy::primitives::U256::ZERO;
+    while path != alloy::primitives::U256::ZERO {
+        reversed_path = update_channel_path(reversed_path, last_channel_from_path(path))?;
+        path = pop_channel_from_path(path);
+    }
+    Ok(reversed_path)
+}
+
+/// The key of a forwarded packet in [`IN_FLIGHT_PACKET`]. The destination channel is fixed by
+/// the source channel, hence the remaining fields uniquely identify the packet.
+pub fn in_flight_packet_key(
+    source_channel_id: u32,
+    timeout_height: u64,
+    timeout_timestamp: u64,
+    data: &[u8],
+) -> Vec<u8> {
+    keccak256(
+        [
+            source_channel_id.to_be_bytes().as_ref(),
+            timeout_height.to_be_bytes().as_ref(),
+            timeout_timestamp.to_be_bytes().as_ref(),
+            data,
+        ]
+        .concat(),
+    )
+    .get()
+    .to_vec()
+}
+
+/// Remove and return the parent of `packet` if it was sent by [`execute_forward`].
+fn take_in_flight_packet(
+    storage: &mut dyn Storage,
+    packet: &Packet,
+) -> Result<Option<Packet>, ContractError> {
+    let key = in_flight_packet_key(
+        packet.source_channel_id,
+        packet.timeout_height,
+        packet.timeout_timestamp,
+        &packet.data,
+    );
+    let parent = IN_FLIGHT_PACKET.may_load(storage, key.clone())?;
+    if parent.is_some() {
+        IN_FLIGHT_PACKET.remove(storage, key);
+    }
+    Ok(parent)
+}
+
+fn write_acknowledgement(
+    ibc_host: &Addr,
+    packet: Packet,
+    acknowledgement: Bytes,
+) -> StdResult<WasmMsg> {
+    wasm_execute(
+        ibc_host,
+        &ibc_union_msg::msg::ExecuteMsg::WriteAcknowledgement(MsgWriteAcknowledgement {
+            channel_id: packet.destination_channel_id,
+            packet,
+            acknowledgement,
+        }),
+        vec![],
+    )
+}
+
 fn predict_wrapped_denom(path: alloy::primitives::U256, channel: u32, token: Bytes) -> String {
     // TokenFactory denom name limit
     const MAX_DENOM_LENGTH: usize = 44;
@@ -655,6 +845,54 @@ fn execute_multiplex(
     }
 }
 
+/// Send the forwarded instruction on `forward.channel_id`. The acknowledgement of the received
+/// packet is written asynchronously, once the forwarded packet is acknowledged or timed out.
+#[allow(clippy::too_many_arguments)]
+fn execute_forward(
+    deps: DepsMut,
+    _env: Env,
+    _info: MessageInfo,
+    packet: Packet,
+    _relayer: Addr,
+    _relayer_msg: Bytes,
+    salt: H256,
+    path: alloy::primitives::U256,
+    forward: Forward,
+) -> Result<(Bytes, Response), ContractError> {
+    let ibc_host = CONFIG.load(deps.storage)?.ibc_host;
+    let forwarded_data: Bytes = ZkgmPacket {
+        salt: keccak256(salt.get()).into(),
+        path: update_channel_path(path, packet.destination_channel_id)?,
+        instruction: forward.instruction,
+    }
+    .abi_encode_params()
+    .into();
+    IN_FLIGHT_PACKET.save(
+        deps.storage,
+        in_flight_packet_key(
+            forward.channel_id,
+            forward.timeout_height,
+            forward.timeout_timestamp,
+            &forwarded_data,
+        ),
+        &packet,
+    )?;
+    Ok((
+        // Empty acknowledgement, written asynchronously
+        Bytes::default(),
+        Response::new().add_message(wasm_execute(
+            &ibc_host,
+            &ibc_union_msg::msg::ExecuteMsg::PacketSend(MsgSendPacket {
+                source_channel: forward.channel_id,
+                timeout_height: forward.timeout_height,
+                timeout_timestamp: forward.timeout_timestamp,
+                data: forwarded_data,
+            }),
+            vec![],
+        )?),
+    ))
+}
+
 #[allow(clippy::too_many_arguments)]
 fn execute_batch(
     mut deps: DepsMut,
@@ -681,6 +919,9 @@ fn execute_batch(
             path,
             instruction,
         )?;
+        if ack.is_empty() {
+            return Err(ContractError::BatchMustBeSync);
+        }
         response = response
             .add_attributes(sub_response.attributes)
             .add_events(sub_response.events)
@@ -936,7 +1177,7 @@ pub fn reply(deps: DepsMut, _env: Env, reply: Reply) -> Result<Response, Contrac
 
 #[allow(clippy::too_many_arguments)]
 fn transfer(
-    deps: DepsMut,
+    mut deps: DepsMut,
     _: Env,
     info: MessageInfo,
     channel_id: u32,
@@ -949,77 +1190,23 @@ fn transfer(
     timeout_timestamp: u64,
     salt: H256,
 ) -> Result<Response, ContractError> {
-    // NOTE(aeryz): We don't check whether the funds are provided here. We check it in the
-    // minter because cw20 token minter doesn't require funds to be given in the native form.
-    if base_amount.is_zero() {
-        return Err(ContractError::InvalidAmount);
-    }
     let minter = TOKEN_MINTER.load(deps.storage)?;
-    // Transfers happen from the local chain which means the base token will always be utf8 string
-    let base_token_id = String::from_utf8_lossy(&query_wrapped_token_identifier(
-        deps.as_ref(),
+    let (base_token_id, origin, message) = escrow_or_burn(
+        deps.branch(),
         &minter,
-        base_token.as_bytes(),
-    )?)
-    .to_string();
-    // If the origin exists, the preimage exists
-    let unwrapped_asset = HASH_TO_FOREIGN_TOKEN.may_load(deps.storage, base_token_id.clone())?;
-    let mut messages = Vec::<CosmosMsg>::new();
-    // TODO: handle forward path
-    let mut origin = TOKEN_ORIGIN.may_load(deps.storage, base_token_id.clone())?;
-    match origin {
-        // Burn as we are going to unescrow on the counterparty
-        Some(path)
-            if path == Uint256::from(channel_id)
-                && unwrapped_asset == Some(quote_token.clone()) =>
-        {
-            messages.push(make_wasm_msg(
-                WrappedTokenMsg::BurnTokens {
-                    denom: base_token_id.clone(),
-                    amount: base_amount,
-                    burn_from_address: minter.to_string(),
-                    sender: info.sender.clone(),
-                },
-                &minter,
-                info.funds,
-            )?)
-        }
-        // Escrow and update the balance, the counterparty will mint the token
-        _ => {
-            origin = None;
-            messages.push(make_wasm_msg(
-                LocalTokenMsg::Escrow {
-                    from: info.sender.to_string(),
-                    denom: base_token_id.clone(),
-                    recipient: minter.to_string(),
-                    amount: base_amount,
-                },
-                &minter,
-                info.funds,
-            )?);
-            CHANNEL_BALANCE.update(
-                deps.storage,
-                (channel_id, base_token_id.clone()),
-                |balance| match balance {
-                    Some(value) => value
-                        .checked_add(base_amount.into())
-                        .map_err(|_| ContractError::InvalidChannelBalance),
-                    None => Ok(base_amount.into()),
-                },
-            )?;
-        }
-    };
+        &info.sender,
+        info.funds,
+        channel_id,
+        alloy::primitives::U256::ZERO,
+        &base_token,
+        base_amount,
+        &quote_token,
+    )?;
+    let mut messages = vec![message];
     let MetadataResponse {
         name: base_token_name,
         symbol: base_token_symbol,
-    } = deps.querier.query::<MetadataResponse>(&QueryRequest::Wasm(
-        cosmwasm_std::WasmQuery::Smart {
-            contract_addr: minter.to_string(),
-            msg: to_json_binary(&ucs03_zkgm_token_minter_api::QueryMsg::Metadata {
-                denom: base_token_id.clone(),
-            })?,
-        },
-    ))?;
+    } = query_token_metadata(deps.as_ref(), &minter, base_token_id)?;
     let config = CONFIG.load(deps.storage)?;
     messages.push(
         wasm_execute(
@@ -1063,6 +1250,291 @@ fn transfer(
     Ok(Response::new().add_messages(messages))
 }
 
+#[allow(clippy::too_many_arguments)]
+fn send(
+    mut deps: DepsMut,
+    info: MessageInfo,
+    channel_id: u32,
+    timeout_height: u64,
+    timeout_timestamp: u64,
+    salt: H256,
+    instruction: Bytes,
+) -> Result<Response, ContractError> {
+    let instruction = Instruction::abi_decode_params(&instruction, true)?;
+    let mut funds = info.funds.clone();
+    let mut messages = Vec::<CosmosMsg>::new();
+    verify_internal(
+        deps.branch(),
+        &info,
+        &mut funds,
+        &mut messages,
+        channel_id,
+        alloy::primitives::U256::ZERO,
+        &instruction,
+    )?;
+    if funds.iter().any(|coin| !coin.amount.is_zero()) {
+        return Err(ContractError::ExcessFunds);
+    }
+    let config = CONFIG.load(deps.storage)?;
+    messages.push(
+        wasm_execute(
+            &config.ibc_host,
+            &ibc_union_msg::msg::ExecuteMsg::PacketSend(MsgSendPacket {
+                source_channel: channel_id,
+                timeout_height,
+                timeout_timestamp,
+                data: ZkgmPacket {
+                    salt: salt.into(),
+                    path: alloy::primitives::U256::ZERO,
+                    instruction,
+                }
+                .abi_encode_params()
+                .into(),
+            }),
+            vec![],
+        )?
+        .into(),
+    );
+    Ok(Response::new().add_messages(messages))
+}
+
+/// Verify an instruction sent by `info.sender` on `channel_id`, escrowing or burning the tokens
+/// of every embedded [`FungibleAssetOrder`]. The funds of each order are taken from `funds`.
+///
+/// The `path` is made of the channels the instruction is forwarded on after `channel_id`.
+#[allow(clippy::too_many_arguments)]
+fn verify_internal(
+    mut deps: DepsMut,
+    info: &MessageInfo,
+    funds: &mut Vec<Coin>,
+    messages: &mut Vec<CosmosMsg>,
+    channel_id: u32,
+    path: alloy::primitives::U256,
+    instruction: &Instruction,
+) -> Result<(), ContractError> {
+    if instruction.version != ZKGM_VERSION_0 {
+        return Err(ContractError::UnsupportedVersion {
+            version: instruction.version,
+        });
+    }
+    match instruction.opcode {
+        OP_FUNGIBLE_ASSET_ORDER => {
+            let order = FungibleAssetOrder::abi_decode_params(&instruction.operand, true)?;
+            messages.push(verify_fungible_asset_order(
+                deps, info, funds, channel_id, path, order,
+            )?);
+            Ok(())
+        }
+        OP_BATCH => {
+            let batch = Batch::abi_decode_params(&instruction.operand, true)?;
+            for instruction in &batch.instructions {
+                if !matches!(instruction.opcode, OP_FUNGIBLE_ASSET_ORDER | OP_MULTIPLEX) {
+                    return Err(ContractError::InvalidBatchInstruction {
+                        opcode: instruction.opcode,
+                    });
+                }
+                verify_internal(
+                    deps.branch(),
+                    info,
+                    funds,
+                    messages,
+                    channel_id,
+                    path,
+                    instruction,
+                )?;
+            }
+            Ok(())
+        }
+        OP_MULTIPLEX => {
+            let multiplex = Multiplex::abi_decode_params(&instruction.operand, true)?;
+            if multiplex.sender.as_ref() != info.sender.as_bytes() {
+                return Err(ContractError::SenderMismatch);
+            }
+            Ok(())
+        }
+        OP_FORWARD => {
+            let forward = Forward::abi_decode_params(&instruction.operand, true)?;
+            if !matches!(
+                forward.instruction.opcode,
+                OP_FUNGIBLE_ASSET_ORDER | OP_MULTIPLEX | OP_BATCH
+            ) {
+                return Err(ContractError::InvalidForwardInstruction {
+                    opcode: forward.instruction.opcode,
+                });
+            }
+            verify_internal(
+                deps,
+                info,
+                funds,
+                messages,
+                channel_id,
+                update_channel_path(path, forward.channel_id)?,
+                &forward.instruction,
+            )
+        }
+        _ => Err(ContractError::UnknownOpcode {
+            opcode: instruction.opcode,
+        }),
+    }
+}
+
+fn verify_fungible_asset_order(
+    mut deps: DepsMut,
+    info: &MessageInfo,
+    funds: &mut Vec<Coin>,
+    channel_id: u32,
+    path: alloy::primitives::U256,
+    order: FungibleAssetOrder,
+) -> Result<CosmosMsg, ContractError> {
+    if order.sender.as_ref() != info.sender.as_bytes() {
+        return Err(ContractError::SenderMismatch);
+    }
+    let base_token =
+        str::from_utf8(&order.base_token).map_err(|_| ContractError::InvalidBaseToken)?;
+    let base_amount =
+        Uint128::new(u128::try_from(order.base_amount).map_err(|_| ContractError::AmountOverflow)?);
+    let minter = TOKEN_MINTER.load(deps.storage)?;
+    let funds = take_funds(deps.as_ref(), funds, base_token, base_amount)?;
+    let (base_token_id, origin, message) = escrow_or_burn(
+        deps.branch(),
+        &minter,
+        &info.sender,
+        funds,
+        channel_id,
+        path,
+        base_token,
+        base_amount,
+        &Vec::from(order.quote_token).into(),
+    )?;
+    let metadata = query_token_metadata(deps.as_ref(), &minter, base_token_id)?;
+    if order.base_token_name != metadata.name {
+        return Err(ContractError::InvalidAssetName);
+    }
+    if order.base_token_symbol != metadata.symbol {
+        return Err(ContractError::InvalidAssetSymbol);
+    }
+    let expected_origin = origin
+        .map(|x| alloy::primitives::U256::from_be_bytes(x.to_be_bytes()))
+        .unwrap_or(alloy::primitives::U256::ZERO);
+    if order.base_token_path != expected_origin {
+        return Err(ContractError::InvalidAssetOrigin);
+    }
+    Ok(message)
+}
+
+/// Take `amount` of `denom` out of the remaining `funds`. Nothing is taken for cw20 tokens, as
+/// they are not provided in the native form.
+fn take_funds(
+    deps: Deps,
+    funds: &mut [Coin],
+    denom: &str,
+    amount: Uint128,
+) -> Result<Vec<Coin>, ContractError> {
+    if deps.querier.query_wasm_contract_info(denom).is_ok() {
+        return Ok(vec![]);
+    }
+    let coin = funds
+        .iter_mut()
+        .find(|coin| coin.denom == denom && coin.amount >= amount)
+        .ok_or(ContractError::MissingFunds)?;
+    coin.amount -= amount;
+    Ok(vec![Coin::new(amount.u128(), denom)])
+}
+
+/// Escrow or burn `base_amount` of `base_token` sent by `sender` on `channel_id`, then forwarded
+/// along `path`. Wrapped tokens are burnt when they are sent back to the chain they originate
+/// from along the path they came from, otherwise they are escrowed and the channel balance
+/// increased.
+///
+/// Returns the token identifier, the origin of the token if it is burnt and the minter message.
+#[allow(clippy::too_many_arguments)]
+fn escrow_or_burn(
+    deps: DepsMut,
+    minter: &Addr,
+    sender: &Addr,
+    funds: Vec<Coin>,
+    channel_id: u32,
+    path: alloy::primitives::U256,
+    base_token: &str,
+    base_amount: Uint128,
+    quote_token: &Bytes,
+) -> Result<(String, Option<Uint256>, CosmosMsg), ContractError> {
+    // NOTE(aeryz): We don't check whether the funds are provided here. We check it in the
+    // minter because cw20 token minter doesn't require funds to be given in the native form.
+    if base_amount.is_zero() {
+        return Err(ContractError::InvalidAmount);
+    }
+    // Transfers happen from the local chain which means the base token will always be utf8 string
+    let base_token_id = String::from_utf8_lossy(&query_wrapped_token_identifier(
+        deps.as_ref(),
+        minter,
+        base_token.as_bytes(),
+    )?)
+    .to_string();
+    // If the origin exists, the preimage exists
+    let unwrapped_asset = HASH_TO_FOREIGN_TOKEN.may_load(deps.storage, base_token_id.clone())?;
+    let mut origin = TOKEN_ORIGIN.may_load(deps.storage, base_token_id.clone())?;
+    // The token must be sent on the last channel of its origin, then forwarded along the
+    // remaining channels of its origin in reverse order
+    let is_origin_path = match origin {
+        Some(origin) => {
+            let origin = alloy::primitives::U256::from_be_bytes(origin.to_be_bytes());
+            last_channel_from_path(origin) == channel_id
+                && reverse_channel_path(pop_channel_from_path(origin))? == path
+        }
+        None => false,
+    };
+    let message = match origin {
+        // Burn as we are going to unescrow on the counterparty
+        Some(_) if is_origin_path && unwrapped_asset.as_ref() == Some(quote_token) => {
+            make_wasm_msg(
+                WrappedTokenMsg::BurnTokens {
+                    denom: base_token_id.clone(),
+                    amount: base_amount,
+                    burn_from_address: minter.to_string(),
+                    sender: sender.clone(),
+                },
+                minter,
+                funds,
+            )?
+        }
+        // Escrow and update the balance, the counterparty will mint the token
+        _ => {
+            origin = None;
+            let message = make_wasm_msg(
+                LocalTokenMsg::Escrow {
+                    from: sender.to_string(),
+                    denom: base_token_id.clone(),
+                    recipient: minter.to_string(),
+                    amount: base_amount,
+                },
+                minter,
+                funds,
+            )?;
+            CHANNEL_BALANCE.update(
+                deps.storage,
+                (channel_id, base_token_id.clone()),
+                |balance| match balance {
+                    Some(value) => value
+                        .checked_add(base_amount.into())
+                        .map_err(|_| ContractError::InvalidChannelBalance),
+                    None => Ok(base_amount.into()),
+                },
+            )?;
+            message
+        }
+    };
+    Ok((base_token_id, origin, message))
+}
+
+fn query_token_metadata(deps: Deps, minter: &Addr, denom: String) -> StdResult<MetadataResponse> {
+    deps.querier
+        .query::<MetadataResponse>(&QueryRequest::Wasm(cosmwasm_std::WasmQuery::Smart {
+            contract_addr: minter.to_string(),
+            msg: to_json_binary(&ucs03_zkgm_token_minter_api::QueryMsg::Metadata { denom })?,
+        }))
+}
+
 fn query_wrapped_token_identifier(deps: Deps, minter: &Addr, token: &[u8]) -> StdResult<Bytes> {
     Ok(deps
         .querier
@@ -1080,6 +1552,58 @@ fn query_wrapped_token_identifier(deps: Deps, minter: &Addr, token: &[u8]) -> St
 #[cosmwasm_schema::cw_serde]
 pub struct MigrateMsg {}
 
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, _: Env, msg: QueryMsg) -> Result<Binary, ContractError> {
+    match msg {
+        QueryMsg::ChannelBalances { start_after, limit } => {
+            let limit = limit.unwrap_or(DEFAULT_QUERY_LIMIT).min(MAX_QUERY_LIMIT) as usize;
+            let balances = CHANNEL_BALANCE
+                .range(
+                    deps.storage,
+                    start_after.map(Bound::exclusive),
+                    None,
+                    Order::Ascending,
+                )
+                .take(limit)
+                .map(|item| {
+                    item.map(|((channel_id, denom), balance)| ChannelBalance {
+                        channel_id,
+                        denom,
+                        balance,
+                    })
+                })
+                .collect::<StdResult<_>>()?;
+            Ok(to_json_binary(&ChannelBalancesResponse { balances })?)
+        }
+        QueryMsg::TokenOrigin { denom } => {
+            let minter = TOKEN_MINTER.load(deps.storage)?;
+            // The origin is stored under the identifier of the token, see `escrow_or_burn`
+            let denom = String::from_utf8_lossy(&query_wrapped_token_identifier(
+                deps,
+                &minter,
+                denom.as_bytes(),
+            )?)
+            .to_string();
+            Ok(to_json_binary(&TokenOriginResponse {
+                origin: TOKEN_ORIGIN.may_load(deps.storage, denom.clone())?,
+                foreign_token: HASH_TO_FOREIGN_TOKEN.may_load(deps.storage, denom)?,
+            })?)
+        }
+        QueryMsg::PredictWrappedToken {
+            path,
+            channel_id,
+            token,
+        } => Ok(to_json_binary(&PredictWrappedTokenResponse {
+            wrapped_token: predict_wrapped_denom(
+                alloy::primitives::U256::from_be_bytes(path.to_be_bytes()),
+                channel_id,
+                token,
+            ),
+        })?),
+        QueryMsg::Config {} => Ok(to_json_binary(&CONFIG.load(deps.storage)?)?),
+    }
+}
+
 #[cosmwasm_std::entry_point]
 pub fn migrate(_: DepsMut, _: Env, _: MigrateMsg) -> Result<Response, ContractError> {
     Ok(Response::default())
@@ -1093,3 +1617,818 @@ fn make_wasm_msg(
     let msg = msg.into();
     Ok(CosmosMsg::Wasm(wasm_execute(minter, &msg, funds)?))
 }
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::U256;
+    use cosmwasm_std::{
+        coin, coins, from_json,
+        testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier, MockStorage},
+        ContractResult, OwnedDeps, SystemError, SystemResult, WasmQuery,
+    };
+
+    use super::*;
+    use crate::msg::Config;
+
+    const IBC_HOST: &str = "ibc_host";
+    const MINTER: &str = "token_minter";
+    const SENDER: &str = "sender";
+    const RELAYER: &str = "relayer";
+    const DENOM: &str = "muno";
+    const WRAPPED_DENOM: &str = "wrapped";
+    const CW20: &str = "cw20_token";
+    const CW20_WRAPPED: &str = "cw20_wrapped";
+    const AMOUNT: u128 = 100;
+
+    /// Dependencies of a `ucs03-zkgm` contract whose minter returns tokens as is (except for
+    /// [`CW20_WRAPPED`], which is the cw20 contract of [`WRAPPED_DENOM`]) and uses the denom as the
+    /// name and symbol of a token. [`CW20`] is the only contract besides the minter.
+    fn setup() -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+        CONFIG
+            .save(
+                deps.as_mut().storage,
+                &Config {
+                    ibc_host: Addr::unchecked(IBC_HOST),
+                    token_minter_code_id: 0,
+                },
+            )
+            .unwrap();
+        TOKEN_MINTER
+            .save(deps.as_mut().storage, &Addr::unchecked(MINTER))
+            .unwrap();
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == MINTER => {
+                let response = match from_json(msg).unwrap() {
+                    ucs03_zkgm_token_minter_api::QueryMsg::TokenToIdentifier { token } => {
+                        to_json_binary(&TokenToIdentifierResponse {
+                            token_identifier: if token.as_slice() == CW20_WRAPPED.as_bytes() {
+                                WRAPPED_DENOM.as_bytes().into()
+                            } else {
+                                token
+                            },
+                        })
+                    }
+                    ucs03_zkgm_token_minter_api::QueryMsg::Metadata { denom } => {
+                        to_json_binary(&MetadataResponse {
+                            name: denom.clone(),
+                            symbol: denom,
+                        })
+                    }
+                };
+                SystemResult::Ok(ContractResult::Ok(response.unwrap()))
+            }
+            WasmQuery::ContractInfo { contract_addr } if contract_addr == CW20 => {
+                SystemResult::Ok(ContractResult::Ok(
+                    to_json_binary(
+                        &serde_json::json!({ "code_id": 1, "creator": MINTER, "pinned": false }),
+                    )
+                    .unwrap(),
+                ))
+            }
+            WasmQuery::ContractInfo { contract_addr } => {
+                SystemResult::Err(SystemError::NoSuchContract {
+                    addr: contract_addr.clone(),
+                })
+            }
+            _ => panic!("unexpected query: {query:?}"),
+        });
+        deps
+    }
+
+    fn order(base_token: &str, base_token_path: U256) -> FungibleAssetOrder {
+        FungibleAssetOrder {
+            sender: SENDER.as_bytes().to_vec().into(),
+            receiver: b"receiver".to_vec().into(),
+            base_token: base_token.as_bytes().to_vec().into(),
+            base_amount: U256::from(AMOUNT),
+            base_token_symbol: base_token.into(),
+            base_token_name: base_token.into(),
+            base_token_path,
+            quote_token: b"quote".to_vec().into(),
+            quote_amount: U256::from(AMOUNT),
+        }
+    }
+
+    fn fungible_asset_order(order: FungibleAssetOrder) -> Instruction {
+        Instruction {
+            version: ZKGM_VERSION_0,
+            opcode: OP_FUNGIBLE_ASSET_ORDER,
+            operand: order.abi_encode_params().into(),
+        }
+    }
+
+    fn forward(channel_id: u32, instruction: Instruction) -> Instruction {
+        Instruction {
+            version: ZKGM_VERSION_0,
+            opcode: OP_FORWARD,
+            operand: Forward {
+                channel_id,
+                timeout_height: 0,
+                timeout_timestamp: 1,
+                instruction,
+            }
+            .abi_encode_params()
+            .into(),
+        }
+    }
+
+    fn batch(instructions: Vec<Instruction>) -> Instruction {
+        Instruction {
+            version: ZKGM_VERSION_0,
+            opcode: OP_BATCH,
+            operand: Batch { instructions }.abi_encode_params().into(),
+        }
+    }
+
+    fn packet(
+        source_channel_id: u32,
+        destination_channel_id: u32,
+        instruction: Instruction,
+    ) -> Packet {
+        Packet {
+            source_channel_id,
+            destination_channel_id,
+            data: ZkgmPacket {
+                salt: [1; 32].into(),
+                path: U256::ZERO,
+                instruction,
+            }
+            .abi_encode_params()
+            .into(),
+            timeout_height: 0,
+            timeout_timestamp: 1,
+        }
+    }
+
+    fn execute_received_packet(deps: DepsMut, packet: Packet) -> Result<Response, ContractError> {
+        let env = mock_env();
+        execute(
+            deps,
+            env.clone(),
+            mock_info(env.contract.address.as_str(), &[]),
+            ExecuteMsg::ExecutePacket {
+                packet,
+                relayer: Addr::unchecked(RELAYER),
+                relayer_msg: Bytes::default(),
+            },
+        )
+    }
+
+    fn execute_ibc_union_msg(deps: DepsMut, msg: IbcUnionMsg) -> Result<Response, ContractError> {
+        execute(
+            deps,
+            mock_env(),
+            mock_info(IBC_HOST, &[]),
+            ExecuteMsg::IbcUnionMsg(msg),
+        )
+    }
+
+    /// Save [`WRAPPED_DENOM`] as the wrapped `quote` token coming from `origin`.
+    fn save_wrapped_token(deps: DepsMut, origin: U256) {
+        TOKEN_ORIGIN
+            .save(
+                deps.storage,
+                WRAPPED_DENOM.into(),
+                &Uint256::from_be_bytes(origin.to_be_bytes()),
+            )
+            .unwrap();
+        HASH_TO_FOREIGN_TOKEN
+            .save(
+                deps.storage,
+                WRAPPED_DENOM.into(),
+                &b"quote".to_vec().into(),
+            )
+            .unwrap();
+    }
+
+    fn verify(
+        deps: DepsMut,
+        funds: &[Coin],
+        channel_id: u32,
+        instruction: &Instruction,
+    ) -> Result<Vec<CosmosMsg>, ContractError> {
+        let info = mock_info(SENDER, funds);
+        let mut funds = info.funds.clone();
+        let mut messages = vec![];
+        verify_internal(
+            deps,
+            &info,
+            &mut funds,
+            &mut messages,
+            channel_id,
+            U256::ZERO,
+            instruction,
+        )?;
+        Ok(messages)
+    }
+
+    fn send_instruction(
+        deps: DepsMut,
+        funds: &[Coin],
+        instruction: Instruction,
+    ) -> Result<Response, ContractError> {
+        execute(
+            deps,
+            mock_env(),
+            mock_info(SENDER, funds),
+            ExecuteMsg::Send {
+                channel_id: 1,
+                timeout_height: 0,
+                timeout_timestamp: 1,
+                salt: H256::default(),
+                instruction: instruction.abi_encode_params().into(),
+            },
+        )
+    }
+
+    fn escrow(funds: Vec<Coin>) -> CosmosMsg {
+        make_wasm_msg(
+            LocalTokenMsg::Escrow {
+                from: SENDER.into(),
+                denom: DENOM.into(),
+                recipient: MINTER.into(),
+                amount: AMOUNT.into(),
+            },
+            MINTER,
+            funds,
+        )
+        .unwrap()
+    }
+
+    fn burn() -> CosmosMsg {
+        make_wasm_msg(
+            WrappedTokenMsg::BurnTokens {
+                denom: WRAPPED_DENOM.into(),
+                amount: AMOUNT.into(),
+                burn_from_address: MINTER.into(),
+                sender: Addr::unchecked(SENDER),
+            },
+            MINTER,
+            coins(AMOUNT, WRAPPED_DENOM),
+        )
+        .unwrap()
+    }
+
+    /// Execute a packet received on channel 2 that forwards an order through channel 3. Returns
+    /// the received packet and the packet that was sent in its place.
+    fn execute_forwarded_packet(deps: DepsMut) -> (Packet, Packet) {
+        let parent = packet(
+            1,
+            2,
+            forward(3, fungible_asset_order(order(DENOM, U256::ZERO))),
+        );
+        let response = execute_received_packet(deps, parent.clone()).unwrap();
+
+        let [SubMsg {
+            msg:
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr, msg, ..
+                }),
+            ..
+        }] = response.messages.as_slice()
+        else {
+            panic!("expected a single message: {:?}", response.messages)
+        };
+        assert_eq!(contract_addr, IBC_HOST);
+        let ibc_union_msg::msg::ExecuteMsg::PacketSend(send) = from_json(msg).unwrap() else {
+            panic!("expected a packet send")
+        };
+        assert_eq!(send.source_channel, 3);
+
+        let child = Packet {
+            source_channel_id: send.source_channel,
+            destination_channel_id: 4,
+            data: send.data,
+            timeout_height: send.timeout_height,
+            timeout_timestamp: send.timeout_timestamp,
+        };
+        (parent, child)
+    }
+
+    #[test]
+    fn update_channel_path_appends_hops() {
+        let path = update_channel_path(U256::ZERO, 1).unwrap();
+        assert_eq!(path, U256::from(1));
+
+        let path = update_channel_path(path, 2).unwrap();
+        assert_eq!(path, U256::from(1) | (U256::from(2) << 32));
+
+        let path = update_channel_path(path, 3).unwrap();
+        assert_eq!(
+            path,
+            U256::from(1) | (U256::from(2) << 32) | (U256::from(3) << 64)
+        );
+    }
+
+    #[test]
+    fn update_channel_path_fails_on_too_many_hops() {
+        let path = (0..9).try_fold(U256::ZERO, |path, channel_id| {
+            update_channel_path(path, channel_id + 1)
+        });
+        assert_eq!(path, Err(ContractError::InvalidHops));
+    }
+
+    #[test]
+    fn channel_path_hops() {
+        let path = U256::from(1) | (U256::from(2) << 32) | (U256::from(3) << 64);
+
+        assert_eq!(last_channel_from_path(path), 3);
+        assert_eq!(
+            pop_channel_from_path(path),
+            U256::from(1) | (U256::from(2) << 32)
+        );
+        assert_eq!(
+            reverse_channel_path(path),
+            Ok(U256::from(3) | (U256::from(2) << 32) | (U256::from(1) << 64))
+        );
+
+        assert_eq!(last_channel_from_path(U256::ZERO), 0);
+        assert_eq!(pop_channel_from_path(U256::from(1)), U256::ZERO);
+        assert_eq!(reverse_channel_path(U256::ZERO), Ok(U256::ZERO));
+    }
+
+    #[test]
+    fn query_channel_balances_is_paginated() {
+        let mut deps = mock_dependencies();
+        for (channel_id, denom) in [(1, "a"), (1, "b"), (2, "a")] {
+            CHANNEL_BALANCE
+                .save(
+                    deps.as_mut().storage,
+                    (channel_id, denom.to_owned()),
+                    &Uint256::from_u128(channel_id.into()),
+                )
+                .unwrap();
+        }
+
+        let query_balances = |start_after, limit| {
+            from_json::<ChannelBalancesResponse>(
+                query(
+                    deps.as_ref(),
+                    mock_env(),
+                    QueryMsg::ChannelBalances { start_after, limit },
+                )
+                .unwrap(),
+            )
+            .unwrap()
+            .balances
+            .into_iter()
+            .map(|balance| (balance.channel_id, balance.denom))
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            query_balances(None, Some(2)),
+            [(1, "a".to_owned()), (1, "b".to_owned())]
+        );
+        assert_eq!(
+            query_balances(Some((1, "b".to_owned())), None),
+            [(2, "a".to_owned())]
+        );
+    }
+
+    fn query_token_origin(deps: Deps, denom: &str) -> TokenOriginResponse {
+        from_json(
+            query(
+                deps,
+                mock_env(),
+                QueryMsg::TokenOrigin {
+                    denom: denom.into(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn query_token_origin_of_native_token() {
+        let mut deps = setup();
+        save_wrapped_token(deps.as_mut(), U256::from(2));
+
+        let origin = query_token_origin(deps.as_ref(), WRAPPED_DENOM);
+        assert_eq!(origin.origin, Some(Uint256::from_u128(2)));
+        assert_eq!(origin.foreign_token, Some(b"quote".to_vec().into()));
+
+        let origin = query_token_origin(deps.as_ref(), DENOM);
+        assert_eq!(origin.origin, None);
+        assert_eq!(origin.foreign_token, None);
+    }
+
+    #[test]
+    fn query_token_origin_of_cw20_token() {
+        let mut deps = setup();
+        save_wrapped_token(deps.as_mut(), U256::from(2));
+
+        // the origin is looked up by the identifier of the cw20 token
+        let origin = query_token_origin(deps.as_ref(), CW20_WRAPPED);
+        assert_eq!(origin.origin, Some(Uint256::from_u128(2)));
+        assert_eq!(origin.foreign_token, Some(b"quote".to_vec().into()));
+
+        let origin = query_token_origin(deps.as_ref(), CW20);
+        assert_eq!(origin.origin, None);
+        assert_eq!(origin.foreign_token, None);
+    }
+
+    #[test]
+    fn query_predict_wrapped_token_matches_received_token() {
+        let mut deps = setup();
+
+        let PredictWrappedTokenResponse { wrapped_token } = from_json(
+            query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::PredictWrappedToken {
+                    path: Uint256::zero(),
+                    channel_id: 2,
+                    token: DENOM.as_bytes().to_vec().into(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        let mut order = order(DENOM, U256::ZERO);
+        order.quote_token = wrapped_token.as_bytes().to_vec().into();
+        execute_received_packet(deps.as_mut(), packet(1, 2, fungible_asset_order(order))).unwrap();
+
+        // the predicted token is the one that was created for the received token
+        let origin = query_token_origin(deps.as_ref(), &wrapped_token);
+        assert_eq!(origin.origin, Some(Uint256::from_u128(2)));
+        assert_eq!(origin.foreign_token, Some(DENOM.as_bytes().to_vec().into()));
+    }
+
+    #[test]
+    fn query_config() {
+        let deps = setup();
+
+        let config: Config =
+            from_json(query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()).unwrap();
+        assert_eq!(
+            config,
+            Config {
+                ibc_host: Addr::unchecked(IBC_HOST),
+                token_minter_code_id: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn forward_acknowledgement_is_written_to_parent() {
+        let mut deps = setup();
+        let (parent, child) = execute_forwarded_packet(deps.as_mut());
+
+        let ack: Bytes = Ack {
+            tag: TAG_ACK_SUCCESS,
+            inner_ack: FungibleAssetOrderAck {
+                fill_type: FILL_TYPE_PROTOCOL,
+                market_maker: Default::default(),
+            }
+            .abi_encode_params()
+            .into(),
+        }
+        .abi_encode_params()
+        .into();
+        let response = execute_ibc_union_msg(
+            deps.as_mut(),
+            IbcUnionMsg::OnAcknowledgementPacket {
+                packet: child.clone(),
+                acknowledgement: ack.clone(),
+                relayer: RELAYER.into(),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            response.messages,
+            [SubMsg::new(
+                write_acknowledgement(&Addr::unchecked(IBC_HOST), parent, ack).unwrap()
+            )]
+        );
+        assert_eq!(
+            take_in_flight_packet(deps.as_mut().storage, &child),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn forward_timeout_fails_parent_and_refunds() {
+        let mut deps = setup();
+        let (parent, child) = execute_forwarded_packet(deps.as_mut());
+
+        let response = execute_ibc_union_msg(
+            deps.as_mut(),
+            IbcUnionMsg::OnTimeoutPacket {
+                packet: child.clone(),
+                relayer: RELAYER.into(),
+            },
+        )
+        .unwrap();
+
+        let failure: Bytes = Ack {
+            tag: TAG_ACK_FAILURE,
+            inner_ack: Default::default(),
+        }
+        .abi_encode_params()
+        .into();
+        assert_eq!(
+            response.messages,
+            [SubMsg::new(
+                write_acknowledgement(&Addr::unchecked(IBC_HOST), parent.clone(), failure.clone())
+                    .unwrap()
+            )]
+        );
+        assert_eq!(
+            take_in_flight_packet(deps.as_mut().storage, &child),
+            Ok(None)
+        );
+
+        // The failure reaches the origin of the parent packet, which refunds the sender.
+        let mut origin = setup();
+        let response = execute_ibc_union_msg(
+            origin.as_mut(),
+            IbcUnionMsg::OnAcknowledgementPacket {
+                packet: parent,
+                acknowledgement: failure,
+                relayer: RELAYER.into(),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            response.messages,
+            [SubMsg::new(
+                make_wasm_msg(
+                    LocalTokenMsg::Unescrow {
+                        denom: DENOM.into(),
+                        recipient: SENDER.into(),
+                        amount: AMOUNT.into(),
+                    },
+                    MINTER,
+                    vec![],
+                )
+                .unwrap()
+            )]
+        );
+    }
+
+    #[test]
+    fn batch_with_async_acknowledgement_is_rejected() {
+        let mut deps = setup();
+
+        assert_eq!(
+            execute_received_packet(
+                deps.as_mut(),
+                packet(
+                    1,
+                    2,
+                    batch(vec![forward(
+                        3,
+                        fungible_asset_order(order(DENOM, U256::ZERO))
+                    )])
+                ),
+            ),
+            Err(ContractError::BatchMustBeSync)
+        );
+    }
+
+    #[test]
+    fn take_funds_takes_order_amount() {
+        let deps = setup();
+        let mut funds = vec![coin(AMOUNT + 1, DENOM), coin(1, "other")];
+
+        assert_eq!(
+            take_funds(deps.as_ref(), &mut funds, DENOM, AMOUNT.into()),
+            Ok(coins(AMOUNT, DENOM))
+        );
+        assert_eq!(funds, [coin(1, DENOM), coin(1, "other")]);
+    }
+
+    #[test]
+    fn take_funds_fails_on_insufficient_funds() {
+        let deps = setup();
+
+        assert_eq!(
+            take_funds(
+                deps.as_ref(),
+                &mut coins(AMOUNT - 1, DENOM),
+                DENOM,
+                AMOUNT.into()
+            ),
+            Err(ContractError::MissingFunds)
+        );
+        assert_eq!(
+            take_funds(
+                deps.as_ref(),
+                &mut coins(AMOUNT, "other"),
+                DENOM,
+                AMOUNT.into()
+            ),
+            Err(ContractError::MissingFunds)
+        );
+    }
+
+    #[test]
+    fn take_funds_takes_nothing_for_cw20() {
+        let deps = setup();
+
+        assert_eq!(
+            take_funds(deps.as_ref(), &mut [], CW20, AMOUNT.into()),
+            Ok(vec![])
+        );
+    }
+
+    #[test]
+    fn send_escrows_order_funds() {
+        let mut deps = setup();
+        let instruction = fungible_asset_order(order(DENOM, U256::ZERO));
+
+        let response =
+            send_instruction(deps.as_mut(), &coins(AMOUNT, DENOM), instruction.clone()).unwrap();
+
+        assert_eq!(
+            response.messages,
+            [
+                SubMsg::new(escrow(coins(AMOUNT, DENOM))),
+                SubMsg::new(
+                    wasm_execute(
+                        IBC_HOST,
+                        &ibc_union_msg::msg::ExecuteMsg::PacketSend(MsgSendPacket {
+                            source_channel: 1,
+                            timeout_height: 0,
+                            timeout_timestamp: 1,
+                            data: ZkgmPacket {
+                                salt: H256::default().into(),
+                                path: U256::ZERO,
+                                instruction,
+                            }
+                            .abi_encode_params()
+                            .into(),
+                        }),
+                        vec![],
+                    )
+                    .unwrap()
+                )
+            ]
+        );
+        assert_eq!(
+            CHANNEL_BALANCE.load(deps.as_ref().storage, (1, DENOM.into())),
+            Ok(Uint256::from(AMOUNT))
+        );
+    }
+
+    #[test]
+    fn send_fails_on_insufficient_funds() {
+        let mut deps = setup();
+
+        assert_eq!(
+            send_instruction(
+                deps.as_mut(),
+                &coins(AMOUNT - 1, DENOM),
+                fungible_asset_order(order(DENOM, U256::ZERO)),
+            ),
+            Err(ContractError::MissingFunds)
+        );
+        // The funds of an order are not reused by the next one
+        assert_eq!(
+            send_instruction(
+                deps.as_mut(),
+                &coins(AMOUNT, DENOM),
+                batch(vec![
+                    fungible_asset_order(order(DENOM, U256::ZERO)),
+                    fungible_asset_order(order(DENOM, U256::ZERO)),
+                ]),
+            ),
+            Err(ContractError::MissingFunds)
+        );
+    }
+
+    #[test]
+    fn send_fails_on_leftover_funds() {
+        let mut deps = setup();
+
+        assert_eq!(
+            send_instruction(
+                deps.as_mut(),
+                &coins(AMOUNT + 1, DENOM),
+                fungible_asset_order(order(DENOM, U256::ZERO)),
+            ),
+            Err(ContractError::ExcessFunds)
+        );
+        assert_eq!(
+            send_instruction(
+                deps.as_mut(),
+                &[coin(AMOUNT, DENOM), coin(1, "other")],
+                fungible_asset_order(order(DENOM, U256::ZERO)),
+            ),
+            Err(ContractError::ExcessFunds)
+        );
+    }
+
+    #[test]
+    fn verify_internal_takes_funds_of_every_order() {
+        let mut deps = setup();
+
+        assert_eq!(
+            verify(
+                deps.as_mut(),
+                &coins(2 * AMOUNT, DENOM),
+                1,
+                &batch(vec![
+                    fungible_asset_order(order(DENOM, U256::ZERO)),
+                    fungible_asset_order(order(DENOM, U256::ZERO)),
+                ]),
+            ),
+            Ok(vec![
+                escrow(coins(AMOUNT, DENOM)),
+                escrow(coins(AMOUNT, DENOM))
+            ])
+        );
+    }
+
+    #[test]
+    fn verify_internal_burns_wrapped_token_sent_back_to_origin() {
+        let mut deps = setup();
+        save_wrapped_token(deps.as_mut(), U256::from(1));
+
+        assert_eq!(
+            verify(
+                deps.as_mut(),
+                &coins(AMOUNT, WRAPPED_DENOM),
+                1,
+                &fungible_asset_order(order(WRAPPED_DENOM, U256::from(1))),
+            ),
+            Ok(vec![burn()])
+        );
+    }
+
+    #[test]
+    fn verify_internal_burns_wrapped_token_forwarded_back_to_origin() {
+        let mut deps = setup();
+        // The token was forwarded through channel 5 of an intermediate chain, then channel 1
+        let origin = U256::from(5) | (U256::from(1) << 32);
+        save_wrapped_token(deps.as_mut(), origin);
+
+        assert_eq!(
+            verify(
+                deps.as_mut(),
+                &coins(AMOUNT, WRAPPED_DENOM),
+                1,
+                &forward(5, fungible_asset_order(order(WRAPPED_DENOM, origin))),
+            ),
+            Ok(vec![burn()])
+        );
+        assert_eq!(
+            verify(
+                deps.as_mut(),
+                &coins(AMOUNT, WRAPPED_DENOM),
+                1,
+                &fungible_asset_order(order(WRAPPED_DENOM, origin)),
+            ),
+            Err(ContractError::InvalidAssetOrigin)
+        );
+    }
+
+    #[test]
+    fn verify_internal_escrows_wrapped_token_forwarded_away_from_origin() {
+        let mut deps = setup();
+        save_wrapped_token(deps.as_mut(), U256::from(1));
+
+        assert!(matches!(
+            verify(
+                deps.as_mut(),
+                &coins(AMOUNT, WRAPPED_DENOM),
+                1,
+                &forward(2, fungible_asset_order(order(WRAPPED_DENOM, U256::ZERO))),
+            )
+            .as_deref(),
+            Ok([CosmosMsg::Wasm(WasmMsg::Execute { .. })])
+        ));
+        assert_eq!(
+            CHANNEL_BALANCE.load(deps.as_ref().storage, (1, WRAPPED_DENOM.into())),
+            Ok(Uint256::from(AMOUNT))
+        );
+        assert_eq!(
+            verify(
+                deps.as_mut(),
+                &coins(AMOUNT, WRAPPED_DENOM),
+                1,
+                &forward(2, fungible_asset_order(order(WRAPPED_DENOM, U256::from(1)))),
+            ),
+            Err(ContractError::InvalidAssetOrigin)
+        );
+    }
+
+    #[test]
+    fn verify_internal_takes_no_funds_for_cw20() {
+        let mut deps = setup();
+
+        assert!(verify(
+            deps.as_mut(),
+            &[],
+            1,
+            &fungible_asset_order(order(CW20, U256::ZERO)),
+        )
+        .is_ok());
+    }
+}
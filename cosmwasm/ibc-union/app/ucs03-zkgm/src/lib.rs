@@ -39,6 +39,8 @@ pub enum ContractError {
     InvalidAmount,
     #[error("transfer require funds to be submitted along the transaction")]
     MissingFunds,
+    #[error("the funds submitted along the transaction exceed the amounts of the orders")]
+    ExcessFunds,
     #[error("receiver must be a valid address")]
     InvalidReceiver,
     #[error("receiver must be a valid address")]
@@ -63,4 +65,20 @@ pub enum ContractError {
     Unimplemented,
     #[error("contract creation event not found during handling `reply`")]
     ContractCreationEventNotFound,
+    #[error("the channel path exceeds the maximum number of hops")]
+    InvalidHops,
+    #[error("batch instructions must be acknowledged synchronously")]
+    BatchMustBeSync,
+    #[error("the instruction sender must be the sender of the message")]
+    SenderMismatch,
+    #[error("the base token name doesn't match the token metadata")]
+    InvalidAssetName,
+    #[error("the base token symbol doesn't match the token metadata")]
+    InvalidAssetSymbol,
+    #[error("the base token path doesn't match the token origin")]
+    InvalidAssetOrigin,
+    #[error("instruction with opcode {opcode} can't be batched")]
+    InvalidBatchInstruction { opcode: u8 },
+    #[error("instruction with opcode {opcode} can't be forwarded")]
+    InvalidForwardInstruction { opcode: u8 },
 }
@@ -44,6 +44,17 @@ pub enum ExecuteMsg {
         timeout_timestamp: u64,
         salt: H256,
     },
+    /// Send an arbitrary zkgm instruction on `channel_id`. The funds of every
+    /// `FungibleAssetOrder` embedded in the instruction must be provided with the message, unless
+    /// the token is a cw20. Funds that are not used by an order are rejected.
+    Send {
+        channel_id: u32,
+        timeout_height: u64,
+        timeout_timestamp: u64,
+        salt: H256,
+        /// The ABI encoded `Instruction`.
+        instruction: Bytes,
+    },
     BatchExecute {
         msgs: Vec<CosmosMsg>,
     },
@@ -65,5 +76,53 @@ pub enum EurekaMsg {
     },
 }
 
+#[cw_serde]
+pub enum QueryMsg {
+    /// Query the balances escrowed on each channel, ordered by channel id and denom.
+    ChannelBalances {
+        /// The `(channel_id, denom)` to start after, exclusive.
+        start_after: Option<(u32, String)>,
+        limit: Option<u32>,
+    },
+    /// Query the origin of a token. For wrapped tokens, this also returns the token on the
+    /// counterparty that it wraps. `denom` is either a native denom or the address of a cw20 token
+    /// created through the token minter.
+    TokenOrigin { denom: String },
+    /// Predict the wrapped token that will be minted when `token` is received on `channel_id`
+    /// over `path`.
+    PredictWrappedToken {
+        path: Uint256,
+        channel_id: u32,
+        token: Bytes,
+    },
+    /// Query the [`Config`] of `ucs03-zkgm`.
+    Config {},
+}
+
+#[cw_serde]
+pub struct ChannelBalance {
+    pub channel_id: u32,
+    pub denom: String,
+    pub balance: Uint256,
+}
+
+#[cw_serde]
+pub struct ChannelBalancesResponse {
+    pub balances: Vec<ChannelBalance>,
+}
+
+#[cw_serde]
+pub struct TokenOriginResponse {
+    /// The channel path the token was received over, if it is a wrapped token.
+    pub origin: Option<Uint256>,
+    /// The counterparty token wrapped by the token, if it is a wrapped token.
+    pub foreign_token: Option<Bytes>,
+}
+
+#[cw_serde]
+pub struct PredictWrappedTokenResponse {
+    pub wrapped_token: String,
+}
+
 #[cw_serde]
 pub struct MigrateMsg {}
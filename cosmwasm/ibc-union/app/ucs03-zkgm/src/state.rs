@@ -20,3 +20,8 @@ pub const EXECUTING_PACKET: Item<Packet> = Item::new("executing_packet");
 pub const EXECUTION_ACK: Item<Bytes> = Item::new("execution_ack");
 
 pub const HASH_TO_FOREIGN_TOKEN: Map<String, Bytes> = Map::new("hash_to_foreign_token");
+
+/// Packets received by `ucs03-zkgm` that were forwarded on another channel, keyed by the
+/// [`in_flight_packet_key`][crate::contract::in_flight_packet_key] of the forwarded packet. The
+/// acknowledgement of the forwarded packet is written back to the stored packet.
+pub const IN_FLIGHT_PACKET: Map<Vec<u8>, Packet> = Map::new("in_flight_packet");
@@ -211,7 +211,7 @@ pub trait IbcClient: Sized {
     fn get_counterparty_chain_id(client_state: &Self::ClientState) -> String;
 
     /// Get the status of the client
-    fn status(client_state: &Self::ClientState) -> Status;
+    fn status(ctx: IbcClientCtx<Self>, client_state: &Self::ClientState) -> Status;
 
     /// Verify the initial state of the client
     fn verify_creation(
@@ -261,11 +261,20 @@ pub fn query<T: IbcClient>(
                 read_client_state::<T>(deps.querier.into_empty(), &ibc_host, client_id)?;
             to_json_binary(&T::get_latest_height(&client_state)).map_err(Into::into)
         }
+        QueryMsg::GetCounterpartyChainId { client_id } => {
+            let ibc_host = IBC_HOST.load(deps.storage)?;
+            let client_state =
+                read_client_state::<T>(deps.querier.into_empty(), &ibc_host, client_id)?;
+            to_json_binary(&T::get_counterparty_chain_id(&client_state)).map_err(Into::into)
+        }
         QueryMsg::GetStatus { client_id } => {
             let ibc_host = IBC_HOST.load(deps.storage)?;
             let client_state =
                 read_client_state::<T>(deps.querier.into_empty(), &ibc_host, client_id)?;
-            let status = T::status(&client_state);
+            let status = T::status(
+                IbcClientCtx::new(client_id, ibc_host, deps, env),
+                &client_state,
+            );
             to_json_binary(&status).map_err(Into::into)
         }
         QueryMsg::VerifyCreation {
@@ -39,6 +39,9 @@ pub enum QueryMsg {
     GetLatestHeight {
         client_id: u32,
     },
+    GetCounterpartyChainId {
+        client_id: u32,
+    },
     GetStatus {
         client_id: u32,
     },
@@ -4,7 +4,10 @@ use unionlabs_primitives::Bytes;
 
 #[derive(Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
-pub struct InitMsg {}
+pub struct InitMsg {
+    /// The admin of the contract, allowed to register and replace client implementations.
+    pub admin: String,
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -13,12 +16,39 @@ pub struct MsgRegisterClient {
     pub client_address: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MsgReplaceClient {
+    pub client_type: String,
+    pub client_address: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MsgRecoverClient {
+    /// The expired or frozen client to recover.
+    pub subject_client_id: u32,
+    /// The active client of the same type whose state is copied into the subject client. It must
+    /// track the same counterparty chain and be ahead of the subject client.
+    pub substitute_client_id: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MsgTransferAdmin {
+    pub new_admin: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(deny_unknown_fields, rename_all = "snake_case")]
 pub enum ExecuteMsg {
     RegisterClient(MsgRegisterClient),
+    ReplaceClient(MsgReplaceClient),
+    TransferAdmin(MsgTransferAdmin),
+    RecoverClient(MsgRecoverClient),
     CreateClient(MsgCreateClient),
     UpdateClient(MsgUpdateClient),
+    Misbehaviour(MsgMisbehaviour),
     ConnectionOpenInit(MsgConnectionOpenInit),
     ConnectionOpenTry(MsgConnectionOpenTry),
     ConnectionOpenAck(MsgConnectionOpenAck),
@@ -66,6 +96,14 @@ pub struct MsgUpdateClient {
     pub relayer: String,
 }
 
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MsgMisbehaviour {
+    pub client_id: u32,
+    pub client_message: Bytes,
+    pub relayer: String,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct MsgConnectionOpenInit {
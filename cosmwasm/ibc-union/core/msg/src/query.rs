@@ -14,4 +14,5 @@ pub enum QueryMsg {
     GetChannels { contract: String },
     GetBatchPackets { channel_id: u32, batch_hash: H256 },
     GetBatchReceipts { channel_id: u32, batch_hash: H256 },
+    GetAdmin {},
 }
@@ -9,7 +9,8 @@ use cosmwasm_std::{
 use cw_storage_plus::Item;
 use ibc_union_msg::{
     lightclient::{
-        QueryMsg as LightClientQuery, Status, VerifyClientMessageUpdate, VerifyCreationResponse,
+        MisbehaviourResponse, QueryMsg as LightClientQuery, Status, VerifyClientMessageUpdate,
+        VerifyCreationResponse,
     },
     module::{ExecuteMsg as ModuleMsg, IbcUnionMsg},
     msg::{
@@ -17,8 +18,9 @@ use ibc_union_msg::{
         MsgChannelCloseInit, MsgChannelOpenAck, MsgChannelOpenConfirm, MsgChannelOpenInit,
         MsgChannelOpenTry, MsgConnectionOpenAck, MsgConnectionOpenConfirm, MsgConnectionOpenInit,
         MsgConnectionOpenTry, MsgCreateClient, MsgIntentPacketRecv, MsgMigrateState,
-        MsgPacketAcknowledgement, MsgPacketRecv, MsgPacketTimeout, MsgRegisterClient,
-        MsgSendPacket, MsgUpdateClient, MsgWriteAcknowledgement,
+        MsgMisbehaviour, MsgPacketAcknowledgement, MsgPacketRecv, MsgPacketTimeout,
+        MsgRecoverClient, MsgRegisterClient, MsgReplaceClient, MsgSendPacket, MsgTransferAdmin,
+        MsgUpdateClient, MsgWriteAcknowledgement,
     },
     query::QueryMsg,
 };
@@ -37,7 +39,7 @@ use unionlabs::{
 
 use crate::{
     state::{
-        CHANNELS, CHANNEL_OWNER, CLIENT_CONSENSUS_STATES, CLIENT_IMPLS, CLIENT_REGISTRY,
+        ADMIN, CHANNELS, CHANNEL_OWNER, CLIENT_CONSENSUS_STATES, CLIENT_IMPLS, CLIENT_REGISTRY,
         CLIENT_STATES, CLIENT_TYPES, CONNECTIONS, CONTRACT_CHANNELS, NEXT_CHANNEL_ID,
         NEXT_CLIENT_ID, NEXT_CONNECTION_ID, QUERY_STORE,
     },
@@ -49,8 +51,11 @@ type ContractResult = Result<Response, ContractError>;
 pub mod events {
     pub mod client {
         pub const REGISTER: &str = "register_client";
+        pub const REPLACE: &str = "replace_client";
+        pub const RECOVER: &str = "recover_client";
         pub const CREATE: &str = "create_client";
         pub const UPDATE: &str = "update_client";
+        pub const MISBEHAVIOUR: &str = "client_misbehaviour";
     }
     pub mod connection {
         pub const OPEN_INIT: &str = "connection_open_init";
@@ -75,6 +80,9 @@ pub mod events {
         pub const BATCH_SEND: &str = "batch_send";
         pub const BATCH_ACKS: &str = "batch_acks";
     }
+    pub mod admin {
+        pub const TRANSFER: &str = "transfer_admin";
+    }
     pub mod attribute {
         pub const CLIENT_ID: &str = "client_id";
         pub const CONNECTION_ID: &str = "connection_id";
@@ -95,6 +103,8 @@ pub mod events {
         pub const PORT_ID: &str = "port_id";
         pub const COUNTERPARTY_PORT_ID: &str = "counterparty_port_id";
         pub const VERSION: &str = "version";
+        pub const ADMIN: &str = "admin";
+        pub const SUBSTITUTE_CLIENT_ID: &str = "substitute_client_id";
     }
 }
 
@@ -103,8 +113,9 @@ pub fn instantiate(
     deps: DepsMut,
     _env: Env,
     _info: MessageInfo,
-    _msg: InitMsg,
+    msg: InitMsg,
 ) -> Result<Response, ContractError> {
+    ADMIN.save(deps.storage, &deps.api.addr_validate(&msg.admin)?)?;
     NEXT_CHANNEL_ID.save(deps.storage, &0)?;
     NEXT_CONNECTION_ID.save(deps.storage, &0)?;
     NEXT_CLIENT_ID.save(deps.storage, &0)?;
@@ -125,8 +136,28 @@ pub fn execute(
             client_address,
         }) => {
             let address = deps.api.addr_validate(&client_address)?;
-            register_client(deps.branch(), client_type, address)
+            register_client(deps.branch(), info.sender, client_type, address)
+        }
+        ExecuteMsg::ReplaceClient(MsgReplaceClient {
+            client_type,
+            client_address,
+        }) => {
+            let address = deps.api.addr_validate(&client_address)?;
+            replace_client(deps.branch(), info.sender, client_type, address)
         }
+        ExecuteMsg::TransferAdmin(MsgTransferAdmin { new_admin }) => {
+            let new_admin = deps.api.addr_validate(&new_admin)?;
+            transfer_admin(deps.branch(), info.sender, new_admin)
+        }
+        ExecuteMsg::RecoverClient(MsgRecoverClient {
+            subject_client_id,
+            substitute_client_id,
+        }) => recover_client(
+            deps.branch(),
+            info.sender,
+            subject_client_id,
+            substitute_client_id,
+        ),
         ExecuteMsg::CreateClient(MsgCreateClient {
             client_type,
             client_state_bytes,
@@ -150,6 +181,14 @@ pub fn execute(
             let relayer = deps.api.addr_validate(&relayer)?;
             update_client(deps.branch(), client_id, client_message.to_vec(), relayer)
         }
+        ExecuteMsg::Misbehaviour(MsgMisbehaviour {
+            client_id,
+            client_message,
+            relayer,
+        }) => {
+            let relayer = deps.api.addr_validate(&relayer)?;
+            misbehaviour(deps.branch(), client_id, client_message.to_vec(), relayer)
+        }
         ExecuteMsg::ConnectionOpenInit(MsgConnectionOpenInit {
             client_id,
             counterparty_client_id,
@@ -456,10 +495,25 @@ fn migrate_state(
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    /// The admin of the contract. This must be provided if and only if the contract was
+    /// instantiated without an admin, the admin of the contract is otherwise changed with
+    /// [`ExecuteMsg::TransferAdmin`].
+    #[serde(default)]
+    pub admin: Option<String>,
+}
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+pub fn migrate(deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    match (ADMIN.may_load(deps.storage)?, msg.admin) {
+        (Some(admin), Some(_)) => return Err(ContractError::MigrateAdminAlreadySet { admin }),
+        (None, None) => return Err(ContractError::MigrateAdminMissing),
+        (None, Some(admin)) => {
+            let admin = deps.api.addr_validate(&admin)?;
+            ADMIN.save(deps.storage, &admin)?;
+        }
+        (Some(_), None) => {}
+    }
     Ok(Response::new())
 }
 
@@ -719,11 +773,30 @@ fn commit_packets(packets: &[Packet]) -> H256 {
     commit(packets.abi_encode())
 }
 
+fn ensure_admin(deps: Deps, caller: Addr) -> Result<(), ContractError> {
+    let admin = ADMIN.load(deps.storage)?;
+    if caller != admin {
+        return Err(ContractError::Unauthorized { caller, admin });
+    }
+    Ok(())
+}
+
+fn transfer_admin(deps: DepsMut, sender: Addr, new_admin: Addr) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), sender)?;
+    ADMIN.save(deps.storage, &new_admin)?;
+
+    Ok(Response::new().add_event(
+        Event::new(events::admin::TRANSFER).add_attribute(events::attribute::ADMIN, new_admin),
+    ))
+}
+
 fn register_client(
     deps: DepsMut,
+    sender: Addr,
     client_type: String,
     client_address: Addr,
 ) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), sender)?;
     if CLIENT_REGISTRY
         .may_load(deps.storage, &client_type)?
         .is_some()
@@ -739,6 +812,159 @@ fn register_client(
     ))
 }
 
+/// Replace the implementation of a registered client type. Only clients created after the
+/// replacement use the new implementation.
+fn replace_client(
+    deps: DepsMut,
+    sender: Addr,
+    client_type: String,
+    client_address: Addr,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), sender)?;
+    if !CLIENT_REGISTRY.has(deps.storage, &client_type) {
+        return Err(ContractError::ClientTypeNotFound { client_type });
+    }
+    CLIENT_REGISTRY.save(deps.storage, &client_type, &client_address)?;
+
+    Ok(Response::new().add_event(
+        Event::new(events::client::REPLACE)
+            .add_attribute(events::attribute::CLIENT_TYPE, client_type)
+            .add_attribute(events::attribute::CLIENT_ADDRESS, client_address),
+    ))
+}
+
+fn query_status(deps: Deps, client_id: u32) -> Result<Status, ContractError> {
+    Ok(deps.querier.query_wasm_smart::<Status>(
+        client_impl(deps, client_id)?,
+        &LightClientQuery::GetStatus { client_id },
+    )?)
+}
+
+/// Recover an expired or frozen client by copying the latest state of an active substitute
+/// client of the same type into it. The connections and channels on top of the subject client
+/// are preserved.
+fn recover_client(
+    mut deps: DepsMut,
+    sender: Addr,
+    subject_client_id: u32,
+    substitute_client_id: u32,
+) -> Result<Response, ContractError> {
+    ensure_admin(deps.as_ref(), sender)?;
+
+    let subject_client_type = CLIENT_TYPES.load(deps.storage, subject_client_id)?;
+    let substitute_client_type = CLIENT_TYPES.load(deps.storage, substitute_client_id)?;
+    if subject_client_type != substitute_client_type {
+        return Err(ContractError::RecoverClientTypeMismatch {
+            subject_client_id,
+            subject_client_type,
+            substitute_client_id,
+            substitute_client_type,
+        });
+    }
+
+    if matches!(
+        query_status(deps.as_ref(), subject_client_id)?,
+        Status::Active
+    ) {
+        return Err(ContractError::RecoverActiveClient {
+            client_id: subject_client_id,
+        });
+    }
+    if !matches!(
+        query_status(deps.as_ref(), substitute_client_id)?,
+        Status::Active
+    ) {
+        return Err(ContractError::RecoverInactiveSubstitute {
+            client_id: substitute_client_id,
+        });
+    }
+
+    let subject_client_impl = client_impl(deps.as_ref(), subject_client_id)?;
+    let substitute_client_impl = client_impl(deps.as_ref(), substitute_client_id)?;
+
+    // the consensus states of the subject above the substitute height would otherwise remain
+    // usable after the recovery
+    let subject_height = deps.querier.query_wasm_smart::<u64>(
+        &subject_client_impl,
+        &LightClientQuery::GetLatestHeight {
+            client_id: subject_client_id,
+        },
+    )?;
+    let height = deps.querier.query_wasm_smart::<u64>(
+        &substitute_client_impl,
+        &LightClientQuery::GetLatestHeight {
+            client_id: substitute_client_id,
+        },
+    )?;
+    if height <= subject_height {
+        return Err(ContractError::RecoverSubstituteNotAhead {
+            subject_client_id,
+            subject_height,
+            substitute_client_id,
+            substitute_height: height,
+        });
+    }
+
+    let subject_chain_id = deps.querier.query_wasm_smart::<String>(
+        &subject_client_impl,
+        &LightClientQuery::GetCounterpartyChainId {
+            client_id: subject_client_id,
+        },
+    )?;
+    let substitute_chain_id = deps.querier.query_wasm_smart::<String>(
+        &substitute_client_impl,
+        &LightClientQuery::GetCounterpartyChainId {
+            client_id: substitute_client_id,
+        },
+    )?;
+    if subject_chain_id != substitute_chain_id {
+        return Err(ContractError::RecoverCounterpartyChainIdMismatch {
+            subject_client_id,
+            subject_chain_id,
+            substitute_client_id,
+            substitute_chain_id,
+        });
+    }
+
+    let client_state = CLIENT_STATES.load(deps.storage, substitute_client_id)?;
+    let consensus_state =
+        CLIENT_CONSENSUS_STATES.load(deps.storage, (substitute_client_id, height))?;
+
+    // the substitute may use a newer implementation if the client type has been replaced
+    CLIENT_IMPLS.save(deps.storage, subject_client_id, &substitute_client_impl)?;
+    CLIENT_STATES.save(deps.storage, subject_client_id, &client_state)?;
+    CLIENT_CONSENSUS_STATES.save(deps.storage, (subject_client_id, height), &consensus_state)?;
+
+    store_commit(
+        deps.branch(),
+        &ClientStatePath {
+            client_id: subject_client_id,
+        }
+        .key(),
+        &commit(client_state),
+    )?;
+    store_commit(
+        deps.branch(),
+        &ConsensusStatePath {
+            client_id: subject_client_id,
+            height,
+        }
+        .key(),
+        &commit(consensus_state),
+    )?;
+
+    Ok(
+        Response::new().add_event(Event::new(events::client::RECOVER).add_attributes([
+            (events::attribute::CLIENT_ID, subject_client_id.to_string()),
+            (
+                events::attribute::SUBSTITUTE_CLIENT_ID,
+                substitute_client_id.to_string(),
+            ),
+            (events::attribute::COUNTERPARTY_HEIGHT, height.to_string()),
+        ])),
+    )
+}
+
 fn create_client(
     mut deps: DepsMut,
     client_type: String,
@@ -847,6 +1073,37 @@ fn update_client(
     )
 }
 
+fn misbehaviour(
+    mut deps: DepsMut,
+    client_id: u32,
+    client_message: Vec<u8>,
+    _relayer: Addr,
+) -> Result<Response, ContractError> {
+    let client_impl = client_impl(deps.as_ref(), client_id)?;
+    let response = deps.querier.query_wasm_smart::<MisbehaviourResponse>(
+        &client_impl,
+        &LightClientQuery::Misbehaviour {
+            client_id,
+            message: client_message.into(),
+        },
+    )?;
+    CLIENT_STATES.save(
+        deps.storage,
+        client_id,
+        &response.client_state.to_vec().into(),
+    )?;
+
+    store_commit(
+        deps.branch(),
+        &ClientStatePath { client_id }.key(),
+        &commit(response.client_state),
+    )?;
+    Ok(Response::new().add_event(
+        Event::new(events::client::MISBEHAVIOUR)
+            .add_attributes([(events::attribute::CLIENT_ID, client_id.to_string())]),
+    ))
+}
+
 fn connection_open_init(
     mut deps: DepsMut,
     client_id: u32,
@@ -1390,7 +1647,7 @@ fn channel_close_confirm(
         ]))
         .add_message(wasm_execute(
             port_id,
-            &ModuleMsg::IbcUnionMsg(IbcUnionMsg::OnChannelOpenConfirm {
+            &ModuleMsg::IbcUnionMsg(IbcUnionMsg::OnChannelCloseConfirm {
                 channel_id,
                 relayer: relayer.into(),
             }),
@@ -1812,6 +2069,7 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> Result<Binary, ContractErr
             let channels = CONTRACT_CHANNELS.load(deps.storage, contract)?;
             Ok(to_json_binary(&channels)?)
         }
+        QueryMsg::GetAdmin {} => Ok(to_json_binary(&ADMIN.load(deps.storage)?)?),
         QueryMsg::GetChannel { channel_id } => {
             let channel = CHANNELS.load(deps.storage, channel_id)?;
             Ok(to_json_binary(&channel)?)
@@ -25,6 +25,70 @@ pub enum ContractError {
         ContractErrorKind::from(self)
     )]
     ClientTypeAlreadyExists,
+    #[error(
+        "{} the client type {client_type} has not been registered",
+        ContractErrorKind::from(self)
+    )]
+    ClientTypeNotFound { client_type: String },
+    #[error(
+        "{} caller {caller} is not the admin {admin}",
+        ContractErrorKind::from(self)
+    )]
+    Unauthorized { caller: Addr, admin: Addr },
+    #[error(
+        "{} the admin is already set to {admin}, use the transfer admin message to change it",
+        ContractErrorKind::from(self)
+    )]
+    MigrateAdminAlreadySet { admin: Addr },
+    #[error(
+        "{} the contract has no admin, an admin must be provided",
+        ContractErrorKind::from(self)
+    )]
+    MigrateAdminMissing,
+    #[error(
+        "{} the subject client {subject_client_id} of type {subject_client_type} can't be \
+        recovered with the substitute client {substitute_client_id} of type \
+        {substitute_client_type}",
+        ContractErrorKind::from(self)
+    )]
+    RecoverClientTypeMismatch {
+        subject_client_id: u32,
+        subject_client_type: String,
+        substitute_client_id: u32,
+        substitute_client_type: String,
+    },
+    #[error(
+        "{} the subject client {client_id} is active and can't be recovered",
+        ContractErrorKind::from(self)
+    )]
+    RecoverActiveClient { client_id: u32 },
+    #[error(
+        "{} the substitute client {client_id} is not active",
+        ContractErrorKind::from(self)
+    )]
+    RecoverInactiveSubstitute { client_id: u32 },
+    #[error(
+        "{} the substitute client {substitute_client_id} at height {substitute_height} must be \
+        ahead of the subject client {subject_client_id} at height {subject_height}",
+        ContractErrorKind::from(self)
+    )]
+    RecoverSubstituteNotAhead {
+        subject_client_id: u32,
+        subject_height: u64,
+        substitute_client_id: u32,
+        substitute_height: u64,
+    },
+    #[error(
+        "{} the subject client {subject_client_id} tracks {subject_chain_id} but the substitute \
+        client {substitute_client_id} tracks {substitute_chain_id}",
+        ContractErrorKind::from(self)
+    )]
+    RecoverCounterpartyChainIdMismatch {
+        subject_client_id: u32,
+        subject_chain_id: String,
+        substitute_client_id: u32,
+        substitute_chain_id: String,
+    },
     #[error("{} an arithmetic overflow occurred", ContractErrorKind::from(self))]
     ArithmeticOverflow,
 
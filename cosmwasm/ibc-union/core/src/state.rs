@@ -6,6 +6,9 @@ use ibc_union_spec::types::{Channel, Connection};
 
 pub const QUERY_STORE: Item<Binary> = Item::new("query_store");
 
+// The admin allowed to register and replace client implementations
+pub const ADMIN: Item<Addr> = Item::new("admin");
+
 pub const CHANNEL_OWNER: Map<u32, Addr> = Map::new("channel_owner");
 
 pub const CHANNELS: Map<u32, Channel> = Map::new("channels");
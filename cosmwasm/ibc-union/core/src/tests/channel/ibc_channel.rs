@@ -21,7 +21,9 @@ fn channel_open_init_ok() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -62,7 +64,9 @@ fn channel_open_init_channel_claimed() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -94,7 +98,9 @@ fn channel_open_init_commitment_saved() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -132,7 +138,9 @@ fn channel_open_try_ok() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -180,7 +188,9 @@ fn channel_open_try_invalid_state() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -237,7 +247,9 @@ fn channel_open_try_channel_claimed() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -290,7 +302,9 @@ fn channel_open_try_commitment_saved() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -349,7 +363,9 @@ fn channel_open_ack_ok() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -407,7 +423,9 @@ fn channel_open_ack_not_found() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -457,7 +475,9 @@ fn channel_open_ack_commitment_saved() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -526,7 +546,9 @@ fn channel_open_confirm_ok() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -588,7 +610,9 @@ fn channel_open_confirm_not_found() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -635,7 +659,9 @@ fn channel_open_confirm_commitment_saved() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -1,17 +1,21 @@
 use cosmwasm_std::{
-    testing::{message_info, mock_dependencies, mock_env},
-    to_json_binary, Addr, Event,
+    testing::{message_info, mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage},
+    to_json_binary, Addr, DepsMut, Event, OwnedDeps, Response,
 };
 use ibc_union_msg::{
     lightclient::{
-        QueryMsg as LightClientQueryMsg, VerifyClientMessageUpdate, VerifyCreationResponse,
+        MisbehaviourResponse, QueryMsg as LightClientQueryMsg, Status, VerifyClientMessageUpdate,
+        VerifyCreationResponse,
+    },
+    msg::{
+        ExecuteMsg, InitMsg, MsgMisbehaviour, MsgRecoverClient, MsgRegisterClient,
+        MsgReplaceClient, MsgTransferAdmin, MsgUpdateClient,
     },
-    msg::{ExecuteMsg, InitMsg, MsgUpdateClient},
 };
 
 use super::*;
 use crate::{
-    contract::{events, execute, instantiate},
+    contract::{events, execute, instantiate, migrate, MigrateMsg},
     ContractError,
 };
 
@@ -26,9 +30,22 @@ fn new_client_registered_event(client_type: &str, client_address: &Addr) -> Even
         .add_attribute(events::attribute::CLIENT_ADDRESS, client_address)
 }
 
+fn instantiate_with_admin(deps: DepsMut) {
+    instantiate(
+        deps,
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
+    )
+    .expect("instantiate ok");
+}
+
 #[test]
 fn register_client_ok() {
     let mut deps = mock_dependencies();
+    instantiate_with_admin(deps.as_mut());
     let res = register_client(deps.as_mut()).unwrap();
 
     assert!(res
@@ -48,6 +65,7 @@ fn register_client_ok() {
 #[test]
 fn register_client_fails_when_duplicate() {
     let mut deps = mock_dependencies();
+    instantiate_with_admin(deps.as_mut());
     register_client(deps.as_mut()).unwrap();
     assert_eq!(
         register_client(deps.as_mut()),
@@ -55,6 +73,163 @@ fn register_client_fails_when_duplicate() {
     );
 }
 
+#[test]
+fn register_client_fails_when_not_admin() {
+    let mut deps = mock_dependencies();
+    instantiate_with_admin(deps.as_mut());
+    let caller = mock_addr("notadmin");
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&caller, &[]),
+            ExecuteMsg::RegisterClient(MsgRegisterClient {
+                client_type: CLIENT_TYPE.to_owned(),
+                client_address: mock_addr(CLIENT_ADDRESS).into_string(),
+            }),
+        ),
+        Err(ContractError::Unauthorized {
+            caller,
+            admin: mock_addr(SENDER)
+        })
+    );
+}
+
+#[test]
+fn register_client_ok_after_migrate() {
+    let mut deps = mock_dependencies();
+    // Contracts instantiated before the admin was introduced have no admin
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            admin: Some(mock_addr(SENDER).into_string()),
+        },
+    )
+    .expect("migrate ok");
+
+    register_client(deps.as_mut()).expect("register client ok");
+    assert_eq!(
+        crate::state::CLIENT_REGISTRY
+            .load(&deps.storage, CLIENT_TYPE)
+            .unwrap(),
+        mock_addr(CLIENT_ADDRESS)
+    );
+}
+
+#[test]
+fn migrate_keeps_admin() {
+    let mut deps = mock_dependencies();
+    instantiate_with_admin(deps.as_mut());
+    migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }).expect("migrate ok");
+
+    assert_eq!(
+        crate::state::ADMIN.load(&deps.storage).unwrap(),
+        mock_addr(SENDER)
+    );
+}
+
+#[test]
+fn migrate_fails_when_admin_already_set() {
+    let mut deps = mock_dependencies();
+    instantiate_with_admin(deps.as_mut());
+
+    assert_eq!(
+        migrate(
+            deps.as_mut(),
+            mock_env(),
+            MigrateMsg {
+                admin: Some(mock_addr("newadmin").into_string()),
+            },
+        ),
+        Err(ContractError::MigrateAdminAlreadySet {
+            admin: mock_addr(SENDER)
+        })
+    );
+    assert_eq!(
+        crate::state::ADMIN.load(&deps.storage).unwrap(),
+        mock_addr(SENDER)
+    );
+}
+
+#[test]
+fn migrate_fails_when_admin_missing() {
+    let mut deps = mock_dependencies();
+
+    assert_eq!(
+        migrate(deps.as_mut(), mock_env(), MigrateMsg { admin: None }),
+        Err(ContractError::MigrateAdminMissing)
+    );
+    assert!(!crate::state::ADMIN.exists(&deps.storage));
+}
+
+#[test]
+fn replace_client_ok() {
+    let mut deps = mock_dependencies();
+    instantiate_with_admin(deps.as_mut());
+    register_client(deps.as_mut()).unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        ExecuteMsg::ReplaceClient(MsgReplaceClient {
+            client_type: CLIENT_TYPE.to_owned(),
+            client_address: mock_addr("newclient").into_string(),
+        }),
+    )
+    .expect("replace client ok");
+
+    assert_eq!(
+        crate::state::CLIENT_REGISTRY
+            .load(&deps.storage, CLIENT_TYPE)
+            .unwrap(),
+        mock_addr("newclient")
+    );
+}
+
+#[test]
+fn replace_client_fails_when_not_registered() {
+    let mut deps = mock_dependencies();
+    instantiate_with_admin(deps.as_mut());
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            message_info(&mock_addr(SENDER), &[]),
+            ExecuteMsg::ReplaceClient(MsgReplaceClient {
+                client_type: CLIENT_TYPE.to_owned(),
+                client_address: mock_addr("newclient").into_string(),
+            }),
+        ),
+        Err(ContractError::ClientTypeNotFound {
+            client_type: CLIENT_TYPE.to_owned()
+        })
+    );
+}
+
+#[test]
+fn transfer_admin_ok() {
+    let mut deps = mock_dependencies();
+    instantiate_with_admin(deps.as_mut());
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        ExecuteMsg::TransferAdmin(MsgTransferAdmin {
+            new_admin: mock_addr("newadmin").into_string(),
+        }),
+    )
+    .expect("transfer admin ok");
+
+    assert_eq!(
+        register_client(deps.as_mut()),
+        Err(ContractError::Unauthorized {
+            caller: mock_addr(SENDER),
+            admin: mock_addr("newadmin")
+        })
+    );
+}
+
 #[test]
 fn create_client_ok() {
     let mut deps = mock_dependencies();
@@ -64,7 +239,9 @@ fn create_client_ok() {
         deps.as_mut(),
         mock_env(),
         message_info(&sender, &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -89,7 +266,9 @@ fn create_client_commitments_saved() {
         deps.as_mut(),
         mock_env(),
         message_info(&sender, &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .expect("instantiate ok");
     deps.querier
@@ -152,7 +331,9 @@ fn update_client_ok() {
         deps.as_mut(),
         mock_env(),
         message_info(&sender, &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .expect("instantiate ok");
     deps.querier
@@ -209,7 +390,9 @@ fn update_client_ko() {
         deps.as_mut(),
         mock_env(),
         message_info(&sender, &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .expect("instantiate ok");
     deps.querier
@@ -260,7 +443,9 @@ fn update_client_commitments_saved() {
         deps.as_mut(),
         mock_env(),
         message_info(&sender, &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .expect("instantiate ok");
     deps.querier
@@ -320,3 +505,308 @@ fn update_client_commitments_saved() {
         vec![3, 2, 1]
     );
 }
+
+#[test]
+fn misbehaviour_client_state_saved() {
+    let mut deps = mock_dependencies();
+    let sender = mock_addr(SENDER);
+
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&sender, &[]),
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
+    )
+    .expect("instantiate ok");
+    deps.querier
+        .update_wasm(wasm_query_handler(|msg| match msg {
+            LightClientQueryMsg::VerifyCreation { .. } => to_json_binary(&VerifyCreationResponse {
+                latest_height: 1,
+                counterparty_chain_id: "testchain".to_owned(),
+            }),
+            LightClientQueryMsg::Misbehaviour { .. } => to_json_binary(&MisbehaviourResponse {
+                client_state: vec![4, 5, 6].into(),
+            }),
+            msg => panic!("should not be called: {:?}", msg),
+        }));
+
+    register_client(deps.as_mut()).expect("register client ok");
+    let res = create_client(deps.as_mut()).expect("create client ok");
+    let client_id: u32 = res
+        .events
+        .iter()
+        .find(|event| event.ty.eq(events::client::CREATE))
+        .expect("create client event exists")
+        .attributes
+        .iter()
+        .find(|attribute| attribute.key.eq(events::attribute::CLIENT_ID))
+        .expect("client type attribute exists")
+        .value
+        .parse()
+        .expect("client type string is u32");
+
+    let msg = ExecuteMsg::Misbehaviour(MsgMisbehaviour {
+        client_id,
+        client_message: vec![3, 2, 1].into(),
+        relayer: mock_addr(RELAYER).into_string(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        message_info(&mock_addr(SENDER), &[]),
+        msg,
+    )
+    .expect("misbehaviour ok");
+
+    assert!(res
+        .events
+        .iter()
+        .any(|event| event.ty.eq(events::client::MISBEHAVIOUR)));
+    assert_eq!(
+        crate::state::CLIENT_STATES
+            .load(&deps.storage, client_id)
+            .unwrap(),
+        vec![4, 5, 6]
+    );
+}
+
+const SUBJECT_CLIENT_ID: u32 = 1;
+const SUBSTITUTE_CLIENT_ID: u32 = 2;
+
+/// The light client state of the clients used in a recovery. The subject client is at height 1.
+struct Recovery {
+    subject_status: fn() -> Status,
+    substitute_status: fn() -> Status,
+    substitute_height: u64,
+    substitute_chain_id: &'static str,
+}
+
+impl Default for Recovery {
+    fn default() -> Self {
+        Self {
+            subject_status: || Status::Expired,
+            substitute_status: || Status::Active,
+            substitute_height: 2,
+            substitute_chain_id: "testchain",
+        }
+    }
+}
+
+/// Instantiates the contract and creates a subject client and a substitute client of the same
+/// type, as described by `recovery`.
+fn setup_recovery(deps: &mut OwnedDeps<MockStorage, MockApi, MockQuerier>, recovery: Recovery) {
+    instantiate_with_admin(deps.as_mut());
+    let Recovery {
+        subject_status,
+        substitute_status,
+        substitute_height,
+        substitute_chain_id,
+    } = recovery;
+    deps.querier
+        .update_wasm(wasm_query_handler(move |msg| match msg {
+            LightClientQueryMsg::VerifyCreation { .. } => to_json_binary(&VerifyCreationResponse {
+                latest_height: 1,
+                counterparty_chain_id: "testchain".to_owned(),
+            }),
+            LightClientQueryMsg::GetStatus {
+                client_id: SUBJECT_CLIENT_ID,
+            } => to_json_binary(&subject_status()),
+            LightClientQueryMsg::GetStatus { .. } => to_json_binary(&substitute_status()),
+            LightClientQueryMsg::GetLatestHeight {
+                client_id: SUBJECT_CLIENT_ID,
+            } => to_json_binary(&1),
+            LightClientQueryMsg::GetLatestHeight { .. } => to_json_binary(&substitute_height),
+            LightClientQueryMsg::GetCounterpartyChainId {
+                client_id: SUBJECT_CLIENT_ID,
+            } => to_json_binary("testchain"),
+            LightClientQueryMsg::GetCounterpartyChainId { .. } => {
+                to_json_binary(substitute_chain_id)
+            }
+            msg => panic!("should not be called: {:?}", msg),
+        }));
+
+    register_client(deps.as_mut()).expect("register client ok");
+    create_client(deps.as_mut()).expect("create subject client ok");
+    create_client(deps.as_mut()).expect("create substitute client ok");
+
+    crate::state::CLIENT_STATES
+        .save(
+            &mut deps.storage,
+            SUBSTITUTE_CLIENT_ID,
+            &vec![4, 5, 6].into(),
+        )
+        .unwrap();
+    crate::state::CLIENT_CONSENSUS_STATES
+        .save(
+            &mut deps.storage,
+            (SUBSTITUTE_CLIENT_ID, substitute_height),
+            &vec![7, 8, 9].into(),
+        )
+        .unwrap();
+}
+
+fn recover_client(deps: DepsMut, sender: &str) -> Result<Response, ContractError> {
+    execute(
+        deps,
+        mock_env(),
+        message_info(&mock_addr(sender), &[]),
+        ExecuteMsg::RecoverClient(MsgRecoverClient {
+            subject_client_id: SUBJECT_CLIENT_ID,
+            substitute_client_id: SUBSTITUTE_CLIENT_ID,
+        }),
+    )
+}
+
+#[test]
+fn recover_client_ok() {
+    let mut deps = mock_dependencies();
+    setup_recovery(&mut deps, Recovery::default());
+
+    let res = recover_client(deps.as_mut(), SENDER).expect("recover client ok");
+
+    assert!(res
+        .events
+        .iter()
+        .any(|event| event.ty.eq(events::client::RECOVER)));
+    assert_eq!(
+        crate::state::CLIENT_STATES
+            .load(&deps.storage, SUBJECT_CLIENT_ID)
+            .unwrap(),
+        vec![4, 5, 6]
+    );
+    assert_eq!(
+        crate::state::CLIENT_CONSENSUS_STATES
+            .load(&deps.storage, (SUBJECT_CLIENT_ID, 2))
+            .unwrap(),
+        vec![7, 8, 9]
+    );
+}
+
+#[test]
+fn recover_client_fails_when_subject_active() {
+    let mut deps = mock_dependencies();
+    setup_recovery(
+        &mut deps,
+        Recovery {
+            subject_status: || Status::Active,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(
+        recover_client(deps.as_mut(), SENDER),
+        Err(ContractError::RecoverActiveClient {
+            client_id: SUBJECT_CLIENT_ID
+        })
+    );
+}
+
+#[test]
+fn recover_client_fails_when_not_admin() {
+    let mut deps = mock_dependencies();
+    setup_recovery(
+        &mut deps,
+        Recovery {
+            subject_status: || Status::Frozen,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(
+        recover_client(deps.as_mut(), "notadmin"),
+        Err(ContractError::Unauthorized {
+            caller: mock_addr("notadmin"),
+            admin: mock_addr(SENDER)
+        })
+    );
+}
+
+#[test]
+fn recover_client_fails_when_substitute_inactive() {
+    let mut deps = mock_dependencies();
+    setup_recovery(
+        &mut deps,
+        Recovery {
+            substitute_status: || Status::Expired,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(
+        recover_client(deps.as_mut(), SENDER),
+        Err(ContractError::RecoverInactiveSubstitute {
+            client_id: SUBSTITUTE_CLIENT_ID
+        })
+    );
+}
+
+#[test]
+fn recover_client_fails_when_client_type_mismatch() {
+    let mut deps = mock_dependencies();
+    setup_recovery(&mut deps, Recovery::default());
+    crate::state::CLIENT_TYPES
+        .save(&mut deps.storage, SUBSTITUTE_CLIENT_ID, &"other".to_owned())
+        .unwrap();
+
+    assert_eq!(
+        recover_client(deps.as_mut(), SENDER),
+        Err(ContractError::RecoverClientTypeMismatch {
+            subject_client_id: SUBJECT_CLIENT_ID,
+            subject_client_type: CLIENT_TYPE.to_owned(),
+            substitute_client_id: SUBSTITUTE_CLIENT_ID,
+            substitute_client_type: "other".to_owned(),
+        })
+    );
+}
+
+#[test]
+fn recover_client_fails_when_substitute_not_ahead() {
+    let mut deps = mock_dependencies();
+    setup_recovery(
+        &mut deps,
+        Recovery {
+            substitute_height: 1,
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(
+        recover_client(deps.as_mut(), SENDER),
+        Err(ContractError::RecoverSubstituteNotAhead {
+            subject_client_id: SUBJECT_CLIENT_ID,
+            subject_height: 1,
+            substitute_client_id: SUBSTITUTE_CLIENT_ID,
+            substitute_height: 1,
+        })
+    );
+    assert_eq!(
+        crate::state::CLIENT_STATES
+            .load(&deps.storage, SUBJECT_CLIENT_ID)
+            .unwrap(),
+        vec![1, 2, 3]
+    );
+}
+
+#[test]
+fn recover_client_fails_when_counterparty_chain_id_mismatch() {
+    let mut deps = mock_dependencies();
+    setup_recovery(
+        &mut deps,
+        Recovery {
+            substitute_chain_id: "otherchain",
+            ..Default::default()
+        },
+    );
+
+    assert_eq!(
+        recover_client(deps.as_mut(), SENDER),
+        Err(ContractError::RecoverCounterpartyChainIdMismatch {
+            subject_client_id: SUBJECT_CLIENT_ID,
+            subject_chain_id: "testchain".to_owned(),
+            substitute_client_id: SUBSTITUTE_CLIENT_ID,
+            substitute_chain_id: "otherchain".to_owned(),
+        })
+    );
+}
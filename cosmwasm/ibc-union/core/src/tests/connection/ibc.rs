@@ -18,7 +18,9 @@ fn connection_open_init_ok() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -53,7 +55,9 @@ fn connection_open_init_commitment_saved() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -86,7 +90,9 @@ fn connection_open_try_ok() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -126,7 +132,9 @@ fn connection_open_try_client_not_found() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -175,7 +183,9 @@ fn connection_open_try_commitment_saved() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -225,7 +235,9 @@ fn connection_open_ack_ok() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -270,7 +282,9 @@ fn connection_open_ack_commitment_saved() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -320,7 +334,9 @@ fn connection_open_confirm_ok() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -364,7 +380,9 @@ fn connection_open_try_confirm_commitment_saved() {
         deps.as_mut(),
         mock_env(),
         message_info(&mock_addr(SENDER), &[]),
-        InitMsg {},
+        InitMsg {
+            admin: mock_addr(SENDER).into_string(),
+        },
     )
     .unwrap();
     deps.querier
@@ -78,7 +78,10 @@ impl IbcClient for BerachainLightClient {
         client_state.chain_id.to_string()
     }
 
-    fn status(_client_state: &Self::ClientState) -> Status {
+    fn status(
+        _ctx: ibc_union_light_client::IbcClientCtx<Self>,
+        _client_state: &Self::ClientState,
+    ) -> Status {
         // FIXME: expose the ctx to this call to allow threading this call to L1
         // client. generally, we want to thread if a client is an L2 so always
         // provide the ctx?
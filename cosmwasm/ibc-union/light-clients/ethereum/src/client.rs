@@ -85,7 +85,7 @@ impl ibc_union_light_client::IbcClient for EthereumLightClient {
         client_state.chain_id.to_string()
     }
 
-    fn status(client_state: &Self::ClientState) -> Status {
+    fn status(_ctx: IbcClientCtx<Self>, client_state: &Self::ClientState) -> Status {
         if client_state.frozen_height.height() != 0 {
             Status::Frozen
         } else {
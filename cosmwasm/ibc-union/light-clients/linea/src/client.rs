@@ -1,47 +1,26 @@
-use cosmwasm_std::{Deps, DepsMut, Env};
-use ethereum_light_client::client::{canonicalize_stored_value, check_commitment_key};
+use cosmwasm_std::Empty;
+use ethereum_light_client::client::{check_commitment_key, EthereumLightClient};
 use gnark_mimc::new_mimc_constants_bls12_377;
-use ics008_wasm_client::{
-    storage_utils::{
-        read_client_state, read_consensus_state, save_client_state, save_consensus_state,
-        update_client_state,
-    },
-    IbcClient, IbcClientError, Status, StorageState,
-};
-use linea_light_client_types::{
-    client_state::ClientState, consensus_state::ConsensusState, header::Header,
+use ibc_union_light_client::{IbcClient, IbcClientCtx, IbcClientError};
+use ibc_union_msg::lightclient::Status;
+use linea_light_client_types::{ClientState, ConsensusState, Header};
+use linea_types::{
+    account::ZkAccount,
+    proof::{InclusionProof, MerkleProof, NonInclusionProof},
 };
 use unionlabs::{
-    cosmwasm::wasm::union::custom_query::{query_consensus_state, UnionCustomQuery},
-    encoding::{DecodeAs, Proto},
-    ethereum::keccak256,
-    ibc::{
-        core::{
-            client::{genesis_metadata::GenesisMetadata, height::Height},
-            commitment::merkle_path::MerklePath,
-        },
-        lightclients::{ethereum, wasm},
-    },
-    linea::{
-        account::ZkAccount,
-        proof::{InclusionProof, NonInclusionProof},
-    },
+    encoding::Bincode,
     primitives::{H256, U256},
 };
 
 use crate::errors::Error;
 
-type WasmClientState = wasm::client_state::ClientState<ClientState>;
-type WasmConsensusState = wasm::consensus_state::ConsensusState<ConsensusState>;
-type WasmL1ConsensusState =
-    wasm::consensus_state::ConsensusState<ethereum::consensus_state::ConsensusState>;
-
-pub struct LineaLightClient;
+pub enum LineaLightClient {}
 
 impl IbcClient for LineaLightClient {
     type Error = Error;
 
-    type CustomQuery = UnionCustomQuery;
+    type CustomQuery = Empty;
 
     type Header = Header;
 
@@ -51,208 +30,174 @@ impl IbcClient for LineaLightClient {
 
     type ConsensusState = ConsensusState;
 
-    type Encoding = Proto;
+    type StorageProof = MerkleProof;
+
+    type Encoding = Bincode;
 
     fn verify_membership(
-        deps: Deps<Self::CustomQuery>,
-        height: Height,
-        _delay_time_period: u64,
-        _delay_block_period: u64,
-        proof: Vec<u8>,
-        mut path: MerklePath,
-        value: ics008_wasm_client::StorageState,
+        ctx: IbcClientCtx<Self>,
+        height: u64,
+        key: Vec<u8>,
+        storage_proof: Self::StorageProof,
+        value: Vec<u8>,
     ) -> Result<(), IbcClientError<Self>> {
-        let consensus_state: WasmConsensusState =
-            read_consensus_state(deps, &height)?.ok_or(Error::ConsensusStateNotFound(height))?;
-        let client_state: WasmClientState = read_client_state(deps)?;
-
-        let path = path.key_path.pop().ok_or(Error::EmptyIbcPath)?;
-
-        // This storage root is verified during the header update, so we don't need to verify it again.
-        let storage_root = consensus_state.data.ibc_storage_root;
-
-        match value {
-            StorageState::Occupied(value) => {
-                let inclusion_proof = InclusionProof::decode_as::<Proto>(&proof)
-                    .map_err(Error::InclusionProofDecode)?;
-                do_verify_membership(path, storage_root, inclusion_proof, value)?
-            }
-            StorageState::Empty => {
-                let noninclusion_proof = NonInclusionProof::decode_as::<Proto>(&proof)
-                    .map_err(Error::InclusionProofDecode)?;
-                do_verify_non_membership(path, storage_root, noninclusion_proof)?
-            }
+        let consensus_state = ctx.read_self_consensus_state(height)?;
+        match storage_proof {
+            MerkleProof::Inclusion(inclusion_proof) => Ok(verify_membership(
+                key,
+                consensus_state.ibc_storage_root,
+                inclusion_proof,
+                value,
+            )?),
+            MerkleProof::NonInclusion(_) => Err(Error::ExpectedInclusionProof.into()),
         }
-
-        Ok(())
     }
 
-    fn verify_header(
-        deps: Deps<Self::CustomQuery>,
-        env: Env,
-        header: Self::Header,
+    fn verify_non_membership(
+        ctx: IbcClientCtx<Self>,
+        height: u64,
+        key: Vec<u8>,
+        storage_proof: Self::StorageProof,
     ) -> Result<(), IbcClientError<Self>> {
-        let client_state: WasmClientState = read_client_state(deps)?;
-        let l1_consensus_state = query_consensus_state::<WasmL1ConsensusState>(
-            deps,
-            &env,
-            client_state.data.l1_client_id.clone(),
-            header.l1_height,
-        )
-        .map_err(Error::CustomQuery)?;
-        linea_verifier::verify_header(
-            client_state.data,
-            header,
-            l1_consensus_state.data.state_root,
-        )
-        .map_err(Error::Verify)?;
-        Ok(())
-    }
-
-    fn verify_misbehaviour(
-        _deps: Deps<Self::CustomQuery>,
-        _env: Env,
-        _misbehaviour: Self::Misbehaviour,
-    ) -> Result<(), IbcClientError<Self>> {
-        Err(Error::Unimplemented.into())
+        let consensus_state = ctx.read_self_consensus_state(height)?;
+        match storage_proof {
+            MerkleProof::NonInclusion(noninclusion_proof) => Ok(verify_non_membership(
+                key,
+                consensus_state.ibc_storage_root,
+                noninclusion_proof,
+            )?),
+            MerkleProof::Inclusion(_) => Err(Error::ExpectedNonInclusionProof.into()),
+        }
     }
 
-    fn update_state(
-        mut deps: DepsMut<Self::CustomQuery>,
-        _env: Env,
+    fn verify_header(
+        ctx: IbcClientCtx<Self>,
         header: Self::Header,
-    ) -> Result<Vec<Height>, IbcClientError<Self>> {
-        let mut client_state: WasmClientState = read_client_state(deps.as_ref())?;
-
-        let updated_height = Height {
-            revision_number: client_state.latest_height.revision_number,
-            revision_height: header.l1_height.revision_height,
-        };
-
-        if client_state.latest_height < header.l1_height {
-            client_state.data.l1_latest_height = updated_height;
-            update_client_state::<Self>(
-                deps.branch(),
-                client_state,
-                updated_height.revision_height,
-            );
+        _caller: cosmwasm_std::Addr,
+    ) -> Result<
+        (u64, Self::ClientState, Self::ConsensusState),
+        ibc_union_light_client::IbcClientError<Self>,
+    > {
+        let mut client_state = ctx.read_self_client_state()?;
+        let l1_consensus_state = ctx
+            .read_consensus_state::<EthereumLightClient>(
+                client_state.l1_client_id,
+                header.l1_height.height(),
+            )
+            .map_err(Into::<Error>::into)?;
+        linea_verifier::verify_header(&client_state, &header, l1_consensus_state.state_root)
+            .map_err(Error::HeaderVerify)?;
+
+        let l2_height = u64::try_from(header.l2_block_number_proof.value)
+            .map_err(|()| Error::L2HeightTooLarge)?;
+
+        let l2_timestamp = u64::try_from(header.l2_timestamp_proof.value)
+            .map_err(|()| Error::L2TimestampTooLarge)?;
+
+        // the account value has been verified against the l2 state root in verify_header
+        let l2_ibc_contract_account = ZkAccount::decode(&header.l2_ibc_contract_proof.proof.value)
+            .map_err(|_| Error::L2IbcContractAccountDecode)?;
+
+        if client_state.l1_latest_height < header.l1_height.height() {
+            client_state.l1_latest_height = header.l1_height.height();
         }
 
-        // Guaranteed to succeed as we previously verified the header
-        let zk_account =
-            ZkAccount::decode(header.l2_ibc_contract_proof.proof.value).expect("impossible");
-
-        let consensus_state = WasmConsensusState {
-            data: ConsensusState {
-                ibc_storage_root: zk_account.storage_root,
-                // must be nanos
-                timestamp: 1_000_000_000
-                    * u64::try_from(header.l2_timestamp_proof.value).expect("impossible"),
-            },
-        };
-        save_consensus_state::<Self>(deps, consensus_state, &updated_height);
-        Ok(vec![updated_height])
-    }
+        if client_state.latest_height < l2_height {
+            client_state.latest_height = l2_height;
+        }
 
-    fn update_state_on_misbehaviour(
-        deps: DepsMut<Self::CustomQuery>,
-        env: Env,
-        _client_message: Vec<u8>,
-    ) -> Result<(), IbcClientError<Self>> {
-        let mut client_state: WasmClientState = read_client_state(deps.as_ref())?;
-        client_state.data.frozen_height = Height {
-            revision_number: client_state.latest_height.revision_number,
-            revision_height: env.block.height,
+        let consensus_state = ConsensusState {
+            ibc_storage_root: l2_ibc_contract_account.storage_root,
+            // must be nanos
+            timestamp: 1_000_000_000 * l2_timestamp,
         };
-        save_client_state::<Self>(deps, client_state);
-        Ok(())
-    }
 
-    fn check_for_misbehaviour_on_header(
-        _deps: Deps<Self::CustomQuery>,
-        _header: Self::Header,
-    ) -> Result<bool, IbcClientError<Self>> {
-        Ok(false)
+        Ok((l2_height, client_state, consensus_state))
     }
 
-    fn check_for_misbehaviour_on_misbehaviour(
-        _deps: Deps<Self::CustomQuery>,
+    fn misbehaviour(
+        _ctx: IbcClientCtx<Self>,
         _misbehaviour: Self::Misbehaviour,
-    ) -> Result<bool, IbcClientError<Self>> {
+    ) -> Result<Self::ClientState, IbcClientError<Self>> {
         Err(Error::Unimplemented.into())
     }
 
-    fn verify_upgrade_and_update_state(
-        _deps: DepsMut<Self::CustomQuery>,
-        _upgrade_client_state: Self::ClientState,
-        _upgrade_consensus_state: Self::ConsensusState,
-        _proof_upgrade_client: Vec<u8>,
-        _proof_upgrade_consensus_state: Vec<u8>,
-    ) -> Result<(), IbcClientError<Self>> {
-        Err(Error::Unimplemented.into())
+    fn status(_ctx: IbcClientCtx<Self>, client_state: &Self::ClientState) -> Status {
+        if client_state.frozen_height.height() != 0 {
+            Status::Frozen
+        } else {
+            Status::Active
+        }
     }
 
-    fn migrate_client_store(_deps: DepsMut<Self::CustomQuery>) -> Result<(), IbcClientError<Self>> {
-        Err(Error::Unimplemented.into())
+    fn verify_creation(
+        _client_state: &Self::ClientState,
+        _consensus_state: &Self::ConsensusState,
+    ) -> Result<(), ibc_union_light_client::IbcClientError<Self>> {
+        Ok(())
     }
 
-    fn status(deps: Deps<Self::CustomQuery>, _env: &Env) -> Result<Status, IbcClientError<Self>> {
-        let client_state: WasmClientState = read_client_state(deps)?;
-
-        if client_state.data.frozen_height != Height::default() {
-            return Ok(Status::Frozen);
-        }
-
-        let Some(_) = read_consensus_state::<Self>(deps, &client_state.latest_height)? else {
-            return Ok(Status::Expired);
-        };
-
-        Ok(Status::Active)
+    fn get_timestamp(consensus_state: &Self::ConsensusState) -> u64 {
+        consensus_state.timestamp
     }
 
-    fn export_metadata(
-        _deps: Deps<Self::CustomQuery>,
-        _env: &Env,
-    ) -> Result<Vec<GenesisMetadata>, IbcClientError<Self>> {
-        Ok(Vec::new())
+    fn get_latest_height(client_state: &Self::ClientState) -> u64 {
+        client_state.latest_height
     }
 
-    fn timestamp_at_height(
-        deps: Deps<Self::CustomQuery>,
-        height: Height,
-    ) -> Result<u64, IbcClientError<Self>> {
-        Ok(read_consensus_state::<Self>(deps, &height)?
-            .ok_or(Error::ConsensusStateNotFound(height))?
-            .data
-            .timestamp)
+    fn get_counterparty_chain_id(client_state: &Self::ClientState) -> String {
+        client_state.chain_id.to_string()
     }
 }
 
-fn do_verify_membership(
-    path: String,
+pub fn verify_membership(
+    key: Vec<u8>,
     storage_root: H256,
     storage_proof: InclusionProof,
-    raw_value: Vec<u8>,
+    value: Vec<u8>,
 ) -> Result<(), Error> {
-    // TODO: handle error
-    let key = U256::try_from_be_bytes(&storage_proof.key).unwrap();
+    let proof_key = H256::try_from(storage_proof.key.as_ref())
+        .map_err(|_| Error::InvalidProofKey(storage_proof.key.clone()))?;
 
-    check_commitment_key(&path, key)?;
+    check_commitment_key(
+        H256::try_from(&key)
+            .map_err(|_| ethereum_light_client::errors::Error::InvalidCommitmentKeyLength(key))?,
+        U256::from_be_bytes(*proof_key.get()),
+    )?;
 
-    // we store the hash of the data, not the data itself to the commitments map
-    let expected_value_hash = keccak256(canonicalize_stored_value(path, raw_value)?);
+    let value = H256::try_from(&value)
+        .map_err(|_| ethereum_light_client::errors::Error::InvalidCommitmentValueLength(value))?;
 
-    // TODO: handle error
-    let proof_value = H256::new(storage_proof.proof.value.clone().try_into().unwrap());
+    verify_storage_inclusion(storage_root, proof_key, &storage_proof, value)
+}
 
-    if expected_value_hash != proof_value {
-        return Err(Error::StoredValueMismatch {
-            expected: expected_value_hash,
-            stored: proof_value,
-        });
-    }
+/// Verifies that no value is committed at `path` in the counterparty light client's storage.
+pub fn verify_non_membership(
+    key: Vec<u8>,
+    storage_root: H256,
+    storage_proof: NonInclusionProof,
+) -> Result<(), Error> {
+    let proof_key = H256::try_from(storage_proof.key.as_ref())
+        .map_err(|_| Error::InvalidProofKey(storage_proof.key.clone()))?;
+
+    check_commitment_key(
+        H256::try_from(&key)
+            .map_err(|_| ethereum_light_client::errors::Error::InvalidCommitmentKeyLength(key))?,
+        U256::from_be_bytes(*proof_key.get()),
+    )?;
 
-    linea_zktrie::verify::verify_inclusion_and_key::<U256>(
+    verify_storage_noninclusion(storage_root, proof_key, &storage_proof)
+}
+
+/// Verifies that `value` is stored at the storage slot `key` in the zktrie with root
+/// `storage_root`.
+fn verify_storage_inclusion(
+    storage_root: H256,
+    key: H256,
+    storage_proof: &InclusionProof,
+    value: H256,
+) -> Result<(), Error> {
+    let (_, proof_value) = linea_zktrie::verify::verify_inclusion_and_key::<H256>(
         &new_mimc_constants_bls12_377(),
         storage_proof.leaf_index,
         &storage_proof.proof,
@@ -260,26 +205,187 @@ fn do_verify_membership(
         key,
     )?;
 
+    if value != proof_value {
+        return Err(Error::StoredValueMismatch {
+            expected: value,
+            stored: proof_value,
+        });
+    }
+
     Ok(())
 }
 
-/// Verifies that no value is committed at `path` in the counterparty light client's storage.
-fn do_verify_non_membership(
-    path: String,
+/// Verifies that the storage slot `key` is empty in the zktrie with root `storage_root`.
+fn verify_storage_noninclusion(
     storage_root: H256,
-    noninclusion_proof: NonInclusionProof,
+    key: H256,
+    storage_proof: &NonInclusionProof,
 ) -> Result<(), Error> {
-    // TODO: handle error
-    let key = U256::try_from_be_bytes(&noninclusion_proof.key).unwrap();
-
-    check_commitment_key(&path, key)?;
-
-    linea_zktrie::verify::verify_noninclusion::<U256>(
+    linea_zktrie::verify::verify_noninclusion::<H256>(
         &new_mimc_constants_bls12_377(),
-        &noninclusion_proof,
+        storage_proof,
         storage_root,
         key,
     )?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+
+    use super::*;
+
+    /// The storage root of account `0x5ff137d4b0fdcd49dca30c7cf57e578a026d2789`, which both of
+    /// the storage proofs below are against.
+    const STORAGE_ROOT: H256 = H256::new(hex!(
+        "1280c3481d8b1510e16062ffaa631812c3ca53639329c1577354f0e4cd850dde"
+    ));
+
+    fn inclusion_proof() -> InclusionProof {
+        match serde_json::from_str(include_str!("./test/storage_inclusion_proof.json")).unwrap() {
+            MerkleProof::Inclusion(proof) => proof,
+            MerkleProof::NonInclusion(_) => panic!("expected an inclusion proof"),
+        }
+    }
+
+    fn noninclusion_proof() -> NonInclusionProof {
+        match serde_json::from_str(include_str!("./test/storage_noninclusion_proof.json")).unwrap()
+        {
+            MerkleProof::NonInclusion(proof) => proof,
+            MerkleProof::Inclusion(_) => panic!("expected a non-inclusion proof"),
+        }
+    }
+
+    #[test]
+    fn storage_inclusion_works() {
+        let proof = inclusion_proof();
+
+        assert!(matches!(
+            verify_storage_inclusion(
+                STORAGE_ROOT,
+                H256::new(hex!(
+                    "975227e2a924779fb36829b74e9ab66f8d906444c0efb23059aaf437a9254f64"
+                )),
+                &proof,
+                H256::new(hex!(
+                    "0000000000000000000000000000000000000000000000000000000000000183"
+                )),
+            ),
+            Ok(())
+        ));
+    }
+
+    #[test]
+    fn storage_inclusion_fails_on_value_mismatch() {
+        let proof = inclusion_proof();
+
+        assert!(matches!(
+            verify_storage_inclusion(
+                STORAGE_ROOT,
+                H256::new(hex!(
+                    "975227e2a924779fb36829b74e9ab66f8d906444c0efb23059aaf437a9254f64"
+                )),
+                &proof,
+                H256::new(hex!(
+                    "0000000000000000000000000000000000000000000000000000000000000184"
+                )),
+            ),
+            Err(Error::StoredValueMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn storage_inclusion_fails_on_key_mismatch() {
+        let proof = inclusion_proof();
+
+        assert!(matches!(
+            verify_storage_inclusion(
+                STORAGE_ROOT,
+                H256::default(),
+                &proof,
+                H256::new(hex!(
+                    "0000000000000000000000000000000000000000000000000000000000000183"
+                )),
+            ),
+            Err(Error::StorageProofVerify(
+                linea_zktrie::verify::Error::KeyMismatch { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn storage_inclusion_fails_on_root_mismatch() {
+        let proof = inclusion_proof();
+
+        assert!(matches!(
+            verify_storage_inclusion(
+                H256::default(),
+                H256::new(hex!(
+                    "975227e2a924779fb36829b74e9ab66f8d906444c0efb23059aaf437a9254f64"
+                )),
+                &proof,
+                H256::new(hex!(
+                    "0000000000000000000000000000000000000000000000000000000000000183"
+                )),
+            ),
+            Err(Error::StorageProofVerify(
+                linea_zktrie::verify::Error::RootMismatch { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn storage_noninclusion_works() {
+        let proof = noninclusion_proof();
+
+        assert!(matches!(
+            verify_storage_noninclusion(STORAGE_ROOT, H256::default(), &proof),
+            Ok(())
+        ));
+    }
+
+    #[test]
+    fn storage_noninclusion_fails_on_key_outside_of_proof_range() {
+        let proof = noninclusion_proof();
+
+        assert!(matches!(
+            verify_storage_noninclusion(
+                STORAGE_ROOT,
+                H256::new(hex!(
+                    "975227e2a924779fb36829b74e9ab66f8d906444c0efb23059aaf437a9254f64"
+                )),
+                &proof
+            ),
+            Err(Error::StorageProofVerify(
+                linea_zktrie::verify::Error::KeyNotInCenter { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn membership_fails_on_commitment_key_mismatch() {
+        assert!(matches!(
+            verify_membership(
+                [0; 32].to_vec(),
+                STORAGE_ROOT,
+                inclusion_proof(),
+                hex!("0000000000000000000000000000000000000000000000000000000000000183").to_vec(),
+            ),
+            Err(Error::Evm(
+                ethereum_light_client::errors::Error::InvalidCommitmentKey { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn non_membership_fails_on_commitment_key_mismatch() {
+        assert!(matches!(
+            verify_non_membership([0; 32].to_vec(), STORAGE_ROOT, noninclusion_proof()),
+            Err(Error::Evm(
+                ethereum_light_client::errors::Error::InvalidCommitmentKey { .. }
+            ))
+        ));
+    }
+}
@@ -1,77 +1,46 @@
-use ethereum_light_client::errors::{CanonicalizeStoredValueError, InvalidCommitmentKey};
-use ics008_wasm_client::IbcClientError;
-use linea_light_client_types::{ClientState, ConsensusState};
-use unionlabs::{
-    encoding::{DecodeErrorOf, Proto},
-    ibc::core::client::height::Height,
-    ics24::PathParseError,
-    linea::proof::InclusionProof,
-    primitives::H256,
-};
+use ethereum_light_client::client::EthereumLightClient;
+use ibc_union_light_client::IbcClientError;
+use unionlabs::primitives::{Bytes, H256};
 
 use crate::client::LineaLightClient;
 
-#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[derive(Debug, thiserror::Error)]
 pub enum Error {
-    #[error("unable to decode storage proof")]
-    InclusionProofDecode(#[source] DecodeErrorOf<Proto, InclusionProof>),
-
-    // #[error("unable to decode counterparty's stored cometbls client state")]
-    // CometblsClientStateDecode(
-    //     #[source] DecodeErrorOf<Proto, Any<cometbls::client_state::ClientState>>,
-    // ),
-    // #[error("unable to decode counterparty's stored cometbls consensus state")]
-    // CometblsConsensusStateDecode(
-    //     #[source]
-    //     DecodeErrorOf<
-    //         Proto,
-    //         Any<wasm::consensus_state::ConsensusState<cometbls::consensus_state::ConsensusState>>,
-    //     >,
-    // ),
-    #[error("unable to decode client state")]
-    ClientStateDecode(#[source] DecodeErrorOf<Proto, ClientState>),
-    #[error("unable to decode consensus state")]
-    ConsensusStateDecode(#[source] DecodeErrorOf<Proto, ConsensusState>),
-
     #[error(transparent)]
-    CanonicalizeStoredValue(#[from] CanonicalizeStoredValueError),
+    Evm(#[from] ethereum_light_client::errors::Error),
 
-    // REVIEW: Move this variant to IbcClientError?
-    #[error("consensus state not found at height {0}")]
-    ConsensusStateNotFound(Height),
+    #[error("invalid storage proof key ({0}), the key must be 32 bytes")]
+    InvalidProofKey(Bytes),
 
-    #[error("IBC path is empty")]
-    EmptyIbcPath,
+    #[error("expected value ({expected}) and stored value ({stored}) don't match")]
+    StoredValueMismatch { expected: H256, stored: H256 },
 
-    #[error(transparent)]
-    InvalidCommitmentKey(#[from] InvalidCommitmentKey),
+    #[error("expected an inclusion proof, found a non-inclusion proof")]
+    ExpectedInclusionProof,
 
-    #[error("proof is empty")]
-    EmptyProof,
+    #[error("expected a non-inclusion proof, found an inclusion proof")]
+    ExpectedNonInclusionProof,
 
-    #[error("batching proofs are not supported")]
-    BatchingProofsNotSupported,
+    #[error("failed to verify linea header: {0}")]
+    HeaderVerify(#[from] linea_verifier::Error),
 
-    #[error("expected value ({expected}) and stored value ({stored}) don't match")]
-    StoredValueMismatch { expected: H256, stored: H256 },
+    #[error("failed to verify linea storage proof: {0}")]
+    StorageProofVerify(#[from] linea_zktrie::verify::Error),
 
-    #[error("unable to parse ics24 path")]
-    PathParse(#[from] PathParseError),
+    #[error("the l2 block number does not fit in a u64")]
+    L2HeightTooLarge,
 
-    #[error("failed to verify linea header: {0}")]
-    Verify(#[from] linea_verifier::Error),
+    #[error("the l2 timestamp does not fit in a u64")]
+    L2TimestampTooLarge,
+
+    #[error("the l2 ibc contract account could not be decoded")]
+    L2IbcContractAccountDecode,
 
     #[error("the operation has not been implemented yet")]
     Unimplemented,
 
-    #[error("error while calling custom query: {0}")]
-    CustomQuery(#[from] unionlabs::cosmwasm::wasm::union::custom_query::Error),
-
-    #[error("L2 account proof must be an inclusion proof")]
-    InvalidL2AccountProof,
-
-    #[error("failed to verify linea membership proof {0}")]
-    InvalidMembershipProof(#[from] linea_zktrie::verify::Error),
+    #[error(transparent)]
+    EvmIbcClient(#[from] IbcClientError<EthereumLightClient>),
 }
 
 impl From<Error> for IbcClientError<LineaLightClient> {
@@ -8,6 +8,7 @@ use unionlabs::{
         account::AccountAddress, storage_proof::StorageProof, transaction_info::TransactionInfo,
     },
     encoding::Bincode,
+    ibc::core::client::height::Height,
     primitives::{H256, U256},
 };
 
@@ -60,12 +61,20 @@ impl ibc_union_light_client::IbcClient for MovementLightClient {
     }
 
     fn verify_non_membership(
-        _ctx: ibc_union_light_client::IbcClientCtx<Self>,
-        _height: u64,
-        _key: Vec<u8>,
-        _storage_proof: Self::StorageProof,
+        ctx: ibc_union_light_client::IbcClientCtx<Self>,
+        height: u64,
+        key: Vec<u8>,
+        storage_proof: Self::StorageProof,
     ) -> Result<(), ibc_union_light_client::IbcClientError<Self>> {
-        unimplemented!()
+        let client_state = ctx.read_self_client_state()?;
+        let consensus_state = ctx.read_self_consensus_state(height)?;
+        verify_non_membership(
+            &key,
+            consensus_state.state_root,
+            client_state.table_handle,
+            storage_proof,
+        )
+        .map_err(Into::into)
     }
 
     fn get_timestamp(consensus_state: &Self::ConsensusState) -> u64 {
@@ -80,7 +89,10 @@ impl ibc_union_light_client::IbcClient for MovementLightClient {
         client_state.chain_id.clone()
     }
 
-    fn status(client_state: &Self::ClientState) -> Status {
+    fn status(
+        _ctx: ibc_union_light_client::IbcClientCtx<Self>,
+        client_state: &Self::ClientState,
+    ) -> Status {
         if client_state.frozen_height.height() != 0 {
             Status::Frozen
         } else {
@@ -121,65 +133,115 @@ impl ibc_union_light_client::IbcClient for MovementLightClient {
         // Also see the related PR: https://github.com/movementlabsxyz/movement/pull/645
 
         #[cfg(feature = "union-movement")]
-        {
-            aptos_verifier::verify_tx_state(
-                &header.tx_proof,
-                *header
-                    .state_proof
-                    .latest_ledger_info()
-                    .commit_info
-                    .executed_state_id
-                    .get(),
-                header.tx_index,
-            )
-            .map_err(Into::<Error>::into)?;
-
-            // TODO(aeryz): make sure the given state_proof_hash_proof.key matches the correct slot
-
-            let l1_consensus_state =
-                ctx.read_consensus_state(client_state.l1_client_id, header.l1_height)?;
-
-            let expected_commitment = BlockCommitment {
-                height: header.new_height.into(),
-                commitment: U256::from_be_bytes(header.state_proof.hash()),
-                // TODO(aeryz): check if hash here is big endian
-                block_id: U256::from_be_bytes(
-                    header
-                        .state_proof
-                        .latest_ledger_info()
-                        .commit_info
-                        .id
-                        .into(),
-                ),
-            };
-
-            evm_storage_verifier::verify_account_storage_root(
-                l1_consensus_state.state_root,
-                &client_state.l1_contract_address,
-                &header.settlement_contract_proof.proof,
-                &header.settlement_contract_proof.storage_root,
-            )
-            .unwrap();
-
-            evm_storage_verifier::verify_storage_proof(
-                header.settlement_contract_proof.storage_root,
-                header.state_proof_hash_proof.key,
-                &rlp::encode(&expected_commitment),
-                &header.state_proof_hash_proof.proof,
-            )
-            .unwrap();
-        }
+        verify_state_commitment(&ctx, &client_state, &header)?;
+
         update_state(client_state, header).map_err(Into::into)
     }
 
     fn misbehaviour(
-        _ctx: ibc_union_light_client::IbcClientCtx<Self>,
-        _misbehaviour: Self::Misbehaviour,
+        ctx: ibc_union_light_client::IbcClientCtx<Self>,
+        header: Self::Misbehaviour,
     ) -> Result<Self::ClientState, ibc_union_light_client::IbcClientError<Self>> {
-        unimplemented!()
+        let mut client_state = ctx.read_self_client_state()?;
+
+        verify_misbehaviour(&ctx, &client_state, header)?;
+
+        client_state.frozen_height = Height::new(1);
+
+        Ok(client_state)
     }
 }
 
+/// Verify that the state commitment in `header` has been settled on the L1 by the settlement
+/// contract.
+#[cfg(feature = "union-movement")]
+fn verify_state_commitment(
+    ctx: &ibc_union_light_client::IbcClientCtx<MovementLightClient>,
+    client_state: &ClientState,
+    header: &Header,
+) -> Result<(), ibc_union_light_client::IbcClientError<MovementLightClient>> {
+    aptos_verifier::verify_tx_state(
+        &header.tx_proof,
+        *header
+            .state_proof
+            .latest_ledger_info()
+            .commit_info
+            .executed_state_id
+            .get(),
+        header.tx_index,
+    )
+    .map_err(Into::<Error>::into)?;
+
+    // TODO(aeryz): make sure the given state_proof_hash_proof.key matches the correct slot
+
+    let l1_consensus_state =
+        ctx.read_consensus_state(client_state.l1_client_id, header.l1_height)?;
+
+    let expected_commitment = BlockCommitment {
+        height: header.new_height.into(),
+        commitment: U256::from_be_bytes(header.state_proof.hash()),
+        // TODO(aeryz): check if hash here is big endian
+        block_id: U256::from_be_bytes(
+            header
+                .state_proof
+                .latest_ledger_info()
+                .commit_info
+                .id
+                .into(),
+        ),
+    };
+
+    evm_storage_verifier::verify_account_storage_root(
+        l1_consensus_state.state_root,
+        &client_state.l1_contract_address,
+        &header.settlement_contract_proof.proof,
+        &header.settlement_contract_proof.storage_root,
+    )
+    .map_err(Error::VerifyAccountStorageRoot)?;
+
+    evm_storage_verifier::verify_storage_proof(
+        header.settlement_contract_proof.storage_root,
+        header.state_proof_hash_proof.key,
+        &rlp::encode(&expected_commitment),
+        &header.state_proof_hash_proof.proof,
+    )
+    .map_err(Error::VerifyStateCommitment)?;
+
+    Ok(())
+}
+
+/// Verify that `header` proves a state commitment settled on the L1 which conflicts with the
+/// consensus state stored for the same height.
+#[cfg(feature = "union-movement")]
+fn verify_misbehaviour(
+    ctx: &ibc_union_light_client::IbcClientCtx<MovementLightClient>,
+    client_state: &ClientState,
+    header: Header,
+) -> Result<(), ibc_union_light_client::IbcClientError<MovementLightClient>> {
+    verify_state_commitment(ctx, client_state, &header)?;
+
+    let consensus_state = ctx.read_self_consensus_state(header.new_height)?;
+
+    let (_, _, settled_consensus_state) = update_state(client_state.clone(), header)?;
+
+    if consensus_state.state_root == settled_consensus_state.state_root {
+        return Err(Error::MisbehaviourNotFound.into());
+    }
+
+    Ok(())
+}
+
+/// Without the state and transaction proofs, the header can't be checked against the settled state
+/// commitment, so misbehaviour can't be proven.
+#[cfg(not(feature = "union-movement"))]
+fn verify_misbehaviour(
+    _ctx: &ibc_union_light_client::IbcClientCtx<MovementLightClient>,
+    _client_state: &ClientState,
+    _header: Header,
+) -> Result<(), ibc_union_light_client::IbcClientError<MovementLightClient>> {
+    Err(Error::MisbehaviourUnverifiable.into())
+}
+
 fn update_state(
     mut client_state: ClientState,
     header: Header,
@@ -245,6 +307,26 @@ pub fn verify_membership(
     )?)
 }
 
+pub fn verify_non_membership(
+    path: &[u8],
+    state_root: H256,
+    table_handle: AccountAddress,
+    proof: StorageProof,
+) -> Result<(), Error> {
+    if proof.state_value.is_some() {
+        return Err(Error::NonMembershipProofWithValue);
+    }
+
+    let key =
+        aptos_verifier::hash_table_key(&bcs::to_bytes(path).expect("cannot fail"), &table_handle);
+
+    Ok(aptos_verifier::verify_non_membership(
+        proof.proof,
+        state_root.into(),
+        key,
+    )?)
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 #[serde(rename = "StateValue")]
 enum PersistedStateValue {
@@ -10,6 +10,10 @@ pub enum Error {
     HeaderVerification(#[from] aptos_verifier::Error),
     #[error("invalid state_proof storage proof")]
     InvalidStateProof,
+    #[error("failed to verify the settlement contract's account storage root")]
+    VerifyAccountStorageRoot(#[source] evm_storage_verifier::error::Error),
+    #[error("failed to verify the settled state commitment")]
+    VerifyStateCommitment(#[source] evm_storage_verifier::error::Error),
     #[error("empty ibc path")]
     EmptyIbcPath,
     #[error("consensus state not found ({0})")]
@@ -22,6 +26,12 @@ pub enum Error {
     ProofValueHashMismatch,
     #[error("proof key hash doesn't match the calculated one")]
     ProofKeyMismatch,
+    #[error("non-membership proof with a value")]
+    NonMembershipProofWithValue,
+    #[error("the settled state commitment matches the stored consensus state")]
+    MisbehaviourNotFound,
+    #[error("misbehaviour can't be verified without state and transaction proofs")]
+    MisbehaviourUnverifiable,
     #[error("invalid ibc path {0}")]
     InvalidIbcPath(String),
     #[error(transparent)]
@@ -5,7 +5,7 @@ use ibc_union_light_client::{IbcClient, IbcClientCtx, IbcClientError};
 use ibc_union_msg::lightclient::Status;
 use scroll_codec::batch_header::BatchHeaderV3;
 use scroll_light_client_types::{ClientState, ConsensusState, Header};
-use unionlabs::{encoding::Proto, primitives::H256};
+use unionlabs::{encoding::Bincode, ibc::core::client::height::Height, primitives::H256};
 
 use crate::errors::Error;
 
@@ -26,7 +26,7 @@ impl IbcClient for ScrollLightClient {
 
     type StorageProof = StorageProof;
 
-    type Encoding = Proto;
+    type Encoding = Bincode;
 
     fn verify_membership(
         ctx: IbcClientCtx<Self>,
@@ -36,35 +36,12 @@ impl IbcClient for ScrollLightClient {
         value: Vec<u8>,
     ) -> Result<(), IbcClientError<Self>> {
         let consensus_state = ctx.read_self_consensus_state(height)?;
-
-        check_commitment_key(
-            H256::try_from(&key).map_err(|_| Error::InvalidCommitmentKeyLength(key))?,
-            storage_proof.key,
-        )
-        .map_err(Into::<Error>::into)?;
-
-        let value =
-            H256::try_from(&value).map_err(|_| Error::InvalidCommitmentValueLength(value))?;
-
-        let proof_value = H256::from(storage_proof.value.to_be_bytes());
-
-        if value != proof_value {
-            return Err(Error::StoredValueMismatch {
-                expected: value,
-                stored: proof_value,
-            }
-            .into());
-        }
-
-        scroll_verifier::verify_zktrie_storage_proof(
+        Ok(verify_membership(
+            key,
             consensus_state.ibc_storage_root,
-            storage_proof.key.to_be_bytes().into(),
-            storage_proof.value.to_be_bytes().as_ref(),
-            &storage_proof.proof,
-        )
-        .map_err(Into::<Error>::into)?;
-
-        Ok(())
+            storage_proof,
+            value,
+        )?)
     }
 
     fn verify_non_membership(
@@ -74,20 +51,11 @@ impl IbcClient for ScrollLightClient {
         storage_proof: Self::StorageProof,
     ) -> Result<(), IbcClientError<Self>> {
         let consensus_state = ctx.read_self_consensus_state(height)?;
-        check_commitment_key(
-            H256::try_from(&key).map_err(|_| Error::InvalidCommitmentKeyLength(key))?,
-            storage_proof.key,
-        )
-        .map_err(Into::<Error>::into)?;
-
-        scroll_verifier::verify_zktrie_storage_absence(
+        Ok(verify_non_membership(
+            key,
             consensus_state.ibc_storage_root,
-            H256::new(storage_proof.key.to_be_bytes()),
-            &storage_proof.proof,
-        )
-        .map_err(Into::<Error>::into)?;
-
-        todo!()
+            storage_proof,
+        )?)
     }
 
     fn get_timestamp(consensus_state: &Self::ConsensusState) -> u64 {
@@ -98,7 +66,11 @@ impl IbcClient for ScrollLightClient {
         client_state.latest_slot
     }
 
-    fn status(client_state: &Self::ClientState) -> Status {
+    fn get_counterparty_chain_id(client_state: &Self::ClientState) -> String {
+        client_state.chain_id.to_string()
+    }
+
+    fn status(_ctx: IbcClientCtx<Self>, client_state: &Self::ClientState) -> Status {
         if client_state.frozen_height.height() == 0 {
             Status::Active
         } else {
@@ -116,36 +88,24 @@ impl IbcClient for ScrollLightClient {
     fn verify_header(
         ctx: IbcClientCtx<Self>,
         header: Header,
+        _caller: cosmwasm_std::Addr,
     ) -> Result<(u64, Self::ClientState, Self::ConsensusState), IbcClientError<Self>> {
-        let mut client_state = ctx.read_self_client_state()?;
+        let client_state = ctx.read_self_client_state()?;
         verify_header(&ctx, &client_state, &header)?;
-
-        let batch_header =
-            BatchHeaderV3::decode(&header.batch_header).map_err(Error::BatchHeaderDecode)?;
-
-        let timestamp = batch_header.last_block_timestamp;
-
-        let updated_height = header.l1_height.height();
-
-        if client_state.latest_slot < updated_height {
-            client_state.latest_slot = updated_height;
-        }
-
-        let consensus_state = ConsensusState {
-            state_root: header.l2_state_root_proof.value.to_be_bytes().into(),
-            // must be nanos
-            timestamp: 1_000_000_000 * timestamp,
-            ibc_storage_root: header.l2_ibc_account_proof.storage_root,
-        };
-
-        Ok((updated_height, client_state, consensus_state))
+        Ok(update_state(client_state, header)?)
     }
 
     fn misbehaviour(
-        _ctx: IbcClientCtx<Self>,
-        _misbehaviour: Self::Misbehaviour,
+        ctx: IbcClientCtx<Self>,
+        header: Self::Misbehaviour,
     ) -> Result<Self::ClientState, IbcClientError<Self>> {
-        todo!()
+        let mut client_state = ctx.read_self_client_state()?;
+
+        verify_misbehaviour(&ctx, &client_state, header)?;
+
+        client_state.frozen_height = Height::new(1);
+
+        Ok(client_state)
     }
 }
 
@@ -162,309 +122,225 @@ pub fn verify_header(
     Ok(())
 }
 
-// #[cfg(test)]
-// mod test {
-//     use cosmwasm_std::{
-//         testing::{MockApi, MockQuerier, MockStorage},
-//         DepsMut, OwnedDeps,
-//     };
-//     use ics008_wasm_client::{
-//         storage_utils::{
-//             consensus_db_key, read_subject_client_state, HOST_CLIENT_STATE_KEY,
-//             SUBJECT_CLIENT_STORE_PREFIX, SUBSTITUTE_CLIENT_STORE_PREFIX,
-//         },
-//         IbcClient,
-//     };
-//     use unionlabs::{
-//         cosmwasm::wasm::union::custom_query::UnionCustomQuery,
-//         encoding::{EncodeAs, Proto},
-//         google::protobuf::any::Any,
-//         primitives::{H160, H256},
-//         ibc::core::client::height::Height,
-//         primitives::U256,
-//     };
-
-//     use super::{
-//         ClientState, ConsensusState, ScrollLightClient, WasmClientState, WasmConsensusState,
-//     };
-//     use crate::errors::Error;
-
-//     const INITIAL_CONSENSUS_STATE_HEIGHT: Height = Height {
-//         revision_number: 0,
-//         revision_height: 950,
-//     };
-
-//     const INITIAL_SUBSTITUTE_CONSENSUS_STATE_HEIGHT: Height = Height {
-//         revision_number: 0,
-//         revision_height: 970,
-//     };
-
-//     #[allow(clippy::type_complexity)]
-//     fn mock_dependencies(
-//     ) -> OwnedDeps<MockStorage, MockApi, MockQuerier<UnionCustomQuery>, UnionCustomQuery> {
-//         OwnedDeps::<_, _, _, UnionCustomQuery> {
-//             storage: MockStorage::default(),
-//             api: MockApi::default(),
-//             querier: MockQuerier::<UnionCustomQuery>::new(&[]),
-//             custom_query_type: std::marker::PhantomData,
-//         }
-//     }
-
-//     fn create_client_state(
-//         l1_client_id: String,
-//         chain_id: U256,
-//         latest_slot: u64,
-//         height: Height,
-//         frozen_height: Height,
-//     ) -> WasmClientState {
-//         WasmClientState {
-//             data: ClientState {
-//                 l1_client_id,
-//                 chain_id,
-//                 latest_slot,
-//                 frozen_height,
-//                 latest_batch_index_slot: U256::from(10),
-//                 l2_contract_address: H160::default(),
-//                 l2_finalized_state_roots_slot: U256::from(10),
-//                 l2_committed_batches_slot: U256::from(10),
-//                 ibc_contract_address: H160::default(),
-//             },
-//             latest_height: height,
-//             checksum: H256::default(),
-//         }
-//     }
-
-//     fn save_states_to_migrate_store(
-//         deps: DepsMut<UnionCustomQuery>,
-//         subject_client_state: &WasmClientState,
-//         substitute_client_state: &WasmClientState,
-//         subject_consensus_state: &WasmConsensusState,
-//         substitute_consensus_state: &WasmConsensusState,
-//     ) {
-//         deps.storage.set(
-//             format!("{SUBJECT_CLIENT_STORE_PREFIX}{HOST_CLIENT_STATE_KEY}").as_bytes(),
-//             &Any(subject_client_state.clone()).encode_as::<Proto>(),
-//         );
-//         deps.storage.set(
-//             format!(
-//                 "{SUBJECT_CLIENT_STORE_PREFIX}{}",
-//                 consensus_db_key(&INITIAL_CONSENSUS_STATE_HEIGHT)
-//             )
-//             .as_bytes(),
-//             &Any(subject_consensus_state.clone()).encode_as::<Proto>(),
-//         );
-//         deps.storage.set(
-//             format!("{SUBSTITUTE_CLIENT_STORE_PREFIX}{HOST_CLIENT_STATE_KEY}").as_bytes(),
-//             &Any(substitute_client_state.clone()).encode_as::<Proto>(),
-//         );
-//         deps.storage.set(
-//             format!(
-//                 "{SUBSTITUTE_CLIENT_STORE_PREFIX}{}",
-//                 consensus_db_key(&INITIAL_SUBSTITUTE_CONSENSUS_STATE_HEIGHT)
-//             )
-//             .as_bytes(),
-//             &Any(substitute_consensus_state.clone()).encode_as::<Proto>(),
-//         );
-//     }
-
-//     #[allow(clippy::type_complexity)]
-//     fn prepare_migrate_tests() -> (
-//         OwnedDeps<MockStorage, MockApi, MockQuerier<UnionCustomQuery>, UnionCustomQuery>,
-//         WasmClientState,
-//         WasmConsensusState,
-//         WasmClientState,
-//         WasmConsensusState,
-//     ) {
-//         let deps = mock_dependencies();
-
-//         let subject_client_state = create_client_state(
-//             "client_1".to_string(),
-//             U256::from(1),
-//             INITIAL_CONSENSUS_STATE_HEIGHT.revision_height,
-//             INITIAL_CONSENSUS_STATE_HEIGHT,
-//             Height::default(),
-//         );
-//         let substitute_client_state = create_client_state(
-//             "client_1".to_string(),
-//             U256::from(1),
-//             INITIAL_SUBSTITUTE_CONSENSUS_STATE_HEIGHT.revision_height,
-//             INITIAL_SUBSTITUTE_CONSENSUS_STATE_HEIGHT,
-//             Height::default(),
-//         );
-
-//         let subject_consensus_state = WasmConsensusState {
-//             data: ConsensusState {
-//                 state_root: H256::default(),
-//                 timestamp: 1000,
-//                 ibc_storage_root: H256::default(),
-//             },
-//         };
-//         let substitute_consensus_state = WasmConsensusState {
-//             data: ConsensusState {
-//                 state_root: H256::default(),
-//                 timestamp: 2000,
-//                 ibc_storage_root: H256::default(),
-//             },
-//         };
-
-//         (
-//             deps,
-//             subject_client_state,
-//             subject_consensus_state,
-//             substitute_client_state,
-//             substitute_consensus_state,
-//         )
-//     }
-
-//     #[test]
-//     fn migrate_client_store_succeeds_with_valid_data() {
-//         let (
-//             mut deps,
-//             mut subject_client_state,
-//             subject_consensus_state,
-//             mut substitute_client_state,
-//             substitute_consensus_state,
-//         ) = prepare_migrate_tests();
-
-//         subject_client_state.data.frozen_height = Height {
-//             revision_number: 0,
-//             revision_height: 1000,
-//         };
-
-//         substitute_client_state.data.frozen_height = Height::default();
-
-//         save_states_to_migrate_store(
-//             deps.as_mut(),
-//             &subject_client_state,
-//             &substitute_client_state,
-//             &subject_consensus_state,
-//             &substitute_consensus_state,
-//         );
-
-//         let original_subject_client_state: WasmClientState =
-//             read_subject_client_state::<ScrollLightClient>(deps.as_ref()).unwrap();
-
-//         assert_eq!(
-//             original_subject_client_state.data.frozen_height,
-//             Height {
-//                 revision_number: 0,
-//                 revision_height: 1000,
-//             }
-//         );
-
-//         // Perform migration
-//         let result = ScrollLightClient::migrate_client_store(deps.as_mut());
-
-//         // Assert success, print error if any
-//         if let Err(ref e) = result {
-//             println!("Migration failed with error: {:?}", e);
-//         }
-//         // Assert success
-//         assert!(result.is_ok());
-//         let updated_subject_client_state: WasmClientState =
-//             read_subject_client_state::<ScrollLightClient>(deps.as_ref()).unwrap();
-//         assert_eq!(
-//             updated_subject_client_state.data.frozen_height,
-//             Height::default()
-//         );
-
-//         assert_eq!(
-//             updated_subject_client_state.latest_height,
-//             substitute_client_state.latest_height
-//         );
-//     }
-//     #[test]
-//     fn migrate_client_store_fails_when_substitute_client_frozen() {
-//         let (
-//             mut deps,
-//             subject_client_state,
-//             subject_consensus_state,
-//             mut substitute_client_state,
-//             substitute_consensus_state,
-//         ) = prepare_migrate_tests();
-
-//         // Make the substitute client frozen
-//         substitute_client_state.data.frozen_height = Height {
-//             revision_number: 0,
-//             revision_height: 100,
-//         };
-
-//         // Save the states into the mock storage
-//         save_states_to_migrate_store(
-//             deps.as_mut(),
-//             &subject_client_state,
-//             &substitute_client_state,
-//             &subject_consensus_state,
-//             &substitute_consensus_state,
-//         );
-
-//         // Perform migration
-//         let result = ScrollLightClient::migrate_client_store(deps.as_mut());
-
-//         // Assert failure
-//         assert_eq!(result, Err(Error::SubstituteClientFrozen.into()));
-//     }
-
-//     #[test]
-//     fn migrate_client_store_fails_when_fields_differ() {
-//         let (
-//             mut deps,
-//             subject_client_state,
-//             subject_consensus_state,
-//             mut substitute_client_state,
-//             substitute_consensus_state,
-//         ) = prepare_migrate_tests();
-
-//         // Alter the chain_id in the substitute client state
-//         substitute_client_state.data.chain_id = U256::from(999);
-
-//         // Save the states into the mock storage
-//         save_states_to_migrate_store(
-//             deps.as_mut(),
-//             &subject_client_state,
-//             &substitute_client_state,
-//             &subject_consensus_state,
-//             &substitute_consensus_state,
-//         );
-
-//         // Perform migration
-//         let result = ScrollLightClient::migrate_client_store(deps.as_mut());
-
-//         // Assert failure
-//         assert_eq!(result, Err(Error::MigrateFieldsChanged.into()));
-//     }
-//     #[test]
-//     fn migrate_client_store_fails_when_substitute_consensus_not_found() {
-//         let (
-//             mut deps,
-//             subject_client_state,
-//             subject_consensus_state,
-//             mut substitute_client_state,
-//             _substitute_consensus_state, // we won't save this to storage
-//         ) = prepare_migrate_tests();
-
-//         // modify latest height to a height where the consensus state is not found
-//         substitute_client_state.latest_height = Height {
-//             revision_number: 0,
-//             revision_height: 15,
-//         };
-
-//         // Save only the client states and subject consensus state (skip substitute consensus state)
-//         save_states_to_migrate_store(
-//             deps.as_mut(),
-//             &subject_client_state,
-//             &substitute_client_state,
-//             &subject_consensus_state,
-//             &subject_consensus_state, // Reusing subject consensus intentionally
-//         );
-
-//         // Perform migration
-//         let result = ScrollLightClient::migrate_client_store(deps.as_mut());
-
-//         // Assert failure
-//         assert_eq!(
-//             result,
-//             Err(Error::ConsensusStateNotFound(substitute_client_state.latest_height).into())
-//         );
-//     }
-// }
+/// A header is misbehaviour if it is valid, but the consensus state it produces conflicts with the
+/// consensus state already stored at its height.
+fn verify_misbehaviour(
+    ctx: &IbcClientCtx<ScrollLightClient>,
+    client_state: &ClientState,
+    header: Header,
+) -> Result<(), IbcClientError<ScrollLightClient>> {
+    verify_header(ctx, client_state, &header)?;
+
+    let consensus_state = ctx.read_self_consensus_state(header.l1_height.height())?;
+
+    let (_, _, verified_consensus_state) = update_state(client_state.clone(), header)?;
+
+    if consensus_state == verified_consensus_state {
+        return Err(Error::MisbehaviourNotFound.into());
+    }
+
+    Ok(())
+}
+
+fn update_state(
+    mut client_state: ClientState,
+    header: Header,
+) -> Result<(u64, ClientState, ConsensusState), Error> {
+    let batch_header =
+        BatchHeaderV3::decode(&header.batch_header).map_err(Error::BatchHeaderDecode)?;
+
+    let timestamp = batch_header.last_block_timestamp;
+
+    let updated_height = header.l1_height.height();
+
+    if client_state.latest_slot < updated_height {
+        client_state.latest_slot = updated_height;
+    }
+
+    let consensus_state = ConsensusState {
+        state_root: header.l2_state_root_proof.value.to_be_bytes().into(),
+        // must be nanos
+        timestamp: 1_000_000_000 * timestamp,
+        ibc_storage_root: header.l2_ibc_account_proof.storage_root,
+    };
+
+    Ok((updated_height, client_state, consensus_state))
+}
+
+pub fn verify_membership(
+    key: Vec<u8>,
+    storage_root: H256,
+    storage_proof: StorageProof,
+    value: Vec<u8>,
+) -> Result<(), Error> {
+    check_commitment_key(
+        H256::try_from(&key).map_err(|_| Error::InvalidCommitmentKeyLength(key))?,
+        storage_proof.key,
+    )?;
+
+    let value = H256::try_from(&value).map_err(|_| Error::InvalidCommitmentValueLength(value))?;
+
+    let proof_value = H256::from(storage_proof.value.to_be_bytes());
+
+    if value != proof_value {
+        return Err(Error::StoredValueMismatch {
+            expected: value,
+            stored: proof_value,
+        });
+    }
+
+    verify_storage_inclusion(storage_root, &storage_proof)
+}
+
+/// Verifies that no value is committed at `key` in the counterparty's IBC storage.
+pub fn verify_non_membership(
+    key: Vec<u8>,
+    storage_root: H256,
+    storage_proof: StorageProof,
+) -> Result<(), Error> {
+    check_commitment_key(
+        H256::try_from(&key).map_err(|_| Error::InvalidCommitmentKeyLength(key))?,
+        storage_proof.key,
+    )?;
+
+    verify_storage_absence(storage_root, &storage_proof)
+}
+
+/// Verifies that `storage_proof.value` is stored at the storage slot `storage_proof.key` in the
+/// zktrie with root `storage_root`.
+fn verify_storage_inclusion(storage_root: H256, storage_proof: &StorageProof) -> Result<(), Error> {
+    scroll_verifier::verify_zktrie_storage_proof(
+        storage_root,
+        storage_proof.key.to_be_bytes().into(),
+        &storage_proof.value.to_be_bytes(),
+        &storage_proof.proof,
+    )?;
+
+    Ok(())
+}
+
+/// Verifies that the storage slot `storage_proof.key` is empty in the zktrie with root
+/// `storage_root`.
+fn verify_storage_absence(storage_root: H256, storage_proof: &StorageProof) -> Result<(), Error> {
+    scroll_verifier::verify_zktrie_storage_absence(
+        storage_root,
+        storage_proof.key.to_be_bytes().into(),
+        &storage_proof.proof,
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use hex_literal::hex;
+    use unionlabs::primitives::U256;
+
+    use super::*;
+
+    /// The storage root that both of the storage proofs below are against.
+    const STORAGE_ROOT: H256 = H256::new(hex!(
+        "1b52888cae05bdba27f8470293a7d2bc3b9a9c822d96affe05ef243e0dfd44a0"
+    ));
+
+    /// A proof of the value stored at slot `0x2`.
+    fn inclusion_proof() -> StorageProof {
+        serde_json::from_str(include_str!("./test/storage_inclusion_proof.json")).unwrap()
+    }
+
+    /// A proof that slot `0xff` is empty.
+    fn absence_proof() -> StorageProof {
+        serde_json::from_str(include_str!("./test/storage_noninclusion_proof.json")).unwrap()
+    }
+
+    #[test]
+    fn storage_inclusion_works() {
+        assert!(matches!(
+            verify_storage_inclusion(STORAGE_ROOT, &inclusion_proof()),
+            Ok(())
+        ));
+    }
+
+    #[test]
+    fn storage_inclusion_fails_on_value_mismatch() {
+        let proof = StorageProof {
+            value: U256::from(1u64),
+            ..inclusion_proof()
+        };
+
+        assert!(matches!(
+            verify_storage_inclusion(STORAGE_ROOT, &proof),
+            Err(Error::Verify(scroll_verifier::Error::ValueMismatch))
+        ));
+    }
+
+    #[test]
+    fn storage_inclusion_fails_on_root_mismatch() {
+        assert!(matches!(
+            verify_storage_inclusion(H256::default(), &inclusion_proof()),
+            Err(Error::Verify(_))
+        ));
+    }
+
+    #[test]
+    fn storage_inclusion_fails_for_absent_key() {
+        assert!(matches!(
+            verify_storage_inclusion(STORAGE_ROOT, &absence_proof()),
+            Err(Error::Verify(scroll_verifier::Error::ValueMismatch))
+        ));
+    }
+
+    #[test]
+    fn storage_absence_works() {
+        assert!(matches!(
+            verify_storage_absence(STORAGE_ROOT, &absence_proof()),
+            Ok(())
+        ));
+    }
+
+    #[test]
+    fn storage_absence_fails_for_present_key() {
+        assert!(matches!(
+            verify_storage_absence(STORAGE_ROOT, &inclusion_proof()),
+            Err(Error::Verify(scroll_verifier::Error::ValueMismatch))
+        ));
+    }
+
+    #[test]
+    fn membership_fails_on_commitment_key_mismatch() {
+        assert!(matches!(
+            verify_membership(
+                [0; 32].to_vec(),
+                STORAGE_ROOT,
+                inclusion_proof(),
+                inclusion_proof().value.to_be_bytes().to_vec(),
+            ),
+            Err(Error::Evm(
+                ethereum_light_client::errors::Error::InvalidCommitmentKey { .. }
+            ))
+        ));
+    }
+
+    #[test]
+    fn membership_fails_on_invalid_key_length() {
+        assert!(matches!(
+            verify_membership(
+                [0; 31].to_vec(),
+                STORAGE_ROOT,
+                inclusion_proof(),
+                inclusion_proof().value.to_be_bytes().to_vec(),
+            ),
+            Err(Error::InvalidCommitmentKeyLength(_))
+        ));
+    }
+
+    #[test]
+    fn non_membership_fails_on_commitment_key_mismatch() {
+        assert!(matches!(
+            verify_non_membership([0; 32].to_vec(), STORAGE_ROOT, absence_proof()),
+            Err(Error::Evm(
+                ethereum_light_client::errors::Error::InvalidCommitmentKey { .. }
+            ))
+        ));
+    }
+}
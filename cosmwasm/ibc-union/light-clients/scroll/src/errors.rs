@@ -2,7 +2,7 @@ use cosmwasm_std::StdError;
 use ethereum_light_client::client::EthereumLightClient;
 use ibc_union_light_client::IbcClientError;
 use scroll_codec::batch_header::BatchHeaderV3DecodeError;
-use unionlabs::{ibc::core::client::height::Height, ics24::PathParseError, primitives::H256};
+use unionlabs::{ibc::core::client::height::Height, primitives::H256};
 
 use crate::client::ScrollLightClient;
 
@@ -21,14 +21,11 @@ pub enum Error {
     #[error("expected value ({expected}) and stored value ({stored}) don't match")]
     StoredValueMismatch { expected: H256, stored: H256 },
 
-    #[error("unable to parse ics24 path")]
-    PathParse(#[from] PathParseError),
-
     #[error("failed to verify scroll header")]
     Verify(#[from] scroll_verifier::Error),
 
-    #[error("the operation has not been implemented yet")]
-    Unimplemented,
+    #[error("the header does not conflict with the stored consensus state")]
+    MisbehaviourNotFound,
 
     #[error("error decoding batch header")]
     BatchHeaderDecode(#[from] BatchHeaderV3DecodeError),
@@ -6,7 +6,7 @@ use cosmwasm_std::Empty;
 use ibc_union_light_client::{IbcClient, IbcClientCtx, IbcClientError};
 use ibc_union_msg::lightclient::Status;
 use ics23::ibc_api::SDK_SPECS;
-use tendermint_light_client_types::{ClientState, ConsensusState, Header};
+use tendermint_light_client_types::{ClientState, ConsensusState, Header, Misbehaviour};
 use tendermint_verifier::types::{HostFns, SignatureVerifier};
 use unionlabs::{
     bounded::BoundedI64,
@@ -36,8 +36,7 @@ impl IbcClient for TendermintLightClient {
 
     type Header = Header;
 
-    // TODO(aeryz): Change this to appropriate misbehavior type when it is implemented
-    type Misbehaviour = Header;
+    type Misbehaviour = Misbehaviour;
 
     type ClientState = ClientState;
 
@@ -97,46 +96,56 @@ impl IbcClient for TendermintLightClient {
     > {
         let client_state = ctx.read_self_client_state()?;
         let consensus_state = ctx.read_self_consensus_state(header.trusted_height.height())?;
-        match header.validator_set.validators.first().map(|v| &v.pub_key) {
-            Some(PublicKey::Bls12_381(_)) => Ok(verify_header(
-                client_state,
-                consensus_state,
-                header,
-                ctx.env.block.time,
-                &SignatureVerifier::new(Bls12Verifier::new(ctx.deps)),
-            )?),
-            Some(PublicKey::Ed25519(_)) => Ok(verify_header(
-                client_state,
-                consensus_state,
-                header,
-                ctx.env.block.time,
-                &SignatureVerifier::new(Ed25519Verifier::new(ctx.deps)),
-            )?),
-            _ => Err(Error::InvalidValidatorSet.into()),
-        }
+
+        Ok(verify_header_with_host_verifier(
+            &ctx,
+            client_state,
+            consensus_state,
+            header,
+        )?)
     }
 
     fn misbehaviour(
-        _ctx: IbcClientCtx<Self>,
-        _misbehaviour: Self::Misbehaviour,
+        ctx: IbcClientCtx<Self>,
+        misbehaviour: Self::Misbehaviour,
     ) -> Result<Self::ClientState, IbcClientError<Self>> {
-        Err(Error::Unimplemented.into())
-    }
-
-    fn status(client_state: &Self::ClientState) -> Status {
-        // FIXME: read latest consensus to verify if client expired
-        // if is_client_expired(
-        //     &consensus_state.timestamp,
-        //     client_state.trusting_period,
-        //     env.block
-        //         .time
-        //         .try_into()
-        //         .map_err(|_| Error::from(InvalidHostTimestamp(env.block.time)))?,
-        // ) {
-        //     return Ok(Status::Expired);
-        // }
+        let mut client_state = ctx.read_self_client_state()?;
+
+        let consensus_state_a =
+            ctx.read_self_consensus_state(misbehaviour.header_a.trusted_height.height())?;
+        let consensus_state_b =
+            ctx.read_self_consensus_state(misbehaviour.header_b.trusted_height.height())?;
+
+        verify_misbehaviour(
+            &ctx,
+            &client_state,
+            consensus_state_a,
+            consensus_state_b,
+            misbehaviour,
+        )?;
+
+        client_state.frozen_height = Some(Height::new(1));
+
+        Ok(client_state)
+    }
+
+    fn status(ctx: IbcClientCtx<Self>, client_state: &Self::ClientState) -> Status {
         if client_state.frozen_height.unwrap_or_default().height() != 0 {
-            Status::Frozen
+            return Status::Frozen;
+        }
+
+        let Ok(consensus_state) =
+            ctx.read_self_consensus_state(client_state.latest_height.height())
+        else {
+            return Status::Expired;
+        };
+
+        if is_client_expired(
+            &consensus_state.timestamp,
+            client_state.trusting_period,
+            host_timestamp(ctx.env.block.time),
+        ) {
+            Status::Expired
         } else {
             Status::Active
         }
@@ -162,6 +171,84 @@ impl IbcClient for TendermintLightClient {
     }
 }
 
+/// Verify `header` against the trusted state, picking the signature verifier based on the key type
+/// of the validator set.
+fn verify_header_with_host_verifier(
+    ctx: &IbcClientCtx<TendermintLightClient>,
+    client_state: ClientState,
+    consensus_state: ConsensusState,
+    header: Header,
+) -> Result<(u64, ClientState, ConsensusState), Error> {
+    match header.validator_set.validators.first().map(|v| &v.pub_key) {
+        Some(PublicKey::Bls12_381(_)) => verify_header(
+            client_state,
+            consensus_state,
+            header,
+            ctx.env.block.time,
+            &SignatureVerifier::new(Bls12Verifier::new(ctx.deps)),
+        ),
+        Some(PublicKey::Ed25519(_)) => verify_header(
+            client_state,
+            consensus_state,
+            header,
+            ctx.env.block.time,
+            &SignatureVerifier::new(Ed25519Verifier::new(ctx.deps)),
+        ),
+        _ => Err(Error::InvalidValidatorSet),
+    }
+}
+
+/// Verify that both headers in `misbehaviour` are valid updates from their trusted heights, and
+/// that together they prove the chain misbehaved: either two different blocks were committed at
+/// the same height (duplicate vote), or the block time did not increase with the height (BFT time
+/// violation).
+pub fn verify_misbehaviour(
+    ctx: &IbcClientCtx<TendermintLightClient>,
+    client_state: &ClientState,
+    consensus_state_a: ConsensusState,
+    consensus_state_b: ConsensusState,
+    misbehaviour: Misbehaviour,
+) -> Result<(), Error> {
+    let header_a = &misbehaviour.header_a.signed_header;
+    let header_b = &misbehaviour.header_b.signed_header;
+
+    if header_a.header.chain_id != header_b.header.chain_id {
+        return Err(Error::MisbehaviourChainIdMismatch {
+            chain_id_a: header_a.header.chain_id.clone(),
+            chain_id_b: header_b.header.chain_id.clone(),
+        });
+    }
+
+    if header_a.header.height < header_b.header.height {
+        return Err(Error::InvalidMisbehaviourHeaderSequence);
+    }
+
+    let is_misbehaviour = if header_a.header.height == header_b.header.height {
+        header_a.commit.block_id.hash != header_b.commit.block_id.hash
+    } else {
+        header_a.header.time <= header_b.header.time
+    };
+
+    if !is_misbehaviour {
+        return Err(Error::MisbehaviourNotFound);
+    }
+
+    verify_header_with_host_verifier(
+        ctx,
+        client_state.clone(),
+        consensus_state_a,
+        misbehaviour.header_a,
+    )?;
+    verify_header_with_host_verifier(
+        ctx,
+        client_state.clone(),
+        consensus_state_b,
+        misbehaviour.header_b,
+    )?;
+
+    Ok(())
+}
+
 pub fn verify_header<V: HostFns>(
     mut client_state: ClientState,
     consensus_state: ConsensusState,
@@ -202,17 +289,7 @@ pub fn verify_header<V: HostFns>(
         .into());
     }
 
-    // FIXME: unionlabs is tied to cosmwasm <2, the TryFrom impl can't be used
-    let block_timestamp_proto = unionlabs::google::protobuf::timestamp::Timestamp {
-        seconds: i64::try_from(block_timestamp.seconds())
-            .expect("impossible")
-            .try_into()
-            .expect("impossible"),
-        nanos: i32::try_from(block_timestamp.subsec_nanos())
-            .expect("impossible")
-            .try_into()
-            .expect("impossible"),
-    };
+    let block_timestamp_proto = host_timestamp(block_timestamp);
 
     tendermint_verifier::verify::verify(
         &construct_partial_header(
@@ -311,6 +388,20 @@ pub fn construct_partial_header(
     }
 }
 
+pub fn host_timestamp(block_timestamp: cosmwasm_std::Timestamp) -> Timestamp {
+    // FIXME: unionlabs is tied to cosmwasm <2, the TryFrom impl can't be used
+    Timestamp {
+        seconds: i64::try_from(block_timestamp.seconds())
+            .expect("impossible")
+            .try_into()
+            .expect("impossible"),
+        nanos: i32::try_from(block_timestamp.subsec_nanos())
+            .expect("impossible")
+            .try_into()
+            .expect("impossible"),
+    }
+}
+
 pub fn is_client_expired(
     consensus_state_timestamp: &Timestamp,
     trusting_period: Duration,
@@ -404,6 +495,370 @@ pub fn verify_non_membership(
     .map_err(Error::VerifyMembership)
 }
 
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, num::NonZeroU64};
+
+    use cometbft_types::{
+        types::{
+            block_id::BlockId, commit_sig::CommitSig, part_set_header::PartSetHeader,
+            validator::Validator,
+        },
+        version::consensus::Consensus,
+    };
+    use cosmwasm_std::{
+        testing::{mock_dependencies, mock_env, MockApi, MockQuerier, MockStorage},
+        to_json_vec, Addr, Binary, ContractResult, OwnedDeps, SystemResult, WasmQuery,
+    };
+    use ed25519_dalek::{Signer, SigningKey};
+    use ibc_union_light_client::{CLIENT_CONSENSUS_STATES, CLIENT_STATES};
+    use tendermint_light_client_types::Fraction;
+    use unionlabs::{
+        encoding::{EncodeAs, EthAbi},
+        primitives::{Bytes, H160},
+    };
+
+    use super::*;
+
+    const CHAIN_ID: &str = "test-chain-1";
+    const CLIENT_ID: u32 = 1;
+    const IBC_HOST: &str = "ibchost";
+    const TRUSTED_HEIGHT: u64 = 10;
+    const NANOS_PER_SECOND: i128 = 1_000_000_000;
+
+    fn signing_key() -> SigningKey {
+        SigningKey::from_bytes(&[7; 32])
+    }
+
+    fn validator_set() -> ValidatorSet {
+        let validator = Validator {
+            address: H160::new([1; 20]).into_encoding(),
+            pub_key: PublicKey::Ed25519(signing_key().verifying_key().to_bytes().to_vec().into()),
+            voting_power: 10.try_into().unwrap(),
+            proposer_priority: 0,
+        };
+
+        ValidatorSet {
+            validators: vec![validator.clone()],
+            proposer: validator,
+            total_voting_power: 10,
+        }
+    }
+
+    /// Seconds relative to the block time of [`mock_env`].
+    fn time(offset_seconds: i64) -> Timestamp {
+        Timestamp::try_from_unix_nanos(
+            i128::from(mock_env().block.time.nanos())
+                + i128::from(offset_seconds) * NANOS_PER_SECOND,
+        )
+        .unwrap()
+    }
+
+    fn client_state() -> ClientState {
+        ClientState {
+            chain_id: CHAIN_ID.to_owned(),
+            trust_level: Fraction {
+                numerator: 1,
+                denominator: NonZeroU64::new(3).unwrap(),
+            },
+            trusting_period: Duration::new(1000, 0).unwrap(),
+            unbonding_period: Duration::new(2000, 0).unwrap(),
+            max_clock_drift: Duration::new(10, 0).unwrap(),
+            frozen_height: None,
+            latest_height: Height::new_with_revision(1, TRUSTED_HEIGHT),
+            proof_specs: vec![],
+            upgrade_path: vec![],
+            contract_address: Default::default(),
+        }
+    }
+
+    fn consensus_state(timestamp: Timestamp) -> ConsensusState {
+        ConsensusState {
+            timestamp,
+            root: MerkleRoot {
+                hash: Default::default(),
+            },
+            next_validators_hash: tendermint_verifier::utils::validators_hash(&validator_set())
+                .into_encoding(),
+        }
+    }
+
+    /// Build a header at `height` that is signed by the only validator of [`validator_set`].
+    /// Headers with different `app_hash`es have different block hashes.
+    fn header(height: i64, time: Timestamp, app_hash: [u8; 32]) -> Header {
+        let validator_set = validator_set();
+        let validators_hash = tendermint_verifier::utils::validators_hash(&validator_set);
+
+        let header = cometbft_types::types::header::Header {
+            version: Consensus { block: 11, app: 0 },
+            chain_id: CHAIN_ID.to_owned(),
+            height: height.try_into().unwrap(),
+            time,
+            last_block_id: BlockId::default(),
+            last_commit_hash: Default::default(),
+            data_hash: Default::default(),
+            validators_hash: validators_hash.into_encoding(),
+            next_validators_hash: validators_hash.into_encoding(),
+            consensus_hash: Default::default(),
+            app_hash: H256::new(app_hash).into_encoding(),
+            last_results_hash: Default::default(),
+            evidence_hash: Default::default(),
+            proposer_address: validator_set.proposer.address,
+        };
+
+        let mut commit = Commit {
+            height: header.height,
+            round: 0.try_into().unwrap(),
+            block_id: BlockId {
+                hash: Some(header.calculate_merkle_root().unwrap().into_encoding()),
+                part_set_header: PartSetHeader {
+                    total: 1,
+                    hash: Some(H256::new([2; 32]).into_encoding()),
+                },
+            },
+            signatures: vec![],
+        };
+
+        let vote = tendermint_verifier::utils::canonical_vote(
+            &commit,
+            &CommitSig::Commit {
+                validator_address: validator_set.proposer.address.into_encoding(),
+                timestamp: time,
+                signature: Bytes::default(),
+            },
+            &time,
+            CHAIN_ID,
+        )
+        .unwrap();
+
+        commit.signatures.push(CommitSig::Commit {
+            validator_address: validator_set.proposer.address.into_encoding(),
+            timestamp: time,
+            signature: signing_key().sign(&vote).to_bytes().to_vec().into(),
+        });
+
+        Header {
+            signed_header: SignedHeader { header, commit },
+            validator_set: validator_set.clone(),
+            trusted_height: Height::new_with_revision(1, TRUSTED_HEIGHT),
+            trusted_validators: validator_set,
+        }
+    }
+
+    /// Mock the raw queries that the light client makes to the IBC host for its client and
+    /// consensus states.
+    fn mock_deps(
+        client_state: &ClientState,
+        consensus_states: &[(u64, ConsensusState)],
+    ) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut store = BTreeMap::<Vec<u8>, Binary>::new();
+
+        store.insert(
+            CLIENT_STATES.key(CLIENT_ID).to_vec(),
+            to_json_vec(&Binary::from(client_state.encode_as::<Bincode>()))
+                .unwrap()
+                .into(),
+        );
+        for (height, consensus_state) in consensus_states {
+            store.insert(
+                CLIENT_CONSENSUS_STATES.key((CLIENT_ID, *height)).to_vec(),
+                to_json_vec(&Binary::from(consensus_state.encode_as::<EthAbi>()))
+                    .unwrap()
+                    .into(),
+            );
+        }
+
+        let mut deps = mock_dependencies();
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Raw { key, .. } => SystemResult::Ok(ContractResult::Ok(
+                store.get(key.as_slice()).cloned().unwrap_or_default(),
+            )),
+            _ => panic!("only raw queries are made by the light client"),
+        });
+
+        deps
+    }
+
+    fn ctx(
+        deps: &OwnedDeps<MockStorage, MockApi, MockQuerier>,
+    ) -> IbcClientCtx<'_, TendermintLightClient> {
+        IbcClientCtx::new(
+            CLIENT_ID,
+            Addr::unchecked(IBC_HOST),
+            deps.as_ref(),
+            mock_env(),
+        )
+    }
+
+    fn verify(misbehaviour: Misbehaviour) -> Result<(), Error> {
+        let deps = mock_deps(&client_state(), &[]);
+
+        verify_misbehaviour(
+            &ctx(&deps),
+            &client_state(),
+            consensus_state(time(-100)),
+            consensus_state(time(-100)),
+            misbehaviour,
+        )
+    }
+
+    #[test]
+    fn misbehaviour_duplicate_vote_freezes_client() {
+        let deps = mock_deps(
+            &client_state(),
+            &[(TRUSTED_HEIGHT, consensus_state(time(-100)))],
+        );
+
+        let client_state = TendermintLightClient::misbehaviour(
+            ctx(&deps),
+            Misbehaviour {
+                header_a: header(12, time(-50), [1; 32]),
+                header_b: header(12, time(-50), [2; 32]),
+            },
+        )
+        .unwrap();
+
+        assert_eq!(client_state.frozen_height, Some(Height::new(1)));
+        assert!(matches!(
+            TendermintLightClient::status(ctx(&deps), &client_state),
+            Status::Frozen
+        ));
+    }
+
+    #[test]
+    fn misbehaviour_bft_time_violation() {
+        assert_eq!(
+            verify(Misbehaviour {
+                header_a: header(13, time(-50), [1; 32]),
+                header_b: header(12, time(-40), [1; 32]),
+            }),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn misbehaviour_same_header_is_not_misbehaviour() {
+        assert_eq!(
+            verify(Misbehaviour {
+                header_a: header(12, time(-50), [1; 32]),
+                header_b: header(12, time(-50), [1; 32]),
+            }),
+            Err(Error::MisbehaviourNotFound)
+        );
+    }
+
+    #[test]
+    fn misbehaviour_increasing_time_is_not_misbehaviour() {
+        assert_eq!(
+            verify(Misbehaviour {
+                header_a: header(13, time(-40), [1; 32]),
+                header_b: header(12, time(-50), [1; 32]),
+            }),
+            Err(Error::MisbehaviourNotFound)
+        );
+    }
+
+    #[test]
+    fn misbehaviour_headers_out_of_order() {
+        assert_eq!(
+            verify(Misbehaviour {
+                header_a: header(12, time(-50), [1; 32]),
+                header_b: header(13, time(-50), [2; 32]),
+            }),
+            Err(Error::InvalidMisbehaviourHeaderSequence)
+        );
+    }
+
+    #[test]
+    fn misbehaviour_chain_id_mismatch() {
+        let mut header_b = header(12, time(-50), [2; 32]);
+        header_b.signed_header.header.chain_id = "other-chain-1".to_owned();
+
+        assert_eq!(
+            verify(Misbehaviour {
+                header_a: header(12, time(-50), [1; 32]),
+                header_b,
+            }),
+            Err(Error::MisbehaviourChainIdMismatch {
+                chain_id_a: CHAIN_ID.to_owned(),
+                chain_id_b: "other-chain-1".to_owned(),
+            })
+        );
+    }
+
+    #[test]
+    fn misbehaviour_invalid_signature() {
+        let mut header_b = header(12, time(-50), [2; 32]);
+        let CommitSig::Commit { signature, .. } = &mut header_b.signed_header.commit.signatures[0]
+        else {
+            unreachable!()
+        };
+        *signature = vec![0; 64].into();
+
+        assert!(matches!(
+            verify(Misbehaviour {
+                header_a: header(12, time(-50), [1; 32]),
+                header_b,
+            }),
+            Err(Error::TendermintVerify(_))
+        ));
+    }
+
+    #[test]
+    fn status_active() {
+        let deps = mock_deps(
+            &client_state(),
+            &[(TRUSTED_HEIGHT, consensus_state(time(-100)))],
+        );
+
+        assert!(matches!(
+            TendermintLightClient::status(ctx(&deps), &client_state()),
+            Status::Active
+        ));
+    }
+
+    #[test]
+    fn status_expired_after_trusting_period() {
+        let deps = mock_deps(
+            &client_state(),
+            &[(TRUSTED_HEIGHT, consensus_state(time(-1001)))],
+        );
+
+        assert!(matches!(
+            TendermintLightClient::status(ctx(&deps), &client_state()),
+            Status::Expired
+        ));
+    }
+
+    #[test]
+    fn status_expired_without_consensus_state() {
+        let deps = mock_deps(&client_state(), &[]);
+
+        assert!(matches!(
+            TendermintLightClient::status(ctx(&deps), &client_state()),
+            Status::Expired
+        ));
+    }
+
+    #[test]
+    fn status_frozen() {
+        let deps = mock_deps(
+            &client_state(),
+            &[(TRUSTED_HEIGHT, consensus_state(time(-100)))],
+        );
+
+        let client_state = ClientState {
+            frozen_height: Some(Height::new(1)),
+            ..client_state()
+        };
+
+        assert!(matches!(
+            TendermintLightClient::status(ctx(&deps), &client_state),
+            Status::Frozen
+        ));
+    }
+}
+
 // #[cfg(test)]
 // mod tests {
 //     use std::fs;
@@ -60,6 +60,18 @@ pub enum Error {
 
     #[error("invalid or empty validator set, supported keys are: bls12381 and ed25519")]
     InvalidValidatorSet,
+
+    #[error("header_a.height must be greater than or equal to header_b.height")]
+    InvalidMisbehaviourHeaderSequence,
+
+    #[error("misbehaviour headers are for different chains ({chain_id_a} and {chain_id_b})")]
+    MisbehaviourChainIdMismatch {
+        chain_id_a: String,
+        chain_id_b: String,
+    },
+
+    #[error("given headers don't prove a misbehaviour")]
+    MisbehaviourNotFound,
 }
 
 #[derive(Debug, Clone, PartialEq, thiserror::Error)]
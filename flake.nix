@@ -193,7 +193,8 @@
         ./cosmwasm/ibc-union/light-clients/ethereum/ethereum-light-client.nix
         ./cosmwasm/ibc-union/light-clients/cometbls/cometbls-light-client.nix
         ./cosmwasm/ibc-union/light-clients/tendermint/tendermint-light-client.nix
-        # ./cosmwasm/ibc-union/light-clients/scroll/scroll-light-client.nix
+        ./cosmwasm/ibc-union/light-clients/scroll/scroll-light-client.nix
+        ./cosmwasm/ibc-union/light-clients/linea/linea-light-client.nix
         # ./cosmwasm/ibc-union/light-clients/arbitrum/arbitrum-light-client.nix
         ./cosmwasm/ibc-union/light-clients/berachain/berachain-light-client.nix
         ./cosmwasm/ibc-union/light-clients/state-lens-ics23-mpt/state-lens-ics23-mpt-light-client.nix
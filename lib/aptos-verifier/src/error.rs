@@ -26,4 +26,8 @@ pub enum StorageVerificationError {
     ExpectedNonMembershipVerification,
     #[error("root hash mismatch (({0}, {1}))")]
     RootHashMismatch(H256, H256),
+    #[error(
+        "key ({key}) would not be in the subtree of the non-membership proof leaf ({leaf_key})"
+    )]
+    InvalidNonMembershipLeaf { key: H256, leaf_key: H256 },
 }
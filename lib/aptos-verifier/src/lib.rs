@@ -17,13 +17,12 @@ use unionlabs::{
         transaction_proof::TransactionInfoWithProof,
     },
     primitives::H256,
-    BytesBitIterator,
 };
 
 pub(crate) const MAX_ACCUMULATOR_PROOF_DEPTH: usize = 63;
-// "SPARSE_MERKLE_PLACEHOLDER_HASH"
+// "SPARSE_MERKLE_PLACEHOLDER_HASH", zero-padded on the right
 pub(crate) const SPARSE_MERKLE_PLACEHOLDER_HASH: [u8; 32] =
-    hex!("00005350415253455F4D45524B4C455F504C414345484F4C4445525F48415348");
+    hex!("5350415253455F4D45524B4C455F504C414345484F4C4445525F484153480000");
 
 /// Verifies an element whose hash is `element_hash` and version is `element_version` exists in
 /// the accumulator whose root hash is `expected_root_hash` using the provided proof.
@@ -122,15 +121,64 @@ pub fn verify_existence_proof(
         .into());
     }
 
-    let current_hash = proof.leaf.map_or(SPARSE_MERKLE_PLACEHOLDER_HASH, |leaf| {
-        hash_sparse_merkle_leaf_node(&leaf)
-    });
+    verify_root_hash(&proof, expected_root_hash, element_key)
+}
+
+/// Verifies that no element with the key `element_key` exists in the sparse merkle tree whose root
+/// hash is `expected_root_hash`.
+///
+/// The proof either shows that `element_key` would be placed at a currently empty position (no
+/// leaf), or that it would split the only existing leaf of the subtree it would be placed in.
+pub fn verify_non_membership(
+    proof: SparseMerkleProof,
+    expected_root_hash: [u8; 32],
+    element_key: [u8; 32],
+) -> Result<(), Error> {
+    if proof.siblings.len() > 256 {
+        return Err(
+            StorageVerificationError::MaxSiblingsExceeded(256, proof.siblings.len()).into(),
+        );
+    }
+
+    if let Some(leaf) = &proof.leaf {
+        if &element_key == leaf.key.get() {
+            return Err(StorageVerificationError::ExpectedNonMembershipVerification.into());
+        }
+
+        // the key must have ended up in the subtree where the leaf in the proof is the only
+        // existing leaf, otherwise this doesn't prove anything about the key
+        let common_prefix_bits_len = key_bits(&element_key)
+            .zip(key_bits(leaf.key.get()))
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        if common_prefix_bits_len < proof.siblings.len() {
+            return Err(StorageVerificationError::InvalidNonMembershipLeaf {
+                key: H256::new(element_key),
+                leaf_key: H256::new(*leaf.key.get()),
+            }
+            .into());
+        }
+    }
+
+    verify_root_hash(&proof, expected_root_hash, element_key)
+}
+
+fn verify_root_hash(
+    proof: &SparseMerkleProof,
+    expected_root_hash: [u8; 32],
+    element_key: [u8; 32],
+) -> Result<(), Error> {
+    let current_hash = proof
+        .leaf
+        .as_ref()
+        .map_or(SPARSE_MERKLE_PLACEHOLDER_HASH, hash_sparse_merkle_leaf_node);
     let actual_root_hash = proof
         .siblings
         .iter()
         .rev()
         .zip(
-            BytesBitIterator::new(&element_key)
+            key_bits(&element_key)
                 .rev()
                 .skip(256 - proof.siblings.len()),
         )
@@ -153,6 +201,13 @@ pub fn verify_existence_proof(
     Ok(())
 }
 
+/// Iterates over the bits of `key` starting from the most significant bit of the first byte, which
+/// is the order in which the sparse merkle tree is traversed from the root.
+fn key_bits(key: &[u8; 32]) -> impl DoubleEndedIterator<Item = bool> + '_ {
+    key.iter()
+        .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 != 0))
+}
+
 pub fn hash_state_value(value: &StateValue) -> [u8; 32] {
     Sha3_256::new()
         .chain_update(Sha3_256::new().chain_update("APTOS::StateValue").finalize())
@@ -235,3 +290,108 @@ impl SparseMerkleInternalNode {
         state.finalize().into()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // state checkpoint hash of a movement transaction, and the proof of one of its state keys
+    const ROOT: [u8; 32] = hex!("02388da3aee85236d64e272fec0b1a6fcd4962986327971faef9ee2951a4ad6a");
+    const KEY: [u8; 32] = hex!("f2d067d8ef7e97deb231d46f40f9f30200e6f1dad495d33e2a7911825a97ad14");
+    const VALUE_HASH: [u8; 32] =
+        hex!("40414333f8109f8cb971c67c9eca3c0049e21e6c5e28551f1a4975c96ab15212");
+    const SIBLINGS: [[u8; 32]; 11] = [
+        hex!("fafdceaec25fd64517ce3745992467dfac306a5ce59e63255da5b9f58d1417ea"),
+        hex!("4480c449082954642653a4570c7cb2ea2114d79b61621b94f095f25d640b6e27"),
+        hex!("0fc055434d70262945d428a5eda3d8396aa960c65ee8c4e79bd20638a95e7a31"),
+        hex!("731e28eb6655e01b8714aa72f76a0f468c330b46eb9c21816a88e56840896f24"),
+        hex!("b120265e60289e6e44216efd4f3fba86a8de645d3eb7912ff09812024c639b2f"),
+        hex!("d90e0a63c7c3cf7ed000841a85f981d8c6bec4c23353822204c7c9e9c5dee4db"),
+        hex!("30b21a8a3bf202b5fe18e415c299fd3b9985462a6292fffdabd5b32fbc27ba30"),
+        SPARSE_MERKLE_PLACEHOLDER_HASH,
+        hex!("5b9096922002407577b4e46e6466aadb15cbb9521fd0e9847d474398ea3736e2"),
+        SPARSE_MERKLE_PLACEHOLDER_HASH,
+        hex!("884f8b72a832aa718c6590d0bfeb1ec85b546611b6c07f8df563827974ad8134"),
+    ];
+
+    fn leaf_proof() -> SparseMerkleProof {
+        SparseMerkleProof {
+            leaf: Some(SparseMerkleLeafNode {
+                key: KEY.into(),
+                value_hash: VALUE_HASH.into(),
+            }),
+            siblings: SIBLINGS.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    #[test]
+    fn membership() {
+        assert_eq!(verify_membership(leaf_proof(), ROOT), Ok(()));
+        assert_eq!(
+            verify_existence_proof(leaf_proof(), ROOT, KEY, VALUE_HASH),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn membership_wrong_root() {
+        assert!(matches!(
+            verify_membership(leaf_proof(), [0; 32]),
+            Err(Error::StorageVerification(
+                StorageVerificationError::RootHashMismatch(_, _)
+            ))
+        ));
+    }
+
+    #[test]
+    fn non_membership_in_leaf_subtree() {
+        // differs from `KEY` only after the leaf's depth, so it would split the leaf
+        let key = hex!("f2d067d8ef7e97deb231d46f48f9f30200e6f1dad495d33e2a7911825a97ad14");
+
+        assert_eq!(verify_non_membership(leaf_proof(), ROOT, key), Ok(()));
+    }
+
+    #[test]
+    fn non_membership_in_empty_subtree() {
+        // the 8th bit is flipped, which leads into the empty subtree that is the 8th sibling of
+        // `KEY`
+        let key = hex!("f3d067d8ef7e97deb231d46f40f9f30200e6f1dad495d33e2a7911825a97ad14");
+
+        let proof = SparseMerkleProof {
+            leaf: None,
+            siblings: SIBLINGS[..7]
+                .iter()
+                .copied()
+                .chain([hex!(
+                    "13f733a05ee32ecf0725514e346286a8df71b6b358b62e42c8671b5896d6ec22"
+                )])
+                .map(Into::into)
+                .collect(),
+        };
+
+        assert_eq!(verify_non_membership(proof, ROOT, key), Ok(()));
+    }
+
+    #[test]
+    fn non_membership_of_existing_key() {
+        assert_eq!(
+            verify_non_membership(leaf_proof(), ROOT, KEY),
+            Err(StorageVerificationError::ExpectedNonMembershipVerification.into())
+        );
+    }
+
+    #[test]
+    fn non_membership_outside_leaf_subtree() {
+        // the 6th bit is flipped, so the key would not end up next to the leaf in the proof
+        let key = hex!("f6d067d8ef7e97deb231d46f40f9f30200e6f1dad495d33e2a7911825a97ad14");
+
+        assert_eq!(
+            verify_non_membership(leaf_proof(), ROOT, key),
+            Err(StorageVerificationError::InvalidNonMembershipLeaf {
+                key: H256::new(key),
+                leaf_key: H256::new(KEY),
+            }
+            .into())
+        );
+    }
+}
@@ -0,0 +1,269 @@
+//! Reading the state of an arbitrum rollup from its L1.
+
+use alloy::{
+    primitives::B256,
+    providers::{Provider, RootProvider},
+    rpc::types::{Block, BlockNumberOrTag, BlockTransactionsKind, Filter},
+    sol_types::SolEvent,
+    transports::{BoxTransport, TransportError},
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+use unionlabs::{
+    bounded::BoundedU32,
+    primitives::{H160, H256, U256},
+};
+
+/// The location of the rollup state in the rollup contract on the L1.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RollupConfig {
+    /// The address of the rollup contract on the L1.
+    pub l1_contract_address: H160,
+    /// The storage slot of `_latestConfirmed` in the rollup contract.
+    pub l1_next_node_num_slot: U256,
+    /// The offset of `_latestConfirmed` in [`Self::l1_next_node_num_slot`], in bytes.
+    pub l1_next_node_num_slot_offset_bytes: BoundedU32<0, 24>,
+    /// The storage slot of the `_nodes` mapping in the rollup contract.
+    pub l1_nodes_slot: U256,
+    /// The offset of `confirmData` in the `Node` struct.
+    pub l1_nodes_confirm_data_offset: U256,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Transport(#[from] TransportError),
+    #[error("expected exactly one `NodeCreated` event for node {node_num}, found {found}")]
+    NodeCreated { node_num: u64, found: usize },
+    #[error("unable to decode `NodeCreated` event")]
+    Decode(#[from] alloy::sol_types::Error),
+    #[error("l2 block {0} not found")]
+    L2BlockNotFound(H256),
+}
+
+impl RollupConfig {
+    /// The number of the latest confirmed node as of the L1 block `l1_height`.
+    #[instrument(skip_all, fields(%self.l1_contract_address, l1_height))]
+    pub async fn latest_confirmed_node_num(
+        &self,
+        l1_provider: &RootProvider<BoxTransport>,
+        l1_height: u64,
+    ) -> Result<u64, Error> {
+        let raw_slot = l1_provider
+            .get_storage_at(
+                self.l1_contract_address.into(),
+                alloy::primitives::U256::from_be_bytes(self.l1_next_node_num_slot.to_be_bytes()),
+            )
+            .block_id(l1_height.into())
+            .await?
+            .to_be_bytes::<32>();
+
+        let node_num = self.node_num_from_slot(raw_slot);
+
+        debug!(node_num, "latest confirmed node");
+
+        Ok(node_num)
+    }
+
+    /// The hash of the L2 block that node `node_num` asserts.
+    #[instrument(skip_all, fields(%self.l1_contract_address, node_num))]
+    pub async fn l2_block_hash_of_node(
+        &self,
+        l1_provider: &RootProvider<BoxTransport>,
+        node_num: u64,
+    ) -> Result<H256, Error> {
+        let logs = l1_provider
+            .get_logs(
+                &Filter::new()
+                    .address(alloy::primitives::Address::from(self.l1_contract_address))
+                    .from_block(BlockNumberOrTag::Earliest)
+                    .to_block(BlockNumberOrTag::Latest)
+                    .event_signature(NodeCreated::SIGNATURE_HASH)
+                    .topic1(B256::from(alloy::primitives::U256::from(node_num))),
+            )
+            .await?;
+
+        let [log] = &*logs else {
+            return Err(Error::NodeCreated {
+                node_num,
+                found: logs.len(),
+            });
+        };
+
+        let event = NodeCreated::decode_log_data(log.data(), true)?;
+
+        let block_hash = event.assertion.afterState.globalState.bytes32Vals[0].into();
+
+        debug!(%block_hash, "l2 block of node");
+
+        Ok(block_hash)
+    }
+
+    /// The latest confirmed node as of the L1 block `l1_height`, and the L2 block it asserts.
+    pub async fn latest_confirmed_l2_block(
+        &self,
+        l1_provider: &RootProvider<BoxTransport>,
+        l2_provider: &RootProvider<BoxTransport>,
+        l1_height: u64,
+    ) -> Result<(u64, Block), Error> {
+        let node_num = self
+            .latest_confirmed_node_num(l1_provider, l1_height)
+            .await?;
+
+        let block_hash = self.l2_block_hash_of_node(l1_provider, node_num).await?;
+
+        let block = l2_provider
+            .get_block_by_hash(block_hash.into(), BlockTransactionsKind::Hashes)
+            .await?
+            .ok_or(Error::L2BlockNotFound(block_hash))?;
+
+        Ok((node_num, block))
+    }
+
+    /// Read `_latestConfirmed` out of the raw value of [`Self::l1_next_node_num_slot`].
+    fn node_num_from_slot(&self, raw_slot: [u8; 32]) -> u64 {
+        let offset = self.l1_next_node_num_slot_offset_bytes.inner() as usize;
+
+        u64::from_be_bytes(
+            raw_slot[offset..offset + 8]
+                .try_into()
+                .expect("size is correct; qed;"),
+        )
+    }
+
+    /// The storage slot of `_nodes[node_num].confirmData` in the rollup contract.
+    #[must_use]
+    pub fn nodes_confirm_data_slot(&self, node_num: u64) -> U256 {
+        arbitrum_verifier::nodes_confirm_data_mapping_key(
+            self.l1_nodes_slot,
+            node_num,
+            self.l1_nodes_confirm_data_offset,
+        )
+    }
+}
+
+// https://github.com/OffchainLabs/nitro-contracts/blob/90037b996509312ef1addb3f9352457b8a99d6a6/src/rollup/IRollupCore.sol
+alloy::sol! {
+    struct GlobalState {
+        bytes32[2] bytes32Vals;
+        uint64[2] u64Vals;
+    }
+
+    struct ExecutionState {
+        GlobalState globalState;
+        uint8 machineStatus;
+    }
+
+    struct Assertion {
+        ExecutionState beforeState;
+        ExecutionState afterState;
+        uint64 numBlocks;
+    }
+
+    event NodeCreated(
+        uint64 indexed nodeNum,
+        bytes32 indexed parentNodeHash,
+        bytes32 indexed nodeHash,
+        bytes32 executionHash,
+        Assertion assertion,
+        bytes32 afterInboxBatchAcc,
+        bytes32 wasmModuleRoot,
+        uint256 inboxMaxCount
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn config() -> RollupConfig {
+        RollupConfig {
+            l1_contract_address: H160::new([0xaa; 20]),
+            l1_next_node_num_slot: U256::from(117u64),
+            l1_next_node_num_slot_offset_bytes: BoundedU32::new(0).unwrap(),
+            l1_nodes_slot: U256::from(117u64),
+            l1_nodes_confirm_data_offset: U256::from(2u64),
+        }
+    }
+
+    #[test]
+    fn config_from_json() {
+        let config = serde_json::from_value::<RollupConfig>(json!({
+            "l1_contract_address": "0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+            "l1_next_node_num_slot": "117",
+            "l1_next_node_num_slot_offset_bytes": 0,
+            "l1_nodes_slot": "117",
+            "l1_nodes_confirm_data_offset": "2"
+        }))
+        .unwrap();
+
+        assert_eq!(config, self::config());
+    }
+
+    #[test]
+    fn config_rejects_out_of_bounds_offset() {
+        let mut value = serde_json::to_value(config()).unwrap();
+        value["l1_next_node_num_slot_offset_bytes"] = json!(25);
+
+        assert!(serde_json::from_value::<RollupConfig>(value).is_err());
+    }
+
+    #[test]
+    fn config_rejects_unknown_fields() {
+        let mut value = serde_json::to_value(config()).unwrap();
+        value["l2_ibc_contract_address"] = json!("0xaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa");
+
+        assert!(serde_json::from_value::<RollupConfig>(value).is_err());
+    }
+
+    #[test]
+    fn node_num_from_slot() {
+        let mut raw_slot = [0xff; 32];
+        raw_slot[..8].copy_from_slice(&1234_u64.to_be_bytes());
+
+        assert_eq!(config().node_num_from_slot(raw_slot), 1234);
+
+        let config = RollupConfig {
+            l1_next_node_num_slot_offset_bytes: BoundedU32::new(24).unwrap(),
+            ..config()
+        };
+
+        let mut raw_slot = [0xff; 32];
+        raw_slot[24..].copy_from_slice(&5678_u64.to_be_bytes());
+
+        assert_eq!(config.node_num_from_slot(raw_slot), 5678);
+    }
+
+    #[test]
+    fn nodes_confirm_data_slot() {
+        // keccak256(abi.encode(uint64(5), uint256(117))) + 2
+        assert_eq!(
+            config().nodes_confirm_data_slot(5),
+            U256::from_be_hex("0xbf2b27a7a1410f04f950972b0ded6b16425657bcade07b1d8115d04fade73ce8")
+                .unwrap()
+        );
+
+        // keccak256(abi.encode(uint64(12345), uint256(117))) + 2
+        assert_eq!(
+            config().nodes_confirm_data_slot(12345),
+            U256::from_be_hex("0x434ff606bc258369372acf04628a0d786ca9159981823b44649716161038d87b")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn nodes_confirm_data_slot_without_offset() {
+        let config = RollupConfig {
+            l1_nodes_confirm_data_offset: U256::from(0u64),
+            ..config()
+        };
+
+        assert_eq!(
+            config.nodes_confirm_data_slot(5) + U256::from(2u64),
+            self::config().nodes_confirm_data_slot(5)
+        );
+    }
+}
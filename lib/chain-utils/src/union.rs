@@ -11,7 +11,10 @@ use unionlabs::{
 
 use crate::{
     cosmos_sdk::{CosmosKeyring, CosmosSdkChain, CosmosSdkChainRpcs, GasConfig},
-    keyring::{ChainKeyring, ConcurrentKeyring, KeyringConfig, KeyringEntry, SignerBalance},
+    keyring::{
+        ChainKeyring, ConcurrentKeyring, KeyType, KeyringConfig, KeyringEntry, KeyringError,
+        SignerBalance,
+    },
 };
 
 #[derive(Debug, Clone)]
@@ -66,6 +69,8 @@ pub enum UnionInitError {
         #[source]
         source: Option<ParseIntError>,
     },
+    #[error("error loading keyring")]
+    Keyring(#[from] KeyringError),
 }
 
 impl Union {
@@ -89,27 +94,23 @@ impl Union {
 
         Ok(Self {
             // TODO: Deduplicate between this and cosmos.rs
-            keyring: CosmosKeyring::new(
-                config.keyring.name,
-                config
-                    .keyring
-                    .keys
-                    .into_iter()
-                    // TODO: Make this configurable or fetch it from the chain
-                    .map(|entry| {
-                        let signer = CosmosSigner::new(
-                            ecdsa::SigningKey::from_bytes(entry.value().as_slice().into())
-                                .expect("invalid private key"),
-                            "union".to_owned(),
-                        );
-
-                        KeyringEntry {
-                            name: entry.name(),
-                            address: signer.to_string(),
-                            signer,
-                        }
-                    }),
-            ),
+            keyring: CosmosKeyring::from_config(
+                config.keyring,
+                KeyType::Secp256k1,
+                |name, key| {
+                    let signer = CosmosSigner::new(
+                        ecdsa::SigningKey::from_slice(&key)?,
+                        // TODO: Make this configurable or fetch it from the chain
+                        "union".to_owned(),
+                    );
+
+                    Ok::<_, ecdsa::Error>(KeyringEntry {
+                        name,
+                        address: signer.to_string(),
+                        signer,
+                    })
+                },
+            )?,
             tm_client,
             chain_id,
             chain_revision,
@@ -15,6 +15,7 @@ use crate::types::{Channel, ChannelId, ClientId, ConnectionId, Packet};
 pub enum Datagram {
     CreateClient(MsgCreateClient),
     UpdateClient(MsgUpdateClient),
+    Misbehaviour(MsgMisbehaviour),
     ConnectionOpenInit(MsgConnectionOpenInit),
     ConnectionOpenTry(MsgConnectionOpenTry),
     ConnectionOpenAck(MsgConnectionOpenAck),
@@ -40,6 +41,7 @@ impl Datagram {
         match self {
             Self::CreateClient(_) => None,
             Self::UpdateClient(_) => None,
+            Self::Misbehaviour(_) => None,
             Self::ConnectionOpenInit(_) => None,
             Self::ConnectionOpenTry(msg) => Some(Height::new(msg.proof_height)),
             Self::ConnectionOpenAck(msg) => Some(Height::new(msg.proof_height)),
@@ -48,14 +50,14 @@ impl Datagram {
             Self::ChannelOpenTry(msg) => Some(Height::new(msg.proof_height)),
             Self::ChannelOpenAck(msg) => Some(Height::new(msg.proof_height)),
             Self::ChannelOpenConfirm(msg) => Some(Height::new(msg.proof_height)),
-            Self::ChannelCloseInit(_msg) => todo!(),
-            Self::ChannelCloseConfirm(_msg) => todo!(),
+            Self::ChannelCloseInit(_) => None,
+            Self::ChannelCloseConfirm(msg) => Some(Height::new(msg.proof_height)),
             Self::PacketRecv(msg) => Some(Height::new(msg.proof_height)),
             Self::PacketAcknowledgement(msg) => Some(Height::new(msg.proof_height)),
-            Self::PacketTimeout(_msg) => todo!(),
-            Self::IntentPacketRecv(_msg) => todo!(),
-            Self::BatchSend(_msg) => todo!(),
-            Self::BatchAcks(_msg) => todo!(),
+            Self::PacketTimeout(msg) => Some(Height::new(msg.proof_height)),
+            Self::IntentPacketRecv(_) => None,
+            Self::BatchSend(_) => None,
+            Self::BatchAcks(_) => None,
         }
     }
 
@@ -63,6 +65,7 @@ impl Datagram {
         match self {
             Self::CreateClient(_) => "create_client",
             Self::UpdateClient(_) => "update_client",
+            Self::Misbehaviour(_) => "misbehaviour",
             Self::ConnectionOpenInit(_) => "connection_open_init",
             Self::ConnectionOpenTry(_) => "connection_open_try",
             Self::ConnectionOpenAck(_) => "connection_open_ack",
@@ -108,6 +111,18 @@ pub struct MsgUpdateClient {
     pub client_message: Bytes,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "snake_case", deny_unknown_fields)
+)]
+pub struct MsgMisbehaviour {
+    pub client_id: ClientId,
+    pub client_message: Bytes,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 #[cfg_attr(
@@ -226,7 +241,9 @@ pub struct MsgChannelOpenConfirm {
     derive(serde::Serialize, serde::Deserialize),
     serde(rename_all = "snake_case", deny_unknown_fields)
 )]
-pub struct MsgChannelCloseInit {}
+pub struct MsgChannelCloseInit {
+    pub channel_id: ChannelId,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
@@ -235,7 +252,11 @@ pub struct MsgChannelCloseInit {}
     derive(serde::Serialize, serde::Deserialize),
     serde(rename_all = "snake_case", deny_unknown_fields)
 )]
-pub struct MsgChannelCloseConfirm {}
+pub struct MsgChannelCloseConfirm {
+    pub channel_id: ChannelId,
+    pub proof_init: Bytes,
+    pub proof_height: u64,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
@@ -272,7 +293,11 @@ pub struct MsgPacketAcknowledgement {
     derive(serde::Serialize, serde::Deserialize),
     serde(rename_all = "snake_case", deny_unknown_fields)
 )]
-pub struct MsgPacketTimeout {}
+pub struct MsgPacketTimeout {
+    pub packet: Packet,
+    pub proof: Bytes,
+    pub proof_height: u64,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
@@ -281,7 +306,14 @@ pub struct MsgPacketTimeout {}
     derive(serde::Serialize, serde::Deserialize),
     serde(rename_all = "snake_case", deny_unknown_fields)
 )]
-pub struct MsgIntentPacketRecv {}
+pub struct MsgIntentPacketRecv {
+    pub packets: Vec<Packet>,
+    pub market_maker_msgs: Vec<Bytes>,
+    /// The address of the market maker on the chain the packets are received on, in the native
+    /// encoding of that chain.
+    pub market_maker: Bytes,
+    pub empty_proof: Bytes,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
@@ -290,7 +322,10 @@ pub struct MsgIntentPacketRecv {}
     derive(serde::Serialize, serde::Deserialize),
     serde(rename_all = "snake_case", deny_unknown_fields)
 )]
-pub struct MsgBatchSend {}
+pub struct MsgBatchSend {
+    pub source_channel: ChannelId,
+    pub packets: Vec<Packet>,
+}
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
@@ -299,4 +334,9 @@ pub struct MsgBatchSend {}
     derive(serde::Serialize, serde::Deserialize),
     serde(rename_all = "snake_case", deny_unknown_fields)
 )]
-pub struct MsgBatchAcks {}
+pub struct MsgBatchAcks {
+    /// The channel on this chain that the packets were received on.
+    pub source_channel: ChannelId,
+    pub packets: Vec<Packet>,
+    pub acks: Vec<Bytes>,
+}
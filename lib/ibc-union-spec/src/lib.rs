@@ -160,8 +160,34 @@ pub fn log_event(e: &FullEvent, chain_id: &voyager_core::ChainId) {
             data.version = %e.version,
             "event"
         ),
-        FullEvent::ChannelCloseInit(_e) => info!(event, "event"),
-        FullEvent::ChannelCloseConfirm(_e) => info!(event, "event"),
+        FullEvent::ChannelCloseInit(e) => info!(
+            event,
+            %chain_id,
+            data.port_id = %e.port_id,
+            data.channel_id = %e.channel_id,
+            data.counterparty_port_id = %e.counterparty_port_id,
+            data.counterparty_channel_id = %e.counterparty_channel_id,
+            data.connection.state = ?e.connection.state,
+            data.connection.client_id = %e.connection.client_id,
+            data.connection.counterparty_client_id = %e.connection.counterparty_client_id,
+            data.connection.counterparty_connection_id = %e.connection.counterparty_connection_id,
+            data.version = %e.version,
+            "event"
+        ),
+        FullEvent::ChannelCloseConfirm(e) => info!(
+            event,
+            %chain_id,
+            data.port_id = %e.port_id,
+            data.channel_id = %e.channel_id,
+            data.counterparty_port_id = %e.counterparty_port_id,
+            data.counterparty_channel_id = %e.counterparty_channel_id,
+            data.connection.state = ?e.connection.state,
+            data.connection.client_id = %e.connection.client_id,
+            data.connection.counterparty_client_id = %e.connection.counterparty_client_id,
+            data.connection.counterparty_connection_id = %e.connection.counterparty_connection_id,
+            data.version = %e.version,
+            "event"
+        ),
         FullEvent::PacketSend(e) => info!(
             event,
             %chain_id,
@@ -193,7 +193,7 @@ impl BatchReceiptsPath {
 impl IbcStorePathKey for BatchReceiptsPath {
     type Spec = IbcUnion;
 
-    type Value = H256;
+    type Value = Option<H256>;
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -223,5 +223,5 @@ impl BatchPacketsPath {
 impl IbcStorePathKey for BatchPacketsPath {
     type Spec = IbcUnion;
 
-    type Value = H256;
+    type Value = Option<H256>;
 }
@@ -6,3 +6,38 @@ pub struct ConsensusState {
     pub ibc_storage_root: H256,
     pub timestamp: u64,
 }
+
+#[cfg(feature = "ethabi")]
+pub mod ethabi {
+    use alloy::sol_types::SolValue;
+    use unionlabs::impl_ethabi_via_try_from_into;
+
+    use super::*;
+
+    impl_ethabi_via_try_from_into!(ConsensusState => SolConsensusState);
+
+    alloy::sol! {
+        struct SolConsensusState {
+            bytes32 ibc_storage_root;
+            uint64 timestamp;
+        }
+    }
+
+    impl From<ConsensusState> for SolConsensusState {
+        fn from(value: ConsensusState) -> Self {
+            Self {
+                ibc_storage_root: value.ibc_storage_root.get().into(),
+                timestamp: value.timestamp,
+            }
+        }
+    }
+
+    impl From<SolConsensusState> for ConsensusState {
+        fn from(value: SolConsensusState) -> Self {
+            Self {
+                ibc_storage_root: H256::new(value.ibc_storage_root.0),
+                timestamp: value.timestamp,
+            }
+        }
+    }
+}
@@ -4,6 +4,7 @@ use unionlabs::ibc::core::client::height::Height;
 
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
 pub struct Header {
     pub l1_height: Height,
     pub l1_rollup_contract_proof: AccountProof,
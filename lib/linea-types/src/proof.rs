@@ -3,6 +3,7 @@ use unionlabs::primitives::Bytes;
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
 pub struct MerklePath {
     pub value: Bytes,
     pub proof_related_nodes: Vec<Bytes>,
@@ -10,6 +11,7 @@ pub struct MerklePath {
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
 pub struct InclusionProof {
     pub key: Bytes,
     pub leaf_index: u64,
@@ -18,6 +20,7 @@ pub struct InclusionProof {
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
 pub struct NonInclusionProof {
     pub key: Bytes,
     pub left_leaf_index: u64,
@@ -28,6 +31,7 @@ pub struct NonInclusionProof {
 
 #[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
 #[serde(untagged)]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
 pub enum MerkleProof {
     Inclusion(InclusionProof),
     NonInclusion(NonInclusionProof),
@@ -29,8 +29,8 @@ pub enum Error {
 // 4. assert rollup.stateRootHashes[l2BlockNumber] = l2StateRoot
 // 5. assert rootHash(l2IbcContract) in l2StateRoot
 pub fn verify_header(
-    client_state: ClientState,
-    header: Header,
+    client_state: &ClientState,
+    header: &Header,
     l1_state_root: H256,
 ) -> Result<(), Error> {
     // 1.
@@ -1,25 +1,36 @@
 use std::{
-    borrow::Borrow, cmp::Eq, collections::HashMap, future::Future, hash::Hash, marker::PhantomData,
+    borrow::Borrow,
+    cmp::Eq,
+    collections::{BTreeMap, HashMap},
+    future::Future,
+    hash::Hash,
+    marker::PhantomData,
     time::Duration,
 };
 
 use frame_support_procedural::{CloneNoBound, DebugNoBound};
 use futures_util::TryStreamExt;
-use itertools::Itertools;
+use queue_common::{
+    all_items, de, item_id, optimized_parents, partition_interest, queue_depth, DepthRow,
+    OPTIMIZE_DEPTH,
+};
 use schemars::JsonSchema;
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde::{Deserialize, Serialize};
 use sqlx::{postgres::PgPoolOptions, prelude::FromRow, types::Json, Either, Executor, PgPool};
 use tracing::{debug, debug_span, info_span, instrument, trace, Instrument};
 use voyager_vm::{
-    filter::{FilterResult, InterestFilter},
+    filter::InterestFilter,
     pass::{Pass, PassResult},
-    Captures, EnqueueResult, ItemId, Op, QueueMessage,
+    Captures, EnqueueResult, ItemId, ItemRecord, Op, ProcessResult, QueueDepth, QueueMessage,
+    QueueSnapshot,
 };
 
-use crate::metrics::{ITEM_PROCESSING_DURATION, OPTIMIZE_ITEM_COUNT, OPTIMIZE_PROCESSING_DURATION};
+use crate::metrics::METRICS;
 
 pub mod metrics;
 
+pub use queue_common::{FailedRecord, RetryingRecord};
+
 /// A fifo queue backed by a postgres table. Not suitable for high-throughput, but enough for ~1k items/sec.
 ///
 /// The queue assumes the following database schema:
@@ -30,6 +41,12 @@ pub mod metrics;
 /// item JSONB
 /// error TEXT
 /// ```
+///
+/// Ready items are processed in order of their `priority` (highest first), and then in the order
+/// they were queued.
+///
+/// Items that fail with a retryable error are requeued in place (keeping their id), with their
+/// `attempts` counter incremented and `retry_at` set to the time they can next be processed.
 #[derive(DebugNoBound, CloneNoBound)]
 pub struct PgQueue<T> {
     client: PgPool,
@@ -69,42 +86,34 @@ struct Record {
     parents: Vec<i64>,
     item: String,
     created_at: sqlx::types::time::OffsetDateTime,
+    // not present on optimize items
+    #[sqlx(default)]
+    attempts: i32,
+    #[sqlx(default)]
+    priority: i32,
 }
 
-#[derive(Debug, FromRow, Serialize)]
-#[serde(bound(serialize = ""))]
-pub struct FailedRecord<T: QueueMessage> {
-    pub id: i64,
-    pub parents: Vec<i64>,
-    pub item: Json<Op<T>>,
-    pub message: String,
-    // pub created_at: sqlx::types::time::OffsetDateTime,
-}
+type FailedRow<T> = queue_common::FailedRow<T, Vec<i64>>;
+type RetryingRow<T> = queue_common::RetryingRow<T, Vec<i64>>;
+type SnapshotRow<T> = queue_common::SnapshotRow<T, Vec<i64>>;
+type LineageRow<T> = queue_common::LineageRow<T, Vec<i64>>;
 
 impl<T: QueueMessage> PgQueue<T> {
     pub async fn query_failed(
         &self,
         page: i64,
         per_page: i64,
-        mut item_filters: Vec<String>,
-        mut message_filters: Vec<String>,
+        item_filters: Vec<String>,
+        message_filters: Vec<String>,
     ) -> Result<Vec<FailedRecord<T>>, sqlx::Error> {
-        // default to all-inclusive filter if none are provided
-        if item_filters.is_empty() {
-            item_filters.push("%".to_owned())
-        }
-
-        if message_filters.is_empty() {
-            message_filters.push("%".to_owned())
-        }
-
         sqlx::query(
             r#"
             SELECT
                 id,
                 parents,
                 item,
-                message
+                message,
+                attempts
             FROM
                 failed 
             WHERE
@@ -118,17 +127,177 @@ impl<T: QueueMessage> PgQueue<T> {
                 $4
             "#,
         )
-        .bind(item_filters)
-        .bind(message_filters)
+        .bind(all_if_empty(item_filters))
+        .bind(all_if_empty(message_filters))
         .bind(per_page)
         .bind((page - 1) * per_page)
-        .map(|row| FailedRecord::<T>::from_row(&row))
+        .map(|row| FailedRow::<T>::from_row(&row).map(Into::into))
+        .fetch_all(&self.client)
+        .await?
+        .into_iter()
+        .collect()
+    }
+
+    /// Query all failed items matching the filters (see [`Self::query_failed`]), oldest first.
+    pub async fn export_failed(
+        &self,
+        item_filters: Vec<String>,
+        message_filters: Vec<String>,
+    ) -> Result<Vec<FailedRecord<T>>, sqlx::Error> {
+        sqlx::query(
+            r#"
+            SELECT
+                id,
+                parents,
+                item,
+                message,
+                attempts
+            FROM
+                failed
+            WHERE
+                item::TEXT LIKE ANY($1)
+                AND message LIKE ANY($2)
+            ORDER BY
+                id ASC
+            "#,
+        )
+        .bind(all_if_empty(item_filters))
+        .bind(all_if_empty(message_filters))
+        .map(|row| FailedRow::<T>::from_row(&row).map(Into::into))
+        .fetch_all(&self.client)
+        .await?
+        .into_iter()
+        .collect()
+    }
+
+    /// Insert previously exported failed items (i.e. from another queue) into the failed table.
+    ///
+    /// The items are assigned new ids, and their parents are dropped since they refer to items in
+    /// the queue they were exported from. Returns the new ids of the items.
+    pub async fn import_failed(
+        &self,
+        records: Vec<FailedRecord<T>>,
+    ) -> Result<Vec<ItemId>, sqlx::Error> {
+        let (items, (messages, attempts)): (Vec<_>, (Vec<_>, Vec<_>)) = records
+            .into_iter()
+            .map(|record| (record.item, (record.message, record.attempts)))
+            .unzip();
+
+        sqlx::query(
+            "
+            INSERT INTO failed (id, item, message, attempts)
+            SELECT nextval('queue_id_seq'::regclass), * FROM UNNEST($1::JSONB[], $2::TEXT[], $3::INT[])
+            RETURNING id
+            ",
+        )
+        .bind(items)
+        .bind(messages)
+        .bind(attempts)
+        .try_map(|row| Id::from_row(&row))
         .fetch_all(&self.client)
         .await?
         .into_iter()
+        .map(|id| item_id(id.id))
         .collect()
     }
 
+    /// Requeue all failed items matching the filters (see [`Self::query_failed`]).
+    ///
+    /// The failed items are moved to the `failed_archive` table, and each requeued item has the
+    /// failed item it was requeued from as its parent. Returns the ids of the requeued items.
+    pub async fn requeue_failed(
+        &self,
+        item_filters: Vec<String>,
+        message_filters: Vec<String>,
+    ) -> Result<Vec<ItemId>, sqlx::Error> {
+        sqlx::query(
+            r#"
+            WITH requeued AS (
+                DELETE FROM
+                    failed
+                WHERE
+                    item::TEXT LIKE ANY($1)
+                    AND message LIKE ANY($2)
+                RETURNING
+                    id,
+                    parents,
+                    item,
+                    message,
+                    created_at,
+                    attempts
+            ),
+            archived AS (
+                INSERT INTO failed_archive (id, parents, item, message, created_at, attempts)
+                SELECT * FROM requeued
+            )
+            INSERT INTO queue (item, parents)
+            SELECT item, ARRAY[id] FROM requeued ORDER BY id ASC
+            RETURNING id
+            "#,
+        )
+        .bind(all_if_empty(item_filters))
+        .bind(all_if_empty(message_filters))
+        .try_map(|row| Id::from_row(&row))
+        .fetch_all(&self.client)
+        .await?
+        .into_iter()
+        .map(|id| item_id(id.id))
+        .collect()
+    }
+
+    /// Move all failed items created more than `older_than` ago to the `failed_archive` table, or
+    /// delete them entirely if `delete` is set.
+    ///
+    /// Returns the amount of items that were archived or deleted.
+    pub async fn archive_failed(
+        &self,
+        older_than: Duration,
+        delete: bool,
+    ) -> Result<u64, sqlx::Error> {
+        let Some(cutoff) = sqlx::types::time::Duration::try_from(older_than)
+            .ok()
+            .and_then(|older_than| {
+                sqlx::types::time::OffsetDateTime::now_utc().checked_sub(older_than)
+            })
+        else {
+            // nothing was created before the earliest representable timestamp
+            return Ok(0);
+        };
+
+        let query = if delete {
+            r#"
+            DELETE FROM
+                failed
+            WHERE
+                created_at < $1
+            "#
+        } else {
+            r#"
+            WITH archived AS (
+                DELETE FROM
+                    failed
+                WHERE
+                    created_at < $1
+                RETURNING
+                    id,
+                    parents,
+                    item,
+                    message,
+                    created_at,
+                    attempts
+            )
+            INSERT INTO failed_archive (id, parents, item, message, created_at, attempts)
+            SELECT * FROM archived
+            "#
+        };
+
+        Ok(sqlx::query(query)
+            .bind(cutoff)
+            .execute(&self.client)
+            .await?
+            .rows_affected())
+    }
+
     pub async fn query_failed_by_id(
         &self,
         id: i64,
@@ -139,7 +308,8 @@ impl<T: QueueMessage> PgQueue<T> {
                id,
                parents,
                item,
-               message
+               message,
+               attempts
             FROM
                failed 
             WHERE
@@ -147,11 +317,256 @@ impl<T: QueueMessage> PgQueue<T> {
             "#,
         )
         .bind(id)
-        .map(|row| FailedRecord::<T>::from_row(&row))
+        .map(|row| FailedRow::<T>::from_row(&row).map(Into::into))
         .fetch_optional(&self.client)
         .await?
         .transpose()
     }
+
+    /// Query the item with the specified id and all of its ancestors (the items it was produced
+    /// from), up to `max_depth` levels deep.
+    pub async fn query_ancestors(
+        &self,
+        id: i64,
+        max_depth: i32,
+    ) -> Result<Vec<ItemRecord<T>>, sqlx::Error> {
+        // the CTE is inlined so that lookups by id and parents hit the indexes of each table
+        let all_items = all_items("NOT MATERIALIZED");
+
+        self.query_lineage(
+            &format!(
+                r#"
+                WITH RECURSIVE {all_items},
+                lineage AS (
+                    SELECT id, parents, item, status, 0 AS depth FROM items WHERE id = $1
+                    UNION
+                    SELECT
+                        items.id, items.parents, items.item, items.status, lineage.depth + 1
+                    FROM
+                        items
+                        JOIN lineage ON items.id = ANY(lineage.parents)
+                    WHERE
+                        lineage.depth < $2
+                )
+                "#
+            ),
+            id,
+            max_depth,
+        )
+        .await
+    }
+
+    /// Query the item with the specified id and all of its descendants (the items produced from
+    /// it), up to `max_depth` levels deep.
+    pub async fn query_descendants(
+        &self,
+        id: i64,
+        max_depth: i32,
+    ) -> Result<Vec<ItemRecord<T>>, sqlx::Error> {
+        let all_items = all_items("NOT MATERIALIZED");
+
+        self.query_lineage(
+            &format!(
+                r#"
+                WITH RECURSIVE {all_items},
+                lineage AS (
+                    SELECT id, parents, item, status, 0 AS depth FROM items WHERE id = $1
+                    UNION
+                    SELECT
+                        items.id, items.parents, items.item, items.status, lineage.depth + 1
+                    FROM
+                        items
+                        JOIN lineage ON items.parents @> ARRAY[lineage.id]
+                    WHERE
+                        lineage.depth < $2
+                )
+                "#
+            ),
+            id,
+            max_depth,
+        )
+        .await
+    }
+
+    async fn query_lineage(
+        &self,
+        cte: &str,
+        id: i64,
+        max_depth: i32,
+    ) -> Result<Vec<ItemRecord<T>>, sqlx::Error> {
+        // an item can be reached through multiple paths, only keep the closest one
+        sqlx::query(&format!(
+            r#"
+            {cte}
+            SELECT DISTINCT ON (id, status)
+                id,
+                parents,
+                item,
+                status,
+                depth
+            FROM
+                lineage
+            ORDER BY
+                id ASC,
+                status ASC,
+                depth ASC
+            "#
+        ))
+        .bind(id)
+        .bind(max_depth)
+        .try_map(|row| LineageRow::<T>::from_row(&row))
+        .fetch_all(&self.client)
+        .await?
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect()
+    }
+
+    /// Query all pending items (the items that are ready to be processed and the items waiting to
+    /// be optimized), i.e. to replay them against an in-memory queue (see
+    /// [`InMemoryQueue::from_snapshot`]).
+    ///
+    /// [`InMemoryQueue::from_snapshot`]: voyager_vm::in_memory::InMemoryQueue::from_snapshot
+    pub async fn snapshot(&self) -> Result<QueueSnapshot<T>, sqlx::Error> {
+        let mut tx = self.client.begin().await?;
+
+        // read both tables from the same snapshot of the database
+        sqlx::query("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+            .execute(tx.as_mut())
+            .await?;
+
+        let queue = sqlx::query(
+            r#"
+            SELECT
+                id,
+                parents,
+                item,
+                attempts,
+                priority
+            FROM
+                queue
+            ORDER BY
+                id ASC
+            "#,
+        )
+        .try_map(|row| SnapshotRow::<T>::from_row(&row))
+        .fetch_all(tx.as_mut())
+        .await?
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect::<Result<_, _>>()?;
+
+        let mut optimize = BTreeMap::<_, Vec<_>>::new();
+
+        for record in sqlx::query(
+            r#"
+            SELECT
+                id,
+                parents,
+                item,
+                tag
+            FROM
+                optimize
+            ORDER BY
+                id ASC
+            "#,
+        )
+        .try_map(|row| SnapshotRow::<T>::from_row(&row))
+        .fetch_all(tx.as_mut())
+        .await?
+        {
+            optimize
+                .entry(record.tag.clone())
+                .or_default()
+                .push(record.try_into()?);
+        }
+
+        tx.commit().await?;
+
+        Ok(QueueSnapshot { queue, optimize })
+    }
+
+    /// Query the amount of items currently held by this queue.
+    ///
+    /// The `queue` and `failed` tables can grow large, so the amount of items in these tables is
+    /// estimated from the statistics postgres keeps for the query planner (which are updated by
+    /// autovacuum), instead of scanning the tables. Tables that have not been analyzed yet are
+    /// counted exactly. Retrying items are counted using a partial index, and the items waiting to
+    /// be optimized are always counted exactly.
+    pub async fn depth(&self) -> Result<QueueDepth, sqlx::Error> {
+        let row = sqlx::query_as::<_, DepthRow>(
+            r#"
+            SELECT
+                (
+                    SELECT
+                        CASE
+                            WHEN reltuples < 0 THEN (SELECT count(*) FROM queue)
+                            ELSE reltuples::BIGINT
+                        END
+                    FROM
+                        pg_class
+                    WHERE
+                        oid = 'queue'::regclass
+                ) AS ready,
+                (SELECT count(*) FROM queue WHERE attempts > 0) AS retrying,
+                (
+                    SELECT
+                        CASE
+                            WHEN reltuples < 0 THEN (SELECT count(*) FROM failed)
+                            ELSE reltuples::BIGINT
+                        END
+                    FROM
+                        pg_class
+                    WHERE
+                        oid = 'failed'::regclass
+                ) AS failed
+            "#,
+        )
+        .fetch_one(&self.client)
+        .await?;
+
+        let optimize = sqlx::query_as::<_, (String, i64)>(OPTIMIZE_DEPTH)
+            .fetch_all(&self.client)
+            .await?;
+
+        Ok(queue_depth(row, optimize))
+    }
+
+    /// Query all items in the queue that have failed at least once with a retryable error, and are
+    /// waiting to be retried.
+    pub async fn query_retrying(
+        &self,
+        page: i64,
+        per_page: i64,
+    ) -> Result<Vec<RetryingRecord<T>>, sqlx::Error> {
+        sqlx::query(
+            r#"
+            SELECT
+                id,
+                parents,
+                item,
+                attempts,
+                last_error
+            FROM
+                queue
+            WHERE
+                attempts > 0
+            ORDER BY
+                id DESC
+            LIMIT
+                $1
+            OFFSET
+                $2
+            "#,
+        )
+        .bind(per_page)
+        .bind((page - 1) * per_page)
+        .map(|row| RetryingRow::<T>::from_row(&row).map(Into::into))
+        .fetch_all(&self.client)
+        .await?
+        .into_iter()
+        .collect()
+    }
 }
 
 impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
@@ -179,7 +594,11 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
                 id BIGSERIAL PRIMARY KEY,
                 item JSONB NOT NULL,
                 parents BIGINT[] DEFAULT '{}',
-                created_at timestamptz NOT NULL DEFAULT now()
+                created_at timestamptz NOT NULL DEFAULT now(),
+                attempts INT NOT NULL DEFAULT 0,
+                retry_at timestamptz,
+                last_error TEXT,
+                priority INT NOT NULL DEFAULT 0
             );
 
             CREATE TABLE IF NOT EXISTS optimize(
@@ -204,10 +623,37 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
                 item JSONB NOT NULL,
                 parents BIGINT[] DEFAULT '{}',
                 message TEXT,
-                created_at timestamptz NOT NULL DEFAULT now()
+                created_at timestamptz NOT NULL DEFAULT now(),
+                attempts INT NOT NULL DEFAULT 0
             );
 
+            CREATE TABLE IF NOT EXISTS failed_archive(
+                id BIGINT PRIMARY KEY,
+                item JSONB NOT NULL,
+                parents BIGINT[] DEFAULT '{}',
+                message TEXT,
+                created_at timestamptz NOT NULL DEFAULT now(),
+                attempts INT NOT NULL DEFAULT 0,
+                archived_at timestamptz NOT NULL DEFAULT now()
+            );
+
+            -- for queues created before retries were tracked
+            ALTER TABLE queue ADD COLUMN IF NOT EXISTS attempts INT NOT NULL DEFAULT 0;
+            ALTER TABLE queue ADD COLUMN IF NOT EXISTS retry_at timestamptz;
+            ALTER TABLE queue ADD COLUMN IF NOT EXISTS last_error TEXT;
+            ALTER TABLE queue ADD COLUMN IF NOT EXISTS priority INT NOT NULL DEFAULT 0;
+            ALTER TABLE failed ADD COLUMN IF NOT EXISTS attempts INT NOT NULL DEFAULT 0;
+
             CREATE INDEX IF NOT EXISTS index_queue_id ON queue(id);
+            CREATE INDEX IF NOT EXISTS index_queue_priority_id ON queue(priority DESC, id ASC);
+            CREATE INDEX IF NOT EXISTS index_queue_retrying ON queue(id) WHERE attempts > 0;
+
+            -- used for descendant lookups (ancestor lookups use the primary keys)
+            CREATE INDEX IF NOT EXISTS index_queue_parents ON queue USING GIN (parents);
+            CREATE INDEX IF NOT EXISTS index_optimize_parents ON optimize USING GIN (parents);
+            CREATE INDEX IF NOT EXISTS index_done_parents ON done USING GIN (parents);
+            CREATE INDEX IF NOT EXISTS index_failed_parents ON failed USING GIN (parents);
+            CREATE INDEX IF NOT EXISTS index_failed_archive_parents ON failed_archive USING GIN (parents);
             "#,
         )
         .try_for_each(|result| async move {
@@ -230,24 +676,24 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
     ) -> Result<EnqueueResult, Self::Error> {
         trace!("enqueue");
 
-        let (optimize, ready): (Vec<_>, Vec<_>) =
-            op.normalize()
-                .into_iter()
-                .partition_map(|op| match filter.check_interest(&op) {
-                    FilterResult::Interest(tag) => Either::Left((op, tag)),
-                    FilterResult::NoInterest => Either::Right(op),
-                });
+        let (optimize, ready) = partition_interest([op], filter);
 
         let mut tx = self.client.begin().await?;
 
         let ready_ids = sqlx::query(
             "
-            INSERT INTO queue (item)
-            SELECT * FROM UNNEST($1::JSONB[])
+            INSERT INTO queue (item, priority)
+            SELECT * FROM UNNEST($1::JSONB[], $2::INT[])
             RETURNING id
             ",
         )
-        .bind(ready.into_iter().map(Json).collect::<Vec<_>>())
+        .bind(ready.iter().map(|(op, _)| Json(op)).collect::<Vec<_>>())
+        .bind(
+            ready
+                .iter()
+                .map(|(_, priority)| *priority)
+                .collect::<Vec<_>>(),
+        )
         .try_map(|x| Id::from_row(&x))
         .fetch_all(tx.as_mut())
         .await?;
@@ -300,8 +746,8 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
         f: F,
     ) -> Result<Option<R>, Self::Error>
     where
-        F: (FnOnce(Op<T>, ItemId) -> Fut) + Send + Captures<'a>,
-        Fut: Future<Output = (R, Result<Vec<Op<T>>, String>)> + Send + Captures<'a>,
+        F: (FnOnce(Op<T>, ItemId, u32) -> Fut) + Send + Captures<'a>,
+        Fut: Future<Output = (R, ProcessResult<T>)> + Send + Captures<'a>,
         R: Send + Sync + 'static,
     {
         trace!("process");
@@ -318,7 +764,10 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
                   id
                 FROM
                   queue
+                WHERE
+                  retry_at IS NULL OR retry_at <= now()
                 ORDER BY
+                  priority DESC,
                   id ASC
                 FOR UPDATE
                   SKIP LOCKED
@@ -327,7 +776,9 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
               id,
               parents,
               item::text,
-              created_at
+              created_at,
+              attempts,
+              priority
             "#,
         )
         .try_map(|x| Record::from_row(&x))
@@ -343,18 +794,24 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
                 // really don't feel like defining a new error type right now
                 let op = de(&row.item).map_err(|e| sqlx::Error::Decode(Box::new(e)))?;
 
-                let timer = ITEM_PROCESSING_DURATION.start_timer();
-                let (r, res) = f(op, ItemId::new(row.id).unwrap()).instrument(span).await;
+                let timer = METRICS.item_processing_duration.start_timer();
+                let (r, res) = f(
+                    op,
+                    ItemId::new(row.id).unwrap(),
+                    row.attempts.try_into().unwrap_or_default(),
+                )
+                .instrument(span)
+                .await;
                 let _ = timer.stop_and_record();
 
                 match res {
-                    Err(error) => {
+                    ProcessResult::Failed(error) => {
                         // insert error message and the op into failed
                         sqlx::query(
                             r#"
                             INSERT INTO
-                            failed (id, parents, item,      created_at, message)
-                            VALUES ($1, $2,      $3::JSONB, $4,         $5     )
+                            failed (id, parents, item,      created_at, message, attempts)
+                            VALUES ($1, $2,      $3::JSONB, $4,         $5,      $6      )
                             "#,
                         )
                         .bind(row.id)
@@ -362,11 +819,33 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
                         .bind(row.item)
                         .bind(row.created_at)
                         .bind(error)
+                        .bind(row.attempts + 1)
                         .execute(tx.as_mut())
                         .await?;
                         tx.commit().await?;
                     }
-                    Ok(ops) => {
+                    ProcessResult::Retry { error, delay } => {
+                        // requeue the op in place, keeping its id and lineage
+                        sqlx::query(
+                            r#"
+                            INSERT INTO
+                            queue  (id, parents, item,      created_at, attempts, retry_at, last_error, priority)
+                            VALUES ($1, $2,      $3::JSONB, $4,         $5,       $6,       $7,         $8      )
+                            "#,
+                        )
+                        .bind(row.id)
+                        .bind(row.parents)
+                        .bind(row.item)
+                        .bind(row.created_at)
+                        .bind(row.attempts + 1)
+                        .bind(sqlx::types::time::OffsetDateTime::now_utc() + delay)
+                        .bind(error)
+                        .bind(row.priority)
+                        .execute(tx.as_mut())
+                        .await?;
+                        tx.commit().await?;
+                    }
+                    ProcessResult::Done(ops) => {
                         'block: {
                             // insert the op we just processed into done
                             sqlx::query(
@@ -387,22 +866,22 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
                                 break 'block;
                             }
 
-                            let (optimize, ready): (Vec<_>, Vec<_>) = ops
-                                .into_iter()
-                                .flat_map(Op::normalize)
-                                .partition_map(|op| match filter.check_interest(&op) {
-                                    FilterResult::Interest(tag) => Either::Left((op, tag)),
-                                    FilterResult::NoInterest => Either::Right(op),
-                                });
+                            let (optimize, ready) = partition_interest(ops, filter);
 
                             sqlx::query(
                                 "
-                                INSERT INTO queue (item, parents)
-                                SELECT *, $1 as parents FROM UNNEST($2::JSONB[])
+                                INSERT INTO queue (item, priority, parents)
+                                SELECT *, $1 as parents FROM UNNEST($2::JSONB[], $3::INT[])
                                 ",
                             )
                             .bind(vec![row.id])
-                            .bind(ready.into_iter().map(Json).collect::<Vec<_>>())
+                            .bind(ready.iter().map(|(op, _)| Json(op)).collect::<Vec<_>>())
+                            .bind(
+                                ready
+                                    .iter()
+                                    .map(|(_, priority)| *priority)
+                                    .collect::<Vec<_>>(),
+                            )
                             .execute(tx.as_mut())
                             .await?;
 
@@ -440,6 +919,7 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
         &'a self,
         tag: &'a str,
         optimizer: &'a O,
+        filter: &'a T::Filter,
     ) -> Result<(), Either<Self::Error, O::Error>> {
         trace!(%tag, "optimize");
 
@@ -486,19 +966,31 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
             return Ok(());
         }
 
-        let (ids, msgs) = msgs
+        // the optimized items are not kept around, so the items they produce are linked to the
+        // parents of the optimized items instead. this keeps the lineage of the produced items
+        // intact without storing every optimized item.
+        let (ids, (parents, msgs)) = msgs
             .into_iter()
             .map(|r| {
                 Ok((
                     r.id,
-                    de(&r.item).map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                    (
+                        r.parents,
+                        de(&r.item).map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                    ),
                 ))
             })
-            .collect::<Result<(Vec<_>, Vec<_>), sqlx::Error>>()
+            .collect::<Result<(Vec<_>, (Vec<_>, Vec<_>)), sqlx::Error>>()
             .map_err(Either::Left)?;
 
-        OPTIMIZE_ITEM_COUNT.observe(msgs.len() as f64);
-        let timer = OPTIMIZE_PROCESSING_DURATION.start_timer();
+        METRICS
+            .optimize_item_count
+            .with_label_values(&[tag])
+            .observe(msgs.len() as f64);
+        let timer = METRICS
+            .optimize_processing_duration
+            .with_label_values(&[tag])
+            .start_timer();
 
         let PassResult {
             optimize_further,
@@ -523,12 +1015,7 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
             "optimized items"
         );
 
-        let get_parent_ids = |parent_idxs: &[usize]| {
-            ids.iter()
-                .enumerate()
-                .filter_map(|(idx, id)| parent_idxs.contains(&idx).then_some(*id))
-                .collect::<Vec<_>>()
-        };
+        let get_parent_ids = |parent_idxs: &[usize]| optimized_parents(&parents, parent_idxs);
 
         for (parent_idxs, new_msg, tag) in optimize_further {
             let parents = get_parent_ids(&parent_idxs);
@@ -557,16 +1044,19 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
             let parents = get_parent_ids(&parent_idxs);
             trace!(parent_idxs = ?&parent_idxs, parents = ?&parents);
 
+            let priority = filter.check_interest(&new_msg).priority();
+
             let new_row = sqlx::query(
                 "
-                INSERT INTO queue (item, parents)
+                INSERT INTO queue (item, parents, priority)
                 VALUES
-                    ($1::JSONB, $2)
+                    ($1::JSONB, $2, $3)
                 RETURNING id
                 ",
             )
             .bind(Json(new_msg))
             .bind(&parents)
+            .bind(priority)
             .try_map(|x| Id::from_row(&x))
             .fetch_one(tx.as_mut())
             .await
@@ -581,12 +1071,13 @@ impl<T: QueueMessage> voyager_vm::Queue<T> for PgQueue<T> {
     }
 }
 
-fn de<T: DeserializeOwned>(s: &str) -> Result<T, serde_json::Error> {
-    let mut deserializer = serde_json::Deserializer::from_str(s);
-    deserializer.disable_recursion_limit();
-    // let deserializer = serde_stacker::Deserializer::new(&mut deserializer);
-    let json = T::deserialize(&mut deserializer)?;
-    Ok(json)
+/// Defaults to an all-inclusive `LIKE` filter if no filters are provided.
+fn all_if_empty(mut filters: Vec<String>) -> Vec<String> {
+    if filters.is_empty() {
+        filters.push("%".to_owned());
+    }
+
+    filters
 }
 
 pub trait MapExt<K, V> {
@@ -0,0 +1,311 @@
+use itertools::Itertools;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use sqlx::{prelude::FromRow, types::Json, Either};
+use voyager_vm::{
+    filter::{FilterResult, InterestFilter, Priority},
+    ItemId, ItemRecord, ItemStatus, Op, QueueDepth, QueueMessage, SnapshotItem,
+};
+
+pub mod metrics;
+
+/// An item that failed with a fatal error. This is also the format that failed items are exported
+/// and imported in, which is the same for all of the queue backends.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(bound(serialize = "", deserialize = ""))]
+pub struct FailedRecord<T: QueueMessage> {
+    pub id: i64,
+    pub parents: Vec<i64>,
+    pub item: Json<Op<T>>,
+    pub message: String,
+    /// The amount of times this item was attempted before it failed.
+    #[serde(default)]
+    pub attempts: i32,
+}
+
+/// An item in the queue that has failed at least once with a retryable error.
+#[derive(Debug, Serialize)]
+#[serde(bound(serialize = ""))]
+pub struct RetryingRecord<T: QueueMessage> {
+    pub id: i64,
+    pub parents: Vec<i64>,
+    pub item: Json<Op<T>>,
+    /// The amount of times this item has been attempted and failed with a retryable error.
+    pub attempts: i32,
+    /// The error returned by the last attempt.
+    pub last_error: String,
+}
+
+/// The parents of an item, as stored by a queue backend (i.e. a `BIGINT[]` in postgres, or a JSON
+/// array in SQLite).
+pub trait Parents {
+    fn into_ids(self) -> Vec<i64>;
+}
+
+impl Parents for Vec<i64> {
+    fn into_ids(self) -> Vec<i64> {
+        self
+    }
+}
+
+impl Parents for Json<Vec<i64>> {
+    fn into_ids(self) -> Vec<i64> {
+        self.0
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct FailedRow<T: QueueMessage, P> {
+    pub id: i64,
+    pub parents: P,
+    pub item: Json<Op<T>>,
+    pub message: String,
+    pub attempts: i32,
+}
+
+impl<T: QueueMessage, P: Parents> From<FailedRow<T, P>> for FailedRecord<T> {
+    fn from(row: FailedRow<T, P>) -> Self {
+        Self {
+            id: row.id,
+            parents: row.parents.into_ids(),
+            item: row.item,
+            message: row.message,
+            attempts: row.attempts,
+        }
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct RetryingRow<T: QueueMessage, P> {
+    pub id: i64,
+    pub parents: P,
+    pub item: Json<Op<T>>,
+    pub attempts: i32,
+    pub last_error: String,
+}
+
+impl<T: QueueMessage, P: Parents> From<RetryingRow<T, P>> for RetryingRecord<T> {
+    fn from(row: RetryingRow<T, P>) -> Self {
+        Self {
+            id: row.id,
+            parents: row.parents.into_ids(),
+            item: row.item,
+            attempts: row.attempts,
+            last_error: row.last_error,
+        }
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct SnapshotRow<T: QueueMessage, P> {
+    pub id: i64,
+    pub parents: P,
+    pub item: Json<Op<T>>,
+    // only present on queue items
+    #[sqlx(default)]
+    pub attempts: i32,
+    #[sqlx(default)]
+    pub priority: i32,
+    // only present on optimize items
+    #[sqlx(default)]
+    pub tag: String,
+}
+
+impl<T: QueueMessage, P: Parents> TryFrom<SnapshotRow<T, P>> for SnapshotItem<T> {
+    type Error = sqlx::Error;
+
+    fn try_from(row: SnapshotRow<T, P>) -> Result<Self, Self::Error> {
+        Ok(SnapshotItem {
+            id: item_id(row.id)?,
+            parents: item_ids(row.parents)?,
+            priority: row.priority,
+            attempts: row.attempts.try_into().unwrap_or_default(),
+            item: row.item.0,
+        })
+    }
+}
+
+#[derive(Debug, FromRow)]
+pub struct LineageRow<T: QueueMessage, P> {
+    pub id: i64,
+    pub parents: P,
+    pub item: Json<Op<T>>,
+    pub status: String,
+    pub depth: i32,
+}
+
+impl<T: QueueMessage, P: Parents> TryFrom<LineageRow<T, P>> for ItemRecord<T> {
+    type Error = sqlx::Error;
+
+    fn try_from(row: LineageRow<T, P>) -> Result<Self, Self::Error> {
+        Ok(ItemRecord {
+            id: item_id(row.id)?,
+            parents: item_ids(row.parents)?,
+            status: match &*row.status {
+                "queue" => ItemStatus::Queue,
+                "optimize" => ItemStatus::Optimize,
+                "done" => ItemStatus::Done,
+                "failed" => ItemStatus::Failed,
+                "archived" => ItemStatus::Archived,
+                status => {
+                    return Err(sqlx::Error::Decode(
+                        format!("invalid status: {status}").into(),
+                    ))
+                }
+            },
+            depth: row.depth.try_into().unwrap_or_default(),
+            item: row.item.0,
+        })
+    }
+}
+
+/// The amount of items in the `queue` and `failed` tables, as returned by the depth query of a
+/// queue backend. These may be estimates, see [`queue_depth`].
+#[derive(Debug, FromRow)]
+pub struct DepthRow {
+    pub ready: i64,
+    pub retrying: i64,
+    pub failed: i64,
+}
+
+/// The amount of items waiting to be optimized, by tag. The `optimize` table only holds the items
+/// until the next optimization pass for their tag, so this is counted exactly.
+pub const OPTIMIZE_DEPTH: &str = r#"
+    SELECT
+        tag,
+        count(*)
+    FROM
+        optimize
+    GROUP BY
+        tag
+"#;
+
+/// Build the [`QueueDepth`] from the results of the depth queries. The counts of the backends that
+/// estimate the size of their tables can be negative (i.e. when the table has not been analyzed
+/// yet), these are clamped to zero.
+pub fn queue_depth(row: DepthRow, optimize: Vec<(String, i64)>) -> QueueDepth {
+    let count = |n: i64| u64::try_from(n).unwrap_or_default();
+
+    QueueDepth {
+        ready: count(row.ready),
+        retrying: count(row.retrying),
+        optimize: optimize
+            .into_iter()
+            .map(|(tag, n)| (tag, count(n)))
+            .collect(),
+        failed: count(row.failed),
+    }
+}
+
+/// The CTE selecting all items in all tables, along with the table they are in, as used by the
+/// lineage queries. `modifier` is inserted before the CTE body (i.e. `NOT MATERIALIZED`).
+pub fn all_items(modifier: &str) -> String {
+    format!(
+        r#"
+    items AS {modifier} (
+        SELECT id, parents, item, 'queue' AS status FROM queue
+        UNION ALL
+        SELECT id, parents, item, 'optimize' AS status FROM optimize
+        UNION ALL
+        SELECT id, parents, item, 'done' AS status FROM done
+        UNION ALL
+        SELECT id, parents, item, 'failed' AS status FROM failed
+        UNION ALL
+        SELECT id, parents, item, 'archived' AS status FROM failed_archive
+    )
+"#
+    )
+}
+
+/// Splits `ops` into the ops that a plugin has expressed interest in (along with the tag to
+/// optimize them under), and the ops that are ready to be processed (along with their priority).
+#[allow(clippy::type_complexity)]
+pub fn partition_interest<'a, T: QueueMessage>(
+    ops: impl IntoIterator<Item = Op<T>>,
+    filter: &'a T::Filter,
+) -> (Vec<(Op<T>, &'a str)>, Vec<(Op<T>, Priority)>) {
+    ops.into_iter()
+        .flat_map(Op::normalize)
+        .partition_map(|op| match filter.check_interest(&op) {
+            FilterResult::Interest(tag) => Either::Left((op, tag)),
+            FilterResult::NoInterest(priority) => Either::Right((op, priority)),
+        })
+}
+
+/// The parents of an op produced by an optimization pass. The optimized items are not kept
+/// around, so the produced op is linked to the parents of the items it was produced from
+/// (`parents[idx]` for every idx in `parent_idxs`) instead. This keeps the lineage of the produced
+/// ops intact without storing every optimized item.
+pub fn optimized_parents(parents: &[Vec<i64>], parent_idxs: &[usize]) -> Vec<i64> {
+    parent_idxs
+        .iter()
+        .filter_map(|idx| parents.get(*idx))
+        .flatten()
+        .copied()
+        .sorted()
+        .dedup()
+        .collect()
+}
+
+pub fn item_id(id: i64) -> Result<ItemId, sqlx::Error> {
+    ItemId::new(id).map_err(|e| sqlx::Error::Decode(format!("invalid id: {e:?}").into()))
+}
+
+pub fn item_ids(parents: impl Parents) -> Result<Vec<ItemId>, sqlx::Error> {
+    parents.into_ids().into_iter().map(item_id).collect()
+}
+
+/// Deserialize an item from the database. Ops can be deeply nested, so the recursion limit is
+/// disabled.
+pub fn de<T: DeserializeOwned>(s: &str) -> Result<T, serde_json::Error> {
+    let mut deserializer = serde_json::Deserializer::from_str(s);
+    deserializer.disable_recursion_limit();
+    let json = T::deserialize(&mut deserializer)?;
+    Ok(json)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    #[test]
+    fn optimized_parents_are_flattened() {
+        let parents = vec![vec![1, 2], vec![], vec![2, 3], vec![4]];
+
+        assert_eq!(optimized_parents(&parents, &[0, 2]), vec![1, 2, 3]);
+        assert_eq!(optimized_parents(&parents, &[1]), Vec::<i64>::new());
+        assert_eq!(optimized_parents(&parents, &[3, 0]), vec![1, 2, 4]);
+        // out of range indexes are ignored
+        assert_eq!(optimized_parents(&parents, &[4]), Vec::<i64>::new());
+    }
+
+    #[test]
+    fn queue_depth_is_clamped() {
+        let depth = queue_depth(
+            DepthRow {
+                ready: 10,
+                retrying: 2,
+                failed: -1,
+            },
+            vec![("a".to_owned(), 3), ("b".to_owned(), 1)],
+        );
+
+        assert_eq!(depth.ready, 10);
+        assert_eq!(depth.retrying, 2);
+        assert_eq!(depth.failed, 0);
+        assert_eq!(
+            depth.optimize,
+            BTreeMap::from([("a".to_owned(), 3), ("b".to_owned(), 1)])
+        );
+    }
+
+    #[test]
+    fn de_deeply_nested() {
+        let depth = 1000;
+        let json = format!("{}1{}", "[".repeat(depth), "]".repeat(depth));
+
+        assert!(serde_json::from_str::<serde_json::Value>(&json).is_err());
+        assert!(de::<serde_json::Value>(&json).is_ok());
+    }
+}
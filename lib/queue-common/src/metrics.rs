@@ -0,0 +1,144 @@
+use prometheus::{Histogram, HistogramOpts, HistogramVec, Registry};
+
+/// The metrics recorded by a queue backend. The names of the metrics are prefixed with the name of
+/// the backend (i.e. `pg_queue_item_processing_duration_seconds`).
+#[derive(Debug, Clone)]
+pub struct QueueMetrics {
+    /// The time it takes to process an item in the queue.
+    pub item_processing_duration: Histogram,
+    /// The time it takes to run a pass over the optimize queue, by tag.
+    pub optimize_processing_duration: HistogramVec,
+    /// The amount of items processed in an optimize pass, by tag.
+    pub optimize_item_count: HistogramVec,
+}
+
+impl QueueMetrics {
+    /// Create the metrics for the backend `prefix`, registering them in `registry`.
+    pub fn new(prefix: &str, registry: &Registry) -> prometheus::Result<Self> {
+        let metrics = Self {
+            item_processing_duration: Histogram::with_opts(HistogramOpts::new(
+                format!("{prefix}_item_processing_duration_seconds"),
+                "The time it takes to process an item in the queue.",
+            ))?,
+            optimize_processing_duration: HistogramVec::new(
+                HistogramOpts::new(
+                    format!("{prefix}_optimize_processing_duration_seconds"),
+                    "The time it takes to run a pass over the optimize queue.",
+                ),
+                &["tag"],
+            )?,
+            optimize_item_count: HistogramVec::new(
+                HistogramOpts::new(
+                    format!("{prefix}_optimize_item_count"),
+                    "The amount of items processed in an optimize pass.",
+                )
+                .buckets(vec![
+                    1.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1000.0, 2000.0,
+                ]),
+                &["tag"],
+            )?,
+        };
+
+        registry.register(Box::new(metrics.item_processing_duration.clone()))?;
+        registry.register(Box::new(metrics.optimize_processing_duration.clone()))?;
+        registry.register(Box::new(metrics.optimize_item_count.clone()))?;
+
+        Ok(metrics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use prometheus::proto::MetricFamily;
+
+    use super::*;
+
+    fn family<'a>(families: &'a [MetricFamily], name: &str) -> &'a MetricFamily {
+        families
+            .iter()
+            .find(|family| family.get_name() == name)
+            .unwrap_or_else(|| panic!("metric {name} not found"))
+    }
+
+    #[test]
+    fn metrics_are_prefixed() {
+        let registry = Registry::new();
+
+        let metrics = QueueMetrics::new("test_queue", &registry).unwrap();
+
+        metrics.item_processing_duration.observe(1.0);
+        metrics
+            .optimize_processing_duration
+            .with_label_values(&["tag"])
+            .observe(1.0);
+        metrics
+            .optimize_item_count
+            .with_label_values(&["tag"])
+            .observe(1.0);
+
+        let mut names = registry
+            .gather()
+            .iter()
+            .map(|family| family.get_name().to_owned())
+            .collect::<Vec<_>>();
+        names.sort();
+
+        assert_eq!(
+            names,
+            [
+                "test_queue_item_processing_duration_seconds",
+                "test_queue_optimize_item_count",
+                "test_queue_optimize_processing_duration_seconds",
+            ]
+        );
+
+        // the same backend can't be registered twice
+        assert!(QueueMetrics::new("test_queue", &registry).is_err());
+        assert!(QueueMetrics::new("other_queue", &registry).is_ok());
+    }
+
+    #[test]
+    fn optimize_metrics_are_labelled_by_tag() {
+        let registry = Registry::new();
+
+        let metrics = QueueMetrics::new("test_queue", &registry).unwrap();
+
+        for (tag, count) in [("a", 5.0), ("a", 15.0), ("b", 1500.0)] {
+            metrics
+                .optimize_item_count
+                .with_label_values(&[tag])
+                .observe(count);
+        }
+
+        let families = registry.gather();
+        let family = family(&families, "test_queue_optimize_item_count");
+
+        let histograms = family
+            .get_metric()
+            .iter()
+            .map(|metric| {
+                (
+                    metric.get_label()[0].get_value().to_owned(),
+                    metric.get_histogram().get_sample_count(),
+                    metric.get_histogram().get_sample_sum(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            histograms,
+            [("a".to_owned(), 2, 20.0), ("b".to_owned(), 1, 1500.0)]
+        );
+
+        // 5.0 and 15.0 fall in the buckets <= 10.0 and <= 20.0 respectively
+        let a = family.get_metric()[0].get_histogram();
+        let cumulative_counts = a
+            .get_bucket()
+            .iter()
+            .map(|bucket| (bucket.get_upper_bound(), bucket.get_cumulative_count()))
+            .take(3)
+            .collect::<Vec<_>>();
+
+        assert_eq!(cumulative_counts, [(1.0, 0), (10.0, 1), (20.0, 2)]);
+    }
+}
@@ -0,0 +1,164 @@
+//! Reading the state of the scroll rollup from its L1.
+
+use alloy::{
+    consensus::Transaction,
+    providers::{Provider, RootProvider},
+    sol_types::SolCall,
+    transports::{BoxTransport, TransportError},
+};
+use scroll_api::ScrollClient;
+use scroll_codec::{
+    batch_header::{BatchHeaderV3, BatchHeaderV3DecodeError},
+    finalizeBundleCall, finalizeBundleWithProofCall,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+use unionlabs::primitives::{H160, H256, U256};
+
+/// The location of the rollup state in the `ScrollChain` contract on the L1.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RollupConfig {
+    /// The address of the `ScrollChain` contract on the L1.
+    pub rollup_contract_address: H160,
+    /// [ScrollChain.lastFinalizedBatchIndex](https://github.com/scroll-tech/scroll/blob/71f88b04f5a69196138c8cec63a75cf1f0ba2d99/contracts/src/L1/rollup/ScrollChain.sol#L153)
+    pub last_finalized_batch_index_slot: U256,
+    /// [ScrollChain.committedBatches](https://github.com/scroll-tech/scroll/blob/71f88b04f5a69196138c8cec63a75cf1f0ba2d99/contracts/src/L1/rollup/ScrollChain.sol#L156)
+    pub committed_batches_slot: U256,
+    /// [ScrollChain.finalizedStateRoots](https://github.com/scroll-tech/scroll/blob/71f88b04f5a69196138c8cec63a75cf1f0ba2d99/contracts/src/L1/rollup/ScrollChain.sol#L159)
+    pub finalized_state_roots_slot: U256,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Transport(#[from] TransportError),
+    #[error("batch index {0} does not fit in a u64")]
+    BatchIndexTooLarge(U256),
+    #[error("batch {batch_index} has not been finalized")]
+    BatchNotFinalized { batch_index: u64 },
+    #[error("finalize transaction {0} not found")]
+    FinalizeTxNotFound(H256),
+    #[error("unable to decode finalize transaction {tx_hash}")]
+    FinalizeTxDecode {
+        tx_hash: H256,
+        #[source]
+        error: alloy::sol_types::Error,
+    },
+    #[error("unable to decode batch header")]
+    BatchHeaderDecode(#[from] BatchHeaderV3DecodeError),
+    #[error(
+        "finalize transaction {tx_hash} finalized batch {found}, \
+        but batch {expected} was expected"
+    )]
+    BatchIndexMismatch {
+        tx_hash: H256,
+        expected: u64,
+        found: u64,
+    },
+}
+
+impl RollupConfig {
+    /// The index of the latest finalized batch as of the L1 block `l1_height`.
+    #[instrument(skip_all, fields(%self.rollup_contract_address, l1_height))]
+    pub async fn last_finalized_batch_index(
+        &self,
+        l1_provider: &RootProvider<BoxTransport>,
+        l1_height: u64,
+    ) -> Result<u64, Error> {
+        let raw_slot = l1_provider
+            .get_storage_at(
+                self.rollup_contract_address.into(),
+                alloy::primitives::U256::from_be_bytes(
+                    self.last_finalized_batch_index_slot.to_be_bytes(),
+                ),
+            )
+            .block_id(l1_height.into())
+            .await?;
+
+        let batch_index = U256::from_be_bytes(raw_slot.to_be_bytes());
+
+        let batch_index =
+            u64::try_from(batch_index).map_err(|()| Error::BatchIndexTooLarge(batch_index))?;
+
+        debug!(batch_index, "last finalized batch");
+
+        Ok(batch_index)
+    }
+
+    /// The encoded header of the finalized batch `batch_index`, read from the calldata of the
+    /// transaction that finalized it.
+    ///
+    /// Batches are finalized in bundles, and only the header of the last batch in a bundle is
+    /// available in the calldata; this is always the case for the last finalized batch.
+    #[instrument(skip_all, fields(%self.rollup_contract_address, batch_index))]
+    pub async fn batch_header(
+        &self,
+        l1_provider: &RootProvider<BoxTransport>,
+        scroll_api_client: &ScrollClient,
+        batch_index: u64,
+    ) -> Result<Vec<u8>, Error> {
+        let tx_hash = scroll_api_client
+            .batch(batch_index)
+            .await
+            .batch
+            .finalize_tx_hash
+            .ok_or(Error::BatchNotFinalized { batch_index })?;
+
+        let tx = l1_provider
+            .get_transaction_by_hash(tx_hash.into())
+            .await?
+            .ok_or(Error::FinalizeTxNotFound(tx_hash))?;
+
+        let batch_header = finalizeBundleWithProofCall::abi_decode(tx.input(), true)
+            .map(|call| call._batchHeader)
+            .or_else(|_| {
+                finalizeBundleCall::abi_decode(tx.input(), true).map(|call| call._batchHeader)
+            })
+            .map_err(|error| Error::FinalizeTxDecode { tx_hash, error })?
+            .to_vec();
+
+        let found = BatchHeaderV3::decode(&batch_header)?.batch_index;
+
+        if found != batch_index {
+            return Err(Error::BatchIndexMismatch {
+                tx_hash,
+                expected: batch_index,
+                found,
+            });
+        }
+
+        Ok(batch_header)
+    }
+
+    /// The storage slot of `finalizedStateRoots[batch_index]` in the `ScrollChain` contract.
+    #[must_use]
+    pub fn finalized_state_root_slot(&self, batch_index: u64) -> U256 {
+        scroll_verifier::mapping_index_to_slot_key(
+            self.finalized_state_roots_slot,
+            U256::from(batch_index),
+        )
+    }
+
+    /// The storage slot of `committedBatches[batch_index]` in the `ScrollChain` contract.
+    #[must_use]
+    pub fn committed_batch_slot(&self, batch_index: u64) -> U256 {
+        scroll_verifier::mapping_index_to_slot_key(
+            self.committed_batches_slot,
+            U256::from(batch_index),
+        )
+    }
+}
+
+/// The height of the last L2 block in the batch `batch_index`.
+#[instrument(skip_all, fields(batch_index))]
+pub async fn l2_height_of_batch(scroll_api_client: &ScrollClient, batch_index: u64) -> u64 {
+    let batch = scroll_api_client.batch(batch_index).await.batch;
+
+    debug!(
+        "batch index {batch_index} is l2 height range {}..={}",
+        batch.start_block_number, batch.end_block_number
+    );
+
+    batch.end_block_number
+}
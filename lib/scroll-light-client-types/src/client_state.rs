@@ -5,6 +5,7 @@ use unionlabs::{
 
 #[derive(Debug, Clone, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
 pub struct ClientState {
     pub chain_id: U256,
     pub frozen_height: Height,
@@ -15,5 +16,7 @@ pub struct ClientState {
     pub l2_contract_address: H160,
     pub l2_finalized_state_roots_slot: U256,
     pub latest_batch_index_slot: U256,
+    /// The latest L1 height this client has been updated to. Consensus states of this client
+    /// are keyed by the L1 height the rollup state was read at.
     pub latest_slot: u64,
 }
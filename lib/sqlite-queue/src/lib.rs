@@ -0,0 +1,1589 @@
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    marker::PhantomData,
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use frame_support_procedural::{CloneNoBound, DebugNoBound};
+use futures_util::TryStreamExt;
+use queue_common::{
+    all_items, de, item_id, optimized_parents, partition_interest, queue_depth, DepthRow,
+    OPTIMIZE_DEPTH,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::{
+    prelude::FromRow,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions},
+    types::Json,
+    Either, Executor, SqliteConnection, SqlitePool,
+};
+use tracing::{debug, debug_span, info_span, instrument, trace, warn, Instrument};
+use voyager_vm::{
+    filter::{InterestFilter, Priority},
+    pass::{Pass, PassResult},
+    Captures, EnqueueResult, ItemId, ItemRecord, Op, ProcessResult, QueueDepth, QueueMessage,
+    QueueSnapshot,
+};
+
+use crate::metrics::METRICS;
+
+pub mod metrics;
+
+pub use queue_common::{FailedRecord, RetryingRecord};
+
+/// A fifo queue backed by an embedded SQLite database. This has the same semantics as the
+/// postgres backed queue (`pg-queue`), but does not require a database server to be provisioned.
+///
+/// The queue uses the following tables, all sharing the same id sequence (`queue_id_seq`):
+///
+/// - `queue`: items ready to be processed
+/// - `optimize`: items waiting to be optimized, keyed by their tag
+/// - `done`: items that have been processed or optimized
+/// - `failed`: items that failed with a fatal error
+/// - `failed_archive`: failed items that have since been requeued or archived
+///
+/// Ready items are processed in order of their `priority` (highest first), and then in the order
+/// they were queued. Items are claimed while they are being processed, and are only removed from
+/// the `queue` table once processing has finished. Any claims left over from a previous run are
+/// released when the queue is opened, so a database file must only be used by a single voyager
+/// instance at a time.
+///
+/// Items that fail with a retryable error are requeued in place (keeping their id), with their
+/// `attempts` counter incremented and `retry_at` set to the time they can next be processed.
+#[derive(DebugNoBound, CloneNoBound)]
+pub struct SqliteQueue<T> {
+    client: SqlitePool,
+    __marker: PhantomData<fn() -> T>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SqliteQueueConfig {
+    /// The path to the database file. This will be created if it does not exist.
+    pub path: PathBuf,
+    pub max_connections: Option<u32>,
+    /// How long to wait for the database to be unlocked before returning an error. Defaults to 5
+    /// seconds.
+    pub busy_timeout: Option<Duration>,
+}
+
+impl SqliteQueueConfig {
+    pub async fn into_sqlite_pool(self) -> sqlx::Result<SqlitePool> {
+        SqlitePoolOptions::new()
+            .max_connections(self.max_connections.unwrap_or(10))
+            .connect_with(
+                SqliteConnectOptions::new()
+                    .filename(&self.path)
+                    .create_if_missing(true)
+                    .journal_mode(SqliteJournalMode::Wal)
+                    .busy_timeout(self.busy_timeout.unwrap_or(Duration::from_secs(5))),
+            )
+            .await
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct Record {
+    id: i64,
+    parents: Json<Vec<i64>>,
+    item: String,
+    created_at: String,
+    // not present on optimize items
+    #[sqlx(default)]
+    attempts: i32,
+    #[sqlx(default)]
+    priority: i32,
+}
+
+type FailedRow<T> = queue_common::FailedRow<T, Json<Vec<i64>>>;
+type RetryingRow<T> = queue_common::RetryingRow<T, Json<Vec<i64>>>;
+type SnapshotRow<T> = queue_common::SnapshotRow<T, Json<Vec<i64>>>;
+type LineageRow<T> = queue_common::LineageRow<T, Json<Vec<i64>>>;
+
+/// Matches the `item` and `message` columns of the `failed` table against the item and message
+/// filters, passed as JSON arrays of `LIKE` patterns in `$1` and `$2`.
+const FAILED_FILTER: &str = r#"
+    EXISTS (SELECT 1 FROM json_each($1) AS f WHERE failed.item LIKE f.value)
+    AND EXISTS (SELECT 1 FROM json_each($2) AS f WHERE failed.message LIKE f.value)
+"#;
+
+impl<T: QueueMessage> SqliteQueue<T> {
+    /// Query the failed items, newest first.
+    ///
+    /// `item_filters` and `message_filters` are `LIKE` patterns matched against the JSON encoded
+    /// item and the error message respectively; an item is returned if it matches any of the item
+    /// filters and any of the message filters. Note that unlike postgres, `LIKE` is case
+    /// insensitive in SQLite.
+    pub async fn query_failed(
+        &self,
+        page: i64,
+        per_page: i64,
+        item_filters: Vec<String>,
+        message_filters: Vec<String>,
+    ) -> Result<Vec<FailedRecord<T>>, sqlx::Error> {
+        Ok(sqlx::query_as::<_, FailedRow<T>>(&format!(
+            r#"
+            SELECT
+                id,
+                parents,
+                item,
+                message,
+                attempts
+            FROM
+                failed
+            WHERE
+                {FAILED_FILTER}
+            ORDER BY
+                id DESC
+            LIMIT
+                $3
+            OFFSET
+                $4
+            "#
+        ))
+        .bind(all_if_empty(item_filters))
+        .bind(all_if_empty(message_filters))
+        .bind(per_page)
+        .bind((page - 1) * per_page)
+        .fetch_all(&self.client)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+    }
+
+    /// Query all failed items matching the filters (see [`Self::query_failed`]), oldest first.
+    pub async fn export_failed(
+        &self,
+        item_filters: Vec<String>,
+        message_filters: Vec<String>,
+    ) -> Result<Vec<FailedRecord<T>>, sqlx::Error> {
+        Ok(sqlx::query_as::<_, FailedRow<T>>(&format!(
+            r#"
+            SELECT
+                id,
+                parents,
+                item,
+                message,
+                attempts
+            FROM
+                failed
+            WHERE
+                {FAILED_FILTER}
+            ORDER BY
+                id ASC
+            "#
+        ))
+        .bind(all_if_empty(item_filters))
+        .bind(all_if_empty(message_filters))
+        .fetch_all(&self.client)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+    }
+
+    /// Insert previously exported failed items (i.e. from another queue) into the failed table.
+    ///
+    /// The items are assigned new ids, and their parents are dropped since they refer to items in
+    /// the queue they were exported from. Returns the new ids of the items.
+    pub async fn import_failed(
+        &self,
+        records: Vec<FailedRecord<T>>,
+    ) -> Result<Vec<ItemId>, sqlx::Error> {
+        let mut tx = self.client.begin().await?;
+
+        let ids = next_ids(tx.as_mut(), records.len()).await?;
+
+        for (id, record) in ids.iter().zip(records) {
+            sqlx::query(
+                "
+                INSERT INTO
+                failed (id, item, message, attempts)
+                VALUES ($1, $2,   $3,      $4      )
+                ",
+            )
+            .bind(id)
+            .bind(record.item)
+            .bind(record.message)
+            .bind(record.attempts)
+            .execute(tx.as_mut())
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        ids.into_iter().map(item_id).collect()
+    }
+
+    /// Requeue all failed items matching the filters (see [`Self::query_failed`]).
+    ///
+    /// The failed items are moved to the `failed_archive` table, and each requeued item has the
+    /// failed item it was requeued from as its parent. Returns the ids of the requeued items.
+    pub async fn requeue_failed(
+        &self,
+        item_filters: Vec<String>,
+        message_filters: Vec<String>,
+    ) -> Result<Vec<ItemId>, sqlx::Error> {
+        let mut tx = self.client.begin().await?;
+
+        let failed = sqlx::query_as::<_, (i64, String)>(&format!(
+            r#"
+            SELECT
+                id,
+                item
+            FROM
+                failed
+            WHERE
+                {FAILED_FILTER}
+            ORDER BY
+                id ASC
+            "#
+        ))
+        .bind(all_if_empty(item_filters.clone()))
+        .bind(all_if_empty(message_filters.clone()))
+        .fetch_all(tx.as_mut())
+        .await?;
+
+        sqlx::query(&format!(
+            r#"
+            INSERT INTO failed_archive (id, parents, item, message, created_at, attempts)
+            SELECT
+                id,
+                parents,
+                item,
+                message,
+                created_at,
+                attempts
+            FROM
+                failed
+            WHERE
+                {FAILED_FILTER}
+            "#
+        ))
+        .bind(all_if_empty(item_filters.clone()))
+        .bind(all_if_empty(message_filters.clone()))
+        .execute(tx.as_mut())
+        .await?;
+
+        sqlx::query(&format!("DELETE FROM failed WHERE {FAILED_FILTER}"))
+            .bind(all_if_empty(item_filters))
+            .bind(all_if_empty(message_filters))
+            .execute(tx.as_mut())
+            .await?;
+
+        let ids = next_ids(tx.as_mut(), failed.len()).await?;
+
+        for (id, (failed_id, item)) in ids.iter().zip(failed) {
+            sqlx::query(
+                "
+                INSERT INTO
+                queue  (id, parents, item)
+                VALUES ($1, $2,      $3  )
+                ",
+            )
+            .bind(id)
+            .bind(Json([failed_id]))
+            .bind(item)
+            .execute(tx.as_mut())
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        ids.into_iter().map(item_id).collect()
+    }
+
+    /// Move all failed items created more than `older_than` ago to the `failed_archive` table, or
+    /// delete them entirely if `delete` is set.
+    ///
+    /// Returns the amount of items that were archived or deleted.
+    pub async fn archive_failed(
+        &self,
+        older_than: Duration,
+        delete: bool,
+    ) -> Result<u64, sqlx::Error> {
+        // unix timestamp in seconds, `created_at` has second precision
+        let cutoff = (now_millis() / 1000)
+            .saturating_sub(older_than.as_secs().try_into().unwrap_or(i64::MAX));
+
+        let mut tx = self.client.begin().await?;
+
+        if !delete {
+            sqlx::query(
+                r#"
+                INSERT INTO failed_archive (id, parents, item, message, created_at, attempts)
+                SELECT
+                    id,
+                    parents,
+                    item,
+                    message,
+                    created_at,
+                    attempts
+                FROM
+                    failed
+                WHERE
+                    created_at < datetime($1, 'unixepoch')
+                "#,
+            )
+            .bind(cutoff)
+            .execute(tx.as_mut())
+            .await?;
+        }
+
+        let count = sqlx::query(
+            r#"
+            DELETE FROM
+                failed
+            WHERE
+                created_at < datetime($1, 'unixepoch')
+            "#,
+        )
+        .bind(cutoff)
+        .execute(tx.as_mut())
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+
+        Ok(count)
+    }
+
+    pub async fn query_failed_by_id(
+        &self,
+        id: i64,
+    ) -> Result<Option<FailedRecord<T>>, sqlx::Error> {
+        Ok(sqlx::query_as::<_, FailedRow<T>>(
+            r#"
+            SELECT
+               id,
+               parents,
+               item,
+               message,
+               attempts
+            FROM
+               failed
+            WHERE
+               id = $1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.client)
+        .await?
+        .map(Into::into))
+    }
+
+    /// Query the item with the specified id and all of its ancestors (the items it was produced
+    /// from), up to `max_depth` levels deep.
+    pub async fn query_ancestors(
+        &self,
+        id: i64,
+        max_depth: i32,
+    ) -> Result<Vec<ItemRecord<T>>, sqlx::Error> {
+        let all_items = all_items("");
+
+        self.query_lineage(
+            &format!(
+                r#"
+                WITH RECURSIVE {all_items},
+                lineage AS (
+                    SELECT id, parents, item, status, 0 AS depth FROM items WHERE id = $1
+                    UNION
+                    SELECT
+                        items.id, items.parents, items.item, items.status, lineage.depth + 1
+                    FROM
+                        lineage,
+                        json_each(lineage.parents) AS parent
+                        JOIN items ON items.id = parent.value
+                    WHERE
+                        lineage.depth < $2
+                )
+                "#
+            ),
+            id,
+            max_depth,
+        )
+        .await
+    }
+
+    /// Query the item with the specified id and all of its descendants (the items produced from
+    /// it), up to `max_depth` levels deep.
+    pub async fn query_descendants(
+        &self,
+        id: i64,
+        max_depth: i32,
+    ) -> Result<Vec<ItemRecord<T>>, sqlx::Error> {
+        let all_items = all_items("");
+
+        self.query_lineage(
+            &format!(
+                r#"
+                WITH RECURSIVE {all_items},
+                lineage AS (
+                    SELECT id, parents, item, status, 0 AS depth FROM items WHERE id = $1
+                    UNION
+                    SELECT
+                        items.id, items.parents, items.item, items.status, lineage.depth + 1
+                    FROM
+                        lineage,
+                        items,
+                        json_each(items.parents) AS parent
+                    WHERE
+                        parent.value = lineage.id
+                        AND lineage.depth < $2
+                )
+                "#
+            ),
+            id,
+            max_depth,
+        )
+        .await
+    }
+
+    async fn query_lineage(
+        &self,
+        cte: &str,
+        id: i64,
+        max_depth: i32,
+    ) -> Result<Vec<ItemRecord<T>>, sqlx::Error> {
+        // an item can be reached through multiple paths, only keep the closest one
+        sqlx::query_as::<_, LineageRow<T>>(&format!(
+            r#"
+            {cte}
+            SELECT
+                id,
+                parents,
+                item,
+                status,
+                MIN(depth) AS depth
+            FROM
+                lineage
+            GROUP BY
+                id,
+                status
+            ORDER BY
+                id ASC,
+                status ASC
+            "#
+        ))
+        .bind(id)
+        .bind(max_depth)
+        .fetch_all(&self.client)
+        .await?
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect()
+    }
+
+    /// Query all pending items (the items that are ready to be processed and the items waiting to
+    /// be optimized), i.e. to replay them against an in-memory queue (see
+    /// [`InMemoryQueue::from_snapshot`]).
+    ///
+    /// [`InMemoryQueue::from_snapshot`]: voyager_vm::in_memory::InMemoryQueue::from_snapshot
+    pub async fn snapshot(&self) -> Result<QueueSnapshot<T>, sqlx::Error> {
+        // read both tables from the same snapshot of the database
+        let mut tx = self.client.begin().await?;
+
+        let queue = sqlx::query_as::<_, SnapshotRow<T>>(
+            r#"
+            SELECT
+                id,
+                parents,
+                item,
+                attempts,
+                priority
+            FROM
+                queue
+            ORDER BY
+                id ASC
+            "#,
+        )
+        .fetch_all(tx.as_mut())
+        .await?
+        .into_iter()
+        .map(TryInto::try_into)
+        .collect::<Result<_, _>>()?;
+
+        let mut optimize = BTreeMap::<_, Vec<_>>::new();
+
+        for record in sqlx::query_as::<_, SnapshotRow<T>>(
+            r#"
+            SELECT
+                id,
+                parents,
+                item,
+                tag
+            FROM
+                optimize
+            ORDER BY
+                id ASC
+            "#,
+        )
+        .fetch_all(tx.as_mut())
+        .await?
+        {
+            optimize
+                .entry(record.tag.clone())
+                .or_default()
+                .push(record.try_into()?);
+        }
+
+        tx.commit().await?;
+
+        Ok(QueueSnapshot { queue, optimize })
+    }
+
+    /// Query the amount of items currently held by this queue.
+    pub async fn depth(&self) -> Result<QueueDepth, sqlx::Error> {
+        let row = sqlx::query_as::<_, DepthRow>(
+            r#"
+            SELECT
+                (SELECT count(*) FROM queue) AS ready,
+                (SELECT count(*) FROM queue WHERE attempts > 0) AS retrying,
+                (SELECT count(*) FROM failed) AS failed
+            "#,
+        )
+        .fetch_one(&self.client)
+        .await?;
+
+        let optimize = sqlx::query_as::<_, (String, i64)>(OPTIMIZE_DEPTH)
+            .fetch_all(&self.client)
+            .await?;
+
+        Ok(queue_depth(row, optimize))
+    }
+
+    /// Query all items in the queue that have failed at least once with a retryable error, and are
+    /// waiting to be retried.
+    pub async fn query_retrying(
+        &self,
+        page: i64,
+        per_page: i64,
+    ) -> Result<Vec<RetryingRecord<T>>, sqlx::Error> {
+        Ok(sqlx::query_as::<_, RetryingRow<T>>(
+            r#"
+            SELECT
+                id,
+                parents,
+                item,
+                attempts,
+                last_error
+            FROM
+                queue
+            WHERE
+                attempts > 0
+            ORDER BY
+                id DESC
+            LIMIT
+                $1
+            OFFSET
+                $2
+            "#,
+        )
+        .bind(per_page)
+        .bind((page - 1) * per_page)
+        .fetch_all(&self.client)
+        .await?
+        .into_iter()
+        .map(Into::into)
+        .collect())
+    }
+
+    /// Write the result of processing `row` to the database, removing it from the queue (or
+    /// requeueing it, if it is to be retried).
+    async fn finish(
+        &self,
+        row: &Record,
+        filter: &T::Filter,
+        res: ProcessResult<T>,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.client.begin().await?;
+
+        match res {
+            ProcessResult::Failed(error) => {
+                sqlx::query("DELETE FROM queue WHERE id = $1")
+                    .bind(row.id)
+                    .execute(tx.as_mut())
+                    .await?;
+
+                // insert error message and the op into failed
+                sqlx::query(
+                    r#"
+                    INSERT INTO
+                    failed (id, parents, item, created_at, message, attempts)
+                    VALUES ($1, $2,      $3,   $4,         $5,      $6      )
+                    "#,
+                )
+                .bind(row.id)
+                .bind(&row.parents)
+                .bind(&row.item)
+                .bind(&row.created_at)
+                .bind(error)
+                .bind(row.attempts + 1)
+                .execute(tx.as_mut())
+                .await?;
+            }
+            ProcessResult::Retry { error, delay } => {
+                // requeue the op in place, keeping its id and lineage
+                sqlx::query(
+                    r#"
+                    UPDATE
+                        queue
+                    SET
+                        attempts = $2,
+                        retry_at = $3,
+                        last_error = $4,
+                        claimed = 0
+                    WHERE
+                        id = $1
+                    "#,
+                )
+                .bind(row.id)
+                .bind(row.attempts + 1)
+                .bind(
+                    now_millis()
+                        .saturating_add(i64::try_from(delay.as_millis()).unwrap_or(i64::MAX)),
+                )
+                .bind(error)
+                .execute(tx.as_mut())
+                .await?;
+            }
+            ProcessResult::Done(ops) => {
+                sqlx::query("DELETE FROM queue WHERE id = $1")
+                    .bind(row.id)
+                    .execute(tx.as_mut())
+                    .await?;
+
+                // insert the op we just processed into done
+                sqlx::query(
+                    "
+                    INSERT INTO
+                    done   (id, parents, item, created_at)
+                    VALUES ($1, $2,      $3,   $4        )
+                    ",
+                )
+                .bind(row.id)
+                .bind(&row.parents)
+                .bind(&row.item)
+                .bind(&row.created_at)
+                .execute(tx.as_mut())
+                .await?;
+
+                let (optimize, ready) = partition_interest(ops, filter);
+
+                insert_ready(tx.as_mut(), ready, &[row.id]).await?;
+                insert_optimize(tx.as_mut(), optimize, &[row.id]).await?;
+            }
+        }
+
+        tx.commit().await
+    }
+}
+
+impl<T: QueueMessage> voyager_vm::Queue<T> for SqliteQueue<T> {
+    type Config = SqliteQueueConfig;
+    type Error = sqlx::Error;
+
+    async fn new(config: Self::Config) -> Result<Self, Self::Error> {
+        let pool = config.into_sqlite_pool().await?;
+
+        pool.execute_many(
+            r#"
+            CREATE TABLE IF NOT EXISTS queue_id_seq(
+                value INTEGER NOT NULL
+            );
+
+            INSERT INTO queue_id_seq (value)
+            SELECT 0 WHERE NOT EXISTS (SELECT 1 FROM queue_id_seq);
+
+            CREATE TABLE IF NOT EXISTS queue(
+                id INTEGER PRIMARY KEY,
+                item TEXT NOT NULL,
+                parents TEXT NOT NULL DEFAULT '[]',
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                -- unix timestamp in milliseconds
+                retry_at INTEGER,
+                last_error TEXT,
+                priority INTEGER NOT NULL DEFAULT 0,
+                claimed INTEGER NOT NULL DEFAULT 0
+            );
+
+            CREATE TABLE IF NOT EXISTS optimize(
+                id INTEGER PRIMARY KEY,
+                item TEXT NOT NULL,
+                tag TEXT NOT NULL,
+                parents TEXT NOT NULL DEFAULT '[]',
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE TABLE IF NOT EXISTS done(
+                id INTEGER NOT NULL,
+                item TEXT NOT NULL,
+                parents TEXT NOT NULL DEFAULT '[]',
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE TABLE IF NOT EXISTS failed(
+                id INTEGER PRIMARY KEY,
+                item TEXT NOT NULL,
+                parents TEXT NOT NULL DEFAULT '[]',
+                message TEXT,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                attempts INTEGER NOT NULL DEFAULT 0
+            );
+
+            CREATE TABLE IF NOT EXISTS failed_archive(
+                id INTEGER PRIMARY KEY,
+                item TEXT NOT NULL,
+                parents TEXT NOT NULL DEFAULT '[]',
+                message TEXT,
+                created_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                attempts INTEGER NOT NULL DEFAULT 0,
+                archived_at TEXT NOT NULL DEFAULT CURRENT_TIMESTAMP
+            );
+
+            CREATE INDEX IF NOT EXISTS index_queue_priority_id ON queue(priority DESC, id ASC);
+            CREATE INDEX IF NOT EXISTS index_optimize_tag_id ON optimize(tag, id);
+            CREATE INDEX IF NOT EXISTS index_done_id ON done(id);
+
+            -- release the claims of any items that were being processed when the queue was last
+            -- shut down
+            UPDATE queue SET claimed = 0 WHERE claimed = 1;
+            "#,
+        )
+        .try_for_each(|result| async move {
+            trace!("rows affected: {}", result.rows_affected());
+            Ok(())
+        })
+        .instrument(info_span!("init"))
+        .await?;
+
+        Ok(Self {
+            client: pool,
+            __marker: PhantomData,
+        })
+    }
+
+    async fn enqueue<'a>(
+        &'a self,
+        op: Op<T>,
+        filter: &'a T::Filter,
+    ) -> Result<EnqueueResult, Self::Error> {
+        trace!("enqueue");
+
+        let (optimize, ready) = partition_interest([op], filter);
+
+        let mut tx = self.client.begin().await?;
+
+        let ready_ids = insert_ready(tx.as_mut(), ready, &[]).await?;
+
+        for ready in &ready_ids {
+            debug!(id = ready, "enqueued ready item");
+        }
+
+        let optimize_further_ids = insert_optimize(tx.as_mut(), optimize, &[]).await?;
+
+        for ready in &optimize_further_ids {
+            debug!(id = ready, "enqueued optimize item");
+        }
+
+        tx.commit().await?;
+
+        Ok(EnqueueResult {
+            queue: ready_ids
+                .into_iter()
+                .map(|id| ItemId::new(id).expect("invalid id returned from database"))
+                .collect(),
+            optimize: optimize_further_ids
+                .into_iter()
+                .map(|id| ItemId::new(id).expect("invalid id returned from database"))
+                .collect(),
+        })
+    }
+
+    #[instrument(skip_all)]
+    async fn process<'a, F, Fut, R>(
+        &'a self,
+        filter: &'a T::Filter,
+        f: F,
+    ) -> Result<Option<R>, Self::Error>
+    where
+        F: (FnOnce(Op<T>, ItemId, u32) -> Fut) + Send + Captures<'a>,
+        Fut: Future<Output = (R, ProcessResult<T>)> + Send + Captures<'a>,
+        R: Send + Sync + 'static,
+    {
+        trace!("process");
+
+        // claim the next item, so that other workers don't pick it up while it's being processed
+        // (a write transaction can't be held open for the duration of the processing, since that
+        // would block all other writers)
+        let row = sqlx::query_as::<_, Record>(
+            r#"
+            UPDATE
+              queue
+            SET
+              claimed = 1
+            WHERE
+              id = (
+                SELECT
+                  id
+                FROM
+                  queue
+                WHERE
+                  claimed = 0
+                  AND (retry_at IS NULL OR retry_at <= $1)
+                ORDER BY
+                  priority DESC,
+                  id ASC
+                LIMIT 1)
+            RETURNING
+              id,
+              parents,
+              item,
+              created_at,
+              attempts,
+              priority
+            "#,
+        )
+        .bind(now_millis())
+        .fetch_optional(&self.client)
+        .await?;
+
+        match row {
+            Some(row) => {
+                let span = info_span!("processing item", item_id = row.id);
+
+                trace!(%row.item);
+
+                let op = match de(&row.item) {
+                    Ok(op) => op,
+                    Err(e) => {
+                        self.release(row.id).await;
+                        return Err(sqlx::Error::Decode(Box::new(e)));
+                    }
+                };
+
+                let timer = METRICS.item_processing_duration.start_timer();
+                let (r, res) = f(
+                    op,
+                    ItemId::new(row.id).unwrap(),
+                    row.attempts.try_into().unwrap_or_default(),
+                )
+                .instrument(span)
+                .await;
+                let _ = timer.stop_and_record();
+
+                if let Err(err) = self.finish(&row, filter, res).await {
+                    self.release(row.id).await;
+                    return Err(err);
+                }
+
+                Ok(Some(r))
+            }
+            None => Ok(None),
+        }
+    }
+
+    async fn optimize<'a, O: Pass<T>>(
+        &'a self,
+        tag: &'a str,
+        optimizer: &'a O,
+        filter: &'a T::Filter,
+    ) -> Result<(), Either<Self::Error, O::Error>> {
+        trace!(%tag, "optimize");
+
+        // the items are only removed once the pass has completed, see below
+        let msgs = sqlx::query_as::<_, Record>(
+            r#"
+            SELECT
+              id,
+              parents,
+              item,
+              created_at
+            FROM
+              optimize
+            WHERE
+              tag = $1
+            ORDER BY
+              id ASC
+            "#,
+        )
+        .bind(tag)
+        .fetch_all(&self.client)
+        .await
+        .map_err(Either::Left)?;
+
+        if msgs.is_empty() {
+            trace!("optimizer queue is empty");
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            return Ok(());
+        }
+
+        let (ids, (parents, ops)) = msgs
+            .iter()
+            .map(|r| {
+                Ok((
+                    r.id,
+                    (
+                        r.parents.0.clone(),
+                        de(&r.item).map_err(|e| sqlx::Error::Decode(Box::new(e)))?,
+                    ),
+                ))
+            })
+            .collect::<Result<(Vec<_>, (Vec<_>, Vec<_>)), sqlx::Error>>()
+            .map_err(Either::Left)?;
+
+        METRICS
+            .optimize_item_count
+            .with_label_values(&[tag])
+            .observe(ops.len() as f64);
+        let timer = METRICS
+            .optimize_processing_duration
+            .with_label_values(&[tag])
+            .start_timer();
+
+        let PassResult {
+            optimize_further,
+            ready,
+        } = optimizer
+            .run_pass(ops)
+            .instrument(debug_span!(
+                "optimizing items",
+                ids = ids
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(",")
+            ))
+            .await
+            .map_err(Either::Right)?;
+        let _ = timer.stop_and_record();
+
+        trace!(
+            ready = ready.len(),
+            optimize_further = optimize_further.len(),
+            "optimized items"
+        );
+
+        let mut tx = self.client.begin().await.map_err(Either::Left)?;
+
+        // the optimized items are not kept around, the items they produce are linked to their
+        // parents instead (see `optimized_parents`)
+        for msg in &msgs {
+            let deleted = sqlx::query("DELETE FROM optimize WHERE id = $1")
+                .bind(msg.id)
+                .execute(tx.as_mut())
+                .await
+                .map_err(Either::Left)?
+                .rows_affected();
+
+            // another optimizer for the same tag picked up this item in the meantime, discard the
+            // results of this pass
+            if deleted == 0 {
+                warn!(id = msg.id, "optimize item was removed during the pass");
+                tx.rollback().await.map_err(Either::Left)?;
+                return Ok(());
+            }
+        }
+
+        let get_parent_ids = |parent_idxs: &[usize]| optimized_parents(&parents, parent_idxs);
+
+        for (parent_idxs, new_msg, tag) in optimize_further {
+            let parents = get_parent_ids(&parent_idxs);
+            trace!(parent_idxs = ?&parent_idxs, parents = ?&parents);
+
+            let new_ids = insert_optimize(tx.as_mut(), vec![(new_msg, &*tag)], &parents)
+                .await
+                .map_err(Either::Left)?;
+
+            debug!(id = new_ids[0], "inserted new optimizer message");
+        }
+
+        for (parent_idxs, new_msg) in ready {
+            let parents = get_parent_ids(&parent_idxs);
+            trace!(parent_idxs = ?&parent_idxs, parents = ?&parents);
+
+            let priority = filter.check_interest(&new_msg).priority();
+
+            let new_ids = insert_ready(tx.as_mut(), vec![(new_msg, priority)], &parents)
+                .await
+                .map_err(Either::Left)?;
+
+            debug!(id = new_ids[0], "inserted new message");
+        }
+
+        tx.commit().await.map_err(Either::Left)?;
+
+        Ok(())
+    }
+}
+
+impl<T> SqliteQueue<T> {
+    /// Release the claim on an item, so that it will be picked up again. This is done on a best
+    /// effort basis, since this is only called when the database is already returning errors.
+    async fn release(&self, id: i64) {
+        if let Err(err) = sqlx::query("UPDATE queue SET claimed = 0 WHERE id = $1")
+            .bind(id)
+            .execute(&self.client)
+            .await
+        {
+            warn!(%id, %err, "unable to release claimed item");
+        }
+    }
+}
+
+/// Reserve `n` new ids from the shared id sequence.
+async fn next_ids(conn: &mut SqliteConnection, n: usize) -> Result<Vec<i64>, sqlx::Error> {
+    if n == 0 {
+        return Ok(vec![]);
+    }
+
+    let n = i64::try_from(n).expect("too many ids requested");
+
+    let (last,) =
+        sqlx::query_as::<_, (i64,)>("UPDATE queue_id_seq SET value = value + $1 RETURNING value")
+            .bind(n)
+            .fetch_one(conn)
+            .await?;
+
+    Ok(((last - n + 1)..=last).collect())
+}
+
+async fn insert_ready<T: QueueMessage>(
+    conn: &mut SqliteConnection,
+    ready: Vec<(Op<T>, Priority)>,
+    parents: &[i64],
+) -> Result<Vec<i64>, sqlx::Error> {
+    let ids = next_ids(conn, ready.len()).await?;
+
+    for (id, (op, priority)) in ids.iter().zip(ready) {
+        sqlx::query(
+            "
+            INSERT INTO
+            queue  (id, item, parents, priority)
+            VALUES ($1, $2,   $3,      $4      )
+            ",
+        )
+        .bind(id)
+        .bind(Json(op))
+        .bind(Json(parents))
+        .bind(priority)
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(ids)
+}
+
+async fn insert_optimize<T: QueueMessage>(
+    conn: &mut SqliteConnection,
+    optimize: Vec<(Op<T>, &str)>,
+    parents: &[i64],
+) -> Result<Vec<i64>, sqlx::Error> {
+    let ids = next_ids(conn, optimize.len()).await?;
+
+    for (id, (op, tag)) in ids.iter().zip(optimize) {
+        sqlx::query(
+            "
+            INSERT INTO
+            optimize (id, item, tag, parents)
+            VALUES   ($1, $2,   $3,  $4     )
+            ",
+        )
+        .bind(id)
+        .bind(Json(op))
+        .bind(tag)
+        .bind(Json(parents))
+        .execute(&mut *conn)
+        .await?;
+    }
+
+    Ok(ids)
+}
+
+fn now_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("time is after the unix epoch")
+        .as_millis()
+        .try_into()
+        .unwrap_or(i64::MAX)
+}
+
+/// Defaults to an all-inclusive `LIKE` filter if no filters are provided. The filters are passed
+/// to the queries as a JSON array.
+fn all_if_empty(mut filters: Vec<String>) -> Json<Vec<String>> {
+    if filters.is_empty() {
+        filters.push("%".to_owned());
+    }
+
+    Json(filters)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::VecDeque, convert::Infallible};
+
+    use voyager_vm::{
+        defer,
+        filter::FilterResult,
+        noop,
+        pass::{Pass, PassResult},
+        CallT, CallbackT, Context, ItemStatus, Op, ProcessResult, Queue, QueueError, QueueMessage,
+    };
+
+    use super::*;
+
+    enum TestMessage {}
+
+    impl QueueMessage for TestMessage {
+        type Data = ();
+        type Call = ();
+        type Callback = ();
+
+        type Filter = DeferFilter;
+
+        type Context = ();
+    }
+
+    impl CallT<TestMessage> for () {
+        async fn process(self, _: Context<&()>) -> Result<Op<TestMessage>, QueueError> {
+            Ok(noop())
+        }
+    }
+
+    impl CallbackT<TestMessage> for () {
+        async fn process(
+            self,
+            _: Context<&()>,
+            _: VecDeque<()>,
+        ) -> Result<Op<TestMessage>, QueueError> {
+            Ok(noop())
+        }
+    }
+
+    /// [`Op::Defer`]s with a timestamp of at least 100 are optimized under the `defer` tag.
+    struct DeferFilter;
+
+    impl InterestFilter<TestMessage> for DeferFilter {
+        fn check_interest<'a>(&'a self, op: &Op<TestMessage>) -> FilterResult<'a> {
+            match op {
+                Op::Defer { until } if *until >= 100 => FilterResult::Interest("defer"),
+                _ => FilterResult::NoInterest(Priority::default()),
+            }
+        }
+    }
+
+    /// Combines all of the optimized items into a single ready item.
+    struct CombinePass;
+
+    impl Pass<TestMessage> for CombinePass {
+        type Error = Infallible;
+
+        async fn run_pass(
+            &self,
+            ops: Vec<Op<TestMessage>>,
+        ) -> Result<PassResult<TestMessage>, Self::Error> {
+            Ok(PassResult {
+                optimize_further: vec![],
+                ready: vec![((0..ops.len()).collect(), defer(ops.len() as u64))],
+            })
+        }
+    }
+
+    async fn queue(name: &str) -> SqliteQueue<TestMessage> {
+        let path = std::env::temp_dir().join(format!(
+            "sqlite-queue-test-{name}-{}.db",
+            std::process::id()
+        ));
+
+        for suffix in ["", "-wal", "-shm"] {
+            let mut file = path.clone().into_os_string();
+            file.push(suffix);
+            let _ = std::fs::remove_file(file);
+        }
+
+        SqliteQueue::new(SqliteQueueConfig {
+            path,
+            max_connections: None,
+            busy_timeout: None,
+        })
+        .await
+        .unwrap()
+    }
+
+    async fn process_with(
+        queue: &SqliteQueue<TestMessage>,
+        res: impl FnOnce() -> ProcessResult<TestMessage> + Send,
+    ) -> Option<(i64, Op<TestMessage>, u32)> {
+        queue
+            .process(&DeferFilter, |op, id, attempts| async move {
+                ((id.raw(), op, attempts), res())
+            })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn enqueue() {
+        let queue = queue("enqueue").await;
+
+        let res = queue.enqueue(defer(1), &DeferFilter).await.unwrap();
+        assert_eq!(res.queue.len(), 1);
+        assert!(res.optimize.is_empty());
+
+        let res = queue.enqueue(defer(100), &DeferFilter).await.unwrap();
+        assert!(res.queue.is_empty());
+        assert_eq!(res.optimize.len(), 1);
+
+        let depth = queue.depth().await.unwrap();
+        assert_eq!(depth.ready, 1);
+        assert_eq!(depth.retrying, 0);
+        assert_eq!(depth.optimize, BTreeMap::from([("defer".to_owned(), 1)]));
+        assert_eq!(depth.failed, 0);
+
+        let snapshot = queue.snapshot().await.unwrap();
+        assert_eq!(snapshot.queue.len(), 1);
+        assert_eq!(snapshot.queue[0].item, defer(1));
+        assert!(snapshot.queue[0].parents.is_empty());
+        assert_eq!(snapshot.optimize["defer"].len(), 1);
+        assert_eq!(snapshot.optimize["defer"][0].item, defer(100));
+    }
+
+    #[tokio::test]
+    async fn process() {
+        let queue = queue("process").await;
+
+        queue.enqueue(defer(1), &DeferFilter).await.unwrap();
+
+        let (id, op, attempts) =
+            process_with(&queue, || ProcessResult::Done(vec![defer(2), defer(3)]))
+                .await
+                .unwrap();
+        assert_eq!(op, defer(1));
+        assert_eq!(attempts, 0);
+
+        // the produced items are queued with the processed item as their parent
+        let snapshot = queue.snapshot().await.unwrap();
+        assert_eq!(
+            snapshot
+                .queue
+                .iter()
+                .map(|item| (item.item.clone(), item.parents.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (defer(2), vec![ItemId::new(id).unwrap()]),
+                (defer(3), vec![ItemId::new(id).unwrap()]),
+            ]
+        );
+
+        let child = snapshot.queue[0].id.raw();
+        let ancestors = queue.query_ancestors(child, 10).await.unwrap();
+        assert_eq!(
+            ancestors
+                .iter()
+                .map(|record| (record.id.raw(), record.status))
+                .collect::<Vec<_>>(),
+            vec![(id, ItemStatus::Done), (child, ItemStatus::Queue)]
+        );
+
+        process_with(&queue, || ProcessResult::Done(vec![])).await;
+        process_with(&queue, || ProcessResult::Done(vec![])).await;
+
+        assert!(process_with(&queue, || ProcessResult::Done(vec![]))
+            .await
+            .is_none());
+        assert_eq!(queue.depth().await.unwrap().ready, 0);
+    }
+
+    #[tokio::test]
+    async fn retry() {
+        let queue = queue("retry").await;
+
+        queue.enqueue(defer(1), &DeferFilter).await.unwrap();
+
+        let (id, _, attempts) = process_with(&queue, || ProcessResult::Retry {
+            error: "retry me".to_owned(),
+            delay: Duration::ZERO,
+        })
+        .await
+        .unwrap();
+        assert_eq!(attempts, 0);
+
+        let depth = queue.depth().await.unwrap();
+        assert_eq!(depth.ready, 1);
+        assert_eq!(depth.retrying, 1);
+
+        let retrying = queue.query_retrying(1, 10).await.unwrap();
+        assert_eq!(retrying.len(), 1);
+        assert_eq!(retrying[0].id, id);
+        assert_eq!(retrying[0].attempts, 1);
+        assert_eq!(retrying[0].last_error, "retry me");
+
+        // the item is requeued in place, keeping its id
+        let (retried_id, _, attempts) = process_with(&queue, || ProcessResult::Retry {
+            error: "retry me later".to_owned(),
+            delay: Duration::from_secs(60 * 60),
+        })
+        .await
+        .unwrap();
+        assert_eq!(retried_id, id);
+        assert_eq!(attempts, 1);
+
+        // the item is not processed again until the delay has passed
+        assert!(process_with(&queue, || ProcessResult::Done(vec![]))
+            .await
+            .is_none());
+        assert_eq!(queue.query_retrying(1, 10).await.unwrap()[0].attempts, 2);
+    }
+
+    #[tokio::test]
+    async fn failed() {
+        let queue = queue("failed").await;
+
+        queue.enqueue(defer(1), &DeferFilter).await.unwrap();
+
+        let (id, _, _) = process_with(&queue, || ProcessResult::Failed("fatal".to_owned()))
+            .await
+            .unwrap();
+
+        let depth = queue.depth().await.unwrap();
+        assert_eq!(depth.ready, 0);
+        assert_eq!(depth.failed, 1);
+
+        let failed = queue.query_failed(1, 10, vec![], vec![]).await.unwrap();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].id, id);
+        assert_eq!(failed[0].message, "fatal");
+        assert_eq!(failed[0].attempts, 1);
+        assert_eq!(failed[0].item.0, defer(1));
+    }
+
+    #[tokio::test]
+    async fn optimize() {
+        let queue = queue("optimize").await;
+
+        // produce two optimize items with different parents
+        let mut parents = vec![];
+        for until in [100, 200] {
+            queue.enqueue(defer(1), &DeferFilter).await.unwrap();
+            let (id, _, _) = process_with(&queue, || ProcessResult::Done(vec![defer(until)]))
+                .await
+                .unwrap();
+            parents.push(id);
+        }
+
+        assert_eq!(
+            queue.depth().await.unwrap().optimize,
+            BTreeMap::from([("defer".to_owned(), 2)])
+        );
+
+        queue
+            .optimize("defer", &CombinePass, &DeferFilter)
+            .await
+            .unwrap();
+
+        let snapshot = queue.snapshot().await.unwrap();
+        assert!(snapshot.optimize.is_empty());
+        assert_eq!(snapshot.queue.len(), 1);
+        assert_eq!(snapshot.queue[0].item, defer(2));
+
+        // the produced item is linked to the parents of the optimized items
+        assert_eq!(
+            snapshot.queue[0]
+                .parents
+                .iter()
+                .map(ItemId::raw)
+                .collect::<Vec<_>>(),
+            parents
+        );
+
+        // the optimized items are not kept around
+        let ancestors = queue
+            .query_ancestors(snapshot.queue[0].id.raw(), 10)
+            .await
+            .unwrap();
+        assert_eq!(
+            ancestors
+                .iter()
+                .map(|record| record.status)
+                .collect::<Vec<_>>(),
+            vec![ItemStatus::Done, ItemStatus::Done, ItemStatus::Queue]
+        );
+    }
+
+    /// Enqueue an item and fail it with the given message, returning its id.
+    async fn fail(queue: &SqliteQueue<TestMessage>, until: u64, message: &str) -> i64 {
+        queue.enqueue(defer(until), &DeferFilter).await.unwrap();
+
+        let message = message.to_owned();
+        let (id, _, _) = process_with(queue, move || ProcessResult::Failed(message))
+            .await
+            .unwrap();
+
+        id
+    }
+
+    async fn archived(queue: &SqliteQueue<TestMessage>) -> Vec<i64> {
+        sqlx::query_scalar("SELECT id FROM failed_archive ORDER BY id ASC")
+            .fetch_all(&queue.client)
+            .await
+            .unwrap()
+    }
+
+    async fn failed_ids(queue: &SqliteQueue<TestMessage>) -> Vec<i64> {
+        queue
+            .export_failed(vec![], vec![])
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|record| record.id)
+            .collect()
+    }
+
+    async fn set_created_at(queue: &SqliteQueue<TestMessage>, id: i64, modifier: &str) {
+        sqlx::query("UPDATE failed SET created_at = datetime('now', $2) WHERE id = $1")
+            .bind(id)
+            .bind(modifier)
+            .execute(&queue.client)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn requeue_failed() {
+        let queue = queue("requeue-failed").await;
+
+        let a = fail(&queue, 1, "fatal: a").await;
+        let b = fail(&queue, 2, "fatal: b").await;
+
+        let ids = queue
+            .requeue_failed(vec![], vec!["%: a".to_owned()])
+            .await
+            .unwrap();
+        assert_eq!(ids.len(), 1);
+
+        // only the matching item is requeued, and the failed item is archived
+        assert_eq!(failed_ids(&queue).await, [b]);
+        assert_eq!(archived(&queue).await, [a]);
+
+        // the requeued item has the failed item as its parent
+        let snapshot = queue.snapshot().await.unwrap();
+        assert_eq!(snapshot.queue.len(), 1);
+        assert_eq!(snapshot.queue[0].id, ids[0]);
+        assert_eq!(snapshot.queue[0].item, defer(1));
+        assert_eq!(snapshot.queue[0].parents, [ItemId::new(a).unwrap()]);
+
+        // without filters, all remaining failed items are requeued
+        let ids = queue.requeue_failed(vec![], vec![]).await.unwrap();
+        assert_eq!(ids.len(), 1);
+        assert!(failed_ids(&queue).await.is_empty());
+        assert_eq!(archived(&queue).await, [a, b]);
+        assert_eq!(queue.depth().await.unwrap().ready, 2);
+    }
+
+    #[tokio::test]
+    async fn archive_failed() {
+        let queue = queue("archive-failed").await;
+
+        let a = fail(&queue, 1, "fatal: a").await;
+        let b = fail(&queue, 2, "fatal: b").await;
+        let c = fail(&queue, 3, "fatal: c").await;
+
+        set_created_at(&queue, a, "-2 days").await;
+        set_created_at(&queue, b, "-1 hours").await;
+
+        const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+        // nothing is old enough
+        assert_eq!(queue.archive_failed(3 * DAY, false).await.unwrap(), 0);
+        assert_eq!(failed_ids(&queue).await, [a, b, c]);
+
+        assert_eq!(queue.archive_failed(DAY, false).await.unwrap(), 1);
+        assert_eq!(failed_ids(&queue).await, [b, c]);
+        assert_eq!(archived(&queue).await, [a]);
+
+        // deleted items are not archived
+        assert_eq!(
+            queue
+                .archive_failed(Duration::from_secs(60), true)
+                .await
+                .unwrap(),
+            1
+        );
+        assert_eq!(failed_ids(&queue).await, [c]);
+        assert_eq!(archived(&queue).await, [a]);
+
+        // durations reaching before the unix epoch are fine
+        assert_eq!(queue.archive_failed(Duration::MAX, false).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn export_import_failed() {
+        let queue = queue("export-import-failed").await;
+
+        let a = fail(&queue, 1, "fatal: a").await;
+        let b = fail(&queue, 2, "fatal: b").await;
+
+        let summary = |records: &[FailedRecord<TestMessage>]| {
+            records
+                .iter()
+                .map(|record| {
+                    (
+                        record.item.0.clone(),
+                        record.message.clone(),
+                        record.attempts,
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let filtered = queue
+            .export_failed(vec![], vec!["%: b".to_owned()])
+            .await
+            .unwrap();
+        assert_eq!(filtered.len(), 1);
+        assert_eq!(filtered[0].id, b);
+
+        // exported oldest first
+        let exported = queue.export_failed(vec![], vec![]).await.unwrap();
+        assert_eq!(
+            exported.iter().map(|record| record.id).collect::<Vec<_>>(),
+            [a, b]
+        );
+        let expected = vec![
+            (defer(1), "fatal: a".to_owned(), 1),
+            (defer(2), "fatal: b".to_owned(), 1),
+        ];
+        assert_eq!(summary(&exported), expected);
+
+        // round trip through the export format
+        let exported = exported
+            .iter()
+            .map(|record| serde_json::to_string(record).unwrap())
+            .map(|line| serde_json::from_str(&line).unwrap())
+            .collect::<Vec<FailedRecord<TestMessage>>>();
+
+        sqlx::query("DELETE FROM failed")
+            .execute(&queue.client)
+            .await
+            .unwrap();
+        assert!(failed_ids(&queue).await.is_empty());
+
+        let ids = queue.import_failed(exported).await.unwrap();
+
+        // the imported items are assigned new ids
+        let imported = queue.export_failed(vec![], vec![]).await.unwrap();
+        assert_eq!(
+            imported.iter().map(|record| record.id).collect::<Vec<_>>(),
+            ids.iter().map(ItemId::raw).collect::<Vec<_>>()
+        );
+        assert!(ids.iter().all(|id| id.raw() > b));
+        assert_eq!(summary(&imported), expected);
+        assert!(imported.iter().all(|record| record.parents.is_empty()));
+    }
+}
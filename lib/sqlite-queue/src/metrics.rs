@@ -0,0 +1,6 @@
+use std::sync::LazyLock;
+
+use queue_common::metrics::QueueMetrics;
+
+pub static METRICS: LazyLock<QueueMetrics> =
+    LazyLock::new(|| QueueMetrics::new("sqlite_queue", prometheus::default_registry()).unwrap());
@@ -64,7 +64,7 @@ pub mod proto {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use cometbft_types::{
         crypto::public_key::PublicKey,
         types::{
@@ -82,7 +82,7 @@ mod tests {
 
     use super::*;
 
-    fn mk_header() -> Header {
+    pub(crate) fn mk_header() -> Header {
         Header {
             signed_header: SignedHeader {
                 header: cometbft_types::types::header::Header {
@@ -0,0 +1,96 @@
+use crate::header::Header;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "bincode", derive(bincode::Encode, bincode::Decode))]
+pub struct Misbehaviour {
+    pub header_a: Header,
+    pub header_b: Header,
+}
+
+#[cfg(feature = "proto")]
+pub mod proto {
+    use unionlabs::{errors::MissingField, impl_proto_via_try_from_into, required};
+
+    use crate::{header, misbehaviour::Misbehaviour};
+
+    impl_proto_via_try_from_into!(Misbehaviour => protos::ibc::lightclients::tendermint::v1::Misbehaviour);
+
+    impl From<Misbehaviour> for protos::ibc::lightclients::tendermint::v1::Misbehaviour {
+        fn from(value: Misbehaviour) -> Self {
+            #[allow(deprecated)]
+            Self {
+                client_id: Default::default(),
+                header_1: Some(value.header_a.into()),
+                header_2: Some(value.header_b.into()),
+            }
+        }
+    }
+
+    #[derive(Debug, PartialEq, Clone, thiserror::Error)]
+    pub enum Error {
+        #[error(transparent)]
+        MissingField(#[from] MissingField),
+        #[error("invalid header")]
+        Header(#[from] header::proto::Error),
+    }
+
+    impl TryFrom<protos::ibc::lightclients::tendermint::v1::Misbehaviour> for Misbehaviour {
+        type Error = Error;
+
+        fn try_from(
+            value: protos::ibc::lightclients::tendermint::v1::Misbehaviour,
+        ) -> Result<Self, Self::Error> {
+            Ok(Self {
+                header_a: required!(value.header_1)?.try_into()?,
+                header_b: required!(value.header_2)?.try_into()?,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use unionlabs::{
+        encoding::{Bincode, Json, Proto},
+        primitives::H256,
+        test_utils::assert_codec_iso,
+    };
+
+    use super::*;
+    use crate::header::tests::mk_header;
+
+    fn mk_misbehaviour() -> Misbehaviour {
+        let header_b = {
+            let mut header = mk_header();
+            header.signed_header.header.app_hash = H256::new([0xBB; 32]);
+            header
+        };
+
+        Misbehaviour {
+            header_a: mk_header(),
+            header_b,
+        }
+    }
+
+    #[test]
+    fn bincode_iso() {
+        assert_codec_iso::<_, Bincode>(&mk_misbehaviour());
+    }
+
+    #[test]
+    fn json_iso() {
+        assert_codec_iso::<_, Json>(&mk_misbehaviour());
+    }
+
+    #[test]
+    fn proto_iso() {
+        let mut misbehaviour = mk_misbehaviour();
+
+        // bls12_381 and bn254 aren't supported by the old tendermint types, which the proto encoding uses
+        misbehaviour.header_a.trusted_validators.validators = vec![];
+        misbehaviour.header_b.trusted_validators.validators = vec![];
+
+        assert_codec_iso::<_, Proto>(&misbehaviour);
+    }
+}
@@ -14,7 +14,7 @@ use unionlabs::{
 
 use crate::{error::Error, merkle::calculate_merkle_root};
 
-pub(crate) fn canonical_vote(
+pub fn canonical_vote(
     commit: &Commit,
     commit_sig: &CommitSig,
     timestamp: &Timestamp,
@@ -9,8 +9,8 @@ use voyager_vm::{call, defer, noop, now, seq, CallT, Op, QueueError};
 
 use crate::{
     context::WithId, core::ChainId, data::IbcDatagram, error_object_to_queue_error,
-    json_rpc_error_to_queue_error, module::PluginClient, Context, PluginMessage, RawClientId,
-    VoyagerMessage,
+    json_rpc_error_to_queue_error, metrics, module::PluginClient, Context, PluginMessage,
+    RawClientId, VoyagerMessage,
 };
 
 #[model]
@@ -147,6 +147,34 @@ impl CallT<VoyagerMessage> for Call {
     async fn process(
         self,
         ctx: voyager_vm::Context<&Context>,
+    ) -> Result<Op<VoyagerMessage>, QueueError> {
+        let ty = self.type_name();
+        let plugin = match &self {
+            Call::Plugin(PluginMessage { plugin, .. }) => plugin.clone(),
+            _ => String::new(),
+        };
+
+        metrics::observe_op("call", ty, &plugin, self.handle(ctx)).await
+    }
+}
+
+impl Call {
+    /// The `@type` of this call, as used in the metrics labels.
+    fn type_name(&self) -> &'static str {
+        match self {
+            Call::FetchBlocks(_) => "fetch_blocks",
+            Call::FetchUpdateHeaders(_) => "fetch_update_headers",
+            Call::SubmitTx(_) => "submit_tx",
+            Call::WaitForHeight(_) => "wait_for_height",
+            Call::WaitForTimestamp(_) => "wait_for_timestamp",
+            Call::WaitForTrustedHeight(_) => "wait_for_trusted_height",
+            Call::Plugin(_) => "plugin",
+        }
+    }
+
+    async fn handle(
+        self,
+        ctx: voyager_vm::Context<&Context>,
     ) -> Result<Op<VoyagerMessage>, QueueError> {
         match self {
             Call::FetchBlocks(FetchBlocks {
@@ -183,10 +211,17 @@ impl CallT<VoyagerMessage> for Call {
             }
 
             Call::SubmitTx(SubmitTx { chain_id, .. }) => {
-                let message = format!(
-                    "transaction submission request received for chain {chain_id} but \
-                    it was not picked up by a plugin"
-                );
+                let message = if ctx.is_replay() {
+                    format!(
+                        "transaction submission request received for chain {chain_id} but \
+                        transactions are not submitted while replaying"
+                    )
+                } else {
+                    format!(
+                        "transaction submission request received for chain {chain_id} but \
+                        it was not picked up by a plugin"
+                    )
+                };
 
                 error!(%message);
 
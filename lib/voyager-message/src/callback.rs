@@ -16,7 +16,7 @@ use crate::{
     context::WithId,
     core::ChainId,
     data::{Data, IbcDatagram, OrderedHeaders},
-    error_object_to_queue_error, json_rpc_error_to_queue_error,
+    error_object_to_queue_error, json_rpc_error_to_queue_error, metrics,
     module::{ClientModuleClient, PluginClient},
     Context, PluginMessage, RawClientId, VoyagerMessage,
 };
@@ -47,6 +47,32 @@ impl CallbackT<VoyagerMessage> for Callback {
         self,
         ctx: voyager_vm::Context<&Context>,
         data: VecDeque<Data>,
+    ) -> Result<Op<VoyagerMessage>, QueueError> {
+        let ty = self.type_name();
+        let plugin = match &self {
+            Callback::Plugin(PluginMessage { plugin, .. }) => plugin.clone(),
+            _ => String::new(),
+        };
+
+        metrics::observe_op("callback", ty, &plugin, self.handle(ctx, data)).await
+    }
+}
+
+impl Callback {
+    /// The `@type` of this callback, as used in the metrics labels.
+    fn type_name(&self) -> &'static str {
+        match self {
+            Callback::AggregateMsgUpdateClientsFromOrderedHeaders(_) => {
+                "aggregate_msg_update_clients_from_ordered_headers"
+            }
+            Callback::Plugin(_) => "plugin",
+        }
+    }
+
+    async fn handle(
+        self,
+        ctx: voyager_vm::Context<&Context>,
+        data: VecDeque<Data>,
     ) -> Result<Op<VoyagerMessage>, QueueError> {
         match self {
             Callback::AggregateMsgUpdateClientsFromOrderedHeaders(
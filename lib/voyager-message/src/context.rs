@@ -1,6 +1,7 @@
 use std::{
     borrow::Cow,
     collections::HashMap,
+    fmt,
     path::{Path, PathBuf},
     process::Stdio,
     sync::Arc,
@@ -14,13 +15,19 @@ use futures::{
     Future, FutureExt, StreamExt, TryStreamExt,
 };
 use jsonrpsee::{
-    core::client::ClientT,
+    core::{
+        async_trait,
+        client::{BatchResponse, ClientT},
+        params::BatchRequestBuilder,
+        traits::ToRpcParams,
+        DeserializeOwned,
+    },
     server::middleware::rpc::RpcServiceT,
     types::{ErrorObject, ErrorObjectOwned},
 };
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use serde_json::{Map, Value};
+use serde_json::{value::RawValue, Map, Value};
 use tokio::time::sleep;
 use tokio_util::sync::CancellationToken;
 use tracing::{
@@ -34,8 +41,10 @@ use voyager_core::{ConsensusType, IbcSpecId};
 use voyager_vm::{ItemId, QueueError};
 
 use crate::{
-    context::ibc_spec_handler::IbcSpecHandlers,
+    context::{ibc_spec_handler::IbcSpecHandlers, recording::ModuleResponses},
     core::{ChainId, ClientType, IbcInterface},
+    hook::SubmitTxHook,
+    metrics::{MODULE_RPC_REQUEST_DURATION, MODULE_RPC_REQUEST_ERRORS},
     module::{
         ClientBootstrapModuleInfo, ClientModuleInfo, ConsensusModuleInfo, PluginInfo,
         ProofModuleInfo, StateModuleInfo,
@@ -49,6 +58,7 @@ pub const STARTUP_ERROR_EXIT_CODE: u8 = 14;
 
 pub mod equivalent_chain_ids;
 pub mod ibc_spec_handler;
+pub mod recording;
 
 #[derive(Debug)]
 pub struct Context {
@@ -58,6 +68,8 @@ pub struct Context {
 
     interest_filters: HashMap<String, String>,
 
+    module_responses: ModuleResponses,
+
     pub cancellation_token: CancellationToken,
 }
 
@@ -88,12 +100,13 @@ impl voyager_vm::ContextT for Context {}
 pub struct ModuleRpcClient {
     #[debug(skip)]
     client: reconnecting_jsonrpc_ws_client::Client,
-    #[allow(dead_code)]
     name: String,
+    #[debug(skip)]
+    responses: ModuleResponses,
 }
 
 impl ModuleRpcClient {
-    fn new(name: &str) -> Self {
+    fn new(name: &str, responses: ModuleResponses) -> Self {
         let socket = Self::make_socket_path(name);
 
         let client = reconnecting_jsonrpc_ws_client::Client::new({
@@ -115,6 +128,7 @@ impl ModuleRpcClient {
         Self {
             client,
             name: name.to_owned(),
+            responses,
         }
     }
 
@@ -139,6 +153,122 @@ impl ModuleRpcClient {
     }
 }
 
+/// Forwards to the inner client, recording the latency and errors of every request in the
+/// [`metrics`](crate::metrics) labelled with the name of this module.
+///
+/// Requests are recorded or served from a recording as configured with [`ModuleResponses`].
+#[async_trait]
+impl ClientT for ModuleRpcClient {
+    async fn notification<Params>(
+        &self,
+        method: &str,
+        params: Params,
+    ) -> Result<(), jsonrpsee::core::client::Error>
+    where
+        Params: ToRpcParams + Send,
+    {
+        self.client.notification(method, params).await
+    }
+
+    async fn request<R, Params>(
+        &self,
+        method: &str,
+        params: Params,
+    ) -> Result<R, jsonrpsee::core::client::Error>
+    where
+        R: DeserializeOwned,
+        Params: ToRpcParams + Send,
+    {
+        let timer = MODULE_RPC_REQUEST_DURATION
+            .with_label_values(&[&self.name, method])
+            .start_timer();
+
+        let res = match &self.responses {
+            ModuleResponses::Live => self.client.request(method, params).await,
+            ModuleResponses::Record(recorder) => {
+                let params = params.to_rpc_params()?;
+
+                let res = self
+                    .client
+                    .request::<Value, _>(method, RawParams(params.clone()))
+                    .await;
+
+                recorder.record(&self.name, method, params.as_deref(), &res);
+
+                res.and_then(|value| serde_json::from_value(value).map_err(Into::into))
+            }
+            ModuleResponses::Replay(recording) => recording
+                .respond(&self.name, method, params.to_rpc_params()?.as_deref())
+                .and_then(|value| serde_json::from_value(value).map_err(Into::into)),
+        };
+
+        timer.observe_duration();
+
+        if res.is_err() {
+            MODULE_RPC_REQUEST_ERRORS
+                .with_label_values(&[&self.name, method])
+                .inc();
+        }
+
+        res
+    }
+
+    async fn batch_request<'a, R>(
+        &self,
+        batch: BatchRequestBuilder<'a>,
+    ) -> Result<BatchResponse<'a, R>, jsonrpsee::core::client::Error>
+    where
+        R: DeserializeOwned + fmt::Debug + 'a,
+    {
+        self.client.batch_request(batch).await
+    }
+}
+
+#[async_trait]
+impl ClientT for &ModuleRpcClient {
+    async fn notification<Params>(
+        &self,
+        method: &str,
+        params: Params,
+    ) -> Result<(), jsonrpsee::core::client::Error>
+    where
+        Params: ToRpcParams + Send,
+    {
+        <ModuleRpcClient as ClientT>::notification(*self, method, params).await
+    }
+
+    async fn request<R, Params>(
+        &self,
+        method: &str,
+        params: Params,
+    ) -> Result<R, jsonrpsee::core::client::Error>
+    where
+        R: DeserializeOwned,
+        Params: ToRpcParams + Send,
+    {
+        <ModuleRpcClient as ClientT>::request(*self, method, params).await
+    }
+
+    async fn batch_request<'a, R>(
+        &self,
+        batch: BatchRequestBuilder<'a>,
+    ) -> Result<BatchResponse<'a, R>, jsonrpsee::core::client::Error>
+    where
+        R: DeserializeOwned + fmt::Debug + 'a,
+    {
+        <ModuleRpcClient as ClientT>::batch_request(*self, batch).await
+    }
+}
+
+/// Params that have already been serialized.
+struct RawParams(Option<Box<RawValue>>);
+
+impl ToRpcParams for RawParams {
+    fn to_rpc_params(self) -> Result<Option<Box<RawValue>>, serde_json::Error> {
+        Ok(self.0)
+    }
+}
+
 pub(crate) trait WithId: Sized + ClientT + Send + Sync
 where
     for<'a> &'a Self: ClientT,
@@ -268,6 +398,7 @@ impl Context {
         module_configs: ModulesConfig,
         equivalent_chain_ids: equivalent_chain_ids::EquivalentChainIds,
         register_ibc_spec_handlers: fn(&mut IbcSpecHandlers),
+        module_responses: ModuleResponses,
     ) -> anyhow::Result<Self> {
         let cancellation_token = CancellationToken::new();
 
@@ -294,6 +425,8 @@ impl Context {
 
         let main_rpc_server = Server::new();
 
+        let replay = module_responses.is_replay();
+
         info!("spawning {} plugins", plugin_configs.len());
 
         stream::iter(plugin_configs)
@@ -320,6 +453,15 @@ impl Context {
                 } else {
                     let plugin_info = get_plugin_info(&plugin_config)?;
 
+                    if replay && SubmitTxHook::is_submit_tx_filter(&plugin_info.interest_filter) {
+                        info!(
+                            "plugin {} handles transaction submission, not starting it while \
+                            replaying",
+                            plugin_info.name
+                        );
+                        return Ok(None);
+                    }
+
                     debug!("starting rpc server for plugin {}", plugin_info.name);
                     tokio::spawn(module_rpc_server(&plugin_info.name, server).await?);
 
@@ -343,7 +485,7 @@ impl Context {
                         cancellation_token.clone(),
                     ));
 
-                    let rpc_client = ModuleRpcClient::new(&name);
+                    let rpc_client = ModuleRpcClient::new(&name, ModuleResponses::Live);
 
                     let prev = plugins.insert(name.clone(), rpc_client.clone());
 
@@ -366,6 +508,7 @@ impl Context {
             module_configs.state,
             cancellation_token.clone(),
             main_rpc_server.clone(),
+            &module_responses,
             |info| info.id(),
             |StateModuleInfo {
                  chain_id,
@@ -397,6 +540,7 @@ impl Context {
             module_configs.proof,
             cancellation_token.clone(),
             main_rpc_server.clone(),
+            &module_responses,
             |info| info.id(),
             |ProofModuleInfo {
                  chain_id,
@@ -428,6 +572,7 @@ impl Context {
             module_configs.consensus,
             cancellation_token.clone(),
             main_rpc_server.clone(),
+            &module_responses,
             |info| info.id(),
             |ConsensusModuleInfo {
                  chain_id,
@@ -465,6 +610,7 @@ impl Context {
             module_configs.client,
             cancellation_token.clone(),
             main_rpc_server.clone(),
+            &module_responses,
             |info| info.id(),
             |ClientModuleInfo {
                  client_type,
@@ -519,6 +665,7 @@ impl Context {
             module_configs.client_bootstrap,
             cancellation_token.clone(),
             main_rpc_server.clone(),
+            &module_responses,
             |info| info.id(),
             |ClientBootstrapModuleInfo {
                  client_type,
@@ -599,6 +746,7 @@ impl Context {
             rpc_server: main_rpc_server,
             plugins,
             interest_filters,
+            module_responses,
             cancellation_token,
         })
     }
@@ -612,17 +760,12 @@ impl Context {
         }
     }
 
-    pub fn plugin(
-        &self,
-        name: impl AsRef<str>,
-    ) -> Result<&reconnecting_jsonrpc_ws_client::Client, PluginNotFound> {
-        Ok(self
-            .plugins
+    pub fn plugin(&self, name: impl AsRef<str>) -> Result<&ModuleRpcClient, PluginNotFound> {
+        self.plugins
             .get(name.as_ref())
             .ok_or_else(|| PluginNotFound {
                 name: name.as_ref().into(),
-            })?
-            .client())
+            })
     }
 
     pub fn plugin_client_raw(
@@ -636,9 +779,21 @@ impl Context {
             })
     }
 
+    /// All of the loaded plugins, along with their names.
+    pub fn plugins(&self) -> impl Iterator<Item = (&str, &ModuleRpcClient)> {
+        self.plugins
+            .iter()
+            .map(|(name, client)| (name.as_str(), client))
+    }
+
     pub fn interest_filters(&self) -> &HashMap<String, String> {
         &self.interest_filters
     }
+
+    /// Whether the module responses are served from a recording, see [`ModuleResponses::Replay`].
+    pub fn is_replay(&self) -> bool {
+        self.module_responses.is_replay()
+    }
 }
 
 impl Modules {
@@ -729,15 +884,13 @@ impl Modules {
         chain_id: &ChainId,
         ibc_spec_id: &IbcSpecId,
         // ) -> Result<&'a (impl RawStateModuleClient + 'a), StateModuleNotFound> {
-    ) -> Result<&'a reconnecting_jsonrpc_ws_client::Client, StateModuleNotFound> {
-        Ok(self
-            .state_modules
+    ) -> Result<&'a ModuleRpcClient, StateModuleNotFound> {
+        self.state_modules
             .get(&(chain_id.clone(), ibc_spec_id.clone()))
             .ok_or_else(|| StateModuleNotFound {
                 chain_id: chain_id.clone(),
                 ibc_spec_id: ibc_spec_id.clone(),
-            })?
-            .client())
+            })
     }
 
     pub fn proof_module<'a, 'b, 'c: 'a>(
@@ -745,27 +898,23 @@ impl Modules {
         chain_id: &ChainId,
         ibc_spec_id: &IbcSpecId,
         // ) -> Result<&'a (impl RawProofModuleClient + 'a), ProofModuleNotFound> {
-    ) -> Result<&'a reconnecting_jsonrpc_ws_client::Client, ProofModuleNotFound> {
-        Ok(self
-            .proof_modules
+    ) -> Result<&'a ModuleRpcClient, ProofModuleNotFound> {
+        self.proof_modules
             .get(&(chain_id.clone(), ibc_spec_id.clone()))
             .ok_or_else(|| ProofModuleNotFound {
                 chain_id: chain_id.clone(),
                 ibc_spec_id: ibc_spec_id.clone(),
-            })?
-            .client())
+            })
     }
 
     pub fn consensus_module<'a, 'b, 'c: 'a>(
         &'a self,
         chain_id: &ChainId,
         // ) -> Result<&'a (impl jsonrpsee::core::client::ClientT + 'a), ConsensusModuleNotFound> {
-    ) -> Result<&'a reconnecting_jsonrpc_ws_client::Client, ConsensusModuleNotFound> {
-        Ok(self
-            .consensus_modules
+    ) -> Result<&'a ModuleRpcClient, ConsensusModuleNotFound> {
+        self.consensus_modules
             .get(chain_id)
-            .ok_or_else(|| ConsensusModuleNotFound(chain_id.clone()))?
-            .client())
+            .ok_or_else(|| ConsensusModuleNotFound(chain_id.clone()))
     }
 
     pub fn client_module<'a, 'b, 'c: 'a>(
@@ -774,13 +923,13 @@ impl Modules {
         ibc_interface: &IbcInterface,
         ibc_spec_id: &IbcSpecId,
         // ) -> Result<&'a (impl ClientModuleClient + 'a), ClientModuleNotFound> {
-    ) -> Result<&'a reconnecting_jsonrpc_ws_client::Client, ClientModuleNotFound> {
+    ) -> Result<&'a ModuleRpcClient, ClientModuleNotFound> {
         match self.client_modules.get(&(
             client_type.clone(),
             ibc_interface.clone(),
             ibc_spec_id.clone(),
         )) {
-            Some(client_module) => Ok(client_module.client()),
+            Some(client_module) => Ok(client_module),
             None => Err(ClientModuleNotFound::NotFound {
                 client_type: client_type.clone(),
                 ibc_interface: ibc_interface.clone(),
@@ -794,15 +943,13 @@ impl Modules {
         chain_id: &ChainId,
         client_type: &ClientType,
         // ) -> Result<&'a (impl jsonrpsee::core::client::ClientT + 'a), ConsensusModuleNotFound> {
-    ) -> Result<&'a reconnecting_jsonrpc_ws_client::Client, ClientBootstrapModuleNotFound> {
-        Ok(self
-            .client_bootstrap_modules
+    ) -> Result<&'a ModuleRpcClient, ClientBootstrapModuleNotFound> {
+        self.client_bootstrap_modules
             .get(&(chain_id.clone(), client_type.clone()))
             .ok_or_else(|| ClientBootstrapModuleNotFound {
                 chain_id: chain_id.clone(),
                 client_type: client_type.clone(),
-            })?
-            .client())
+            })
     }
 }
 
@@ -1082,6 +1229,7 @@ async fn module_startup<Info: Serialize + Clone + Unpin + Send + 'static>(
     configs: Vec<ModuleConfig<Info>>,
     cancellation_token: CancellationToken,
     main_rpc_server: Server,
+    module_responses: &ModuleResponses,
     id_f: fn(&Info) -> String,
     mut push_f: impl FnMut(&Info, ModuleRpcClient) -> anyhow::Result<()>,
 ) -> anyhow::Result<()> {
@@ -1107,11 +1255,14 @@ async fn module_startup<Info: Serialize + Clone + Unpin + Send + 'static>(
                 );
                 anyhow::Result::Ok(None)
             } else {
-                debug!(
-                    "starting rpc server for module {}",
-                    id_f(&module_config.info)
-                );
-                tokio::spawn(module_rpc_server(&id_f(&module_config.info), server).await?);
+                // the modules are not started when replaying, so there is nothing to serve
+                if !module_responses.is_replay() {
+                    debug!(
+                        "starting rpc server for module {}",
+                        id_f(&module_config.info)
+                    );
+                    tokio::spawn(module_rpc_server(&id_f(&module_config.info), server).await?);
+                }
 
                 anyhow::Result::Ok(Some(module_config))
             }
@@ -1124,13 +1275,15 @@ async fn module_startup<Info: Serialize + Clone + Unpin + Send + 'static>(
 
             info!("registering module {}", id);
 
-            tokio::spawn(module_child_process(
-                id.clone(),
-                module_config.clone(),
-                cancellation_token.clone(),
-            ));
+            if !module_responses.is_replay() {
+                tokio::spawn(module_child_process(
+                    id.clone(),
+                    module_config.clone(),
+                    cancellation_token.clone(),
+                ));
+            }
 
-            let rpc_client = ModuleRpcClient::new(&id);
+            let rpc_client = ModuleRpcClient::new(&id, module_responses.clone());
 
             push_f(&module_config.info, rpc_client)?;
 
@@ -0,0 +1,333 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::Context as _;
+use jsonrpsee::{core::client::Error, types::ErrorObject};
+use serde::{Deserialize, Serialize};
+use serde_json::{value::RawValue, Value};
+use tracing::warn;
+use unionlabs::ErrorReporter;
+
+use crate::ParamsWithItemId;
+
+/// How the requests made to the modules are handled.
+#[derive(Debug, Clone, Default)]
+pub enum ModuleResponses {
+    /// Forward all requests to the modules.
+    #[default]
+    Live,
+    /// Forward all requests to the modules, and record their responses with the [`Recorder`].
+    Record(Recorder),
+    /// Serve all requests from a [`Recording`]. The modules are not started in this mode, and any
+    /// plugins that handle transaction submission are not loaded.
+    Replay(Arc<Recording>),
+}
+
+impl ModuleResponses {
+    pub fn is_replay(&self) -> bool {
+        matches!(self, Self::Replay(_))
+    }
+}
+
+/// A response returned by a module.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecordedResponse {
+    pub module: String,
+    pub method: String,
+    /// The params of the request, without the id of the item the request was made for.
+    pub params: Value,
+    pub response: RecordedResult,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordedResult {
+    Ok(Value),
+    Err {
+        code: i32,
+        message: String,
+        data: Option<Value>,
+    },
+}
+
+/// Appends the responses of the modules to a file, one JSON encoded [`RecordedResponse`] per line.
+///
+/// Only responses returned by the modules themselves are recorded; transport errors (i.e. a module
+/// not being connected yet) are not.
+#[derive(Debug, Clone)]
+pub struct Recorder {
+    file: Arc<Mutex<File>>,
+}
+
+impl Recorder {
+    /// Open the recording at `path`, creating it if it does not exist.
+    pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+
+        Ok(Self {
+            file: Arc::new(Mutex::new(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(path)
+                    .with_context(|| format!("unable to open `{}`", path.to_string_lossy()))?,
+            )),
+        })
+    }
+
+    pub fn record(
+        &self,
+        module: &str,
+        method: &str,
+        params: Option<&RawValue>,
+        response: &Result<Value, Error>,
+    ) {
+        let response = match response {
+            Ok(value) => RecordedResult::Ok(value.clone()),
+            Err(Error::Call(error)) => RecordedResult::Err {
+                code: error.code(),
+                message: error.message().to_owned(),
+                data: error
+                    .data()
+                    .and_then(|data| serde_json::from_str(data.get()).ok()),
+            },
+            Err(_) => return,
+        };
+
+        let mut line = serde_json::to_vec(&RecordedResponse {
+            module: module.to_owned(),
+            method: method.to_owned(),
+            params: request_params(params),
+            response,
+        })
+        .expect("serialization is infallible; qed;");
+        line.push(b'\n');
+
+        if let Err(err) = self
+            .file
+            .lock()
+            .expect("lock is not poisoned; qed;")
+            .write_all(&line)
+        {
+            warn!(%module, %method, err = %ErrorReporter(err), "unable to record response");
+        }
+    }
+}
+
+/// The responses recorded by a [`Recorder`].
+///
+/// Responses are served in the order they were recorded for every distinct request (module, method
+/// and params). Once all of the responses for a request have been served, the last one is served
+/// for any further requests.
+#[derive(Debug, Default)]
+pub struct Recording {
+    responses: Mutex<HashMap<(String, String, String), VecDeque<RecordedResult>>>,
+}
+
+impl Recording {
+    pub fn read(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let path = path.as_ref();
+
+        let file = File::open(path)
+            .with_context(|| format!("unable to open `{}`", path.to_string_lossy()))?;
+
+        BufReader::new(file)
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| {
+                let line = line?;
+                serde_json::from_str::<RecordedResponse>(&line).with_context(|| {
+                    format!(
+                        "invalid recorded response at `{}:{}`",
+                        path.to_string_lossy(),
+                        idx + 1
+                    )
+                })
+            })
+            .collect()
+    }
+
+    pub fn respond(
+        &self,
+        module: &str,
+        method: &str,
+        params: Option<&RawValue>,
+    ) -> Result<Value, Error> {
+        let mut responses = self.responses.lock().expect("lock is not poisoned; qed;");
+
+        let key = (
+            module.to_owned(),
+            method.to_owned(),
+            request_params(params).to_string(),
+        );
+
+        let response = match responses.get_mut(&key) {
+            Some(responses) if responses.len() > 1 => responses.pop_front(),
+            Some(responses) => responses.front().cloned(),
+            None => None,
+        };
+
+        match response {
+            Some(RecordedResult::Ok(value)) => Ok(value),
+            Some(RecordedResult::Err {
+                code,
+                message,
+                data,
+            }) => Err(Error::Call(ErrorObject::owned(code, message, data))),
+            None => Err(Error::Custom(format!(
+                "no recorded response for `{method}` on module `{module}` with params {}",
+                key.2
+            ))),
+        }
+    }
+}
+
+impl FromIterator<RecordedResponse> for Recording {
+    fn from_iter<I: IntoIterator<Item = RecordedResponse>>(iter: I) -> Self {
+        let mut responses = HashMap::<_, VecDeque<_>>::new();
+
+        for response in iter {
+            responses
+                .entry((
+                    response.module,
+                    response.method,
+                    response.params.to_string(),
+                ))
+                .or_default()
+                .push_back(response.response);
+        }
+
+        Self {
+            responses: Mutex::new(responses),
+        }
+    }
+}
+
+/// The params of a request, without the id of the item the request was made for (if any), since
+/// item ids are not stable across replays.
+fn request_params(params: Option<&RawValue>) -> Value {
+    let Some(params) = params else {
+        return Value::Null;
+    };
+
+    match serde_json::from_str::<ParamsWithItemId>(params.get()) {
+        Ok(ParamsWithItemId { params, .. }) => params
+            .and_then(|params| serde_json::from_str(params.get()).ok())
+            .unwrap_or_default(),
+        Err(_) => serde_json::from_str(params.get()).unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use voyager_vm::ItemId;
+
+    use super::*;
+
+    fn raw(value: Value) -> Box<RawValue> {
+        RawValue::from_string(value.to_string()).unwrap()
+    }
+
+    fn with_item_id(item_id: i64, params: Value) -> Box<RawValue> {
+        raw(json!({
+            "item_id": ItemId::new(item_id).unwrap(),
+            "params": params,
+        }))
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let path = std::env::temp_dir().join(format!(
+            "voyager-module-recording-{}.jsonl",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+
+        let recorder = Recorder::open(&path).unwrap();
+
+        recorder.record(
+            "state",
+            "query_latest_height",
+            Some(&with_item_id(1, json!([false]))),
+            &Ok(json!(1)),
+        );
+        recorder.record(
+            "state",
+            "query_latest_height",
+            Some(&with_item_id(2, json!([false]))),
+            &Ok(json!(2)),
+        );
+        recorder.record(
+            "state",
+            "query_ibc_state",
+            None,
+            &Err(Error::Call(ErrorObject::owned(
+                -1,
+                "not found",
+                Some(json!({ "height": 1 })),
+            ))),
+        );
+        // not a response of the module, not recorded
+        recorder.record(
+            "state",
+            "query_latest_height",
+            Some(&raw(json!([true]))),
+            &Err(Error::Custom("not yet connected".to_owned())),
+        );
+
+        let recording = Recording::read(&path).unwrap();
+
+        // the item ids are not part of the request
+        let params = raw(json!([false]));
+
+        assert_eq!(
+            recording
+                .respond("state", "query_latest_height", Some(&params))
+                .unwrap(),
+            json!(1)
+        );
+        assert_eq!(
+            recording
+                .respond(
+                    "state",
+                    "query_latest_height",
+                    Some(&with_item_id(7, json!([false])))
+                )
+                .unwrap(),
+            json!(2)
+        );
+        // the last response is served once the recorded responses have been exhausted
+        assert_eq!(
+            recording
+                .respond("state", "query_latest_height", Some(&params))
+                .unwrap(),
+            json!(2)
+        );
+
+        match recording.respond("state", "query_ibc_state", None) {
+            Err(Error::Call(error)) => {
+                assert_eq!(error.code(), -1);
+                assert_eq!(error.message(), "not found");
+            }
+            res => panic!("unexpected response: {res:?}"),
+        }
+
+        assert!(matches!(
+            recording.respond("state", "query_latest_height", Some(&raw(json!([true])))),
+            Err(Error::Custom(_))
+        ));
+        assert!(matches!(
+            recording.respond("proof", "query_latest_height", Some(&params)),
+            Err(Error::Custom(_))
+        ));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -5,7 +5,7 @@ use jaq_interpret::{Ctx, Filter, FilterT, ParseCtx, RcIter, Val};
 use tracing::{error, instrument, trace};
 use unionlabs::ErrorReporter;
 use voyager_vm::{
-    filter::{FilterResult, InterestFilter},
+    filter::{FilterResult, InterestFilter, Priority},
     Op,
 };
 
@@ -80,15 +80,28 @@ impl InterestFilter<VoyagerMessage> for JaqInterestFilter {
     fn check_interest<'a>(&'a self, op: &Op<VoyagerMessage>) -> FilterResult<'a> {
         let msg_json = Val::from(serde_json::to_value(op.clone()).unwrap());
 
+        let mut priority = Priority::default();
+
         for (filter, plugin_name) in &self.filters {
             match run_filter(filter, plugin_name, msg_json.clone()) {
                 Ok(interest @ FilterResult::Interest(_)) => return interest,
-                Ok(FilterResult::NoInterest) => {}
+                Ok(FilterResult::NoInterest(p)) => priority = combine_priorities(priority, p),
                 Err(_) => {}
             }
         }
 
-        FilterResult::NoInterest
+        FilterResult::NoInterest(priority)
+    }
+}
+
+/// Combine the priorities assigned to an op by multiple plugins. If any plugin raised the priority
+/// of the op, the highest priority is used, otherwise the lowest is used. This ensures that the
+/// result does not depend on the order the filters are run in.
+fn combine_priorities(a: Priority, b: Priority) -> Priority {
+    if a > 0 || b > 0 {
+        a.max(b)
+    } else {
+        a.min(b)
     }
 }
 
@@ -150,7 +163,50 @@ pub fn run_filter<'a>(
             Val::Bool(false) => {
                 trace!("no interest");
 
-                Ok(FilterResult::NoInterest)
+                Ok(FilterResult::NoInterest(Priority::default()))
+            }
+            Val::Obj(ref obj) => {
+                let get = |key: &str| {
+                    obj.iter()
+                        .find_map(|(k, v)| (k.as_str() == key).then_some(v))
+                };
+
+                let interest = match get("interest") {
+                    None => false,
+                    Some(Val::Bool(interest)) => *interest,
+                    Some(value) => {
+                        error!("filter returned a non-boolean interest: {value:?}");
+
+                        return Err(());
+                    }
+                };
+
+                let priority = match get("priority") {
+                    None => Priority::default(),
+                    Some(Val::Int(priority)) => match Priority::try_from(*priority) {
+                        Ok(priority) => priority,
+                        Err(_) => {
+                            error!("filter returned an out of range priority: {priority}");
+
+                            return Err(());
+                        }
+                    },
+                    Some(value) => {
+                        error!("filter returned a non-integer priority: {value:?}");
+
+                        return Err(());
+                    }
+                };
+
+                if interest {
+                    trace!("interest");
+
+                    Ok(FilterResult::Interest(plugin_name))
+                } else {
+                    trace!(%priority, "no interest");
+
+                    Ok(FilterResult::NoInterest(priority))
+                }
             }
             _ => {
                 error!("filter returned a non-boolean value: {result:?}");
@@ -160,3 +216,147 @@ pub fn run_filter<'a>(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use voyager_core::ChainId;
+    use voyager_vm::{call, noop};
+
+    use super::*;
+    use crate::{call::SubmitTx, hook::SubmitTxHook, PluginMessage};
+
+    fn filter(name: &str, interest_filter: &str) -> JaqInterestFilter {
+        JaqInterestFilter::new(vec![PluginInfo {
+            name: name.to_owned(),
+            interest_filter: interest_filter.to_owned(),
+        }])
+        .unwrap()
+    }
+
+    fn run(interest_filter: &str) -> Result<FilterResult<'static>, ()> {
+        let (filter, _) = make_filter(PluginInfo {
+            name: "plugin".to_owned(),
+            interest_filter: interest_filter.to_owned(),
+        })
+        .unwrap();
+
+        run_filter(
+            &filter,
+            "plugin",
+            Val::from(serde_json::to_value(noop::<VoyagerMessage>()).unwrap()),
+        )
+    }
+
+    #[test]
+    fn combine_priorities_raised() {
+        assert_eq!(combine_priorities(0, 10), 10);
+        assert_eq!(combine_priorities(10, 0), 10);
+        assert_eq!(combine_priorities(5, 10), 10);
+        // a raised priority wins over a lowered one
+        assert_eq!(combine_priorities(-5, 10), 10);
+        assert_eq!(combine_priorities(10, -5), 10);
+    }
+
+    #[test]
+    fn combine_priorities_lowered() {
+        assert_eq!(combine_priorities(0, 0), 0);
+        assert_eq!(combine_priorities(0, -5), -5);
+        assert_eq!(combine_priorities(-5, 0), -5);
+        assert_eq!(combine_priorities(-5, -10), -10);
+    }
+
+    #[test]
+    fn bool_filters() {
+        assert_eq!(run("true"), Ok(FilterResult::Interest("plugin")));
+        assert_eq!(run("false"), Ok(FilterResult::NoInterest(0)));
+    }
+
+    #[test]
+    fn object_filters() {
+        assert_eq!(
+            run(r#"{"interest": true}"#),
+            Ok(FilterResult::Interest("plugin"))
+        );
+        // the priority is ignored if there is interest
+        assert_eq!(
+            run(r#"{"interest": true, "priority": 10}"#),
+            Ok(FilterResult::Interest("plugin"))
+        );
+        assert_eq!(run(r#"{"priority": 10}"#), Ok(FilterResult::NoInterest(10)));
+        assert_eq!(
+            run(r#"{"interest": false, "priority": -10}"#),
+            Ok(FilterResult::NoInterest(-10))
+        );
+        assert_eq!(run("{}"), Ok(FilterResult::NoInterest(0)));
+    }
+
+    #[test]
+    fn invalid_object_filters() {
+        assert_eq!(run(r#"{"interest": 1}"#), Err(()));
+        assert_eq!(run(r#"{"priority": "high"}"#), Err(()));
+        assert_eq!(run(r#"{"priority": 1.5}"#), Err(()));
+        assert_eq!(run(r#"{"priority": 4294967296}"#), Err(()));
+        assert_eq!(run("1"), Err(()));
+    }
+
+    #[test]
+    fn priorities_are_combined_across_plugins() {
+        let filter = JaqInterestFilter {
+            filters: [
+                ("a", r#"{"priority": 10}"#),
+                ("b", r#"{"priority": -5}"#),
+                ("c", "false"),
+            ]
+            .into_iter()
+            .map(|(name, interest_filter)| {
+                make_filter(PluginInfo {
+                    name: name.to_owned(),
+                    interest_filter: interest_filter.to_owned(),
+                })
+                .unwrap()
+            })
+            .collect(),
+        };
+
+        assert_eq!(filter.check_interest(&noop()), FilterResult::NoInterest(10));
+    }
+
+    #[test]
+    fn submit_tx_hook_priority() {
+        let chain_id = ChainId::new("chain".to_owned());
+
+        let submit_tx = call::<VoyagerMessage>(SubmitTx {
+            chain_id: chain_id.clone(),
+            datagrams: vec![],
+        });
+        let own_call = call::<VoyagerMessage>(PluginMessage::new("tx", json!({})));
+        let other_call = call::<VoyagerMessage>(PluginMessage::new("other", json!({})));
+
+        // no priority is assigned by default
+        let unprioritized = filter("tx", &SubmitTxHook::filter(&chain_id, 0));
+        assert_eq!(
+            unprioritized.check_interest(&submit_tx),
+            FilterResult::Interest("tx")
+        );
+        assert_eq!(
+            unprioritized.check_interest(&own_call),
+            FilterResult::NoInterest(0)
+        );
+
+        // only the calls to the plugin itself are prioritized
+        let prioritized = filter("tx", &SubmitTxHook::filter(&chain_id, 10));
+        assert_eq!(
+            prioritized.check_interest(&submit_tx),
+            FilterResult::Interest("tx")
+        );
+        assert_eq!(
+            prioritized.check_interest(&own_call),
+            FilterResult::NoInterest(10)
+        );
+        assert_eq!(
+            prioritized.check_interest(&other_call),
+            FilterResult::NoInterest(0)
+        );
+    }
+}
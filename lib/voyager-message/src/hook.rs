@@ -1,6 +1,6 @@
 use tracing::info;
 use voyager_core::{ChainId, ClientType};
-use voyager_vm::Visit;
+use voyager_vm::{filter::Priority, Visit};
 
 use crate::{
     call::{Call, FetchUpdateHeaders, SubmitTx},
@@ -67,7 +67,10 @@ impl<'a, F: for<'b> Fn(&'b SubmitTx) -> Call> SubmitTxHook<'a, F> {
 }
 
 impl SubmitTxHook<'_, for<'b> fn(&'b SubmitTx) -> Call> {
-    pub fn filter(chain_id: &ChainId) -> String {
+    /// The interest filter for a transaction plugin on `chain_id`. If `priority` is non-zero, the
+    /// calls to the plugin (i.e. the calls that submit the transactions) are queued with this
+    /// priority.
+    pub fn filter(chain_id: &ChainId, priority: Priority) -> String {
         // if ."@type" == "data" then
         //     ."@value" as $data |
 
@@ -82,10 +85,31 @@ impl SubmitTxHook<'_, for<'b> fn(&'b SubmitTx) -> Call> {
         //     false
         // end
 
-        format!(
-            r#"[.. | ."@type"? == "submit_tx" and ."@value".chain_id == "{}"] | any"#,
-            chain_id
-        )
+        let submit_tx = format!(
+            r#"[.. | ."@type"? == "submit_tx" and ."@value".chain_id == "{chain_id}"] | any"#
+        );
+
+        if priority == 0 {
+            submit_tx
+        } else {
+            format!(
+                r#"
+if {submit_tx} then
+    true
+elif ."@type" == "call" and ."@value"."@type" == "plugin" and ."@value"."@value".plugin == $PLUGIN_NAME then
+    {{"priority": {priority}}}
+else
+    false
+end
+"#
+            )
+        }
+    }
+
+    /// Whether `filter` is the interest filter of a transaction plugin, as built by
+    /// [`Self::filter`].
+    pub fn is_submit_tx_filter(filter: &str) -> bool {
+        filter.contains(r#"."@type"? == "submit_tx""#)
     }
 }
 
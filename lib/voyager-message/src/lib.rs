@@ -65,6 +65,7 @@ pub mod data;
 
 pub mod context;
 pub mod filter;
+pub mod metrics;
 pub mod module;
 pub mod pass;
 
@@ -281,6 +282,12 @@ pub trait Plugin: PluginServer<Self::Call, Self::Callback> + Sized {
 
     async fn cmd(config: Self::Config, cmd: Self::Cmd);
 
+    /// The [`RpcModule`] this plugin is served with. Override this to serve additional methods
+    /// alongside [`PluginServer`], i.e. [`SignerServer`](module::SignerServer).
+    fn into_rpc_module(self) -> RpcModule<Self> {
+        <Self as PluginServer<Self::Call, Self::Callback>>::into_rpc(self)
+    }
+
     async fn run() {
         init_log();
 
@@ -304,7 +311,7 @@ pub trait Plugin: PluginServer<Self::Call, Self::Callback> + Sized {
                     config,
                     socket,
                     Self::new,
-                    Self::into_rpc,
+                    Self::into_rpc_module,
                 )
                 .instrument(debug_span!("run_plugin_server", %name))
                 .await
@@ -669,6 +676,30 @@ impl VoyagerClient {
         Ok(proof)
     }
 
+    #[instrument(
+        skip_all,
+        name = "voyager_client_encode_misbehaviour",
+        fields(
+            %client_type,
+            %ibc_interface,
+            %misbehaviour
+        )
+    )]
+    pub async fn encode_misbehaviour<V: IbcSpec>(
+        &self,
+        client_type: ClientType,
+        ibc_interface: IbcInterface,
+        misbehaviour: Value,
+    ) -> RpcResult<Bytes> {
+        let misbehaviour = self
+            .0
+            .encode_misbehaviour(client_type, ibc_interface, V::ID, misbehaviour)
+            .await
+            .map_err(json_rpc_error_to_error_object)?;
+
+        Ok(misbehaviour)
+    }
+
     pub async fn decode_client_state<V: IbcSpec>(
         &self,
         client_type: ClientType,
@@ -684,6 +715,21 @@ impl VoyagerClient {
         Ok(client_state)
     }
 
+    pub async fn decode_consensus_state<V: IbcSpec>(
+        &self,
+        client_type: ClientType,
+        ibc_interface: IbcInterface,
+        consensus_state_bytes: Bytes,
+    ) -> RpcResult<Value> {
+        let consensus_state = self
+            .0
+            .decode_consensus_state(client_type, ibc_interface, V::ID, consensus_state_bytes)
+            .await
+            .map_err(json_rpc_error_to_error_object)?;
+
+        Ok(consensus_state)
+    }
+
     pub async fn query_ibc_state<P: IbcStorePathKey>(
         &self,
         chain_id: ChainId,
@@ -0,0 +1,70 @@
+use std::{future::Future, sync::LazyLock};
+
+use prometheus::{register_histogram_vec, register_int_counter_vec, HistogramVec, IntCounterVec};
+use voyager_vm::QueueError;
+
+pub static MODULE_RPC_REQUEST_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "voyager_module_rpc_request_duration_seconds",
+        "The time it takes for a plugin or module to respond to a request.",
+        &["module", "method"],
+    )
+    .unwrap()
+});
+
+pub static MODULE_RPC_REQUEST_ERRORS: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "voyager_module_rpc_request_errors_total",
+        "The amount of requests to a plugin or module that returned an error.",
+        &["module", "method"],
+    )
+    .unwrap()
+});
+
+pub static OP_PROCESSING_DURATION: LazyLock<HistogramVec> = LazyLock::new(|| {
+    register_histogram_vec!(
+        "voyager_op_processing_duration_seconds",
+        "The time it takes to process a call or callback, by `@type`.",
+        &["kind", "type", "plugin"],
+    )
+    .unwrap()
+});
+
+pub static OP_PROCESSED: LazyLock<IntCounterVec> = LazyLock::new(|| {
+    register_int_counter_vec!(
+        "voyager_op_processed_total",
+        "The amount of calls and callbacks processed, by `@type` and result.",
+        &["kind", "type", "plugin", "result"],
+    )
+    .unwrap()
+});
+
+/// Record the duration and result of processing a call or callback. `plugin` is empty for ops
+/// that are not handled by a plugin.
+pub(crate) async fn observe_op<T>(
+    kind: &str,
+    ty: &str,
+    plugin: &str,
+    fut: impl Future<Output = Result<T, QueueError>>,
+) -> Result<T, QueueError> {
+    let timer = OP_PROCESSING_DURATION
+        .with_label_values(&[kind, ty, plugin])
+        .start_timer();
+
+    let res = fut.await;
+
+    timer.observe_duration();
+
+    let result = match &res {
+        Ok(_) => "ok",
+        Err(QueueError::Retry(_)) => "retry",
+        Err(QueueError::Fatal(_)) => "fatal",
+        Err(QueueError::Unprocessable(_)) => "unprocessable",
+    };
+
+    OP_PROCESSED
+        .with_label_values(&[kind, ty, plugin, result])
+        .inc();
+
+    res
+}
@@ -1,10 +1,17 @@
-use std::collections::VecDeque;
+use std::{collections::VecDeque, future::Future};
 
-use jsonrpsee::{core::RpcResult, proc_macros::rpc};
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    proc_macros::rpc,
+    types::ErrorObject,
+    Extensions,
+};
 use macros::model;
 use schemars::JsonSchema;
 use serde_json::Value;
-use unionlabs::{ibc::core::client::height::Height, primitives::Bytes, traits::Member};
+use unionlabs::{
+    ibc::core::client::height::Height, primitives::Bytes, traits::Member, ErrorReporter,
+};
 use voyager_core::{ConsensusType, IbcSpecId, Timestamp};
 use voyager_vm::{pass::PassResult, BoxDynError, Op};
 
@@ -13,6 +20,7 @@ use crate::{
         ChainId, ClientInfo, ClientStateMeta, ClientType, ConsensusStateMeta, IbcInterface, IbcSpec,
     },
     data::Data,
+    into_value,
     rpc::ProofType,
     RawClientId, VoyagerMessage,
 };
@@ -301,6 +309,14 @@ pub struct PluginInfo {
     /// This ***MUST*** return a bool. If this returns `true`, the message will
     /// be pushed to the optimization queue with this plugin's name as the tag,
     /// otherwise it will be passed on to the next plugin to be filtered.
+    ///
+    /// This may also return an object of the form
+    /// `{ "interest": bool, "priority": int }` (both fields are optional,
+    /// defaulting to `false` and `0`). If there is no interest, the priority
+    /// will be used when the message is queued to be processed; messages with a
+    /// higher priority are processed first. This can be used to process
+    /// time-sensitive messages (i.e. transaction submissions) ahead of bulk
+    /// work (i.e. indexing).
     pub interest_filter: String,
 }
 
@@ -321,6 +337,66 @@ pub trait Plugin<C: Member, Cb: Member> {
     async fn callback(&self, aggregate: Cb, data: VecDeque<Data>) -> RpcResult<Op<VoyagerMessage>>;
 }
 
+/// Optional methods for plugins that sign and submit transactions. This is served alongside
+/// [`PluginServer`], see [`Plugin::into_rpc_module`](crate::Plugin::into_rpc_module).
+#[rpc(client, server, namespace = "signer")]
+pub trait Signer {
+    /// Fetch the current balance of every key in this plugin's keyring.
+    #[method(name = "balances", with_extensions)]
+    async fn balances(&self) -> RpcResult<Vec<SignerBalance>>;
+}
+
+#[model]
+pub struct SignerBalance {
+    pub key_name: String,
+    pub address: String,
+    /// The balance of this key, in the smallest unit of `denom`.
+    #[serde(with = "::serde_utils::string")]
+    pub balance: u128,
+    pub denom: String,
+}
+
+/// Plugins that sign and submit transactions with the keys in a keyring. Implementing this provides
+/// [`SignerServer`] for the plugin, and allows for using [`SignerCmd`] as its
+/// [`Plugin::Cmd`](crate::Plugin::Cmd).
+pub trait SignerBalances: Send + Sync + 'static {
+    /// Fetch the current balance of every key in the keyring. This is expected to also take any
+    /// keys below the minimum balance out of rotation.
+    fn signer_balances(
+        &self,
+    ) -> impl Future<Output = Result<Vec<SignerBalance>, BoxDynError>> + Send;
+}
+
+#[async_trait]
+impl<T: SignerBalances> SignerServer for T {
+    async fn balances(&self, _: &Extensions) -> RpcResult<Vec<SignerBalance>> {
+        self.signer_balances().await.map_err(|err| {
+            ErrorObject::owned(
+                -1,
+                format!("error fetching signer balances: {}", ErrorReporter(&*err)),
+                None::<()>,
+            )
+        })
+    }
+}
+
+/// The commands of plugins that implement [`SignerBalances`].
+#[derive(clap::Subcommand)]
+pub enum SignerCmd {
+    /// Print the balance of every key in the keyring.
+    Balances,
+}
+
+impl SignerCmd {
+    pub async fn run(self, signer: impl SignerBalances) {
+        match self {
+            SignerCmd::Balances => {
+                print!("{}", into_value(signer.signer_balances().await.unwrap()))
+            }
+        }
+    }
+}
+
 #[rpc(
     client,
     server,
@@ -416,6 +492,10 @@ pub trait ClientModule {
     /// Encode the proof, provided as JSON.
     #[method(name = "encodeProof", with_extensions)]
     async fn encode_proof(&self, proof: Value) -> RpcResult<Bytes>;
+
+    /// Encode the misbehaviour, provided as JSON.
+    #[method(name = "encodeMisbehaviour", with_extensions)]
+    async fn encode_misbehaviour(&self, misbehaviour: Value) -> RpcResult<Bytes>;
 }
 
 /// Client modules provide functionality for interacting with a specific chain
@@ -116,6 +116,15 @@ pub trait VoyagerRpc {
         proof: Value,
     ) -> RpcResult<Bytes>;
 
+    #[method(name = "encodeMisbehaviour", with_extensions)]
+    async fn encode_misbehaviour(
+        &self,
+        client_type: ClientType,
+        ibc_interface: IbcInterface,
+        ibc_spec_id: IbcSpecId,
+        misbehaviour: Value,
+    ) -> RpcResult<Bytes>;
+
     #[method(name = "decodeClientStateMeta", with_extensions)]
     async fn decode_client_state_meta(
         &self,
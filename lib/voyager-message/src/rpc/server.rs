@@ -520,6 +520,37 @@ impl Server {
             .await
     }
 
+    // TODO: Use valuable here
+    #[instrument(skip_all, fields(%client_type, %ibc_interface, %ibc_spec_id, %misbehaviour))]
+    pub async fn encode_misbehaviour(
+        &self,
+        client_type: &ClientType,
+        ibc_interface: &IbcInterface,
+        ibc_spec_id: &IbcSpecId,
+        misbehaviour: Value,
+    ) -> RpcResult<Bytes> {
+        self.span()
+            .in_scope(|| async {
+                trace!("encoding misbehaviour");
+
+                let client_module = self
+                    .inner
+                    .modules()?
+                    .client_module(client_type, ibc_interface, ibc_spec_id)?
+                    .with_id(self.item_id);
+
+                let misbehaviour = client_module
+                    .encode_misbehaviour(misbehaviour)
+                    .await
+                    .map_err(json_rpc_error_to_error_object)?;
+
+                trace!(%misbehaviour, "encoded misbehaviour");
+
+                Ok(misbehaviour)
+            })
+            .await
+    }
+
     // TODO: Use valuable here
     #[instrument(skip_all, fields(%client_type, %ibc_interface, %ibc_spec_id))]
     pub async fn decode_client_state_meta(
@@ -741,6 +772,20 @@ impl VoyagerRpcServer for Server {
             .await
     }
 
+    // TODO: Use valuable here
+    async fn encode_misbehaviour(
+        &self,
+        e: &Extensions,
+        client_type: ClientType,
+        ibc_interface: IbcInterface,
+        ibc_spec_id: IbcSpecId,
+        misbehaviour: Value,
+    ) -> RpcResult<Bytes> {
+        self.with_id(e.try_get().ok().cloned())
+            .encode_misbehaviour(&client_type, &ibc_interface, &ibc_spec_id, misbehaviour)
+            .await
+    }
+
     // TODO: Use valuable here
     async fn decode_client_state_meta(
         &self,
@@ -0,0 +1,6 @@
+use enumorph::Enumorph;
+use macros::model;
+
+#[model]
+#[derive(Enumorph)]
+pub enum ModuleCallback {}
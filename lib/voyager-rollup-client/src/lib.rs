@@ -0,0 +1,220 @@
+//! Shared voyager plumbing for the L2 light clients (arbitrum, scroll, linea), which all encode
+//! their states the same way and are updated with the same plugin messages and flow (see
+//! [`update::fetch_update`]).
+
+use std::{
+    error::Error,
+    fmt::{Debug, Display},
+    marker::PhantomData,
+};
+
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    types::ErrorObject,
+    Extensions,
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::instrument;
+use unionlabs::{
+    encoding::{Bincode, Decode, DecodeAs, Encode, EncodeAs, Encoding, EthAbi},
+    ibc::core::client::height::Height,
+    primitives::Bytes,
+    ErrorReporter,
+};
+use voyager_message::{
+    core::{ChainId, ClientStateMeta, ConsensusStateMeta, IbcInterface, Timestamp},
+    module::{ClientModuleInfo, ClientModuleServer},
+    ClientModule, FATAL_JSONRPC_ERROR_CODE,
+};
+use voyager_vm::BoxDynError;
+
+pub mod call;
+pub mod callback;
+pub mod update;
+
+/// The types of an L2 light client.
+///
+/// The consensus state is encoded with [`EthAbi`], everything else with [`Bincode`].
+pub trait RollupClient: Debug + Send + Sync + 'static {
+    const CLIENT_TYPE: &'static str;
+    const CONSENSUS_TYPE: &'static str;
+
+    type ClientState: Serialize
+        + DeserializeOwned
+        + Encode<Bincode>
+        + Decode<Bincode, Error: Display>
+        + Debug;
+    type ConsensusState: Serialize
+        + DeserializeOwned
+        + Encode<EthAbi>
+        + Decode<EthAbi, Error: Error>
+        + Debug;
+    type Header: DeserializeOwned + Encode<Bincode>;
+    type Misbehaviour: DeserializeOwned + Encode<Bincode>;
+    type Proof: DeserializeOwned + Encode<Bincode>;
+
+    /// The chain id of the L2 tracked by the client.
+    fn chain_id(client_state: &Self::ClientState) -> ChainId;
+
+    /// The height that the latest consensus state of the client is stored at.
+    fn latest_height(client_state: &Self::ClientState) -> u64;
+
+    /// The timestamp of the consensus state, in nanoseconds.
+    fn timestamp(consensus_state: &Self::ConsensusState) -> u64;
+}
+
+#[derive(Debug, Clone)]
+pub struct RollupClientModule<C> {
+    __marker: PhantomData<fn() -> C>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {}
+
+impl<C: RollupClient> ClientModule for RollupClientModule<C> {
+    type Config = Config;
+
+    async fn new(Config {}: Self::Config, info: ClientModuleInfo) -> Result<Self, BoxDynError> {
+        info.ensure_client_type(C::CLIENT_TYPE)?;
+        info.ensure_consensus_type(C::CONSENSUS_TYPE)?;
+        info.ensure_ibc_interface(IbcInterface::IBC_COSMWASM)?;
+
+        Ok(Self {
+            __marker: PhantomData,
+        })
+    }
+}
+
+impl<C: RollupClient> RollupClientModule<C> {
+    pub fn decode_consensus_state(consensus_state: &[u8]) -> RpcResult<C::ConsensusState> {
+        C::ConsensusState::decode_as::<EthAbi>(consensus_state).map_err(|err| {
+            ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!("unable to decode consensus state: {}", ErrorReporter(err)),
+                None::<()>,
+            )
+        })
+    }
+
+    pub fn decode_client_state(client_state: &[u8]) -> RpcResult<C::ClientState> {
+        C::ClientState::decode_as::<Bincode>(client_state).map_err(|err| {
+            ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!("unable to decode client state: {err}"),
+                None::<()>,
+            )
+        })
+    }
+
+    pub fn make_height(revision_height: u64) -> Height {
+        Height::new(revision_height)
+    }
+}
+
+/// Deserialize `value` as a `T` and encode it with `Enc`.
+fn encode<T: DeserializeOwned + Encode<Enc>, Enc: Encoding>(
+    value: Value,
+    what: &str,
+) -> RpcResult<Bytes> {
+    serde_json::from_value::<T>(value)
+        .map_err(|err| {
+            ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!("unable to deserialize {what}: {}", ErrorReporter(err)),
+                None::<()>,
+            )
+        })
+        .map(|t| t.encode_as::<Enc>())
+        .map(Into::into)
+}
+
+#[async_trait]
+impl<C: RollupClient> ClientModuleServer for RollupClientModule<C> {
+    #[instrument]
+    async fn decode_client_state_meta(
+        &self,
+        _: &Extensions,
+        client_state: Bytes,
+    ) -> RpcResult<ClientStateMeta> {
+        let cs = Self::decode_client_state(&client_state)?;
+
+        Ok(ClientStateMeta {
+            chain_id: C::chain_id(&cs),
+            counterparty_height: Self::make_height(C::latest_height(&cs)),
+        })
+    }
+
+    #[instrument]
+    async fn decode_consensus_state_meta(
+        &self,
+        _: &Extensions,
+        consensus_state: Bytes,
+    ) -> RpcResult<ConsensusStateMeta> {
+        let cs = Self::decode_consensus_state(&consensus_state)?;
+
+        Ok(ConsensusStateMeta {
+            timestamp_nanos: Timestamp::from_nanos(C::timestamp(&cs)),
+        })
+    }
+
+    #[instrument]
+    async fn decode_client_state(&self, _: &Extensions, client_state: Bytes) -> RpcResult<Value> {
+        Ok(serde_json::to_value(Self::decode_client_state(&client_state)?).unwrap())
+    }
+
+    #[instrument]
+    async fn decode_consensus_state(
+        &self,
+        _: &Extensions,
+        consensus_state: Bytes,
+    ) -> RpcResult<Value> {
+        Ok(serde_json::to_value(Self::decode_consensus_state(&consensus_state)?).unwrap())
+    }
+
+    #[instrument]
+    async fn encode_client_state(
+        &self,
+        _: &Extensions,
+        client_state: Value,
+        metadata: Value,
+    ) -> RpcResult<Bytes> {
+        if !metadata.is_null() {
+            return Err(ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                "metadata was provided, but this client type does not require \
+                metadata for client state encoding",
+                Some(json!({
+                    "provided_metadata": metadata,
+                })),
+            ));
+        }
+
+        encode::<C::ClientState, Bincode>(client_state, "client state")
+    }
+
+    #[instrument]
+    async fn encode_consensus_state(
+        &self,
+        _: &Extensions,
+        consensus_state: Value,
+    ) -> RpcResult<Bytes> {
+        encode::<C::ConsensusState, EthAbi>(consensus_state, "consensus state")
+    }
+
+    #[instrument]
+    async fn encode_header(&self, _: &Extensions, header: Value) -> RpcResult<Bytes> {
+        encode::<C::Header, Bincode>(header, "header")
+    }
+
+    #[instrument]
+    async fn encode_misbehaviour(&self, _: &Extensions, misbehaviour: Value) -> RpcResult<Bytes> {
+        encode::<C::Misbehaviour, Bincode>(misbehaviour, "misbehaviour")
+    }
+
+    #[instrument]
+    async fn encode_proof(&self, _: &Extensions, proof: Value) -> RpcResult<Bytes> {
+        encode::<C::Proof, Bincode>(proof, "proof")
+    }
+}
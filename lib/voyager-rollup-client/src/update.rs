@@ -0,0 +1,174 @@
+use std::fmt::Debug;
+
+use ibc_union_spec::{path::ClientStatePath, IbcUnion};
+use jsonrpsee::{core::RpcResult, types::ErrorObject};
+use serde::{de::DeserializeOwned, Serialize};
+use tracing::{debug, info};
+use unionlabs::{ibc::core::client::height::Height, ErrorReporter};
+use voyager_message::{
+    call::{FetchUpdateHeaders, WaitForTrustedHeight},
+    callback::AggregateMsgUpdateClientsFromOrderedHeaders,
+    core::{ClientStateMeta, IbcSpec, QueryHeight},
+    data::{DecodedHeaderMeta, OrderedHeaders},
+    into_value,
+    rpc::missing_state,
+    RawClientId, VoyagerClient, VoyagerMessage, FATAL_JSONRPC_ERROR_CODE,
+};
+use voyager_vm::{call, conc, data, promise, seq, Op};
+
+use crate::call::FetchUpdate;
+
+/// The rollup specific parts of updating an L2 client, see [`fetch_update`].
+#[allow(async_fn_in_trait)]
+pub trait RollupUpdate {
+    /// The name of the rollup, used in errors and logs.
+    const NAME: &'static str;
+
+    type ClientState: DeserializeOwned + Debug;
+    type Header: Serialize;
+
+    /// The id of the client tracking the L1, on the same chain as the L2 client.
+    fn l1_client_id(client_state: &Self::ClientState) -> u32;
+
+    /// The L1 height to update the L2 client at. The L1 client is updated to this height first if
+    /// it is behind.
+    async fn l1_height(
+        &self,
+        voyager_client: &VoyagerClient,
+        l1_client_meta: &ClientStateMeta,
+        update_to: Height,
+    ) -> RpcResult<Height>;
+
+    /// Fetch the header to update the client described by `client_state` with, using the L1
+    /// state at `l1_height`. Returns the height that the header updates the client to.
+    async fn fetch_header(
+        &self,
+        client_state: &Self::ClientState,
+        l1_height: Height,
+        update_to: Height,
+    ) -> RpcResult<(Height, Self::Header)>;
+}
+
+/// Update the L2 client `client_id` on `counterparty_chain_id`.
+///
+/// The L1 client that the L2 client is verified against is updated first if it is behind the L1
+/// height returned by [`RollupUpdate::l1_height`], and the L2 header is only emitted once the L1
+/// client has a consensus state at that height.
+pub async fn fetch_update<R: RollupUpdate>(
+    rollup: &R,
+    voyager_client: &VoyagerClient,
+    FetchUpdate {
+        counterparty_chain_id,
+        client_id,
+        update_from: _,
+        update_to,
+    }: FetchUpdate,
+) -> RpcResult<Op<VoyagerMessage>> {
+    // L2 client running on the counterparty, tracking the rollup
+    let raw_client_state = voyager_client
+        .query_ibc_state(
+            counterparty_chain_id.clone(),
+            QueryHeight::Latest,
+            ClientStatePath { client_id },
+        )
+        .await?
+        .state
+        .ok_or_else(missing_state(
+            format!("{} client state doesn't exist?", R::NAME),
+            None,
+        ))?;
+
+    let client_info = voyager_client
+        .client_info::<IbcUnion>(counterparty_chain_id.clone(), client_id)
+        .await?;
+
+    let client_state = serde_json::from_value::<R::ClientState>(
+        voyager_client
+            .decode_client_state::<IbcUnion>(
+                client_info.client_type,
+                client_info.ibc_interface,
+                raw_client_state,
+            )
+            .await?,
+    )
+    .map_err(|e| {
+        ErrorObject::owned(
+            FATAL_JSONRPC_ERROR_CODE,
+            format!(
+                "unable to deserialize {} client state: {}",
+                R::NAME,
+                ErrorReporter(e)
+            ),
+            None::<()>,
+        )
+    })?;
+
+    debug!(?client_state);
+
+    let l1_client_id = R::l1_client_id(&client_state);
+
+    // the client on the counterparty that is tracking the L1
+    let l1_client_meta = voyager_client
+        .client_meta::<IbcUnion>(
+            counterparty_chain_id.clone(),
+            QueryHeight::Latest,
+            l1_client_id,
+        )
+        .await?;
+
+    debug!(?l1_client_meta);
+
+    let l1_height = rollup
+        .l1_height(voyager_client, &l1_client_meta, update_to)
+        .await?;
+
+    let (height, header) = rollup
+        .fetch_header(&client_state, l1_height, update_to)
+        .await?;
+
+    let headers = data(OrderedHeaders {
+        headers: vec![(DecodedHeaderMeta { height }, into_value(header))],
+    });
+
+    if l1_client_meta.counterparty_height >= l1_height {
+        info!("l1 client is already at height {l1_height}");
+
+        return Ok(headers);
+    }
+
+    let l1_client_type = voyager_client
+        .client_info::<IbcUnion>(counterparty_chain_id.clone(), l1_client_id)
+        .await?
+        .client_type;
+
+    // dispatch an update for the L1 on the counterparty, then dispatch the L2 update once the L1
+    // client has been updated
+    Ok(conc([
+        promise(
+            [call(FetchUpdateHeaders {
+                client_type: l1_client_type,
+                chain_id: l1_client_meta.chain_id.clone(),
+                counterparty_chain_id: counterparty_chain_id.clone(),
+                client_id: RawClientId::new(l1_client_id),
+                update_from: l1_client_meta.counterparty_height,
+                update_to: l1_height,
+            })],
+            [],
+            AggregateMsgUpdateClientsFromOrderedHeaders {
+                ibc_spec_id: IbcUnion::ID,
+                chain_id: counterparty_chain_id.clone(),
+                client_id: RawClientId::new(l1_client_id),
+            },
+        ),
+        seq([
+            call(WaitForTrustedHeight {
+                chain_id: counterparty_chain_id,
+                ibc_spec_id: IbcUnion::ID,
+                client_id: RawClientId::new(l1_client_id),
+                height: l1_height,
+                finalized: false,
+            }),
+            headers,
+        ]),
+    ]))
+}
@@ -5,20 +5,110 @@ use tokio::time::sleep;
 use tracing::{error, info, warn};
 use unionlabs::ErrorReporter;
 
-use crate::{defer, now, seq, BoxDynError, Context, Queue, QueueError, QueueMessage};
+use serde::{Deserialize, Serialize};
+
+use crate::{BoxDynError, Context, ProcessResult, Queue, QueueError, QueueMessage};
 
 pub struct Engine<'a, T: QueueMessage, Q: Queue<T>> {
     store: &'a T::Context,
     queue: &'a Q,
     optimizer: &'a T::Filter,
+    retry_policy: &'a RetryPolicy,
+}
+
+/// How items that fail with [`QueueError::Retry`] are retried.
+///
+/// The delay before the `n`th retry (starting at 0) is `base_delay_milliseconds * multiplier^n`,
+/// capped at `max_delay_milliseconds`. Once an item has been attempted `max_attempts` times, it is
+/// marked as failed with the last error it returned.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct RetryPolicy {
+    #[serde(default = "default_base_delay_milliseconds")]
+    pub base_delay_milliseconds: u64,
+    #[serde(default = "default_multiplier")]
+    pub multiplier: f64,
+    #[serde(default = "default_max_delay_milliseconds")]
+    pub max_delay_milliseconds: u64,
+    /// The maximum amount of times an item will be attempted before it is marked as failed. `0`
+    /// will retry items indefinitely.
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            base_delay_milliseconds: default_base_delay_milliseconds(),
+            multiplier: default_multiplier(),
+            max_delay_milliseconds: default_max_delay_milliseconds(),
+            max_attempts: default_max_attempts(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The delay before retrying an item that has already been attempted `attempts` times.
+    #[must_use]
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        clippy::cast_precision_loss
+    )]
+    pub fn delay(&self, attempts: u32) -> Duration {
+        let exponent = i32::try_from(attempts.saturating_sub(1)).unwrap_or(i32::MAX);
+
+        let delay = (self.base_delay_milliseconds as f64 * self.multiplier.powi(exponent))
+            .min(self.max_delay_milliseconds as f64);
+
+        // NaN and negative delays (from a nonsensical multiplier) saturate to 0
+        Duration::from_millis(delay as u64)
+    }
+
+    /// Whether an item that has been attempted `attempts` times has run out of attempts.
+    #[must_use]
+    pub fn is_exhausted(&self, attempts: u32) -> bool {
+        self.max_attempts != 0 && attempts >= self.max_attempts
+    }
+}
+
+#[must_use]
+#[inline]
+pub const fn default_base_delay_milliseconds() -> u64 {
+    3000
+}
+
+#[must_use]
+#[inline]
+pub const fn default_multiplier() -> f64 {
+    2.0
+}
+
+#[must_use]
+#[inline]
+pub const fn default_max_delay_milliseconds() -> u64 {
+    5 * 60 * 1000
+}
+
+#[must_use]
+#[inline]
+pub const fn default_max_attempts() -> u32 {
+    20
 }
 
 impl<'a, T: QueueMessage, Q: Queue<T>> Engine<'a, T, Q> {
-    pub fn new(store: &'a T::Context, queue: &'a Q, filter: &'a T::Filter) -> Self {
+    pub fn new(
+        store: &'a T::Context,
+        queue: &'a Q,
+        filter: &'a T::Filter,
+        retry_policy: &'a RetryPolicy,
+    ) -> Self {
         Self {
             store,
             queue,
             optimizer: filter,
+            retry_policy,
         }
     }
 
@@ -31,33 +121,59 @@ impl<'a, T: QueueMessage, Q: Queue<T>> Engine<'a, T, Q> {
         .flat_map(|x| stream::iter(x.transpose()))
     }
 
-    pub(crate) fn step<'b>(
+    /// Process a single item from the queue.
+    ///
+    /// Returns `None` if there were no items ready to be processed, and otherwise the data produced
+    /// by the item, if any.
+    pub fn step<'b>(
         &'b self,
     ) -> impl Future<Output = Result<Option<Option<T::Data>>, BoxDynError>> + use<'a, 'b, T, Q> + Send
     {
         // yield back to the runtime and throttle a bit, prevents 100% cpu usage while still allowing for a fast spin-loop
         sleep(Duration::from_millis(10)).then(|()| {
             self.queue
-                .process::<_, _, Option<T::Data>>(self.optimizer, |op, id| {
-                    op.clone()
-                        .process(Context::new(id, self.store), 0)
-                        .map(|res| match res {
-                            Ok(op) => (None, Ok(op.into_iter().collect())),
+                .process::<_, _, Option<T::Data>>(self.optimizer, |op, id, attempts| {
+                    op.process(Context::new(id, self.store), 0)
+                        .map(move |res| match res {
+                            Ok(op) => (None, ProcessResult::Done(op.into_iter().collect())),
                             Err(QueueError::Fatal(fatal)) => {
                                 let full_err = ErrorReporter(&*fatal);
                                 error!(error = %full_err, "fatal error");
-                                (None, Err(full_err.to_string()))
+                                (None, ProcessResult::Failed(full_err.to_string()))
                             }
                             Err(QueueError::Unprocessable(unprocessable)) => {
                                 let full_err = ErrorReporter(&*unprocessable);
                                 info!(error = %full_err, "unprocessable message");
-                                (None, Err(full_err.to_string()))
+                                (None, ProcessResult::Failed(full_err.to_string()))
                             }
                             Err(QueueError::Retry(retry)) => {
-                                // TODO: Add some backoff logic here based on `full_err`?
                                 let full_err = ErrorReporter(&*retry);
-                                warn!(error = %full_err, "retryable error");
-                                (None, Ok(vec![seq([defer(now() + 3), op])]))
+                                // this attempt is included in the count
+                                let attempts = attempts + 1;
+
+                                if self.retry_policy.is_exhausted(attempts) {
+                                    error!(
+                                        error = %full_err,
+                                        %attempts,
+                                        "retryable error, max attempts reached"
+                                    );
+                                    (None, ProcessResult::Failed(full_err.to_string()))
+                                } else {
+                                    let delay = self.retry_policy.delay(attempts);
+                                    warn!(
+                                        error = %full_err,
+                                        %attempts,
+                                        delay_milliseconds = %delay.as_millis(),
+                                        "retryable error"
+                                    );
+                                    (
+                                        None,
+                                        ProcessResult::Retry {
+                                            error: full_err.to_string(),
+                                            delay,
+                                        },
+                                    )
+                                }
                             }
                         })
                 })
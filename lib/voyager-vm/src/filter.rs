@@ -1,16 +1,33 @@
 use crate::{Op, QueueMessage};
 
+/// The priority of an op in the queue. Ops with a higher priority are processed first, and ops with
+/// the same priority are processed in the order they were queued.
+pub type Priority = i32;
+
 /// A filter to run on [`Op`]s before they're pushed into the queue.
 pub trait InterestFilter<T: QueueMessage>: Send + Sync + Sized + 'static {
     fn check_interest<'a>(&'a self, op: &Op<T>) -> FilterResult<'a>;
 }
 
 /// The result of running an [`InterestFilter`] on an [`Op`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FilterResult<'a> {
     /// Interest has been expressed in this Op, with the contained tag. It will be inserted into the optimization queue under this tag.
     Interest(&'a str),
-    /// No interest.
-    NoInterest,
+    /// No interest. The op will be queued to be processed with the contained priority.
+    NoInterest(Priority),
+}
+
+impl FilterResult<'_> {
+    /// The priority to use if the op is queued to be processed. Ops that something has expressed
+    /// interest in use the default priority.
+    #[must_use]
+    pub fn priority(&self) -> Priority {
+        match self {
+            FilterResult::Interest(_) => Priority::default(),
+            FilterResult::NoInterest(priority) => *priority,
+        }
+    }
 }
 
 /// A noop implementation of an interest filter that never expresses interest in any messages.
@@ -18,6 +35,6 @@ impl<T: QueueMessage> InterestFilter<T> for () {
     fn check_interest<'a>(&'a self, op: &Op<T>) -> FilterResult<'a> {
         let _ = op;
 
-        FilterResult::NoInterest
+        FilterResult::NoInterest(Priority::default())
     }
 }
@@ -1,20 +1,24 @@
 use std::{
-    collections::BTreeMap,
+    cmp::Reverse,
+    collections::{BTreeMap, BTreeSet, VecDeque},
     future::Future,
+    num::TryFromIntError,
     sync::{
         atomic::{AtomicU32, Ordering},
         Arc, Mutex,
     },
+    time::Instant,
 };
 
 use either::Either;
 use frame_support_procedural::{CloneNoBound, DebugNoBound};
-use tracing::{debug, info_span, warn, Instrument};
+use tracing::{debug, error, info_span, warn, Instrument};
 
 use crate::{
-    filter::{FilterResult, InterestFilter},
+    filter::{FilterResult, InterestFilter, Priority},
     pass::Pass,
-    Captures, EnqueueResult, ItemId, Op, Queue, QueueMessage,
+    Captures, EnqueueResult, ItemId, ItemRecord, ItemStatus, Op, ProcessResult, Queue, QueueDepth,
+    QueueMessage, QueueSnapshot, SnapshotItem,
 };
 
 #[derive(DebugNoBound, CloneNoBound)]
@@ -22,15 +26,260 @@ pub struct InMemoryQueue<T: QueueMessage> {
     idx: Arc<AtomicU32>,
     ready: Arc<Mutex<BTreeMap<u32, Item<T>>>>,
     done: Arc<Mutex<BTreeMap<u32, Item<T>>>>,
+    failed: Arc<Mutex<BTreeMap<u32, (Item<T>, String)>>>,
     #[allow(clippy::type_complexity)]
     optimizer_queue: Arc<Mutex<BTreeMap<String, BTreeMap<u32, Item<T>>>>>,
 }
 
 #[derive(DebugNoBound, CloneNoBound)]
 pub(crate) struct Item<T: QueueMessage> {
-    #[allow(dead_code)] // used in debug
     parents: Vec<u32>,
     op: Op<T>,
+    /// The amount of times this item has been attempted and failed with a retryable error.
+    attempts: u32,
+    /// If set, this item will not be processed before this time.
+    retry_at: Option<Instant>,
+    priority: Priority,
+}
+
+impl<T: QueueMessage> Item<T> {
+    fn new(parents: Vec<u32>, op: Op<T>, priority: Priority) -> Self {
+        Self {
+            parents,
+            op,
+            attempts: 0,
+            retry_at: None,
+            priority,
+        }
+    }
+}
+
+impl<T: QueueMessage> InMemoryQueue<T> {
+    /// Create a queue containing the pending items of `snapshot`, keeping their ids. New items will
+    /// be assigned ids after the highest id in the snapshot.
+    ///
+    /// Retry delays are not part of the snapshot, so all items in the snapshot are immediately
+    /// ready to be processed.
+    ///
+    /// # Errors
+    ///
+    /// This will error if any of the ids in the snapshot do not fit in a `u32`.
+    pub fn from_snapshot(snapshot: QueueSnapshot<T>) -> Result<Self, TryFromIntError> {
+        let id = |id: ItemId| u32::try_from(id.raw());
+
+        let item = |item: SnapshotItem<T>| {
+            Ok::<_, TryFromIntError>((
+                id(item.id)?,
+                Item {
+                    parents: item.parents.into_iter().map(id).collect::<Result<_, _>>()?,
+                    op: item.item,
+                    attempts: item.attempts,
+                    retry_at: None,
+                    priority: item.priority,
+                },
+            ))
+        };
+
+        let ready = snapshot
+            .queue
+            .into_iter()
+            .map(item)
+            .collect::<Result<BTreeMap<_, _>, _>>()?;
+
+        let optimizer_queue = snapshot
+            .optimize
+            .into_iter()
+            .map(|(tag, items)| {
+                Ok::<_, TryFromIntError>((
+                    tag,
+                    items
+                        .into_iter()
+                        .map(item)
+                        .collect::<Result<BTreeMap<_, _>, _>>()?,
+                ))
+            })
+            .collect::<Result<BTreeMap<_, _>, _>>()?;
+
+        let next_id = ready
+            .keys()
+            .chain(optimizer_queue.values().flat_map(BTreeMap::keys))
+            .max()
+            .map_or(0, |id| id + 1);
+
+        Ok(Self {
+            idx: Arc::new(AtomicU32::new(next_id)),
+            ready: Arc::new(Mutex::new(ready)),
+            done: Arc::new(Mutex::new(BTreeMap::default())),
+            failed: Arc::new(Mutex::new(BTreeMap::default())),
+            optimizer_queue: Arc::new(Mutex::new(optimizer_queue)),
+        })
+    }
+
+    /// Returns the pending items of this queue. See [`Self::from_snapshot`].
+    #[must_use]
+    pub fn snapshot(&self) -> QueueSnapshot<T> {
+        // same lock order as `optimize`
+        let optimizer_queue = self.optimizer_queue.lock().expect("mutex is poisoned");
+        let ready = self.ready.lock().expect("mutex is poisoned");
+
+        let item = |(id, item): (&u32, &Item<T>)| SnapshotItem {
+            id: ItemId::new(i64::from(*id)).expect("infallible"),
+            parents: item
+                .parents
+                .iter()
+                .map(|&parent| ItemId::new(i64::from(parent)).expect("infallible"))
+                .collect(),
+            priority: item.priority,
+            attempts: item.attempts,
+            item: item.op.clone(),
+        };
+
+        QueueSnapshot {
+            queue: ready.iter().map(item).collect(),
+            optimize: optimizer_queue
+                .iter()
+                .filter(|(_, items)| !items.is_empty())
+                .map(|(tag, items)| (tag.clone(), items.iter().map(item).collect()))
+                .collect(),
+        }
+    }
+
+    /// Returns the amount of items currently held by this queue.
+    #[must_use]
+    pub fn depth(&self) -> QueueDepth {
+        // same lock order as `optimize`
+        let optimizer_queue = self.optimizer_queue.lock().expect("mutex is poisoned");
+        let ready = self.ready.lock().expect("mutex is poisoned");
+
+        QueueDepth {
+            ready: ready.len() as u64,
+            retrying: ready.values().filter(|item| item.attempts > 0).count() as u64,
+            optimize: optimizer_queue
+                .iter()
+                .filter(|(_, items)| !items.is_empty())
+                .map(|(tag, items)| (tag.clone(), items.len() as u64))
+                .collect(),
+            failed: self.failed.lock().expect("mutex is poisoned").len() as u64,
+        }
+    }
+
+    /// Returns the items that have failed, along with the error they failed with.
+    #[must_use]
+    pub fn failed(&self) -> Vec<(ItemId, Op<T>, String)> {
+        self.failed
+            .lock()
+            .expect("mutex is poisoned")
+            .iter()
+            .map(|(id, (item, error))| {
+                (
+                    ItemId::new(i64::from(*id)).expect("infallible"),
+                    item.op.clone(),
+                    error.clone(),
+                )
+            })
+            .collect()
+    }
+
+    /// Returns the item with the specified id and all of its ancestors, up to `max_depth` levels
+    /// deep.
+    #[must_use]
+    pub fn ancestors(&self, id: ItemId, max_depth: u32) -> Vec<ItemRecord<T>> {
+        self.lineage(id, max_depth, |items, id| {
+            items
+                .get(&id)
+                .map(|(_, item)| item.parents.clone())
+                .unwrap_or_default()
+        })
+    }
+
+    /// Returns the item with the specified id and all of its descendants, up to `max_depth` levels
+    /// deep.
+    #[must_use]
+    pub fn descendants(&self, id: ItemId, max_depth: u32) -> Vec<ItemRecord<T>> {
+        self.lineage(id, max_depth, |items, id| {
+            items
+                .iter()
+                .filter(|(_, (_, item))| item.parents.contains(&id))
+                .map(|(child_id, _)| *child_id)
+                .collect()
+        })
+    }
+
+    /// Walks the lineage of the item with the specified id, breadth first, using `next` to find the
+    /// neighbours of each item.
+    fn lineage(
+        &self,
+        id: ItemId,
+        max_depth: u32,
+        next: impl Fn(&BTreeMap<u32, (ItemStatus, Item<T>)>, u32) -> Vec<u32>,
+    ) -> Vec<ItemRecord<T>> {
+        let Ok(id) = u32::try_from(id.raw()) else {
+            return vec![];
+        };
+
+        let items = self.all_items();
+
+        let mut seen = BTreeSet::new();
+        let mut queue = VecDeque::from([(id, 0)]);
+        let mut records = vec![];
+
+        while let Some((id, depth)) = queue.pop_front() {
+            if !seen.insert(id) {
+                continue;
+            }
+
+            let Some((status, item)) = items.get(&id) else {
+                continue;
+            };
+
+            records.push(ItemRecord {
+                id: ItemId::new(i64::from(id)).expect("infallible"),
+                parents: item
+                    .parents
+                    .iter()
+                    .map(|&parent| ItemId::new(i64::from(parent)).expect("infallible"))
+                    .collect(),
+                status: *status,
+                depth,
+                item: item.op.clone(),
+            });
+
+            if depth < max_depth {
+                queue.extend(next(&items, id).into_iter().map(|id| (id, depth + 1)));
+            }
+        }
+
+        records
+    }
+
+    /// A snapshot of all items in the queue, keyed by their id.
+    fn all_items(&self) -> BTreeMap<u32, (ItemStatus, Item<T>)> {
+        // same lock order as `optimize`
+        let optimizer_queue = self.optimizer_queue.lock().expect("mutex is poisoned");
+        let ready = self.ready.lock().expect("mutex is poisoned");
+        let done = self.done.lock().expect("mutex is poisoned");
+        let failed = self.failed.lock().expect("mutex is poisoned");
+
+        done.iter()
+            .map(|(id, item)| (*id, (ItemStatus::Done, item.clone())))
+            .chain(
+                failed
+                    .iter()
+                    .map(|(id, (item, _))| (*id, (ItemStatus::Failed, item.clone()))),
+            )
+            .chain(
+                optimizer_queue
+                    .values()
+                    .flatten()
+                    .map(|(id, item)| (*id, (ItemStatus::Optimize, item.clone()))),
+            )
+            .chain(
+                ready
+                    .iter()
+                    .map(|(id, item)| (*id, (ItemStatus::Queue, item.clone()))),
+            )
+            .collect()
+    }
 }
 
 impl<T: QueueMessage> Queue<T> for InMemoryQueue<T> {
@@ -41,6 +290,7 @@ impl<T: QueueMessage> Queue<T> for InMemoryQueue<T> {
         futures::future::ok(Self {
             idx: Arc::new(AtomicU32::default()),
             done: Arc::new(Mutex::new(BTreeMap::default())),
+            failed: Arc::new(Mutex::new(BTreeMap::default())),
             ready: Arc::new(Mutex::new(BTreeMap::default())),
             optimizer_queue: Arc::new(Mutex::new(BTreeMap::default())),
         })
@@ -61,19 +311,13 @@ impl<T: QueueMessage> Queue<T> for InMemoryQueue<T> {
                 FilterResult::Interest(tag) => {
                     optimizer_queue.entry(tag.to_owned()).or_default().insert(
                         self.idx.fetch_add(1, Ordering::SeqCst),
-                        Item {
-                            parents: vec![],
-                            op,
-                        },
+                        Item::new(vec![], op, Priority::default()),
                     );
                 }
-                FilterResult::NoInterest => {
+                FilterResult::NoInterest(priority) => {
                     ready.insert(
                         self.idx.fetch_add(1, Ordering::SeqCst),
-                        Item {
-                            parents: vec![],
-                            op,
-                        },
+                        Item::new(vec![], op, priority),
                     );
                 }
             }
@@ -93,13 +337,22 @@ impl<T: QueueMessage> Queue<T> for InMemoryQueue<T> {
         f: F,
     ) -> Result<Option<R>, Self::Error>
     where
-        F: (FnOnce(Op<T>, ItemId) -> Fut) + Send + Captures<'a>,
-        Fut: Future<Output = (R, Result<Vec<Op<T>>, String>)> + Send + Captures<'a>,
+        F: (FnOnce(Op<T>, ItemId, u32) -> Fut) + Send + Captures<'a>,
+        Fut: Future<Output = (R, ProcessResult<T>)> + Send + Captures<'a>,
         R: Send + Sync + 'static,
     {
         let op = {
             let mut queue = self.ready.lock().expect("mutex is poisoned");
-            let op = queue.pop_first();
+
+            // skip over any items that are waiting to be retried, and then take the oldest item
+            // with the highest priority
+            let now = Instant::now();
+            let op = queue
+                .iter()
+                .filter(|(_, item)| item.retry_at.is_none_or(|retry_at| retry_at <= now))
+                .max_by_key(|(item_id, item)| (item.priority, Reverse(**item_id)))
+                .map(|(item_id, _)| *item_id)
+                .and_then(|item_id| queue.remove_entry(&item_id));
 
             drop(queue);
 
@@ -110,19 +363,20 @@ impl<T: QueueMessage> Queue<T> for InMemoryQueue<T> {
             Some((item_id, item)) => {
                 let span = info_span!("processing item", %item_id);
 
-                self.done
-                    .lock()
-                    .expect("mutex is poisoned")
-                    .insert(item_id, item.clone());
-
                 let (r, res) = f(
                     item.op.clone(),
                     ItemId::new(i64::from(item_id)).expect("infallible"),
+                    item.attempts,
                 )
                 .instrument(span)
                 .await;
                 match res {
-                    Ok(ops) => {
+                    ProcessResult::Done(ops) => {
+                        self.done
+                            .lock()
+                            .expect("mutex is poisoned")
+                            .insert(item_id, item);
+
                         let mut optimizer_queue =
                             self.optimizer_queue.lock().expect("mutex is poisoned");
                         let mut ready = self.ready.lock().expect("mutex is poisoned");
@@ -132,19 +386,13 @@ impl<T: QueueMessage> Queue<T> for InMemoryQueue<T> {
                                 FilterResult::Interest(tag) => {
                                     optimizer_queue.entry(tag.to_owned()).or_default().insert(
                                         self.idx.fetch_add(1, Ordering::SeqCst),
-                                        Item {
-                                            parents: vec![item_id],
-                                            op,
-                                        },
+                                        Item::new(vec![item_id], op, Priority::default()),
                                     );
                                 }
-                                FilterResult::NoInterest => {
+                                FilterResult::NoInterest(priority) => {
                                     ready.insert(
                                         self.idx.fetch_add(1, Ordering::SeqCst),
-                                        Item {
-                                            parents: vec![item_id],
-                                            op,
-                                        },
+                                        Item::new(vec![item_id], op, priority),
                                     );
                                 }
                             }
@@ -152,7 +400,31 @@ impl<T: QueueMessage> Queue<T> for InMemoryQueue<T> {
 
                         Ok(Some(r))
                     }
-                    Err(why) => panic!("{why}"),
+                    ProcessResult::Retry { error, delay } => {
+                        debug!(%error, attempts = item.attempts + 1, "requeueing item");
+
+                        // requeue the item in place, keeping its id
+                        self.ready.lock().expect("mutex is poisoned").insert(
+                            item_id,
+                            Item {
+                                attempts: item.attempts + 1,
+                                retry_at: Some(Instant::now() + delay),
+                                ..item
+                            },
+                        );
+
+                        Ok(Some(r))
+                    }
+                    ProcessResult::Failed(error) => {
+                        error!(%error, "item failed");
+
+                        self.failed
+                            .lock()
+                            .expect("mutex is poisoned")
+                            .insert(item_id, (item, error));
+
+                        Ok(Some(r))
+                    }
                 }
             }
             None => {
@@ -170,6 +442,7 @@ impl<T: QueueMessage> Queue<T> for InMemoryQueue<T> {
         &'a self,
         tag: &'a str,
         optimizer: &'a O,
+        filter: &'a T::Filter,
     ) -> impl Future<Output = Result<(), Either<Self::Error, O::Error>>> + 'a {
         async move {
             let tagged_optimizer_queue = {
@@ -198,22 +471,26 @@ impl<T: QueueMessage> Queue<T> for InMemoryQueue<T> {
             done.append(&mut tagged_optimizer_queue.clone());
 
             for (parents_idxs, op) in res.ready {
+                let priority = filter.check_interest(&op).priority();
+
                 ready.insert(
                     self.idx.fetch_add(1, Ordering::SeqCst),
-                    Item {
-                        parents: parents_idxs.iter().map(|&i| &ids[i]).copied().collect(),
+                    Item::new(
+                        parents_idxs.iter().map(|&i| &ids[i]).copied().collect(),
                         op,
-                    },
+                        priority,
+                    ),
                 );
             }
 
             for (parents_idxs, op, tag) in res.optimize_further {
                 optimizer_queue.entry(tag.clone()).or_default().insert(
                     self.idx.fetch_add(1, Ordering::SeqCst),
-                    Item {
-                        parents: parents_idxs.iter().map(|&i| &ids[i]).copied().collect(),
+                    Item::new(
+                        parents_idxs.iter().map(|&i| &ids[i]).copied().collect(),
                         op,
-                    },
+                        Priority::default(),
+                    ),
                 );
             }
 
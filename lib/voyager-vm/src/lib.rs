@@ -5,7 +5,7 @@
 
 use std::{
     self,
-    collections::VecDeque,
+    collections::{BTreeMap, VecDeque},
     error::Error,
     fmt::Debug,
     future::Future,
@@ -24,7 +24,10 @@ use unionlabs::{
     never::Never,
 };
 
-use crate::{filter::InterestFilter, pass::Pass};
+use crate::{
+    filter::{InterestFilter, Priority},
+    pass::Pass,
+};
 
 pub mod engine;
 pub mod filter;
@@ -54,6 +57,8 @@ pub trait Queue<T: QueueMessage>: Debug + Clone + Send + Sync + Sized + 'static
 
     /// Process the item at the front of the queue, if there is one. New items will be pre-processed by `filter` before being reenqueued.
     ///
+    /// `f` is called with the item, its id, and the amount of times it has previously been attempted.
+    ///
     /// All items will be enqueued to be optimized, unless marked as ready by `filter`.
     fn process<'a, F, Fut, R>(
         &'a self,
@@ -61,14 +66,17 @@ pub trait Queue<T: QueueMessage>: Debug + Clone + Send + Sync + Sized + 'static
         f: F,
     ) -> impl Future<Output = Result<Option<R>, Self::Error>> + Send + Captures<'a>
     where
-        F: (FnOnce(Op<T>, ItemId) -> Fut) + Send + Captures<'a>,
-        Fut: Future<Output = (R, Result<Vec<Op<T>>, String>)> + Send + Captures<'a>,
+        F: (FnOnce(Op<T>, ItemId, u32) -> Fut) + Send + Captures<'a>,
+        Fut: Future<Output = (R, ProcessResult<T>)> + Send + Captures<'a>,
         R: Send + Sync + 'static;
 
+    /// Run an optimization pass over all items in the optimization queue tagged with `tag`. `filter`
+    /// is used to assign priorities to the ops that are ready to be processed.
     fn optimize<'a, O: Pass<T>>(
         &'a self,
         tag: &'a str,
         optimizer: &'a O,
+        filter: &'a T::Filter,
     ) -> impl Future<Output = Result<(), Either<Self::Error, O::Error>>> + Send + 'a;
 }
 
@@ -79,6 +87,19 @@ pub struct EnqueueResult {
     pub optimize: Vec<ItemId>,
 }
 
+/// The result of processing an item in [`Queue::process`].
+#[derive(::frame_support_procedural::DebugNoBound)]
+pub enum ProcessResult<T: QueueMessage> {
+    /// The item was processed successfully. The returned ops will be queued with the item as their
+    /// parent.
+    Done(Vec<Op<T>>),
+    /// The item failed with a retryable error. It will be requeued with its attempt counter
+    /// incremented, and will not be processed again until `delay` has elapsed.
+    Retry { error: String, delay: Duration },
+    /// The item failed, and will not be retried.
+    Failed(String),
+}
+
 /// The ID of an item in the queue.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -100,6 +121,187 @@ impl ItemId {
     }
 }
 
+/// Where an item currently is in the queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ItemStatus {
+    /// The item is ready to be processed.
+    Queue,
+    /// The item is waiting to be optimized.
+    Optimize,
+    /// The item has been processed or optimized.
+    Done,
+    /// The item failed and will not be retried.
+    Failed,
+    /// The item failed, and has since been requeued or archived.
+    Archived,
+}
+
+/// An item in the queue, as returned by lineage queries.
+#[derive(
+    ::macros::Debug,
+    ::frame_support_procedural::CloneNoBound,
+    ::frame_support_procedural::PartialEqNoBound,
+    ::serde::Serialize,
+    ::serde::Deserialize,
+)]
+#[serde(bound(serialize = "", deserialize = ""), deny_unknown_fields)]
+#[debug(bound())]
+pub struct ItemRecord<T: QueueMessage> {
+    pub id: ItemId,
+    /// The items that this item was produced from.
+    pub parents: Vec<ItemId>,
+    pub status: ItemStatus,
+    /// The distance of this item from the item the lineage was queried for.
+    pub depth: u32,
+    pub item: Op<T>,
+}
+
+/// The lineage of an item, as a tree rooted at the item the lineage was queried for.
+#[derive(
+    ::macros::Debug,
+    ::frame_support_procedural::CloneNoBound,
+    ::frame_support_procedural::PartialEqNoBound,
+    ::serde::Serialize,
+    ::serde::Deserialize,
+)]
+#[serde(bound(serialize = "", deserialize = ""), deny_unknown_fields)]
+#[debug(bound())]
+pub struct ItemTree<T: QueueMessage> {
+    pub id: ItemId,
+    pub status: ItemStatus,
+    pub item: Op<T>,
+    /// The items linked to this item. For an ancestor tree these are the items this item was
+    /// produced from, for a descendant tree these are the items that were produced from it.
+    pub links: Vec<ItemTree<T>>,
+}
+
+impl<T: QueueMessage> ItemTree<T> {
+    /// Builds the ancestor tree from the records returned by an ancestor query. Returns `None` if
+    /// the queried item (the record with a depth of 0) is not present.
+    #[must_use]
+    pub fn ancestors(records: Vec<ItemRecord<T>>) -> Option<Self> {
+        let records = records
+            .into_iter()
+            .map(|record| (record.id, record))
+            .collect::<BTreeMap<_, _>>();
+
+        // parents are always created before their children, so following links to lower ids
+        // always terminates
+        Self::build(&records, |record| {
+            record
+                .parents
+                .iter()
+                .copied()
+                .filter(|parent| *parent < record.id)
+                .collect()
+        })
+    }
+
+    /// Builds the descendant tree from the records returned by a descendant query. Returns `None`
+    /// if the queried item (the record with a depth of 0) is not present.
+    #[must_use]
+    pub fn descendants(records: Vec<ItemRecord<T>>) -> Option<Self> {
+        let records = records
+            .into_iter()
+            .map(|record| (record.id, record))
+            .collect::<BTreeMap<_, _>>();
+
+        Self::build(&records, |record| {
+            records
+                .values()
+                .filter(|child| child.id > record.id && child.parents.contains(&record.id))
+                .map(|child| child.id)
+                .collect()
+        })
+    }
+
+    fn build(
+        records: &BTreeMap<ItemId, ItemRecord<T>>,
+        links: impl Fn(&ItemRecord<T>) -> Vec<ItemId>,
+    ) -> Option<Self> {
+        fn go<T: QueueMessage>(
+            records: &BTreeMap<ItemId, ItemRecord<T>>,
+            record: &ItemRecord<T>,
+            links: &impl Fn(&ItemRecord<T>) -> Vec<ItemId>,
+        ) -> ItemTree<T> {
+            ItemTree {
+                id: record.id,
+                status: record.status,
+                item: record.item.clone(),
+                links: links(record)
+                    .into_iter()
+                    // items past the max depth of the query are not present
+                    .filter_map(|id| records.get(&id))
+                    .map(|record| go(records, record, links))
+                    .collect(),
+            }
+        }
+
+        let root = records.values().find(|record| record.depth == 0)?;
+
+        Some(go(records, root, &links))
+    }
+}
+
+/// The pending items of a queue (the items that are ready to be processed and the items waiting to
+/// be optimized) at a point in time. This can be used to replay the state of a queue elsewhere,
+/// see [`in_memory::InMemoryQueue::from_snapshot`].
+#[derive(
+    ::macros::Debug,
+    ::frame_support_procedural::CloneNoBound,
+    ::frame_support_procedural::PartialEqNoBound,
+    ::serde::Serialize,
+    ::serde::Deserialize,
+)]
+#[serde(bound(serialize = "", deserialize = ""), deny_unknown_fields)]
+#[debug(bound())]
+pub struct QueueSnapshot<T: QueueMessage> {
+    /// The items that are ready to be processed, ordered by id.
+    pub queue: Vec<SnapshotItem<T>>,
+    /// The items waiting to be optimized, keyed by the tag of the optimizer they are waiting on and
+    /// ordered by id.
+    pub optimize: BTreeMap<String, Vec<SnapshotItem<T>>>,
+}
+
+/// An item in a [`QueueSnapshot`].
+#[derive(
+    ::macros::Debug,
+    ::frame_support_procedural::CloneNoBound,
+    ::frame_support_procedural::PartialEqNoBound,
+    ::serde::Serialize,
+    ::serde::Deserialize,
+)]
+#[serde(bound(serialize = "", deserialize = ""), deny_unknown_fields)]
+#[debug(bound())]
+pub struct SnapshotItem<T: QueueMessage> {
+    pub id: ItemId,
+    /// The items that this item was produced from.
+    pub parents: Vec<ItemId>,
+    /// Only used for items that are ready to be processed.
+    #[serde(default)]
+    pub priority: Priority,
+    /// The amount of times this item has been attempted and failed with a retryable error. Only
+    /// used for items that are ready to be processed.
+    #[serde(default)]
+    pub attempts: u32,
+    pub item: Op<T>,
+}
+
+/// The amount of items held by a queue at a point in time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueueDepth {
+    /// The amount of items that are ready to be processed, including those waiting to be retried.
+    pub ready: u64,
+    /// The amount of items waiting to be retried after failing with a retryable error.
+    pub retrying: u64,
+    /// The amount of items waiting to be optimized, keyed by the tag of the optimizer they are
+    /// waiting on.
+    pub optimize: BTreeMap<String, u64>,
+    /// The amount of items that have failed.
+    pub failed: u64,
+}
+
 #[derive(
     ::macros::Debug,
     ::frame_support_procedural::CloneNoBound,
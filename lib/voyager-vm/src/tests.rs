@@ -1,9 +1,17 @@
+use std::time::Duration;
+
+use itertools::Itertools;
 use macros::model;
 
 use crate::{
-    call, conc, data, defer, noop, now, promise, seq,
+    call, conc, data, defer,
+    engine::RetryPolicy,
+    filter::{FilterResult, InterestFilter, Priority},
+    in_memory::InMemoryQueue,
+    noop, now, promise, seq,
     tests::utils::{BuildPrintAbc, DataA, DataB, DataC, FetchA, FetchB, PrintAbc, SimpleMessage},
-    CallT, CallbackT, Context, Op, QueueError, QueueMessage, VecDeque,
+    CallT, CallbackT, Context, ItemId, ItemRecord, ItemStatus, ItemTree, Op, ProcessResult, Queue,
+    QueueError, QueueMessage, VecDeque,
 };
 
 pub mod utils;
@@ -36,6 +44,46 @@ impl CallbackT<UnitMessage> for () {
     }
 }
 
+/// A message whose [`Op::Defer`]s are queued with a priority equal to their timestamp.
+enum PriorityMessage {}
+
+impl QueueMessage for PriorityMessage {
+    type Data = ();
+    type Call = ();
+    type Callback = ();
+
+    type Filter = DeferPriorityFilter;
+
+    type Context = ();
+}
+
+impl CallT<PriorityMessage> for () {
+    async fn process(self, _: Context<&()>) -> Result<Op<PriorityMessage>, QueueError> {
+        Ok(noop())
+    }
+}
+
+impl CallbackT<PriorityMessage> for () {
+    async fn process(
+        self,
+        _: Context<&()>,
+        _: VecDeque<()>,
+    ) -> Result<Op<PriorityMessage>, QueueError> {
+        Ok(noop())
+    }
+}
+
+struct DeferPriorityFilter;
+
+impl InterestFilter<PriorityMessage> for DeferPriorityFilter {
+    fn check_interest<'a>(&'a self, op: &Op<PriorityMessage>) -> FilterResult<'a> {
+        match op {
+            Op::Defer { until } => FilterResult::NoInterest((*until).try_into().unwrap()),
+            _ => FilterResult::NoInterest(Priority::default()),
+        }
+    }
+}
+
 #[model]
 pub struct SimpleData {}
 #[model]
@@ -287,3 +335,242 @@ fn seq_conc_conc() {
 
     assert_eq!(op.normalize(), expected_output);
 }
+
+#[test]
+fn retry_policy_backoff() {
+    let policy = RetryPolicy {
+        base_delay_milliseconds: 1000,
+        multiplier: 2.0,
+        max_delay_milliseconds: 5000,
+        max_attempts: 4,
+    };
+
+    assert_eq!(policy.delay(1), Duration::from_secs(1));
+    assert_eq!(policy.delay(2), Duration::from_secs(2));
+    assert_eq!(policy.delay(3), Duration::from_secs(4));
+    assert_eq!(policy.delay(4), Duration::from_secs(5));
+    assert_eq!(policy.delay(u32::MAX), Duration::from_secs(5));
+
+    assert!(!policy.is_exhausted(3));
+    assert!(policy.is_exhausted(4));
+
+    let unbounded = RetryPolicy {
+        max_attempts: 0,
+        ..policy
+    };
+
+    assert!(!unbounded.is_exhausted(u32::MAX));
+}
+
+#[tokio::test]
+async fn in_memory_snapshot_roundtrip() {
+    let queue = InMemoryQueue::<UnitMessage>::new(()).await.unwrap();
+
+    queue.enqueue(seq([defer(1), defer(2)]), &()).await.unwrap();
+    queue.enqueue(defer(3), &()).await.unwrap();
+
+    let snapshot = queue.snapshot();
+    assert_eq!(snapshot.queue.len(), 2);
+
+    let restored = InMemoryQueue::from_snapshot(snapshot.clone()).unwrap();
+    assert_eq!(restored.snapshot(), snapshot);
+
+    // new items are assigned ids after the ones in the snapshot
+    restored.enqueue(defer(4), &()).await.unwrap();
+    assert_eq!(
+        restored.snapshot().queue.last().unwrap().id,
+        ItemId::new(2).unwrap()
+    );
+}
+
+#[tokio::test]
+async fn in_memory_failed_items_are_kept() {
+    let queue = InMemoryQueue::<UnitMessage>::new(()).await.unwrap();
+
+    queue.enqueue(defer(1), &()).await.unwrap();
+
+    // a retryable failure requeues the item with its attempt counter incremented
+    let attempts = queue
+        .process(&(), |_, _, attempts| async move {
+            (
+                attempts,
+                ProcessResult::Retry {
+                    error: "retry".to_owned(),
+                    delay: Duration::ZERO,
+                },
+            )
+        })
+        .await
+        .unwrap();
+    assert_eq!(attempts, Some(0));
+    assert_eq!(queue.depth().retrying, 1);
+
+    // once the retry policy is exhausted, the item is moved to the failed set instead of being
+    // dropped
+    let attempts = queue
+        .process(&(), |_, _, attempts| async move {
+            (attempts, ProcessResult::Failed("failed".to_owned()))
+        })
+        .await
+        .unwrap();
+    assert_eq!(attempts, Some(1));
+
+    assert_eq!(queue.depth().ready, 0);
+    assert_eq!(queue.depth().failed, 1);
+    assert_eq!(
+        queue.failed(),
+        vec![(ItemId::new(0).unwrap(), defer(1), "failed".to_owned())]
+    );
+
+    // failed items are not processed again
+    let res = queue
+        .process(&(), |_, _, _| async { ((), ProcessResult::Done(vec![])) })
+        .await
+        .unwrap();
+    assert_eq!(res, None);
+}
+
+/// Builds the following lineage in an in-memory queue, returning the queue:
+///
+/// ```text
+/// 0 ─┬─ 1 ── 3
+///    └─ 2
+/// ```
+async fn in_memory_lineage_queue() -> InMemoryQueue<UnitMessage> {
+    let queue = InMemoryQueue::<UnitMessage>::new(()).await.unwrap();
+
+    queue.enqueue(defer(0), &()).await.unwrap();
+
+    for children in [vec![defer(1), defer(2)], vec![defer(3)]] {
+        queue
+            .process(
+                &(),
+                |_, _, _| async move { ((), ProcessResult::Done(children)) },
+            )
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    queue
+}
+
+fn ids(records: &[ItemRecord<UnitMessage>]) -> Vec<(i64, u32)> {
+    records
+        .iter()
+        .map(|record| (record.id.raw(), record.depth))
+        .collect()
+}
+
+#[tokio::test]
+async fn in_memory_ancestors() {
+    let queue = in_memory_lineage_queue().await;
+
+    let ancestors = queue.ancestors(ItemId::new(3).unwrap(), 10);
+    assert_eq!(ids(&ancestors), vec![(3, 0), (1, 1), (0, 2)]);
+    assert_eq!(ancestors[0].status, ItemStatus::Queue);
+    assert_eq!(ancestors[0].parents, vec![ItemId::new(1).unwrap()]);
+    assert_eq!(ancestors[1].status, ItemStatus::Done);
+
+    assert_eq!(
+        ids(&queue.ancestors(ItemId::new(3).unwrap(), 1)),
+        vec![(3, 0), (1, 1)]
+    );
+
+    assert_eq!(
+        ids(&queue.ancestors(ItemId::new(2).unwrap(), 10)),
+        vec![(2, 0), (0, 1)]
+    );
+
+    assert!(queue.ancestors(ItemId::new(100).unwrap(), 10).is_empty());
+}
+
+#[tokio::test]
+async fn in_memory_descendants() {
+    let queue = in_memory_lineage_queue().await;
+
+    assert_eq!(
+        ids(&queue.descendants(ItemId::new(0).unwrap(), 10)),
+        vec![(0, 0), (1, 1), (2, 1), (3, 2)]
+    );
+
+    assert_eq!(
+        ids(&queue.descendants(ItemId::new(0).unwrap(), 1)),
+        vec![(0, 0), (1, 1), (2, 1)]
+    );
+
+    assert_eq!(
+        ids(&queue.descendants(ItemId::new(2).unwrap(), 10)),
+        vec![(2, 0)]
+    );
+
+    assert!(queue.descendants(ItemId::new(100).unwrap(), 10).is_empty());
+}
+
+#[tokio::test]
+async fn lineage_tree() {
+    let queue = in_memory_lineage_queue().await;
+
+    fn tree(item_tree: &ItemTree<UnitMessage>) -> String {
+        if item_tree.links.is_empty() {
+            item_tree.id.raw().to_string()
+        } else {
+            format!(
+                "{}({})",
+                item_tree.id.raw(),
+                item_tree.links.iter().map(tree).join(",")
+            )
+        }
+    }
+
+    let ancestors = ItemTree::ancestors(queue.ancestors(ItemId::new(3).unwrap(), 10)).unwrap();
+    assert_eq!(tree(&ancestors), "3(1(0))");
+    assert_eq!(ancestors.status, ItemStatus::Queue);
+    assert_eq!(ancestors.item, defer(3));
+
+    let descendants =
+        ItemTree::descendants(queue.descendants(ItemId::new(0).unwrap(), 10)).unwrap();
+    assert_eq!(tree(&descendants), "0(1(3),2)");
+
+    // items past the max depth are not linked
+    let descendants = ItemTree::descendants(queue.descendants(ItemId::new(0).unwrap(), 1)).unwrap();
+    assert_eq!(tree(&descendants), "0(1,2)");
+
+    assert_eq!(ItemTree::<UnitMessage>::ancestors(vec![]), None);
+}
+
+#[tokio::test]
+async fn in_memory_priority_ordering() {
+    let queue = InMemoryQueue::<PriorityMessage>::new(()).await.unwrap();
+
+    for until in [1, 3, 2, 3, 0] {
+        queue
+            .enqueue(defer(until), &DeferPriorityFilter)
+            .await
+            .unwrap();
+    }
+
+    let mut processed = vec![];
+
+    while let Some((id, op)) = queue
+        .process(&DeferPriorityFilter, |op, id, _| async move {
+            ((id.raw(), op), ProcessResult::Done(vec![]))
+        })
+        .await
+        .unwrap()
+    {
+        processed.push((id, op));
+    }
+
+    // highest priority first, ties are processed in the order they were queued
+    assert_eq!(
+        processed,
+        vec![
+            (1, defer(3)),
+            (3, defer(3)),
+            (2, defer(2)),
+            (0, defer(1)),
+            (4, defer(0)),
+        ]
+    );
+}
@@ -0,0 +1,184 @@
+use alloy::{
+    providers::{Provider, ProviderBuilder, RootProvider},
+    rpc::types::BlockTransactionsKind,
+    transports::BoxTransport,
+};
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    types::ErrorObject,
+    Extensions,
+};
+use scroll_api::ScrollClient;
+use scroll_client::RollupConfig;
+use scroll_light_client_types::{ClientState, ConsensusState};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tracing::{debug, instrument};
+use unionlabs::{ibc::core::client::height::Height, primitives::H160, ErrorReporter};
+use voyager_message::{
+    core::{ChainId, ClientType},
+    into_value,
+    module::{ClientBootstrapModuleInfo, ClientBootstrapModuleServer},
+    ClientBootstrapModule,
+};
+use voyager_vm::BoxDynError;
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    Module::run().await
+}
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub chain_id: ChainId,
+
+    /// The id of the client tracking the L1 on the counterparty chain.
+    pub l1_client_id: u32,
+
+    pub rollup: RollupConfig,
+
+    /// The address of the `IBCHandler` smart contract.
+    pub ibc_handler_address: H160,
+
+    pub l1_provider: RootProvider<BoxTransport>,
+    pub l2_provider: RootProvider<BoxTransport>,
+
+    pub scroll_api_client: ScrollClient,
+    pub scroll_rpc: scroll_rpc::JsonRpcClient,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The id of the client tracking the L1 on the counterparty chain.
+    pub l1_client_id: u32,
+
+    /// The location of the rollup state on the L1.
+    pub rollup: RollupConfig,
+
+    /// The address of the `IBCHandler` smart contract.
+    pub ibc_handler_address: H160,
+
+    /// The RPC endpoint for the L1 execution chain.
+    pub l1_rpc_url: String,
+    /// The websocket RPC endpoint for the L2 execution chain.
+    pub l2_rpc_url: String,
+    /// The URL of the scroll batch api.
+    pub scroll_api_url: String,
+}
+
+impl ClientBootstrapModule for Module {
+    type Config = Config;
+
+    async fn new(
+        config: Self::Config,
+        info: ClientBootstrapModuleInfo,
+    ) -> Result<Self, BoxDynError> {
+        let l1_provider = ProviderBuilder::new()
+            .on_builtin(&config.l1_rpc_url)
+            .await?;
+        let l2_provider = ProviderBuilder::new()
+            .on_builtin(&config.l2_rpc_url)
+            .await?;
+
+        let chain_id = ChainId::new(l2_provider.get_chain_id().await?.to_string());
+
+        info.ensure_chain_id(chain_id.to_string())?;
+        info.ensure_client_type(ClientType::SCROLL)?;
+
+        Ok(Self {
+            chain_id,
+            l1_client_id: config.l1_client_id,
+            rollup: config.rollup,
+            ibc_handler_address: config.ibc_handler_address,
+            l1_provider,
+            l2_provider,
+            scroll_api_client: ScrollClient::new(config.scroll_api_url),
+            scroll_rpc: scroll_rpc::JsonRpcClient::new(config.l2_rpc_url).await?,
+        })
+    }
+}
+
+#[async_trait]
+impl ClientBootstrapModuleServer for Module {
+    #[instrument(skip_all, fields(chain_id = %self.chain_id, %height))]
+    async fn self_client_state(&self, _: &Extensions, height: Height) -> RpcResult<Value> {
+        Ok(into_value(ClientState {
+            chain_id: self
+                .chain_id
+                .as_str()
+                .parse()
+                .expect("self.chain_id is a valid u256"),
+            frozen_height: Height::new(0),
+            ibc_contract_address: self.ibc_handler_address,
+            l1_client_id: self.l1_client_id,
+            l2_committed_batches_slot: self.rollup.committed_batches_slot,
+            l2_contract_address: self.rollup.rollup_contract_address,
+            l2_finalized_state_roots_slot: self.rollup.finalized_state_roots_slot,
+            latest_batch_index_slot: self.rollup.last_finalized_batch_index_slot,
+            latest_slot: height.height(),
+        }))
+    }
+
+    /// The consensus state on this chain at the specified `Height`.
+    ///
+    /// `height` is an L1 height, and the consensus state is the state of the last L2 block of
+    /// the latest batch finalized as of that height.
+    #[instrument(skip_all, fields(chain_id = %self.chain_id, %height))]
+    async fn self_consensus_state(&self, _: &Extensions, height: Height) -> RpcResult<Value> {
+        let batch_index = self
+            .rollup
+            .last_finalized_batch_index(&self.l1_provider, height.height())
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    ErrorReporter(e).with_message("error fetching last finalized batch index"),
+                    None::<()>,
+                )
+            })?;
+
+        let l2_height =
+            scroll_client::l2_height_of_batch(&self.scroll_api_client, batch_index).await;
+
+        debug!(batch_index, l2_height, "latest finalized l2 block");
+
+        let block = self
+            .l2_provider
+            .get_block_by_number(l2_height.into(), BlockTransactionsKind::Hashes)
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    ErrorReporter(e).with_message("error fetching block"),
+                    None::<()>,
+                )
+            })?
+            .ok_or_else(|| {
+                ErrorObject::owned(-1, format!("block {l2_height} not found"), None::<()>)
+            })?;
+
+        let account_proof = self
+            .scroll_rpc
+            .get_proof(
+                self.ibc_handler_address,
+                [],
+                scroll_rpc::BlockId::Number(l2_height),
+            )
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    ErrorReporter(e).with_message("error fetching account proof"),
+                    None::<()>,
+                )
+            })?;
+
+        Ok(into_value(ConsensusState {
+            state_root: block.header.state_root.into(),
+            // Normalize to nanos in order to be compliant with cosmos
+            timestamp: block.header.timestamp * 1_000_000_000,
+            ibc_storage_root: account_proof.storage_hash,
+        }))
+    }
+}
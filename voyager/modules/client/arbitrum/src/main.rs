@@ -0,0 +1,38 @@
+use arbitrum_light_client_types::{ClientState, ConsensusState, Header};
+use ethereum_light_client_types::StorageProof;
+use voyager_message::{
+    core::{ChainId, ClientType, ConsensusType},
+    ClientModule,
+};
+use voyager_rollup_client::{RollupClient, RollupClientModule};
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    RollupClientModule::<Arbitrum>::run().await
+}
+
+#[derive(Debug, Clone)]
+pub enum Arbitrum {}
+
+impl RollupClient for Arbitrum {
+    const CLIENT_TYPE: &'static str = ClientType::ARBITRUM;
+    const CONSENSUS_TYPE: &'static str = ConsensusType::ARBITRUM;
+
+    type ClientState = ClientState;
+    type ConsensusState = ConsensusState;
+    type Header = Header;
+    type Misbehaviour = Header;
+    type Proof = StorageProof;
+
+    fn chain_id(client_state: &ClientState) -> ChainId {
+        ChainId::new(client_state.chain_id.to_string())
+    }
+
+    fn latest_height(client_state: &ClientState) -> u64 {
+        client_state.l1_latest_slot
+    }
+
+    fn timestamp(consensus_state: &ConsensusState) -> u64 {
+        consensus_state.timestamp
+    }
+}
@@ -1,6 +1,6 @@
 use alloy::sol_types::SolValue;
 use ark_serialize::{CanonicalSerialize, SerializationError, Valid};
-use cometbls_light_client_types::{ClientState, ConsensusState, Header};
+use cometbls_light_client_types::{ClientState, ConsensusState, Header, Misbehaviour};
 use jsonrpsee::{
     core::{async_trait, RpcResult},
     types::ErrorObject,
@@ -356,6 +356,38 @@ impl ClientModuleServer for Module {
             .map(Into::into)
     }
 
+    #[instrument]
+    async fn encode_misbehaviour(&self, _: &Extensions, misbehaviour: Value) -> RpcResult<Bytes> {
+        let misbehaviour = serde_json::from_value::<Misbehaviour>(misbehaviour).map_err(|err| {
+            ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!("unable to deserialize misbehaviour: {}", ErrorReporter(err)),
+                None::<()>,
+            )
+        })?;
+
+        match self.ibc_interface {
+            SupportedIbcInterface::IbcCosmwasm => Ok(misbehaviour.encode_as::<Bincode>()),
+            SupportedIbcInterface::IbcGoV8_08Wasm => {
+                Ok(
+                    Any(wasm::client_message::ClientMessage { data: misbehaviour })
+                        .encode_as::<Proto>(),
+                )
+            }
+            SupportedIbcInterface::IbcSolidity | SupportedIbcInterface::IbcMoveAptos => {
+                Err(ErrorObject::owned(
+                    FATAL_JSONRPC_ERROR_CODE,
+                    format!(
+                        "misbehaviour is not supported on {}",
+                        self.ibc_interface.as_str()
+                    ),
+                    None::<()>,
+                ))
+            }
+        }
+        .map(Into::into)
+    }
+
     #[instrument(skip_all)]
     async fn encode_proof(&self, _: &Extensions, proof: Value) -> RpcResult<Bytes> {
         debug!(%proof, "encoding proof");
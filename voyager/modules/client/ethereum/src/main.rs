@@ -1,5 +1,7 @@
 use beacon_api_types::PresetBaseKind;
-use ethereum_light_client_types::{ClientState, ConsensusState, Header, StorageProof};
+use ethereum_light_client_types::{
+    ClientState, ConsensusState, Header, Misbehaviour, StorageProof,
+};
 use jsonrpsee::{
     core::{async_trait, RpcResult},
     types::ErrorObject,
@@ -192,6 +194,20 @@ impl ClientModuleServer for Module {
             .map(Into::into)
     }
 
+    #[instrument]
+    async fn encode_misbehaviour(&self, _: &Extensions, misbehaviour: Value) -> RpcResult<Bytes> {
+        serde_json::from_value::<Misbehaviour>(misbehaviour)
+            .map_err(|err| {
+                ErrorObject::owned(
+                    FATAL_JSONRPC_ERROR_CODE,
+                    format!("unable to deserialize misbehaviour: {}", ErrorReporter(err)),
+                    None::<()>,
+                )
+            })
+            .map(|misbehaviour| misbehaviour.encode_as::<Bincode>())
+            .map(Into::into)
+    }
+
     #[instrument]
     async fn encode_proof(&self, _: &Extensions, proof: Value) -> RpcResult<Bytes> {
         serde_json::from_value::<StorageProof>(proof)
@@ -0,0 +1,38 @@
+use linea_light_client_types::{ClientState, ConsensusState, Header};
+use linea_types::proof::MerkleProof;
+use voyager_message::{
+    core::{ChainId, ClientType, ConsensusType},
+    ClientModule,
+};
+use voyager_rollup_client::{RollupClient, RollupClientModule};
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    RollupClientModule::<Linea>::run().await
+}
+
+#[derive(Debug, Clone)]
+pub enum Linea {}
+
+impl RollupClient for Linea {
+    const CLIENT_TYPE: &'static str = ClientType::LINEA;
+    const CONSENSUS_TYPE: &'static str = ConsensusType::LINEA;
+
+    type ClientState = ClientState;
+    type ConsensusState = ConsensusState;
+    type Header = Header;
+    type Misbehaviour = Header;
+    type Proof = MerkleProof;
+
+    fn chain_id(client_state: &ClientState) -> ChainId {
+        ChainId::new(client_state.chain_id.to_string())
+    }
+
+    fn latest_height(client_state: &ClientState) -> u64 {
+        client_state.latest_height
+    }
+
+    fn timestamp(consensus_state: &ConsensusState) -> u64 {
+        consensus_state.timestamp
+    }
+}
@@ -189,6 +189,20 @@ impl ClientModuleServer for Module {
             .map(Into::into)
     }
 
+    #[instrument]
+    async fn encode_misbehaviour(&self, _: &Extensions, misbehaviour: Value) -> RpcResult<Bytes> {
+        serde_json::from_value::<Header>(misbehaviour)
+            .map_err(|err| {
+                ErrorObject::owned(
+                    FATAL_JSONRPC_ERROR_CODE,
+                    format!("unable to deserialize misbehaviour: {}", ErrorReporter(err)),
+                    None::<()>,
+                )
+            })
+            .map(|misbehaviour| misbehaviour.encode_as::<Bincode>())
+            .map(Into::into)
+    }
+
     #[instrument]
     async fn encode_proof(&self, _: &Extensions, proof: Value) -> RpcResult<Bytes> {
         serde_json::from_value::<StorageProof>(proof)
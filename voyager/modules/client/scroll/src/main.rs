@@ -0,0 +1,38 @@
+use ethereum_light_client_types::StorageProof;
+use scroll_light_client_types::{ClientState, ConsensusState, Header};
+use voyager_message::{
+    core::{ChainId, ClientType, ConsensusType},
+    ClientModule,
+};
+use voyager_rollup_client::{RollupClient, RollupClientModule};
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    RollupClientModule::<Scroll>::run().await
+}
+
+#[derive(Debug, Clone)]
+pub enum Scroll {}
+
+impl RollupClient for Scroll {
+    const CLIENT_TYPE: &'static str = ClientType::SCROLL;
+    const CONSENSUS_TYPE: &'static str = ConsensusType::SCROLL;
+
+    type ClientState = ClientState;
+    type ConsensusState = ConsensusState;
+    type Header = Header;
+    type Misbehaviour = Header;
+    type Proof = StorageProof;
+
+    fn chain_id(client_state: &ClientState) -> ChainId {
+        ChainId::new(client_state.chain_id.to_string())
+    }
+
+    fn latest_height(client_state: &ClientState) -> u64 {
+        client_state.latest_slot
+    }
+
+    fn timestamp(consensus_state: &ConsensusState) -> u64 {
+        consensus_state.timestamp
+    }
+}
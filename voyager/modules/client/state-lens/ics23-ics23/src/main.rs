@@ -187,6 +187,18 @@ impl ClientModuleServer for Module {
             .map(Into::into)
     }
 
+    #[instrument]
+    async fn encode_misbehaviour(&self, _: &Extensions, _misbehaviour: Value) -> RpcResult<Bytes> {
+        Err(ErrorObject::owned(
+            FATAL_JSONRPC_ERROR_CODE,
+            format!(
+                "misbehaviour is not supported on {}",
+                IbcInterface::IBC_SOLIDITY
+            ),
+            None::<()>,
+        ))
+    }
+
     #[instrument]
     async fn encode_proof(&self, _: &Extensions, proof: Value) -> RpcResult<Bytes> {
         let proof = serde_json::from_value::<
@@ -256,6 +256,32 @@ impl ClientModuleServer for Module {
             .map(Into::into)
     }
 
+    #[instrument]
+    async fn encode_misbehaviour(&self, _: &Extensions, misbehaviour: Value) -> RpcResult<Bytes> {
+        let misbehaviour = serde_json::from_value::<Header>(misbehaviour).map_err(|err| {
+            ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!("unable to deserialize misbehaviour: {err}"),
+                None::<()>,
+            )
+        })?;
+
+        match self.ibc_interface {
+            SupportedIbcInterface::IbcCosmwasm => Ok(misbehaviour.encode_as::<Bincode>()),
+            SupportedIbcInterface::IbcSolidity | SupportedIbcInterface::IbcMoveAptos => {
+                Err(ErrorObject::owned(
+                    FATAL_JSONRPC_ERROR_CODE,
+                    format!(
+                        "misbehaviour is not supported on {}",
+                        self.ibc_interface.as_str()
+                    ),
+                    None::<()>,
+                ))
+            }
+        }
+        .map(Into::into)
+    }
+
     #[instrument]
     async fn encode_proof(&self, _: &Extensions, proof: Value) -> RpcResult<Bytes> {
         let proof = serde_json::from_value::<StorageProof>(proof).map_err(|err| {
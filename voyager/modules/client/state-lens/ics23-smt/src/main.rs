@@ -241,6 +241,30 @@ impl ClientModuleServer for Module {
             .map(Into::into)
     }
 
+    #[instrument]
+    async fn encode_misbehaviour(&self, _: &Extensions, misbehaviour: Value) -> RpcResult<Bytes> {
+        let misbehaviour = serde_json::from_value::<Header>(misbehaviour).map_err(|err| {
+            ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!("unable to deserialize misbehaviour: {err}"),
+                None::<()>,
+            )
+        })?;
+
+        match self.ibc_interface {
+            SupportedIbcInterface::IbcCosmwasm => Ok(misbehaviour.encode_as::<Bincode>()),
+            SupportedIbcInterface::IbcSolidity => Err(ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!(
+                    "misbehaviour is not supported on {}",
+                    self.ibc_interface.as_str()
+                ),
+                None::<()>,
+            )),
+        }
+        .map(Into::into)
+    }
+
     #[instrument]
     async fn encode_proof(&self, _: &Extensions, _proof: Value) -> RpcResult<Bytes> {
         // TODO(aeryz): we cannot verify the proofs on evm yet, this will be mock until we have cw
@@ -6,7 +6,7 @@ use jsonrpsee::{
 use macros::model;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use tendermint_light_client_types::{ClientState, ConsensusState, Header};
+use tendermint_light_client_types::{ClientState, ConsensusState, Header, Misbehaviour};
 use tracing::{debug, instrument};
 use unionlabs::{
     self,
@@ -254,6 +254,30 @@ impl ClientModuleServer for Module {
             })
     }
 
+    #[instrument]
+    async fn encode_misbehaviour(&self, _: &Extensions, misbehaviour: Value) -> RpcResult<Bytes> {
+        let misbehaviour = serde_json::from_value::<Misbehaviour>(misbehaviour).map_err(|err| {
+            ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!("unable to deserialize misbehaviour: {}", ErrorReporter(err)),
+                None::<()>,
+            )
+        })?;
+
+        match self.ibc_interface {
+            SupportedIbcInterface::IbcCosmwasm => Ok(misbehaviour.encode_as::<Bincode>()),
+            SupportedIbcInterface::IbcGoV8Native => Err(ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!(
+                    "misbehaviour is not supported on {}",
+                    self.ibc_interface.as_str()
+                ),
+                None::<()>,
+            )),
+        }
+        .map(Into::into)
+    }
+
     #[instrument(skip_all)]
     async fn encode_proof(&self, _: &Extensions, proof: Value) -> RpcResult<Bytes> {
         debug!(%proof, "encoding proof");
@@ -0,0 +1,136 @@
+use std::fmt::Debug;
+
+use alloy::{
+    providers::{Provider, ProviderBuilder, RootProvider},
+    transports::BoxTransport,
+};
+use arbitrum_client::RollupConfig;
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    types::ErrorObject,
+    Extensions,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+use unionlabs::{ibc::core::client::height::Height, ErrorReporter};
+use voyager_message::{
+    core::{ChainId, ConsensusType, Timestamp},
+    module::{ConsensusModuleInfo, ConsensusModuleServer},
+    ConsensusModule, ExtensionsExt, VoyagerClient,
+};
+use voyager_vm::BoxDynError;
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    Module::run().await
+}
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub chain_id: ChainId,
+    pub l1_chain_id: ChainId,
+
+    pub rollup: RollupConfig,
+
+    pub l1_provider: RootProvider<BoxTransport>,
+    pub l2_provider: RootProvider<BoxTransport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The chain id of the L1 that this chain settles on.
+    pub l1_chain_id: ChainId,
+
+    /// The location of the rollup state on the L1.
+    pub rollup: RollupConfig,
+
+    /// The RPC endpoint for the L1 execution chain.
+    pub l1_rpc_url: String,
+    /// The RPC endpoint for the L2 execution chain.
+    pub l2_rpc_url: String,
+}
+
+impl ConsensusModule for Module {
+    type Config = Config;
+
+    async fn new(config: Self::Config, info: ConsensusModuleInfo) -> Result<Self, BoxDynError> {
+        let l1_provider = ProviderBuilder::new()
+            .on_builtin(&config.l1_rpc_url)
+            .await?;
+        let l2_provider = ProviderBuilder::new()
+            .on_builtin(&config.l2_rpc_url)
+            .await?;
+
+        let l1_chain_id = ChainId::new(l1_provider.get_chain_id().await?.to_string());
+
+        if l1_chain_id != config.l1_chain_id {
+            return Err(format!(
+                "incorrect l1 chain id: expected `{}`, but found `{}`",
+                config.l1_chain_id, l1_chain_id
+            )
+            .into());
+        }
+
+        let chain_id = ChainId::new(l2_provider.get_chain_id().await?.to_string());
+
+        info.ensure_chain_id(chain_id.as_str())?;
+        info.ensure_consensus_type(ConsensusType::ARBITRUM)?;
+
+        Ok(Self {
+            chain_id,
+            l1_chain_id,
+            rollup: config.rollup,
+            l1_provider,
+            l2_provider,
+        })
+    }
+}
+
+#[async_trait]
+impl ConsensusModuleServer for Module {
+    /// Query the latest finalized height of this chain.
+    ///
+    /// The arbitrum client is updated with the rollup state as of an L1 height, so the height of
+    /// this chain is the height of the L1.
+    #[instrument(skip_all, fields(chain_id = %self.chain_id, finalized))]
+    async fn query_latest_height(&self, ext: &Extensions, finalized: bool) -> RpcResult<Height> {
+        ext.try_get::<VoyagerClient>()?
+            .query_latest_height(self.l1_chain_id.clone(), finalized)
+            .await
+    }
+
+    /// Query the latest finalized timestamp of this chain.
+    ///
+    /// This is the timestamp of the L2 block asserted by the latest confirmed node, as of the
+    /// latest height of the L1.
+    #[instrument(skip_all, fields(chain_id = %self.chain_id, finalized))]
+    async fn query_latest_timestamp(
+        &self,
+        ext: &Extensions,
+        finalized: bool,
+    ) -> RpcResult<Timestamp> {
+        let l1_height = self.query_latest_height(ext, finalized).await?;
+
+        let (node_num, block) = self
+            .rollup
+            .latest_confirmed_l2_block(&self.l1_provider, &self.l2_provider, l1_height.height())
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    ErrorReporter(e).with_message("error fetching latest confirmed l2 block"),
+                    None::<()>,
+                )
+            })?;
+
+        debug!(
+            %l1_height,
+            node_num,
+            l2_height = block.header.number,
+            "latest confirmed l2 block"
+        );
+
+        Ok(Timestamp::from_secs(block.header.timestamp))
+    }
+}
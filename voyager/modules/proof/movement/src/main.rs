@@ -116,7 +116,7 @@ impl ProofModuleServer<IbcUnion> for Module {
         &self,
         _: &Extensions,
         at: Height,
-        _path: StorePath,
+        path: StorePath,
     ) -> RpcResult<(Value, ProofType)> {
         let ledger_version = self.ledger_version_of_height(at.height()).await;
 
@@ -125,7 +125,7 @@ impl ProofModuleServer<IbcUnion> for Module {
             .await
             .unwrap();
 
-        let _address_str = self
+        let commitments_handle_str = self
             .aptos_client
             .get_account_resource(
                 vault_addr.into(),
@@ -140,28 +140,32 @@ impl ProofModuleServer<IbcUnion> for Module {
             .as_str()
             .unwrap()
             .to_owned();
-        let _address = <H256>::new(U256::from_be_hex(_address_str).unwrap().to_be_bytes());
-
-        // NOTE(aeryz): This only works with Union's custom Movement node. When the following PR is merged,
-        // we will uncomment this: https://github.com/movementlabsxyz/movement/pull/645
-        // let storage_proof = get_storage_proof(
-        //     &self.ctx.movement_rpc_url,
-        //     address,
-        //     hex::encode(bcs::to_bytes(&path.to_string().as_bytes()).expect("won't fail")),
-        //     at.revision_height,
-        // ).await;
-
-        Ok((
-            into_value(StorageProof {
-                state_value: None,
-                proof: SparseMerkleProof {
-                    leaf: None,
-                    siblings: Vec::new(),
-                },
-            }),
-            // TODO: Implement properly, see above
-            ProofType::Membership,
-        ))
+        let commitments_handle = <H256>::new(
+            U256::from_be_hex(commitments_handle_str)
+                .unwrap()
+                .to_be_bytes(),
+        );
+
+        // NOTE: This only works with Union's custom Movement node, until the following PR
+        // is merged: https://github.com/movementlabsxyz/movement/pull/645
+        let storage_proof = get_storage_proof(
+            &self.movement_rpc_url,
+            commitments_handle,
+            // the commitments table is keyed by the commitment key, and `aptos_std::table` bcs
+            // encodes the key
+            hex::encode(bcs::to_bytes(path.key().as_ref()).expect("won't fail")),
+            at.height(),
+        )
+        .await?;
+
+        // the table item only exists if the path has been committed to
+        let proof_type = if storage_proof.state_value.is_some() {
+            ProofType::Membership
+        } else {
+            ProofType::NonMembership
+        };
+
+        Ok((into_value(storage_proof), proof_type))
     }
 }
 
@@ -169,12 +173,20 @@ pub fn rest_error_to_rpc_error(e: RestError) -> ErrorObjectOwned {
     ErrorObject::owned(-1, format!("rest error: {}", ErrorReporter(e)), None::<()>)
 }
 
+pub fn reqwest_error_to_rpc_error(e: reqwest::Error) -> ErrorObjectOwned {
+    ErrorObject::owned(
+        -1,
+        format!("error fetching storage proof: {}", ErrorReporter(e)),
+        None::<()>,
+    )
+}
+
 pub async fn get_storage_proof(
     movement_rpc_url: &str,
     address: H256,
     key: String,
     height: u64,
-) -> StorageProof {
+) -> RpcResult<StorageProof> {
     let client = reqwest::Client::new();
 
     let req =
@@ -183,9 +195,16 @@ pub async fn get_storage_proof(
     let (state_value, proof): (
         Option<aptos_types::state_store::state_value::StateValue>,
         aptos_types::proof::SparseMerkleProof,
-    ) = client.get(req).send().await.unwrap().json().await.unwrap();
-
-    StorageProof {
+    ) = client
+        .get(req)
+        .send()
+        .await
+        .map_err(reqwest_error_to_rpc_error)?
+        .json()
+        .await
+        .map_err(reqwest_error_to_rpc_error)?;
+
+    Ok(StorageProof {
         state_value: state_value.map(|s| {
             let (metadata, data) = s.unpack();
             match metadata.into_persistable() {
@@ -227,5 +246,5 @@ pub async fn get_storage_proof(
                 .map(Into::into)
                 .collect(),
         },
-    }
+    })
 }
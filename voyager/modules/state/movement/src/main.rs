@@ -234,16 +234,18 @@ impl StateModuleServer<IbcUnion> for Module {
                     .unwrap();
                 into_value(convert_channel(channel))
             }
-            // TODO(aeryz): check if we have to do `TryInto<H256>` here
-            StorePath::BatchPackets(path) => into_value(
-                self.get_commitment(
-                    self.ibc_handler_address.into(),
-                    Some(ledger_version),
-                    (path.key().into_bytes().into(),),
-                )
-                .await
-                .map_err(rest_error_to_rpc_error)?,
-            ),
+            StorePath::BatchPackets(path) => {
+                let commitment = self
+                    .get_commitment(
+                        self.ibc_handler_address.into(),
+                        Some(ledger_version),
+                        (path.key().into_bytes().into(),),
+                    )
+                    .await
+                    .map_err(rest_error_to_rpc_error)?;
+
+                into_value(decode_commitment(commitment)?)
+            }
             StorePath::BatchReceipts(path) => {
                 let commitment = self
                     .get_commitment(
@@ -254,16 +256,27 @@ impl StateModuleServer<IbcUnion> for Module {
                     .await
                     .map_err(rest_error_to_rpc_error)?;
 
-                into_value(match &commitment[..] {
-                    [] => false,
-                    [1] => true,
-                    _ => panic!("not a bool??? {commitment:?}"),
-                })
+                into_value(decode_commitment(commitment)?)
             }
         })
     }
 }
 
+/// Commitments are stored as a 32 byte value, and missing commitments are returned as an empty vec.
+fn decode_commitment(commitment: Vec<u8>) -> RpcResult<Option<H256>> {
+    if commitment.is_empty() {
+        Ok(None)
+    } else {
+        H256::try_from(&commitment[..]).map(Some).map_err(|e| {
+            ErrorObject::owned(
+                -1,
+                format!("invalid commitment: {}", ErrorReporter(e)),
+                None::<()>,
+            )
+        })
+    }
+}
+
 pub fn rest_error_to_rpc_error(e: RestError) -> ErrorObjectOwned {
     ErrorObject::owned(-1, format!("rest error: {}", ErrorReporter(e)), None::<()>)
 }
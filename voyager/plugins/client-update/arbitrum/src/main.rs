@@ -0,0 +1,346 @@
+use std::{collections::VecDeque, fmt::Debug};
+
+use alloy::{
+    providers::{Provider, ProviderBuilder, RootProvider},
+    rpc::types::EIP1186AccountProofResponse,
+    transports::BoxTransport,
+};
+use arbitrum_client::RollupConfig;
+use arbitrum_light_client_types::{ClientState, Header, L2Header};
+use ethereum_light_client_types::{AccountProof, StorageProof};
+use ibc_union_spec::IbcUnion;
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    types::ErrorObject,
+    Extensions,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+use unionlabs::{
+    ibc::core::client::height::Height,
+    primitives::{H256, U256},
+    ErrorReporter,
+};
+use voyager_message::{
+    call::Call,
+    core::{ChainId, ClientStateMeta, ClientType},
+    data::Data,
+    hook::UpdateHook,
+    module::{PluginInfo, PluginServer},
+    DefaultCmd, ExtensionsExt, Plugin, PluginMessage, VoyagerClient, VoyagerMessage,
+    FATAL_JSONRPC_ERROR_CODE,
+};
+use voyager_rollup_client::{
+    call::{FetchUpdate, ModuleCall},
+    callback::ModuleCallback,
+    update::{fetch_update, RollupUpdate},
+};
+use voyager_vm::{pass::PassResult, BoxDynError, Op, Visit};
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    Module::run().await
+}
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub chain_id: ChainId,
+
+    pub l1_provider: RootProvider<BoxTransport>,
+    pub l2_provider: RootProvider<BoxTransport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub chain_id: ChainId,
+
+    /// The RPC endpoint for the L1 execution chain.
+    pub l1_rpc_url: String,
+    /// The RPC endpoint for the L2 execution chain.
+    pub l2_rpc_url: String,
+}
+
+impl Plugin for Module {
+    type Call = ModuleCall;
+    type Callback = ModuleCallback;
+
+    type Config = Config;
+    type Cmd = DefaultCmd;
+
+    async fn new(config: Self::Config) -> Result<Self, BoxDynError> {
+        let l1_provider = ProviderBuilder::new()
+            .on_builtin(&config.l1_rpc_url)
+            .await?;
+        let l2_provider = ProviderBuilder::new()
+            .on_builtin(&config.l2_rpc_url)
+            .await?;
+
+        let chain_id = ChainId::new(l2_provider.get_chain_id().await?.to_string());
+
+        if chain_id != config.chain_id {
+            return Err(format!(
+                "incorrect chain id: expected `{}`, but found `{}`",
+                config.chain_id, chain_id
+            )
+            .into());
+        }
+
+        Ok(Self {
+            chain_id,
+            l1_provider,
+            l2_provider,
+        })
+    }
+
+    fn info(config: Self::Config) -> PluginInfo {
+        PluginInfo {
+            name: plugin_name(&config.chain_id),
+            interest_filter: UpdateHook::filter(
+                &config.chain_id,
+                &ClientType::new(ClientType::ARBITRUM),
+            ),
+        }
+    }
+
+    async fn cmd(_config: Self::Config, cmd: Self::Cmd) {
+        match cmd {}
+    }
+}
+
+fn plugin_name(chain_id: &ChainId) -> String {
+    pub const PLUGIN_NAME: &str = env!("CARGO_PKG_NAME");
+
+    format!("{PLUGIN_NAME}/{}", chain_id)
+}
+
+impl Module {
+    fn plugin_name(&self) -> String {
+        plugin_name(&self.chain_id)
+    }
+}
+
+impl RollupUpdate for Module {
+    const NAME: &'static str = "arbitrum";
+
+    type ClientState = ClientState;
+    type Header = Header;
+
+    fn l1_client_id(client_state: &ClientState) -> u32 {
+        client_state.l1_client_id
+    }
+
+    async fn l1_height(
+        &self,
+        _: &VoyagerClient,
+        l1_client_meta: &ClientStateMeta,
+        update_to: Height,
+    ) -> RpcResult<Height> {
+        // the heights of the arbitrum client are L1 heights, and the header can only be verified at
+        // a height the L1 client has a consensus state for, so if the L1 client is already ahead
+        // of update_to, update to its latest height instead
+        Ok(l1_client_meta.counterparty_height.max(update_to))
+    }
+
+    /// The header updates the client to `l1_height`.
+    async fn fetch_header(
+        &self,
+        client_state: &ClientState,
+        l1_height: Height,
+        _: Height,
+    ) -> RpcResult<(Height, Header)> {
+        let rollup = RollupConfig {
+            l1_contract_address: client_state.l1_contract_address,
+            l1_next_node_num_slot: client_state.l1_next_node_num_slot,
+            l1_next_node_num_slot_offset_bytes: client_state.l1_next_node_num_slot_offset_bytes,
+            l1_nodes_slot: client_state.l1_nodes_slot,
+            l1_nodes_confirm_data_offset: client_state.l1_nodes_confirm_data_offset,
+        };
+
+        let (node_num, block) = rollup
+            .latest_confirmed_l2_block(&self.l1_provider, &self.l2_provider, l1_height.height())
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    ErrorReporter(e).with_message("error fetching latest confirmed l2 block"),
+                    None::<()>,
+                )
+            })?;
+
+        let l2_height = Height::new(block.header.number);
+
+        debug!(%l1_height, node_num, %l2_height, "latest confirmed l2 block");
+
+        let l1_proof = self
+            .l1_provider
+            .get_proof(
+                rollup.l1_contract_address.into(),
+                vec![
+                    rollup.l1_next_node_num_slot.to_be_bytes().into(),
+                    rollup
+                        .nodes_confirm_data_slot(node_num)
+                        .to_be_bytes()
+                        .into(),
+                ],
+            )
+            .block_id(l1_height.height().into())
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    ErrorReporter(e).with_message("error fetching rollup contract proof"),
+                    None::<()>,
+                )
+            })?;
+
+        let l2_ibc_account_proof = self
+            .l2_provider
+            .get_proof(client_state.l2_ibc_contract_address.into(), vec![])
+            .block_id(l2_height.height().into())
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    ErrorReporter(e).with_message("error fetching ibc account proof"),
+                    None::<()>,
+                )
+            })?;
+
+        let l1_account_proof = account_proof(&l1_proof);
+
+        let [l1_next_node_num_slot_proof, l1_nodes_slot_proof] =
+            <[_; 2]>::try_from(l1_proof.storage_proof).map_err(|invalid| {
+                ErrorObject::owned(
+                    -1,
+                    format!(
+                        "received invalid response from eth_getProof, expected 2 storage \
+                        proofs but got {}",
+                        invalid.len()
+                    ),
+                    None::<()>,
+                )
+            })?;
+
+        let header = &block.header;
+
+        let l2_header = L2Header {
+            parent_hash: header.parent_hash.into(),
+            sha3_uncles: header.ommers_hash.into(),
+            miner: header.beneficiary.into(),
+            state_root: header.state_root.into(),
+            transactions_root: header.transactions_root.into(),
+            receipts_root: header.receipts_root.into(),
+            logs_bloom: Box::new(header.logs_bloom.0.into()),
+            difficulty: U256::from_be_bytes(header.difficulty.to_be_bytes()),
+            number: header.number.into(),
+            gas_limit: header.gas_limit,
+            gas_used: header.gas_used,
+            timestamp: header.timestamp,
+            // the extra data of an arbitrum block is the send root
+            extra_data: <[u8; 32]>::try_from(&*header.extra_data)
+                .map(H256::new)
+                .map_err(|_| {
+                    ErrorObject::owned(
+                        FATAL_JSONRPC_ERROR_CODE,
+                        format!(
+                            "invalid extra data in block {l2_height}, expected 32 bytes: {}",
+                            header.extra_data
+                        ),
+                        None::<()>,
+                    )
+                })?,
+            mix_hash: header.mix_hash.into(),
+            nonce: header.nonce.into(),
+            base_fee_per_gas: header.base_fee_per_gas.unwrap_or_default().into(),
+        };
+
+        let header = Header {
+            l1_height,
+            l1_account_proof,
+            l2_ibc_account_proof: account_proof(&l2_ibc_account_proof),
+            l1_next_node_num_slot_proof: storage_proof(l1_next_node_num_slot_proof),
+            l1_nodes_slot_proof: storage_proof(l1_nodes_slot_proof),
+            l2_header,
+        };
+
+        Ok((l1_height, header))
+    }
+}
+
+fn account_proof(proof: &EIP1186AccountProofResponse) -> AccountProof {
+    AccountProof {
+        storage_root: proof.storage_hash.into(),
+        proof: proof.account_proof.iter().map(|x| x.to_vec()).collect(),
+    }
+}
+
+fn storage_proof(proof: alloy::rpc::types::EIP1186StorageProof) -> StorageProof {
+    StorageProof {
+        key: U256::from_be_bytes(proof.key.as_b256().0),
+        value: U256::from_be_bytes(proof.value.to_be_bytes()),
+        proof: proof.proof.into_iter().map(|bytes| bytes.into()).collect(),
+    }
+}
+
+#[async_trait]
+impl PluginServer<ModuleCall, ModuleCallback> for Module {
+    #[instrument(skip_all, fields(chain_id = %self.chain_id))]
+    async fn run_pass(
+        &self,
+        _: &Extensions,
+        msgs: Vec<Op<VoyagerMessage>>,
+    ) -> RpcResult<PassResult<VoyagerMessage>> {
+        Ok(PassResult {
+            optimize_further: vec![],
+            ready: msgs
+                .into_iter()
+                .map(|mut op| {
+                    UpdateHook::new(
+                        &self.chain_id,
+                        &ClientType::new(ClientType::ARBITRUM),
+                        |fetch| {
+                            Call::Plugin(PluginMessage::new(
+                                self.plugin_name(),
+                                ModuleCall::from(FetchUpdate {
+                                    counterparty_chain_id: fetch.counterparty_chain_id.clone(),
+                                    client_id: fetch
+                                        .client_id
+                                        .clone()
+                                        .decode_spec::<IbcUnion>()
+                                        .unwrap(),
+                                    update_from: fetch.update_from,
+                                    update_to: fetch.update_to,
+                                }),
+                            ))
+                        },
+                    )
+                    .visit_op(&mut op);
+
+                    op
+                })
+                .enumerate()
+                .map(|(i, op)| (vec![i], op))
+                .collect(),
+        })
+    }
+
+    #[instrument(skip_all, fields(chain_id = %self.chain_id))]
+    async fn call(&self, ext: &Extensions, msg: ModuleCall) -> RpcResult<Op<VoyagerMessage>> {
+        match msg {
+            ModuleCall::FetchUpdate(fetch) => {
+                fetch_update(self, ext.try_get::<VoyagerClient>()?, fetch).await
+            }
+        }
+    }
+
+    #[instrument(skip_all, fields(chain_id = %self.chain_id))]
+    async fn callback(
+        &self,
+        _: &Extensions,
+        callback: ModuleCallback,
+        _data: VecDeque<Data>,
+    ) -> RpcResult<Op<VoyagerMessage>> {
+        match callback {}
+    }
+}
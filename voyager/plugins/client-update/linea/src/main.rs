@@ -0,0 +1,360 @@
+use std::{collections::VecDeque, fmt::Debug};
+
+use alloy::{
+    providers::{Provider, ProviderBuilder, RootProvider},
+    transports::BoxTransport,
+};
+use ethereum_light_client_types::{AccountProof, StorageProof};
+use ibc_union_spec::IbcUnion;
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    types::ErrorObject,
+    Extensions,
+};
+use linea_light_client_types::{ClientState, Header};
+use linea_types::proof::{GetProof, MerkleProof};
+use linea_verifier::state_root_hashes_mapping_key;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+use unionlabs::{
+    ibc::core::client::height::Height,
+    primitives::{H256, U256},
+    ErrorReporter,
+};
+use voyager_message::{
+    call::Call,
+    core::{ChainId, ClientStateMeta, ClientType},
+    data::Data,
+    hook::UpdateHook,
+    module::{PluginInfo, PluginServer},
+    DefaultCmd, ExtensionsExt, Plugin, PluginMessage, VoyagerClient, VoyagerMessage,
+    FATAL_JSONRPC_ERROR_CODE,
+};
+use voyager_rollup_client::{
+    call::{FetchUpdate, ModuleCall},
+    callback::ModuleCallback,
+    update::{fetch_update, RollupUpdate},
+};
+use voyager_vm::{pass::PassResult, BoxDynError, Op, Visit};
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    Module::run().await
+}
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub chain_id: ChainId,
+
+    pub l1_provider: RootProvider<BoxTransport>,
+    pub l2_provider: RootProvider<BoxTransport>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub chain_id: ChainId,
+
+    /// The RPC endpoint for the L1 execution chain.
+    pub l1_rpc_url: String,
+    /// The RPC endpoint for the L2 execution chain. This must support `linea_getProof`.
+    pub l2_rpc_url: String,
+}
+
+impl Plugin for Module {
+    type Call = ModuleCall;
+    type Callback = ModuleCallback;
+
+    type Config = Config;
+    type Cmd = DefaultCmd;
+
+    async fn new(config: Self::Config) -> Result<Self, BoxDynError> {
+        let l1_provider = ProviderBuilder::new()
+            .on_builtin(&config.l1_rpc_url)
+            .await?;
+        let l2_provider = ProviderBuilder::new()
+            .on_builtin(&config.l2_rpc_url)
+            .await?;
+
+        let chain_id = ChainId::new(l2_provider.get_chain_id().await?.to_string());
+
+        if chain_id != config.chain_id {
+            return Err(format!(
+                "incorrect chain id: expected `{}`, but found `{}`",
+                config.chain_id, chain_id
+            )
+            .into());
+        }
+
+        Ok(Self {
+            chain_id,
+            l1_provider,
+            l2_provider,
+        })
+    }
+
+    fn info(config: Self::Config) -> PluginInfo {
+        PluginInfo {
+            name: plugin_name(&config.chain_id),
+            interest_filter: UpdateHook::filter(
+                &config.chain_id,
+                &ClientType::new(ClientType::LINEA),
+            ),
+        }
+    }
+
+    async fn cmd(_config: Self::Config, cmd: Self::Cmd) {
+        match cmd {}
+    }
+}
+
+fn plugin_name(chain_id: &ChainId) -> String {
+    pub const PLUGIN_NAME: &str = env!("CARGO_PKG_NAME");
+
+    format!("{PLUGIN_NAME}/{}", chain_id)
+}
+
+impl Module {
+    fn plugin_name(&self) -> String {
+        plugin_name(&self.chain_id)
+    }
+}
+
+impl RollupUpdate for Module {
+    const NAME: &'static str = "linea";
+
+    type ClientState = ClientState;
+    type Header = Header;
+
+    fn l1_client_id(client_state: &ClientState) -> u32 {
+        client_state.l1_client_id
+    }
+
+    async fn l1_height(
+        &self,
+        voyager_client: &VoyagerClient,
+        l1_client_meta: &ClientStateMeta,
+        _: Height,
+    ) -> RpcResult<Height> {
+        let l1_latest_height = voyager_client
+            .query_latest_height(l1_client_meta.chain_id.clone(), true)
+            .await?;
+
+        debug!(%l1_latest_height);
+
+        // the L1 client only has consensus states at the heights it was updated to, so if it is
+        // already ahead of the latest finalized L1 height, use its latest height instead of
+        // updating it
+        Ok(l1_client_meta.counterparty_height.max(l1_latest_height))
+    }
+
+    async fn fetch_header(
+        &self,
+        client_state: &ClientState,
+        l1_height: Height,
+        update_to: Height,
+    ) -> RpcResult<(Height, Header)> {
+        let l2_block_number = self
+            .l1_provider
+            .get_storage_at(
+                client_state.l1_rollup_contract_address.into(),
+                alloy::primitives::U256::from_be_bytes(
+                    client_state
+                        .l1_rollup_current_l2_block_number_slot
+                        .to_be_bytes(),
+                ),
+            )
+            .block_id(l1_height.height().into())
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    ErrorReporter(e).with_message("error fetching current l2 block number"),
+                    None::<()>,
+                )
+            })?;
+
+        let l2_height = Height::new(u64::try_from(l2_block_number).map_err(|_| {
+            ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!("l2 block number {l2_block_number} does not fit in a u64"),
+                None::<()>,
+            )
+        })?);
+
+        debug!(%l1_height, %l2_height, "latest finalized l2 block");
+
+        if l2_height < update_to {
+            return Err(ErrorObject::owned(
+                -1,
+                format!(
+                    "latest finalized l2 block as of l1 height {l1_height} is {l2_height}, but \
+                    update_to request is {update_to}"
+                ),
+                None::<()>,
+            ));
+        }
+
+        let l1_proof = self
+            .l1_provider
+            .get_proof(
+                client_state.l1_rollup_contract_address.into(),
+                vec![
+                    client_state
+                        .l1_rollup_current_l2_block_number_slot
+                        .to_be_bytes()
+                        .into(),
+                    client_state
+                        .l1_rollup_current_l2_timestamp_slot
+                        .to_be_bytes()
+                        .into(),
+                    state_root_hashes_mapping_key(
+                        &client_state.l1_rollup_l2_state_root_hashes_slot,
+                        &l2_height.height().into(),
+                    )
+                    .to_be_bytes()
+                    .into(),
+                ],
+            )
+            .block_id(l1_height.height().into())
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    ErrorReporter(e).with_message("error fetching rollup contract proof"),
+                    None::<()>,
+                )
+            })?;
+
+        let l2_ibc_contract_proof = self
+            .l2_provider
+            .raw_request::<_, GetProof>(
+                "linea_getProof".into(),
+                (
+                    client_state.l2_ibc_contract_address,
+                    Vec::<H256>::new(),
+                    format!("{:#x}", l2_height.height()),
+                ),
+            )
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    ErrorReporter(e).with_message("error fetching ibc contract proof"),
+                    None::<()>,
+                )
+            })?
+            .account_proof;
+
+        let MerkleProof::Inclusion(l2_ibc_contract_proof) = l2_ibc_contract_proof else {
+            return Err(ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                format!(
+                    "the ibc contract {} does not exist at l2 height {l2_height}",
+                    client_state.l2_ibc_contract_address
+                ),
+                None::<()>,
+            ));
+        };
+
+        let l1_rollup_contract_proof = AccountProof {
+            storage_root: l1_proof.storage_hash.into(),
+            proof: l1_proof.account_proof.iter().map(|x| x.to_vec()).collect(),
+        };
+
+        let [l2_block_number_proof, l2_timestamp_proof, l2_state_root_proof] =
+            <[_; 3]>::try_from(l1_proof.storage_proof).map_err(|invalid| {
+                ErrorObject::owned(
+                    -1,
+                    format!(
+                        "received invalid response from eth_getProof, expected 3 storage \
+                        proofs but got {}",
+                        invalid.len()
+                    ),
+                    None::<()>,
+                )
+            })?;
+
+        Ok((
+            l2_height,
+            Header {
+                l1_height,
+                l1_rollup_contract_proof,
+                l2_timestamp_proof: storage_proof(l2_timestamp_proof),
+                l2_block_number_proof: storage_proof(l2_block_number_proof),
+                l2_state_root_proof: storage_proof(l2_state_root_proof),
+                l2_ibc_contract_proof,
+            },
+        ))
+    }
+}
+
+fn storage_proof(proof: alloy::rpc::types::EIP1186StorageProof) -> StorageProof {
+    StorageProof {
+        key: U256::from_be_bytes(proof.key.as_b256().0),
+        value: U256::from_be_bytes(proof.value.to_be_bytes()),
+        proof: proof.proof.into_iter().map(|bytes| bytes.into()).collect(),
+    }
+}
+
+#[async_trait]
+impl PluginServer<ModuleCall, ModuleCallback> for Module {
+    #[instrument(skip_all, fields(chain_id = %self.chain_id))]
+    async fn run_pass(
+        &self,
+        _: &Extensions,
+        msgs: Vec<Op<VoyagerMessage>>,
+    ) -> RpcResult<PassResult<VoyagerMessage>> {
+        Ok(PassResult {
+            optimize_further: vec![],
+            ready: msgs
+                .into_iter()
+                .map(|mut op| {
+                    UpdateHook::new(
+                        &self.chain_id,
+                        &ClientType::new(ClientType::LINEA),
+                        |fetch| {
+                            Call::Plugin(PluginMessage::new(
+                                self.plugin_name(),
+                                ModuleCall::from(FetchUpdate {
+                                    counterparty_chain_id: fetch.counterparty_chain_id.clone(),
+                                    client_id: fetch
+                                        .client_id
+                                        .clone()
+                                        .decode_spec::<IbcUnion>()
+                                        .unwrap(),
+                                    update_from: fetch.update_from,
+                                    update_to: fetch.update_to,
+                                }),
+                            ))
+                        },
+                    )
+                    .visit_op(&mut op);
+
+                    op
+                })
+                .enumerate()
+                .map(|(i, op)| (vec![i], op))
+                .collect(),
+        })
+    }
+
+    #[instrument(skip_all, fields(chain_id = %self.chain_id))]
+    async fn call(&self, ext: &Extensions, msg: ModuleCall) -> RpcResult<Op<VoyagerMessage>> {
+        match msg {
+            ModuleCall::FetchUpdate(fetch) => {
+                fetch_update(self, ext.try_get::<VoyagerClient>()?, fetch).await
+            }
+        }
+    }
+
+    #[instrument(skip_all, fields(chain_id = %self.chain_id))]
+    async fn callback(
+        &self,
+        _: &Extensions,
+        callback: ModuleCallback,
+        _data: VecDeque<Data>,
+    ) -> RpcResult<Op<VoyagerMessage>> {
+        match callback {}
+    }
+}
@@ -0,0 +1,336 @@
+use std::{collections::VecDeque, fmt::Debug};
+
+use alloy::{
+    providers::{Provider, ProviderBuilder, RootProvider},
+    rpc::types::EIP1186AccountProofResponse,
+    transports::BoxTransport,
+};
+use ethereum_light_client_types::{AccountProof, StorageProof};
+use ibc_union_spec::IbcUnion;
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    types::ErrorObject,
+    Extensions,
+};
+use scroll_api::ScrollClient;
+use scroll_client::RollupConfig;
+use scroll_light_client_types::{ClientState, Header};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+use unionlabs::{ibc::core::client::height::Height, primitives::U256, ErrorReporter};
+use voyager_message::{
+    call::Call,
+    core::{ChainId, ClientStateMeta, ClientType},
+    data::Data,
+    hook::UpdateHook,
+    module::{PluginInfo, PluginServer},
+    DefaultCmd, ExtensionsExt, Plugin, PluginMessage, VoyagerClient, VoyagerMessage,
+};
+use voyager_rollup_client::{
+    call::{FetchUpdate, ModuleCall},
+    callback::ModuleCallback,
+    update::{fetch_update, RollupUpdate},
+};
+use voyager_vm::{pass::PassResult, BoxDynError, Op, Visit};
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    Module::run().await
+}
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub chain_id: ChainId,
+
+    pub l1_provider: RootProvider<BoxTransport>,
+
+    pub scroll_api_client: ScrollClient,
+    pub scroll_rpc: scroll_rpc::JsonRpcClient,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub chain_id: ChainId,
+
+    /// The RPC endpoint for the L1 execution chain.
+    pub l1_rpc_url: String,
+    /// The websocket RPC endpoint for the L2 execution chain.
+    pub l2_rpc_url: String,
+    /// The URL of the scroll batch api.
+    pub scroll_api_url: String,
+}
+
+impl Plugin for Module {
+    type Call = ModuleCall;
+    type Callback = ModuleCallback;
+
+    type Config = Config;
+    type Cmd = DefaultCmd;
+
+    async fn new(config: Self::Config) -> Result<Self, BoxDynError> {
+        let l1_provider = ProviderBuilder::new()
+            .on_builtin(&config.l1_rpc_url)
+            .await?;
+        let l2_provider = ProviderBuilder::new()
+            .on_builtin(&config.l2_rpc_url)
+            .await?;
+
+        let chain_id = ChainId::new(l2_provider.get_chain_id().await?.to_string());
+
+        if chain_id != config.chain_id {
+            return Err(format!(
+                "incorrect chain id: expected `{}`, but found `{}`",
+                config.chain_id, chain_id
+            )
+            .into());
+        }
+
+        Ok(Self {
+            chain_id,
+            l1_provider,
+            scroll_api_client: ScrollClient::new(config.scroll_api_url),
+            scroll_rpc: scroll_rpc::JsonRpcClient::new(config.l2_rpc_url).await?,
+        })
+    }
+
+    fn info(config: Self::Config) -> PluginInfo {
+        PluginInfo {
+            name: plugin_name(&config.chain_id),
+            interest_filter: UpdateHook::filter(
+                &config.chain_id,
+                &ClientType::new(ClientType::SCROLL),
+            ),
+        }
+    }
+
+    async fn cmd(_config: Self::Config, cmd: Self::Cmd) {
+        match cmd {}
+    }
+}
+
+fn plugin_name(chain_id: &ChainId) -> String {
+    pub const PLUGIN_NAME: &str = env!("CARGO_PKG_NAME");
+
+    format!("{PLUGIN_NAME}/{}", chain_id)
+}
+
+impl Module {
+    fn plugin_name(&self) -> String {
+        plugin_name(&self.chain_id)
+    }
+}
+
+impl RollupUpdate for Module {
+    const NAME: &'static str = "scroll";
+
+    type ClientState = ClientState;
+    type Header = Header;
+
+    fn l1_client_id(client_state: &ClientState) -> u32 {
+        client_state.l1_client_id
+    }
+
+    async fn l1_height(
+        &self,
+        _: &VoyagerClient,
+        l1_client_meta: &ClientStateMeta,
+        update_to: Height,
+    ) -> RpcResult<Height> {
+        // the heights of the scroll client are L1 heights, and the header can only be verified at
+        // a height the L1 client has a consensus state for, so if the L1 client is already ahead
+        // of update_to, update to its latest height instead
+        Ok(l1_client_meta.counterparty_height.max(update_to))
+    }
+
+    /// The header updates the client to `l1_height`.
+    async fn fetch_header(
+        &self,
+        client_state: &ClientState,
+        l1_height: Height,
+        _: Height,
+    ) -> RpcResult<(Height, Header)> {
+        let rollup = RollupConfig {
+            rollup_contract_address: client_state.l2_contract_address,
+            last_finalized_batch_index_slot: client_state.latest_batch_index_slot,
+            committed_batches_slot: client_state.l2_committed_batches_slot,
+            finalized_state_roots_slot: client_state.l2_finalized_state_roots_slot,
+        };
+
+        let batch_index = rollup
+            .last_finalized_batch_index(&self.l1_provider, l1_height.height())
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    ErrorReporter(e).with_message("error fetching last finalized batch index"),
+                    None::<()>,
+                )
+            })?;
+
+        let batch_header = rollup
+            .batch_header(&self.l1_provider, &self.scroll_api_client, batch_index)
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    ErrorReporter(e).with_message("error fetching batch header"),
+                    None::<()>,
+                )
+            })?;
+
+        let l2_height =
+            scroll_client::l2_height_of_batch(&self.scroll_api_client, batch_index).await;
+
+        debug!(%l1_height, batch_index, l2_height, "latest finalized l2 block");
+
+        let l1_proof = self
+            .l1_provider
+            .get_proof(
+                rollup.rollup_contract_address.into(),
+                vec![
+                    rollup.last_finalized_batch_index_slot.to_be_bytes().into(),
+                    rollup
+                        .finalized_state_root_slot(batch_index)
+                        .to_be_bytes()
+                        .into(),
+                    rollup
+                        .committed_batch_slot(batch_index)
+                        .to_be_bytes()
+                        .into(),
+                ],
+            )
+            .block_id(l1_height.height().into())
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    ErrorReporter(e).with_message("error fetching rollup contract proof"),
+                    None::<()>,
+                )
+            })?;
+
+        let l2_ibc_account_proof = self
+            .scroll_rpc
+            .get_proof(
+                client_state.ibc_contract_address,
+                [],
+                scroll_rpc::BlockId::Number(l2_height),
+            )
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    ErrorReporter(e).with_message("error fetching ibc account proof"),
+                    None::<()>,
+                )
+            })?;
+
+        let l1_account_proof = account_proof(&l1_proof);
+
+        let [last_batch_index_proof, l2_state_root_proof, batch_hash_proof] =
+            <[_; 3]>::try_from(l1_proof.storage_proof).map_err(|invalid| {
+                ErrorObject::owned(
+                    -1,
+                    format!(
+                        "received invalid response from eth_getProof, expected 3 storage \
+                        proofs but got {}",
+                        invalid.len()
+                    ),
+                    None::<()>,
+                )
+            })?;
+
+        let header = Header {
+            l1_height,
+            l1_account_proof,
+            l2_state_root_proof: storage_proof(l2_state_root_proof),
+            last_batch_index_proof: storage_proof(last_batch_index_proof),
+            batch_hash_proof: storage_proof(batch_hash_proof),
+            l2_ibc_account_proof: AccountProof {
+                storage_root: l2_ibc_account_proof.storage_hash,
+                proof: l2_ibc_account_proof.account_proof,
+            },
+            batch_header,
+        };
+
+        Ok((l1_height, header))
+    }
+}
+
+fn account_proof(proof: &EIP1186AccountProofResponse) -> AccountProof {
+    AccountProof {
+        storage_root: proof.storage_hash.into(),
+        proof: proof.account_proof.iter().map(|x| x.to_vec()).collect(),
+    }
+}
+
+fn storage_proof(proof: alloy::rpc::types::EIP1186StorageProof) -> StorageProof {
+    StorageProof {
+        key: U256::from_be_bytes(proof.key.as_b256().0),
+        value: U256::from_be_bytes(proof.value.to_be_bytes()),
+        proof: proof.proof.into_iter().map(|bytes| bytes.into()).collect(),
+    }
+}
+
+#[async_trait]
+impl PluginServer<ModuleCall, ModuleCallback> for Module {
+    #[instrument(skip_all, fields(chain_id = %self.chain_id))]
+    async fn run_pass(
+        &self,
+        _: &Extensions,
+        msgs: Vec<Op<VoyagerMessage>>,
+    ) -> RpcResult<PassResult<VoyagerMessage>> {
+        Ok(PassResult {
+            optimize_further: vec![],
+            ready: msgs
+                .into_iter()
+                .map(|mut op| {
+                    UpdateHook::new(
+                        &self.chain_id,
+                        &ClientType::new(ClientType::SCROLL),
+                        |fetch| {
+                            Call::Plugin(PluginMessage::new(
+                                self.plugin_name(),
+                                ModuleCall::from(FetchUpdate {
+                                    counterparty_chain_id: fetch.counterparty_chain_id.clone(),
+                                    client_id: fetch
+                                        .client_id
+                                        .clone()
+                                        .decode_spec::<IbcUnion>()
+                                        .unwrap(),
+                                    update_from: fetch.update_from,
+                                    update_to: fetch.update_to,
+                                }),
+                            ))
+                        },
+                    )
+                    .visit_op(&mut op);
+
+                    op
+                })
+                .enumerate()
+                .map(|(i, op)| (vec![i], op))
+                .collect(),
+        })
+    }
+
+    #[instrument(skip_all, fields(chain_id = %self.chain_id))]
+    async fn call(&self, ext: &Extensions, msg: ModuleCall) -> RpcResult<Op<VoyagerMessage>> {
+        match msg {
+            ModuleCall::FetchUpdate(fetch) => {
+                fetch_update(self, ext.try_get::<VoyagerClient>()?, fetch).await
+            }
+        }
+    }
+
+    #[instrument(skip_all, fields(chain_id = %self.chain_id))]
+    async fn callback(
+        &self,
+        _: &Extensions,
+        callback: ModuleCallback,
+        _data: VecDeque<Data>,
+    ) -> RpcResult<Op<VoyagerMessage>> {
+        match callback {}
+    }
+}
@@ -300,6 +300,26 @@ pub enum IbcEvent {
         connection_id: u32,
     },
 
+    #[serde(rename = "wasm-channel_close_init")]
+    WasmChannelCloseInit {
+        port_id: Bech32<H256>,
+        #[serde(with = "serde_utils::string")]
+        channel_id: u32,
+        counterparty_port_id: Bytes<HexUnprefixed>,
+        #[serde(with = "serde_utils::string")]
+        counterparty_channel_id: u32,
+    },
+
+    #[serde(rename = "wasm-channel_close_confirm")]
+    WasmChannelCloseConfirm {
+        port_id: Bech32<H256>,
+        #[serde(with = "serde_utils::string")]
+        channel_id: u32,
+        counterparty_port_id: Bytes<HexUnprefixed>,
+        #[serde(with = "serde_utils::string")]
+        counterparty_channel_id: u32,
+    },
+
     #[serde(rename = "wasm-packet_send")]
     WasmPacketSend {
         #[serde(with = "stringified_json")]
@@ -425,6 +445,8 @@ impl IbcEvent {
             IbcEvent::WasmChannelOpenTry { .. } => "channel_open_try",
             IbcEvent::WasmChannelOpenAck { .. } => "channel_open_ack",
             IbcEvent::WasmChannelOpenConfirm { .. } => "channel_open_confirm",
+            IbcEvent::WasmChannelCloseInit { .. } => "channel_close_init",
+            IbcEvent::WasmChannelCloseConfirm { .. } => "channel_close_confirm",
             IbcEvent::WasmPacketRecv { .. } => "recv_packet",
             IbcEvent::WasmPacketSend { .. } => "send_packet",
             IbcEvent::WasmPacketAck { .. } => "acknowledge_packet",
@@ -553,6 +553,65 @@ impl Module {
         ))
     }
 
+    /// Build the chain event for a channel close event. The channel close events only differ in
+    /// their type, `mk_event` builds the event from the channel's connection and version.
+    async fn make_channel_close_event(
+        &self,
+        voyager_client: &VoyagerClient,
+        height: Height,
+        tx_hash: H256,
+        channel_id: u32,
+        mk_event: impl FnOnce(
+            ibc_union_spec::types::Connection,
+            String,
+        ) -> ibc_union_spec::event::FullEvent,
+    ) -> RpcResult<Op<VoyagerMessage>> {
+        let channel = voyager_client
+            .query_ibc_state(
+                self.chain_id.clone(),
+                QueryHeight::Specific(height),
+                ibc_union_spec::path::ChannelPath { channel_id },
+            )
+            .await?
+            .state
+            .ok_or_else(missing_state("channel must exist", None))?;
+
+        let connection = voyager_client
+            .query_ibc_state(
+                self.chain_id.clone(),
+                QueryHeight::Specific(height),
+                ibc_union_spec::path::ConnectionPath {
+                    connection_id: channel.connection_id,
+                },
+            )
+            .await?
+            .state
+            .ok_or_else(missing_state("connection must exist", None))?;
+
+        let client_info = voyager_client
+            .client_info::<IbcUnion>(self.chain_id.clone(), connection.client_id)
+            .await?;
+
+        let client_meta = voyager_client
+            .client_meta::<IbcUnion>(self.chain_id.clone(), height.into(), connection.client_id)
+            .await?;
+
+        let event = mk_event(connection, channel.version);
+
+        ibc_union_spec::log_event(&event, &self.chain_id);
+
+        Ok(data(ChainEvent {
+            chain_id: self.chain_id.clone(),
+            client_info,
+            counterparty_chain_id: client_meta.chain_id,
+            tx_hash,
+            // events at height N are provable at height N+k where k<0
+            provable_height: height.increment(),
+            ibc_spec_id: IbcUnion::ID,
+            event: into_value::<ibc_union_spec::event::FullEvent>(event),
+        }))
+    }
+
     #[instrument(level = "info", skip_all, fields(%height, %tx_hash))]
     async fn make_chain_event(
         &self,
@@ -1525,6 +1584,56 @@ impl Module {
                     event: into_value::<ibc_union_spec::event::FullEvent>(event),
                 }))
             }
+            IbcEvent::WasmChannelCloseInit {
+                port_id,
+                channel_id,
+                counterparty_port_id,
+                counterparty_channel_id,
+            } => {
+                self.make_channel_close_event(
+                    voyager_client,
+                    height,
+                    tx_hash,
+                    channel_id,
+                    |connection, version| {
+                        ibc_union_spec::event::ChannelCloseInit {
+                            port_id: port_id.to_string().into_bytes().into(),
+                            channel_id,
+                            counterparty_port_id: counterparty_port_id.into_encoding(),
+                            counterparty_channel_id,
+                            connection,
+                            version,
+                        }
+                        .into()
+                    },
+                )
+                .await
+            }
+            IbcEvent::WasmChannelCloseConfirm {
+                port_id,
+                channel_id,
+                counterparty_port_id,
+                counterparty_channel_id,
+            } => {
+                self.make_channel_close_event(
+                    voyager_client,
+                    height,
+                    tx_hash,
+                    channel_id,
+                    |connection, version| {
+                        ibc_union_spec::event::ChannelCloseConfirm {
+                            port_id: port_id.to_string().into_bytes().into(),
+                            channel_id,
+                            counterparty_port_id: counterparty_port_id.into_encoding(),
+                            counterparty_channel_id,
+                            connection,
+                            version,
+                        }
+                        .into()
+                    },
+                )
+                .await
+            }
             IbcEvent::WasmPacketSend { packet } => {
                 let source_channel = voyager_client
                     .query_ibc_state(
@@ -20,6 +20,17 @@ pub struct FetchGetLogs {
     /// If set, only fetch blocks up to this range; otherwise indefinitely unfold.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub up_to: Option<u64>,
+    /// The blocks that have already been fetched but are not yet finalized, in ascending order.
+    /// Only tracked if the plugin is configured with a `confirmation_depth`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unfinalized_blocks: Vec<UnfinalizedBlock>,
+}
+
+/// A block that has been fetched before it was finalized, and as such may still be reorged out.
+#[model]
+pub struct UnfinalizedBlock {
+    pub block_number: u64,
+    pub block_hash: H256,
 }
 
 /// Construct a full ChainEvent from the given EVM event and associated metadata.
@@ -1,6 +1,22 @@
 use enumorph::Enumorph;
 use macros::model;
+use unionlabs::primitives::H256;
+use voyager_message::core::ChainId;
 
 #[model]
 #[derive(Enumorph)]
-pub enum ModuleData {}
+pub enum ModuleData {
+    EventsRetracted(EventsRetracted),
+}
+
+/// Blocks that events have already been emitted for were replaced by a reorg. The events emitted
+/// for the blocks `from_block_number..=to_block_number` are no longer valid; the events of the
+/// blocks that replaced them will be emitted separately.
+#[model]
+pub struct EventsRetracted {
+    pub chain_id: ChainId,
+    pub from_block_number: u64,
+    pub to_block_number: u64,
+    /// The hashes of the replaced blocks, starting at `from_block_number`.
+    pub block_hashes: Vec<H256>,
+}
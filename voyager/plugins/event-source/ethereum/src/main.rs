@@ -1,10 +1,10 @@
 #![warn(clippy::unwrap_used)]
 
-use std::collections::VecDeque;
+use std::{collections::VecDeque, future::Future};
 
 use alloy::{
     providers::{Provider, ProviderBuilder, RootProvider},
-    rpc::types::Filter,
+    rpc::types::{Block, BlockTransactionsKind, Filter, Log},
     sol_types::SolEventInterface,
     transports::BoxTransport,
 };
@@ -12,10 +12,10 @@ use beacon_api::client::BeaconApiClient;
 use ibc_solidity::Ibc;
 use ibc_union_spec::{
     event::{
-        ChannelMetadata, ChannelOpenAck, ChannelOpenConfirm, ChannelOpenInit, ChannelOpenTry,
-        ConnectionMetadata, ConnectionOpenAck, ConnectionOpenConfirm, ConnectionOpenInit,
-        ConnectionOpenTry, CreateClient, FullEvent, PacketAck, PacketMetadata, PacketRecv,
-        PacketSend, PacketTimeout, UpdateClient, WriteAck,
+        ChannelCloseConfirm, ChannelCloseInit, ChannelMetadata, ChannelOpenAck, ChannelOpenConfirm,
+        ChannelOpenInit, ChannelOpenTry, ConnectionMetadata, ConnectionOpenAck,
+        ConnectionOpenConfirm, ConnectionOpenInit, ConnectionOpenTry, CreateClient, FullEvent,
+        PacketAck, PacketMetadata, PacketRecv, PacketSend, PacketTimeout, UpdateClient, WriteAck,
     },
     path::{ChannelPath, ConnectionPath},
     IbcUnion,
@@ -45,8 +45,9 @@ use voyager_message::{
 use voyager_vm::{call, conc, data, defer, noop, now, pass::PassResult, seq, BoxDynError, Op};
 
 use crate::{
-    call::{FetchGetLogs, IbcEvents, MakeFullEvent, ModuleCall},
+    call::{FetchGetLogs, IbcEvents, MakeFullEvent, ModuleCall, UnfinalizedBlock},
     callback::ModuleCallback,
+    data::{EventsRetracted, ModuleData},
 };
 
 pub mod call;
@@ -65,7 +66,9 @@ pub struct Module {
     pub ibc_handler_address: H160,
 
     pub provider: RootProvider<BoxTransport>,
-    pub beacon_api_client: BeaconApiClient,
+    pub beacon_api_client: Option<BeaconApiClient>,
+
+    pub confirmation_depth: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -79,8 +82,18 @@ pub struct Config {
 
     /// The RPC endpoint for the execution chain.
     pub rpc_url: String,
-    /// The RPC endpoint for the beacon chain.
-    pub beacon_rpc_url: String,
+    /// The RPC endpoint for the beacon chain. Not required for chains without a beacon chain
+    /// (such as L2s).
+    #[serde(default)]
+    pub beacon_rpc_url: Option<String>,
+
+    /// If set, events are emitted once their block is this many blocks deep, instead of once the
+    /// block is finalized. Blocks are tracked until they are finalized, and any block that is
+    /// replaced by a reorg is refetched after an [`EventsRetracted`] message is emitted for it.
+    ///
+    /// This should only be used for chains tracked by clients that can prove unfinalized state.
+    #[serde(default)]
+    pub confirmation_depth: Option<u64>,
 }
 
 impl Plugin for Module {
@@ -130,7 +143,11 @@ impl Module {
             chain_id: ChainId::new(chain_id.to_string()),
             ibc_handler_address: config.ibc_handler_address,
             provider,
-            beacon_api_client: BeaconApiClient::new(config.beacon_rpc_url).await?,
+            beacon_api_client: match config.beacon_rpc_url {
+                Some(beacon_rpc_url) => Some(BeaconApiClient::new(beacon_rpc_url).await?),
+                None => None,
+            },
+            confirmation_depth: config.confirmation_depth,
         })
     }
 
@@ -240,6 +257,7 @@ impl PluginServer<ModuleCall, ModuleCallback> for Module {
                             ModuleCall::from(FetchGetLogs {
                                 block_number: fetch.start_height.height(),
                                 up_to: None,
+                                unfinalized_blocks: vec![],
                             }),
                         ))
                     }
@@ -275,10 +293,23 @@ impl PluginServer<ModuleCall, ModuleCallback> for Module {
             ModuleCall::FetchGetLogs(FetchGetLogs {
                 block_number,
                 up_to,
-            }) => {
-                self.fetch_get_logs(e.try_get::<VoyagerClient>()?, block_number, up_to)
+                unfinalized_blocks,
+            }) => match self.confirmation_depth {
+                Some(confirmation_depth) => {
+                    self.fetch_unfinalized_get_logs(
+                        e.try_get::<VoyagerClient>()?,
+                        block_number,
+                        up_to,
+                        unfinalized_blocks,
+                        confirmation_depth,
+                    )
                     .await
-            }
+                }
+                None => {
+                    self.fetch_get_logs(e.try_get::<VoyagerClient>()?, block_number, up_to)
+                        .await
+                }
+            },
         }
     }
 }
@@ -313,6 +344,7 @@ impl Module {
                     ModuleCall::from(FetchGetLogs {
                         block_number,
                         up_to,
+                        unfinalized_blocks: vec![],
                     }),
                 ))),
             ]));
@@ -344,6 +376,164 @@ impl Module {
 
         info!("found {} logs", logs.len());
 
+        let events = self.make_full_event_calls(block_number, logs);
+
+        Ok(conc(
+            self.next_fetch_get_logs(block_number, up_to, vec![])
+                .into_iter()
+                .chain(events),
+        ))
+    }
+
+    /// Fetch the logs of the unfinalized block `block_number` once it is `confirmation_depth`
+    /// blocks deep.
+    ///
+    /// The hashes of all fetched blocks are tracked until they are finalized. If a fetched block
+    /// turns out to have been replaced by a reorg, an [`EventsRetracted`] message is emitted for
+    /// the replaced blocks, and fetching restarts at the first replaced block.
+    #[instrument(skip_all, fields(block_number, ?up_to, confirmation_depth))]
+    async fn fetch_unfinalized_get_logs(
+        &self,
+        voyager_client: &VoyagerClient,
+        block_number: u64,
+        up_to: Option<u64>,
+        mut unfinalized_blocks: Vec<UnfinalizedBlock>,
+        confirmation_depth: u64,
+    ) -> RpcResult<Op<VoyagerMessage>> {
+        if up_to.is_some_and(|up_to| up_to < block_number) {
+            return Err(ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                "`up_to` must be either > `block_number` or null",
+                None::<()>,
+            ));
+        }
+
+        let latest_height = voyager_client
+            .query_latest_height(self.chain_id.clone(), false)
+            .await?;
+
+        if latest_height.height() < block_number.saturating_add(confirmation_depth) {
+            debug!(
+                block_number,
+                %latest_height,
+                "block does not yet have enough confirmations"
+            );
+
+            return Ok(seq([
+                defer(now() + 1),
+                call(Call::Plugin(PluginMessage::new(
+                    self.plugin_name(),
+                    ModuleCall::from(FetchGetLogs {
+                        block_number,
+                        up_to,
+                        unfinalized_blocks,
+                    }),
+                ))),
+            ]));
+        }
+
+        let block = self.get_block(block_number).await?;
+
+        if parent_was_reorged(
+            &unfinalized_blocks,
+            block_number,
+            block.header.parent_hash.into(),
+        ) {
+            return self
+                .retract_reorged_blocks(block_number, up_to, unfinalized_blocks)
+                .await;
+        }
+
+        let finalized_height = voyager_client
+            .query_latest_height(self.chain_id.clone(), true)
+            .await?;
+
+        track_unfinalized_block(
+            &mut unfinalized_blocks,
+            UnfinalizedBlock {
+                block_number,
+                block_hash: block.header.hash.into(),
+            },
+            finalized_height.height(),
+        );
+
+        debug!("fetching logs in execution block");
+
+        // fetch by hash, such that the logs are guaranteed to be from the block that is tracked
+        let logs = self
+            .provider
+            .get_logs(
+                &Filter::new()
+                    .address(alloy::primitives::Address::from(
+                        self.ibc_handler_address.get(),
+                    ))
+                    .at_block_hash(block.header.hash),
+            )
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    format!(
+                        "error fetching logs in block {block_number}: {}",
+                        ErrorReporter(e)
+                    ),
+                    None::<()>,
+                )
+            })?;
+
+        info!("found {} logs", logs.len());
+
+        let events = self.make_full_event_calls(block_number, logs);
+
+        Ok(conc(
+            self.next_fetch_get_logs(block_number, up_to, unfinalized_blocks)
+                .into_iter()
+                .chain(events),
+        ))
+    }
+
+    /// Drop all blocks that were replaced by a reorg from `unfinalized_blocks`, retract the events
+    /// that were emitted for them and restart the fetch from the first replaced block.
+    #[instrument(skip_all, fields(block_number))]
+    async fn retract_reorged_blocks(
+        &self,
+        block_number: u64,
+        up_to: Option<u64>,
+        mut unfinalized_blocks: Vec<UnfinalizedBlock>,
+    ) -> RpcResult<Op<VoyagerMessage>> {
+        let reorged = pop_reorged_blocks(&mut unfinalized_blocks, |block_number| async move {
+            Ok(self.get_block(block_number).await?.header.hash.into())
+        })
+        .await?;
+
+        info!(reorged = reorged.len(), "reorg detected, refetching blocks");
+
+        Ok(reorg_ops(
+            &self.chain_id,
+            block_number,
+            up_to,
+            unfinalized_blocks,
+            reorged,
+        ))
+    }
+
+    async fn get_block(&self, block_number: u64) -> RpcResult<Block> {
+        self.provider
+            .get_block_by_number(block_number.into(), BlockTransactionsKind::Hashes)
+            .await
+            .map_err(|e| {
+                ErrorObject::owned(
+                    -1,
+                    ErrorReporter(e).with_message(&format!("error fetching block {block_number}")),
+                    None::<()>,
+                )
+            })?
+            .ok_or_else(|| {
+                ErrorObject::owned(-1, format!("block {block_number} not found"), None::<()>)
+            })
+    }
+
+    fn make_full_event_calls(&self, block_number: u64, logs: Vec<Log>) -> Vec<Op<VoyagerMessage>> {
         let events = logs.into_iter().flat_map(|log| {
             let tx_hash = log
                 .transaction_hash
@@ -434,30 +624,86 @@ impl Module {
             })
         });
 
-        let next_fetch = match up_to {
-            Some(up_to) => {
-                if up_to > block_number {
-                    Some(call(Call::Plugin(PluginMessage::new(
-                        self.plugin_name(),
-                        ModuleCall::from(FetchGetLogs {
-                            block_number: block_number + 1,
-                            up_to: Some(up_to),
-                        }),
-                    ))))
-                } else {
-                    None
-                }
-            }
-            None => Some(call(Call::Plugin(PluginMessage::new(
-                self.plugin_name(),
-                ModuleCall::from(FetchGetLogs {
-                    block_number: block_number + 1,
-                    up_to: None,
-                }),
-            )))),
-        };
+        events.collect()
+    }
+
+    fn next_fetch_get_logs(
+        &self,
+        block_number: u64,
+        up_to: Option<u64>,
+        unfinalized_blocks: Vec<UnfinalizedBlock>,
+    ) -> Option<Op<VoyagerMessage>> {
+        if up_to.is_some_and(|up_to| up_to <= block_number) {
+            return None;
+        }
+
+        Some(call(Call::Plugin(PluginMessage::new(
+            self.plugin_name(),
+            ModuleCall::from(FetchGetLogs {
+                block_number: block_number + 1,
+                up_to,
+                unfinalized_blocks,
+            }),
+        ))))
+    }
+
+    /// Build the chain event for a channel close event. The channel close events only differ in
+    /// their type, `mk_event` builds the event from the channel's connection and version.
+    async fn make_channel_close_event(
+        &self,
+        voyager_client: &VoyagerClient,
+        provable_height: Height,
+        tx_hash: H256,
+        channel_id: u32,
+        mk_event: impl FnOnce(ibc_union_spec::types::Connection, String) -> FullEvent,
+    ) -> RpcResult<Op<VoyagerMessage>> {
+        let channel = voyager_client
+            .query_ibc_state(
+                self.chain_id.clone(),
+                provable_height.into(),
+                ChannelPath { channel_id },
+            )
+            .await?
+            .state
+            .ok_or_else(missing_state("channel must exist", None))?;
+
+        let connection = voyager_client
+            .query_ibc_state(
+                self.chain_id.clone(),
+                provable_height.into(),
+                ConnectionPath {
+                    connection_id: channel.connection_id,
+                },
+            )
+            .await?
+            .state
+            .ok_or_else(missing_state("connection must exist", None))?;
+
+        let client_info = voyager_client
+            .client_info::<IbcUnion>(self.chain_id.clone(), connection.client_id)
+            .await?;
+
+        let client_meta = voyager_client
+            .client_meta::<IbcUnion>(
+                self.chain_id.clone(),
+                provable_height.into(),
+                connection.client_id,
+            )
+            .await?;
 
-        Ok(conc(next_fetch.into_iter().chain(events)))
+        let event = mk_event(connection, channel.version);
+
+        ibc_union_spec::log_event(&event, &self.chain_id);
+
+        Ok(data(ChainEvent {
+            chain_id: self.chain_id.clone(),
+            client_info,
+            counterparty_chain_id: client_meta.chain_id,
+            tx_hash,
+            provable_height,
+            ibc_spec_id: IbcUnion::ID,
+            event: into_value::<FullEvent>(event),
+        }))
     }
 
     #[instrument(skip_all, fields(block_number, %tx_hash))]
@@ -901,10 +1147,45 @@ impl Module {
                 }))
             }
 
-            IbcEvents::ChannelCloseInit(_) | IbcEvents::ChannelCloseConfirm(_) => {
-                warn!("observed channel close message, these are not handled currently");
-
-                Ok(noop())
+            IbcEvents::ChannelCloseInit(raw_event) => {
+                self.make_channel_close_event(
+                    voyager_client,
+                    provable_height,
+                    tx_hash,
+                    raw_event.channel_id,
+                    |connection, version| {
+                        ChannelCloseInit {
+                            port_id: raw_event.port_id.into(),
+                            channel_id: raw_event.channel_id,
+                            counterparty_port_id: raw_event.counterparty_port_id.into(),
+                            counterparty_channel_id: raw_event.counterparty_channel_id,
+                            connection,
+                            version,
+                        }
+                        .into()
+                    },
+                )
+                .await
+            }
+            IbcEvents::ChannelCloseConfirm(raw_event) => {
+                self.make_channel_close_event(
+                    voyager_client,
+                    provable_height,
+                    tx_hash,
+                    raw_event.channel_id,
+                    |connection, version| {
+                        ChannelCloseConfirm {
+                            port_id: raw_event.port_id.into(),
+                            channel_id: raw_event.channel_id,
+                            counterparty_port_id: raw_event.counterparty_port_id.into(),
+                            counterparty_channel_id: raw_event.counterparty_channel_id,
+                            connection,
+                            version,
+                        }
+                        .into()
+                    },
+                )
+                .await
             }
 
             // packet origin is this chain
@@ -1078,3 +1359,269 @@ impl Module {
         }
     }
 }
+
+/// Whether the block `block_number` with parent `parent_hash` doesn't build on the last tracked
+/// block, i.e. the last tracked block has been replaced by a reorg.
+fn parent_was_reorged(
+    unfinalized_blocks: &[UnfinalizedBlock],
+    block_number: u64,
+    parent_hash: H256,
+) -> bool {
+    unfinalized_blocks.last().is_some_and(|parent| {
+        parent.block_number + 1 == block_number && parent.block_hash != parent_hash
+    })
+}
+
+/// Track `block` if it is not yet finalized, and stop tracking all blocks that are.
+fn track_unfinalized_block(
+    unfinalized_blocks: &mut Vec<UnfinalizedBlock>,
+    block: UnfinalizedBlock,
+    finalized_height: u64,
+) {
+    unfinalized_blocks.retain(|unfinalized| unfinalized.block_number > finalized_height);
+
+    if block.block_number > finalized_height {
+        unfinalized_blocks.push(block);
+    }
+}
+
+/// Pop blocks off of `unfinalized_blocks` until one is found that is still canonical, returning
+/// the popped blocks in descending order.
+async fn pop_reorged_blocks<Fut: Future<Output = RpcResult<H256>>>(
+    unfinalized_blocks: &mut Vec<UnfinalizedBlock>,
+    mut canonical_block_hash: impl FnMut(u64) -> Fut,
+) -> RpcResult<Vec<UnfinalizedBlock>> {
+    let mut reorged = vec![];
+
+    while let Some(tracked) = unfinalized_blocks.pop() {
+        let canonical_hash = canonical_block_hash(tracked.block_number).await?;
+
+        if canonical_hash == tracked.block_hash {
+            unfinalized_blocks.push(tracked);
+            break;
+        }
+
+        warn!(
+            block_number = tracked.block_number,
+            block_hash = %tracked.block_hash,
+            %canonical_hash,
+            "block was reorged"
+        );
+
+        reorged.push(tracked);
+    }
+
+    Ok(reorged)
+}
+
+/// Retract the events of the `reorged` blocks (in descending order) and refetch from the first
+/// reorged block, or from `block_number` if no tracked block was reorged.
+fn reorg_ops(
+    chain_id: &ChainId,
+    block_number: u64,
+    up_to: Option<u64>,
+    unfinalized_blocks: Vec<UnfinalizedBlock>,
+    reorged: Vec<UnfinalizedBlock>,
+) -> Op<VoyagerMessage> {
+    let retracted = reorged
+        .first()
+        .zip(reorged.last())
+        .map(|(last, first)| EventsRetracted {
+            chain_id: chain_id.clone(),
+            from_block_number: first.block_number,
+            to_block_number: last.block_number,
+            block_hashes: reorged.iter().rev().map(|block| block.block_hash).collect(),
+        });
+
+    let refetch_from = retracted
+        .as_ref()
+        .map_or(block_number, |retracted| retracted.from_block_number);
+
+    conc(
+        retracted
+            .map(|retracted| {
+                data(PluginMessage::new(
+                    plugin_name(chain_id),
+                    ModuleData::from(retracted),
+                ))
+            })
+            .into_iter()
+            .chain([call(Call::Plugin(PluginMessage::new(
+                plugin_name(chain_id),
+                ModuleCall::from(FetchGetLogs {
+                    block_number: refetch_from,
+                    up_to,
+                    unfinalized_blocks,
+                }),
+            )))]),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, future::ready};
+
+    use super::*;
+
+    fn block(block_number: u64, hash: u8) -> UnfinalizedBlock {
+        UnfinalizedBlock {
+            block_number,
+            block_hash: H256::new([hash; 32]),
+        }
+    }
+
+    async fn pop_reorged(
+        unfinalized_blocks: &mut Vec<UnfinalizedBlock>,
+        canonical: &[(u64, u8)],
+    ) -> RpcResult<Vec<UnfinalizedBlock>> {
+        let canonical = canonical.iter().copied().collect::<BTreeMap<_, _>>();
+
+        pop_reorged_blocks(unfinalized_blocks, |block_number| {
+            ready(
+                canonical
+                    .get(&block_number)
+                    .map(|hash| H256::new([*hash; 32]))
+                    .ok_or_else(|| ErrorObject::owned(-1, "block not found", None::<()>)),
+            )
+        })
+        .await
+    }
+
+    #[test]
+    fn track_unfinalized_block_drops_finalized_blocks() {
+        let mut unfinalized_blocks = vec![block(10, 1), block(11, 2)];
+
+        track_unfinalized_block(&mut unfinalized_blocks, block(12, 3), 10);
+
+        assert_eq!(unfinalized_blocks, [block(11, 2), block(12, 3)]);
+    }
+
+    #[test]
+    fn track_unfinalized_block_ignores_finalized_block() {
+        let mut unfinalized_blocks = vec![block(10, 1)];
+
+        track_unfinalized_block(&mut unfinalized_blocks, block(11, 2), 11);
+
+        assert!(unfinalized_blocks.is_empty());
+    }
+
+    #[test]
+    fn parent_was_reorged_checks_parent_hash() {
+        let unfinalized_blocks = [block(10, 1), block(11, 2)];
+
+        assert!(!parent_was_reorged(
+            &unfinalized_blocks,
+            12,
+            H256::new([2; 32])
+        ));
+        assert!(parent_was_reorged(
+            &unfinalized_blocks,
+            12,
+            H256::new([3; 32])
+        ));
+        // only the direct parent can be checked
+        assert!(!parent_was_reorged(
+            &unfinalized_blocks,
+            13,
+            H256::new([3; 32])
+        ));
+        assert!(!parent_was_reorged(&[], 12, H256::new([3; 32])));
+    }
+
+    fn chain_id() -> ChainId {
+        ChainId::new("1")
+    }
+
+    fn fetch_get_logs(
+        block_number: u64,
+        unfinalized_blocks: Vec<UnfinalizedBlock>,
+    ) -> Op<VoyagerMessage> {
+        call(Call::Plugin(PluginMessage::new(
+            plugin_name(&chain_id()),
+            ModuleCall::from(FetchGetLogs {
+                block_number,
+                up_to: None,
+                unfinalized_blocks,
+            }),
+        )))
+    }
+
+    fn events_retracted(from_block_number: u64, block_hashes: &[u8]) -> Op<VoyagerMessage> {
+        data(PluginMessage::new(
+            plugin_name(&chain_id()),
+            ModuleData::from(EventsRetracted {
+                chain_id: chain_id(),
+                from_block_number,
+                to_block_number: from_block_number + block_hashes.len() as u64 - 1,
+                block_hashes: block_hashes
+                    .iter()
+                    .map(|hash| H256::new([*hash; 32]))
+                    .collect(),
+            }),
+        ))
+    }
+
+    #[tokio::test]
+    async fn pop_reorged_blocks_stops_at_canonical_block() {
+        let mut unfinalized_blocks = vec![block(10, 1), block(11, 2), block(12, 3)];
+
+        let reorged = pop_reorged(&mut unfinalized_blocks, &[(10, 1), (11, 12), (12, 13)])
+            .await
+            .unwrap();
+
+        assert_eq!(reorged, [block(12, 3), block(11, 2)]);
+        assert_eq!(unfinalized_blocks, [block(10, 1)]);
+
+        assert_eq!(
+            reorg_ops(&chain_id(), 13, None, unfinalized_blocks, reorged),
+            conc([
+                events_retracted(11, &[2, 3]),
+                fetch_get_logs(11, vec![block(10, 1)]),
+            ])
+        );
+    }
+
+    #[tokio::test]
+    async fn pop_reorged_blocks_without_reorg() {
+        let mut unfinalized_blocks = vec![block(10, 1), block(11, 2)];
+
+        let reorged = pop_reorged(&mut unfinalized_blocks, &[(10, 1), (11, 2)])
+            .await
+            .unwrap();
+
+        assert!(reorged.is_empty());
+        assert_eq!(unfinalized_blocks, [block(10, 1), block(11, 2)]);
+
+        // nothing to retract, the block is refetched
+        assert_eq!(
+            reorg_ops(&chain_id(), 12, None, unfinalized_blocks, reorged),
+            conc([fetch_get_logs(12, vec![block(10, 1), block(11, 2)])])
+        );
+    }
+
+    #[tokio::test]
+    async fn pop_reorged_blocks_all_reorged() {
+        let mut unfinalized_blocks = vec![block(10, 1), block(11, 2)];
+
+        let reorged = pop_reorged(&mut unfinalized_blocks, &[(10, 11), (11, 12)])
+            .await
+            .unwrap();
+
+        assert_eq!(reorged, [block(11, 2), block(10, 1)]);
+        assert!(unfinalized_blocks.is_empty());
+
+        assert_eq!(
+            reorg_ops(&chain_id(), 12, None, unfinalized_blocks, reorged),
+            conc([events_retracted(10, &[1, 2]), fetch_get_logs(10, vec![])])
+        );
+    }
+
+    #[tokio::test]
+    async fn pop_reorged_blocks_fails_if_block_is_missing() {
+        let mut unfinalized_blocks = vec![block(10, 1), block(11, 2)];
+
+        assert!(pop_reorged(&mut unfinalized_blocks, &[(10, 1)])
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,25 @@
+use enumorph::Enumorph;
+use macros::model;
+use unionlabs::ibc::core::client::height::Height;
+use voyager_message::core::{ChainId, ClientInfo};
+
+#[model]
+#[derive(Enumorph)]
+pub enum ModuleCall {
+    CheckUpdate(CheckUpdate),
+}
+
+/// Check the header at the height of an `UpdateClient` on this chain against all of the configured
+/// endpoints of the counterparty chain.
+#[model]
+pub struct CheckUpdate {
+    /// The client that was updated.
+    pub client_id: u32,
+    pub client_info: ClientInfo,
+    /// The chain tracked by the client.
+    pub counterparty_chain_id: ChainId,
+    /// The provable height of the `UpdateClient` event on this chain.
+    pub provable_height: Height,
+    /// The height of the consensus state written by the update.
+    pub height: u64,
+}
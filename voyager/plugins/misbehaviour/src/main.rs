@@ -0,0 +1,489 @@
+//! Watches `UpdateClient` events for a set of ibc-union clients, and fetches the header at the
+//! updated height from every configured RPC endpoint of the chain tracked by the clients. If any
+//! two endpoints return conflicting headers for the same height, a misbehaviour built from the two
+//! headers is submitted to freeze the client. Nothing is submitted if all endpoints agree.
+//!
+//! Misbehaviour is currently only built for tendermint clients. The headers of cometbls clients
+//! carry a zero knowledge proof which can only be produced by the prover, not fetched from an
+//! endpoint.
+
+use std::{collections::VecDeque, num::NonZeroU64};
+
+use cometbft_rpc::rpc_types::CommitResponse;
+use cometbft_types::types::{
+    signed_header::SignedHeader, validator::Validator, validator_set::ValidatorSet,
+};
+use ibc_union_spec::{
+    datagram::{Datagram, MsgMisbehaviour},
+    event::FullEvent,
+    IbcUnion,
+};
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    types::ErrorObject,
+    Extensions,
+};
+use serde::{Deserialize, Serialize};
+use tendermint_light_client_types::{Header, Misbehaviour};
+use tracing::{debug, error, info, instrument, warn};
+use unionlabs::{
+    ibc::core::client::height::Height,
+    primitives::{encoding::HexUnprefixed, H160},
+    ErrorReporter,
+};
+use voyager_message::{
+    call::SubmitTx,
+    core::{ChainId, ClientType, IbcSpec, QueryHeight},
+    data::{ChainEvent, Data, IbcDatagram},
+    into_value,
+    module::{PluginInfo, PluginServer},
+    DefaultCmd, ExtensionsExt, Plugin, PluginMessage, VoyagerClient, VoyagerMessage,
+    FATAL_JSONRPC_ERROR_CODE,
+};
+use voyager_vm::{call, conc, noop, pass::PassResult, BoxDynError, Op};
+
+use crate::{
+    call::{CheckUpdate, ModuleCall},
+    callback::ModuleCallback,
+};
+
+pub mod call;
+pub mod callback;
+
+#[tokio::main(flavor = "multi_thread")]
+async fn main() {
+    Module::run().await
+}
+
+#[derive(Debug, Clone)]
+pub struct Module {
+    pub chain_id: ChainId,
+    pub client_ids: Vec<u32>,
+
+    /// The chain tracked by the watched clients.
+    pub counterparty_chain_id: ChainId,
+    /// One client per configured endpoint of [`Self::counterparty_chain_id`].
+    pub cometbft_clients: Vec<cometbft_rpc::Client>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    /// The chain the watched clients are on.
+    pub chain_id: ChainId,
+    /// The ibc-union clients on [`Self::chain_id`] to check updates of.
+    pub client_ids: Vec<u32>,
+    /// The CometBFT RPC endpoints of the chain tracked by the clients. Headers are compared
+    /// across all of them, so at least two are required.
+    pub rpc_urls: Vec<String>,
+}
+
+impl Plugin for Module {
+    type Call = ModuleCall;
+    type Callback = ModuleCallback;
+
+    type Config = Config;
+    type Cmd = DefaultCmd;
+
+    async fn new(config: Self::Config) -> Result<Self, BoxDynError> {
+        if config.rpc_urls.len() < 2 {
+            return Err(format!(
+                "at least two rpc urls are required to detect conflicting headers, but found {}",
+                config.rpc_urls.len()
+            )
+            .into());
+        }
+
+        let mut counterparty_chain_id = None::<ChainId>;
+        let mut cometbft_clients = vec![];
+
+        for rpc_url in config.rpc_urls {
+            let cometbft_client = cometbft_rpc::Client::new(&rpc_url).await?;
+
+            let chain_id = ChainId::new(
+                cometbft_client
+                    .status()
+                    .await?
+                    .node_info
+                    .network
+                    .to_string(),
+            );
+
+            match &counterparty_chain_id {
+                Some(counterparty_chain_id) if counterparty_chain_id != &chain_id => {
+                    return Err(format!(
+                        "all rpc urls must be for the same chain: expected `{}`, but `{rpc_url}` \
+                        is for `{}`",
+                        counterparty_chain_id, chain_id
+                    )
+                    .into());
+                }
+                Some(_) => {}
+                None => counterparty_chain_id = Some(chain_id),
+            }
+
+            cometbft_clients.push(cometbft_client);
+        }
+
+        Ok(Self {
+            chain_id: config.chain_id,
+            client_ids: config.client_ids,
+            counterparty_chain_id: counterparty_chain_id.expect("there are at least two rpc urls"),
+            cometbft_clients,
+        })
+    }
+
+    fn info(config: Self::Config) -> PluginInfo {
+        PluginInfo {
+            name: plugin_name(&config.chain_id),
+            interest_filter: format!(
+                r#"
+if ."@type" == "data" then
+    ."@value" as $data |
+
+    if $data."@type" == "ibc_event" and $data."@value".chain_id == "{chain_id}" and $data."@value".ibc_spec_id == "{ibc_union_id}" then
+        $data."@value".event."@type" == "update_client"
+        and ($data."@value".event."@value".client_id as $client_id | {client_ids} | any(. == $client_id))
+    else
+        false
+    end
+else
+    false
+end
+"#,
+                chain_id = config.chain_id,
+                ibc_union_id = IbcUnion::ID,
+                client_ids = serde_json::to_string(&config.client_ids).unwrap(),
+            ),
+        }
+    }
+
+    async fn cmd(_config: Self::Config, cmd: Self::Cmd) {
+        match cmd {}
+    }
+}
+
+fn plugin_name(chain_id: &ChainId) -> String {
+    pub const PLUGIN_NAME: &str = env!("CARGO_PKG_NAME");
+
+    format!("{PLUGIN_NAME}/{}", chain_id)
+}
+
+impl Module {
+    fn plugin_name(&self) -> String {
+        plugin_name(&self.chain_id)
+    }
+
+    #[instrument(
+        skip_all,
+        fields(
+            chain_id = %self.chain_id,
+            client_id = check_update.client_id,
+            counterparty_chain_id = %check_update.counterparty_chain_id,
+            provable_height = %check_update.provable_height,
+            height = check_update.height
+        )
+    )]
+    async fn check_update(
+        &self,
+        voyager_client: &VoyagerClient,
+        check_update: CheckUpdate,
+    ) -> RpcResult<Op<VoyagerMessage>> {
+        let CheckUpdate {
+            client_id,
+            client_info,
+            counterparty_chain_id,
+            provable_height,
+            height,
+        } = check_update;
+
+        if counterparty_chain_id != self.counterparty_chain_id {
+            warn!(
+                expected = %self.counterparty_chain_id,
+                "client does not track the chain of the configured rpc urls"
+            );
+
+            return Ok(noop());
+        }
+
+        if client_info.client_type.as_str() != ClientType::TENDERMINT {
+            warn!(
+                client_type = %client_info.client_type,
+                "building misbehaviour is not supported for this client type"
+            );
+
+            return Ok(noop());
+        }
+
+        let Some(height) = NonZeroU64::new(height) else {
+            return Err(ErrorObject::owned(
+                FATAL_JSONRPC_ERROR_CODE,
+                "update to height 0",
+                None::<()>,
+            ));
+        };
+
+        let mut signed_headers = vec![];
+        for cometbft_client in &self.cometbft_clients {
+            signed_headers.push(fetch_commit(cometbft_client, height).await?.signed_header);
+        }
+
+        let Some((a, b)) = find_conflict(
+            &signed_headers
+                .iter()
+                .map(|signed_header| &signed_header.header)
+                .collect::<Vec<_>>(),
+        ) else {
+            debug!("all endpoints agree on the header");
+
+            return Ok(noop());
+        };
+
+        warn!(
+            endpoint_a = a,
+            endpoint_b = b,
+            "endpoints returned conflicting headers for the same height"
+        );
+
+        // provable_height is at most one block after the block the update was included in, so
+        // the client state two blocks before it predates the update
+        let trusted_height = voyager_client
+            .client_meta::<IbcUnion>(
+                self.chain_id.clone(),
+                QueryHeight::Specific(Height::new_with_revision(
+                    provable_height.revision(),
+                    provable_height.height().saturating_sub(2),
+                )),
+                client_id,
+            )
+            .await?
+            .counterparty_height;
+
+        if trusted_height.height() >= height.get() {
+            error!(
+                %trusted_height,
+                "no trusted height below the conflicting height, unable to build misbehaviour"
+            );
+
+            return Ok(noop());
+        }
+
+        let header_a = self
+            .fetch_header(a, height, signed_headers[a].clone(), trusted_height)
+            .await?;
+        let header_b = self
+            .fetch_header(b, height, signed_headers[b].clone(), trusted_height)
+            .await?;
+
+        let misbehaviour = voyager_client
+            .encode_misbehaviour::<IbcUnion>(
+                client_info.client_type,
+                client_info.ibc_interface,
+                into_value(Misbehaviour { header_a, header_b }),
+            )
+            .await?;
+
+        info!("submitting misbehaviour");
+
+        Ok(call(SubmitTx {
+            chain_id: self.chain_id.clone(),
+            datagrams: vec![IbcDatagram::new::<IbcUnion>(Datagram::from(
+                MsgMisbehaviour {
+                    client_id,
+                    client_message: misbehaviour,
+                },
+            ))],
+        }))
+    }
+
+    /// Build a header for `signed_header` at `height`, as returned by the endpoint at `idx`,
+    /// trusting the validators at `trusted_height` as returned by the same endpoint.
+    async fn fetch_header(
+        &self,
+        idx: usize,
+        height: NonZeroU64,
+        signed_header: SignedHeader,
+        trusted_height: Height,
+    ) -> RpcResult<Header> {
+        let cometbft_client = &self.cometbft_clients[idx];
+
+        let trusted_next_height =
+            NonZeroU64::new(trusted_height.height() + 1).expect("height + 1 is non-zero; qed;");
+
+        let trusted_commit = fetch_commit(cometbft_client, trusted_next_height).await?;
+
+        let validators = fetch_validators(cometbft_client, height).await?;
+        let trusted_validators = fetch_validators(cometbft_client, trusted_next_height).await?;
+
+        Ok(Header {
+            validator_set: mk_validator_set(validators, signed_header.header.proposer_address)?,
+            trusted_validators: mk_validator_set(
+                trusted_validators,
+                trusted_commit.signed_header.header.proposer_address,
+            )?,
+            signed_header,
+            trusted_height,
+        })
+    }
+}
+
+async fn fetch_commit(
+    cometbft_client: &cometbft_rpc::Client,
+    height: NonZeroU64,
+) -> RpcResult<CommitResponse> {
+    cometbft_client.commit(Some(height)).await.map_err(|e| {
+        ErrorObject::owned(
+            -1,
+            ErrorReporter(e).with_message(&format!("error fetching commit at height {height}")),
+            None::<()>,
+        )
+    })
+}
+
+async fn fetch_validators(
+    cometbft_client: &cometbft_rpc::Client,
+    height: NonZeroU64,
+) -> RpcResult<Vec<Validator>> {
+    cometbft_client
+        .all_validators(Some(height))
+        .await
+        .map(|response| response.validators)
+        .map_err(|e| {
+            ErrorObject::owned(
+                -1,
+                ErrorReporter(e)
+                    .with_message(&format!("error fetching validators at height {height}")),
+                None::<()>,
+            )
+        })
+}
+
+fn mk_validator_set(
+    validators: Vec<Validator>,
+    proposer_address: H160<HexUnprefixed>,
+) -> RpcResult<ValidatorSet> {
+    let proposer = validators
+        .iter()
+        .find(|val| val.address == proposer_address)
+        .ok_or_else(|| {
+            ErrorObject::owned(
+                -1,
+                format!("proposer {proposer_address} is not in the validator set"),
+                None::<()>,
+            )
+        })?
+        .clone();
+
+    let total_voting_power = validators
+        .iter()
+        .map(|v| v.voting_power.inner())
+        .sum::<i64>();
+
+    Ok(ValidatorSet {
+        validators,
+        proposer,
+        total_voting_power,
+    })
+}
+
+/// Find two items that conflict with each other, returning their indices.
+fn find_conflict<T: PartialEq>(items: &[T]) -> Option<(usize, usize)> {
+    let (first, rest) = items.split_first()?;
+
+    rest.iter()
+        .position(|item| item != first)
+        .map(|idx| (0, idx + 1))
+}
+
+#[async_trait]
+impl PluginServer<ModuleCall, ModuleCallback> for Module {
+    #[instrument(skip_all, fields(chain_id = %self.chain_id))]
+    async fn run_pass(
+        &self,
+        _: &Extensions,
+        msgs: Vec<Op<VoyagerMessage>>,
+    ) -> RpcResult<PassResult<VoyagerMessage>> {
+        Ok(PassResult {
+            optimize_further: vec![],
+            ready: msgs
+                .into_iter()
+                .enumerate()
+                .map(|(idx, op)| {
+                    let Op::Data(data) = &op else {
+                        error!("unexpected message: {op:?}");
+                        return (vec![idx], op);
+                    };
+
+                    let Ok(chain_event) = ChainEvent::try_from(data.clone()) else {
+                        error!("unexpected data: {data:?}");
+                        return (vec![idx], op);
+                    };
+
+                    let Some(Ok(FullEvent::UpdateClient(update_client))) =
+                        chain_event.decode_event::<IbcUnion>()
+                    else {
+                        error!("unexpected event: {}", chain_event.event);
+                        return (vec![idx], op);
+                    };
+
+                    // the event is passed through unchanged alongside the check
+                    (
+                        vec![idx],
+                        conc([
+                            op,
+                            call(PluginMessage::new(
+                                self.plugin_name(),
+                                ModuleCall::from(CheckUpdate {
+                                    client_id: update_client.client_id,
+                                    client_info: chain_event.client_info,
+                                    counterparty_chain_id: chain_event.counterparty_chain_id,
+                                    provable_height: chain_event.provable_height,
+                                    height: update_client.height,
+                                }),
+                            )),
+                        ]),
+                    )
+                })
+                .collect(),
+        })
+    }
+
+    #[instrument(skip_all, fields(chain_id = %self.chain_id))]
+    async fn call(&self, e: &Extensions, msg: ModuleCall) -> RpcResult<Op<VoyagerMessage>> {
+        match msg {
+            ModuleCall::CheckUpdate(check_update) => {
+                self.check_update(e.try_get::<VoyagerClient>()?, check_update)
+                    .await
+            }
+        }
+    }
+
+    #[instrument(skip_all, fields(chain_id = %self.chain_id))]
+    async fn callback(
+        &self,
+        _: &Extensions,
+        cb: ModuleCallback,
+        _datas: VecDeque<Data>,
+    ) -> RpcResult<Op<VoyagerMessage>> {
+        match cb {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn no_conflict() {
+        assert_eq!(find_conflict::<u8>(&[]), None);
+        assert_eq!(find_conflict(&[1]), None);
+        assert_eq!(find_conflict(&[1, 1, 1]), None);
+    }
+
+    #[test]
+    fn conflict() {
+        assert_eq!(find_conflict(&[1, 2]), Some((0, 1)));
+        assert_eq!(find_conflict(&[1, 1, 2, 3]), Some((0, 2)));
+        assert_eq!(find_conflict(&[2, 1, 1]), Some((0, 1)));
+    }
+}
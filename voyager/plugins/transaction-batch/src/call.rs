@@ -1,24 +1,33 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use alloy::sol_types::SolValue;
 use enumorph::Enumorph;
 use ibc_classic_spec::IbcClassic;
-use ibc_union_spec::IbcUnion;
+use ibc_solidity::Packet;
+use ibc_union_spec::{
+    path::{BatchPacketsPath, BatchReceiptsPath},
+    types::ClientId,
+    IbcUnion,
+};
 use jsonrpsee::{core::RpcResult, types::ErrorObject};
 use macros::model;
 use serde_json::json;
-use tracing::info;
-use unionlabs::ibc::core::client::height::Height;
+use tracing::{debug, info, warn};
+use unionlabs::{ethereum::keccak256, ibc::core::client::height::Height, primitives::H256};
 use voyager_message::{
     call::FetchUpdateHeaders,
     callback::AggregateMsgUpdateClientsFromOrderedHeaders,
     core::{ChainId, QueryHeight},
     PluginMessage, RawClientId, VoyagerClient, VoyagerMessage, FATAL_JSONRPC_ERROR_CODE,
 };
-use voyager_vm::{now, promise, Op};
+use voyager_vm::{data, defer, noop, now, promise, seq, Op};
 
 use crate::{
     call,
     callback::{make_msgs, MakeBatchTransaction, MakeIbcMessagesFromUpdate, ModuleCallback},
-    data::BatchableEvent,
-    IbcSpecExt, Module,
+    data::{BatchableEvent, EventBatch, EventUnion, ModuleData, PacketTimeout},
+    is_timeout_provable, mk_ready_ops, IbcSpecExt, Module, BATCH_COMMIT_CHECK_INTERVAL,
+    BATCH_COMMIT_MAX_CHECKS, PLUGIN_NAME,
 };
 
 #[model]
@@ -29,6 +38,9 @@ pub enum ModuleCall {
 
     MakeMsgV1(MakeMsg<IbcClassic>),
     MakeMsgUnion(MakeMsg<IbcUnion>),
+
+    CheckPacketTimeout(CheckPacketTimeout),
+    CheckBatchCommitted(CheckBatchCommitted),
 }
 
 /// Constructs multiple batch transactions, where all of the batches are provable at the new consensus height.
@@ -153,3 +165,303 @@ pub struct MakeMsg<V: IbcSpecExt> {
     /// The original event that was emitted on the origin chain.
     pub event: V::BatchableEvent,
 }
+
+/// Check whether a packet sent to this chain has timed out without being received.
+///
+/// - If the packet has been received on this chain, or the commitment no longer exists on the
+///   origin chain, there is nothing left to do.
+/// - If the packet has timed out on this chain, a [`PacketTimeout`] is handed off to the transaction
+///   batch plugin for the origin chain, which will then submit the timeout there.
+/// - Otherwise, this check is requeued after the configured
+///   [`packet_timeout_check_interval`](crate::Config::packet_timeout_check_interval).
+#[model]
+pub struct CheckPacketTimeout {
+    /// The chain the packet was sent from.
+    pub origin_chain_id: ChainId,
+    /// The original send event of the packet.
+    pub packet_send: ibc_union_spec::event::PacketSend,
+}
+
+impl CheckPacketTimeout {
+    pub async fn call(
+        self,
+        module: &Module,
+        voyager_client: &VoyagerClient,
+    ) -> RpcResult<Op<VoyagerMessage>> {
+        let packet = Packet {
+            source_channel_id: self.packet_send.packet.source_channel.channel_id,
+            destination_channel_id: self.packet_send.packet.destination_channel.channel_id,
+            data: self.packet_send.packet_data.clone().into(),
+            timeout_height: self.packet_send.packet.timeout_height,
+            timeout_timestamp: self.packet_send.packet.timeout_timestamp,
+        };
+        let batch_hash = keccak256(packet.abi_encode());
+
+        // query the timestamp before the height, such that the timestamp at the queried height is
+        // guaranteed to be >= the queried timestamp
+        let latest_timestamp = voyager_client
+            .query_latest_timestamp(module.chain_id.clone(), true)
+            .await?;
+        let latest_height = voyager_client
+            .query_latest_height(module.chain_id.clone(), true)
+            .await?;
+
+        let receipt = voyager_client
+            .query_ibc_state(
+                module.chain_id.clone(),
+                QueryHeight::Specific(latest_height),
+                BatchReceiptsPath {
+                    channel_id: packet.destination_channel_id,
+                    batch_hash,
+                },
+            )
+            .await?
+            .state;
+
+        if receipt.unwrap_or_default() != H256::default() {
+            debug!(%batch_hash, "packet has been received");
+
+            return Ok(noop());
+        }
+
+        if !is_timeout_provable(&packet, latest_height, &latest_timestamp) {
+            debug!(
+                %batch_hash,
+                %latest_height,
+                %latest_timestamp,
+                "packet has not yet timed out"
+            );
+
+            return Ok(seq([
+                defer(now() + module.packet_timeout_check_interval.as_secs()),
+                call(PluginMessage::new(
+                    module.plugin_name(),
+                    ModuleCall::from(self),
+                )),
+            ]));
+        }
+
+        let commitment = voyager_client
+            .query_ibc_state(
+                self.origin_chain_id.clone(),
+                QueryHeight::Latest,
+                BatchPacketsPath {
+                    channel_id: packet.source_channel_id,
+                    batch_hash,
+                },
+            )
+            .await?
+            .state;
+
+        if commitment.unwrap_or_default() == H256::default() {
+            info!(
+                %batch_hash,
+                "packet has timed out, but the commitment no longer \
+                exists on the origin chain"
+            );
+
+            return Ok(noop());
+        }
+
+        info!(
+            %batch_hash,
+            %latest_height,
+            %latest_timestamp,
+            origin_chain_id = %self.origin_chain_id,
+            "packet has timed out"
+        );
+
+        let first_seen_at: u64 = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+            .try_into()
+            .expect("how many milliseconds can there be man");
+
+        Ok(data(PluginMessage::new(
+            format!("{PLUGIN_NAME}/{}", self.origin_chain_id),
+            ModuleData::from(EventBatch::<IbcUnion> {
+                // the client on the origin chain tracking this chain
+                client_id: self.packet_send.packet.source_channel.connection.client_id,
+                events: vec![BatchableEvent {
+                    first_seen_at,
+                    // the timeout is provable at any height >= this height
+                    provable_height: latest_height,
+                    event: EventUnion::from(PacketTimeout {
+                        packet_send: self.packet_send,
+                    }),
+                }],
+            }),
+        )))
+    }
+}
+
+/// Check whether a [`BatchSend`] or [`BatchAcks`] has been committed on the origin chain.
+///
+/// - If the batch commitment exists, the batch is handed back to this plugin as a single event,
+///   provable at the height the commitment was read at.
+/// - If the commitment still doesn't exist after [`BATCH_COMMIT_MAX_CHECKS`] checks (for example if
+///   the commit transaction failed), the events in the batch are relayed individually instead.
+/// - Otherwise, this check is requeued after [`BATCH_COMMIT_CHECK_INTERVAL`].
+///
+/// [`BatchSend`]: crate::data::BatchSend
+/// [`BatchAcks`]: crate::data::BatchAcks
+#[model]
+pub struct CheckBatchCommitted {
+    /// The chain the batch was committed on.
+    pub origin_chain_id: ChainId,
+    /// The client on this chain tracking the origin chain.
+    pub client_id: ClientId,
+    /// Either a [`BatchSend`] or a [`BatchAcks`].
+    ///
+    /// [`BatchSend`]: crate::data::BatchSend
+    /// [`BatchAcks`]: crate::data::BatchAcks
+    pub batch: EventUnion,
+    pub checks: u32,
+}
+
+impl CheckBatchCommitted {
+    pub async fn call(
+        self,
+        module: &Module,
+        voyager_client: &VoyagerClient,
+    ) -> RpcResult<Op<VoyagerMessage>> {
+        let latest_height = voyager_client
+            .query_latest_height(self.origin_chain_id.clone(), true)
+            .await?;
+
+        let (batch_hash, commitment, events) = match &self.batch {
+            EventUnion::BatchSend(batch_send) => {
+                let batch_hash = batch_send.batch_hash();
+
+                let commitment = voyager_client
+                    .query_ibc_state(
+                        self.origin_chain_id.clone(),
+                        QueryHeight::Specific(latest_height),
+                        BatchPacketsPath {
+                            channel_id: batch_send.packet_sends[0].packet.source_channel.channel_id,
+                            batch_hash,
+                        },
+                    )
+                    .await?
+                    .state;
+
+                (
+                    batch_hash,
+                    commitment,
+                    batch_send
+                        .packet_sends
+                        .iter()
+                        .cloned()
+                        .map(EventUnion::from)
+                        .collect::<Vec<_>>(),
+                )
+            }
+            EventUnion::BatchAcks(batch_acks) => {
+                let batch_hash = batch_acks.batch_hash();
+
+                let commitment = voyager_client
+                    .query_ibc_state(
+                        self.origin_chain_id.clone(),
+                        QueryHeight::Specific(latest_height),
+                        BatchReceiptsPath {
+                            channel_id: batch_acks.write_acks[0]
+                                .packet
+                                .destination_channel
+                                .channel_id,
+                            batch_hash,
+                        },
+                    )
+                    .await?
+                    .state;
+
+                (
+                    batch_hash,
+                    commitment,
+                    batch_acks
+                        .write_acks
+                        .iter()
+                        .cloned()
+                        .map(EventUnion::from)
+                        .collect::<Vec<_>>(),
+                )
+            }
+            batch => {
+                return Err(ErrorObject::owned(
+                    FATAL_JSONRPC_ERROR_CODE,
+                    format!(
+                        "only packet batches can be committed, found {}",
+                        IbcUnion::event_name(batch)
+                    ),
+                    None::<()>,
+                ))
+            }
+        };
+
+        let first_seen_at: u64 = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis()
+            .try_into()
+            .expect("how many milliseconds can there be man");
+
+        if commitment.unwrap_or_default() != H256::default() {
+            info!(%batch_hash, %latest_height, "batch has been committed");
+
+            return Ok(data(PluginMessage::new(
+                module.plugin_name(),
+                ModuleData::from(EventBatch::<IbcUnion> {
+                    client_id: self.client_id,
+                    events: vec![BatchableEvent {
+                        first_seen_at,
+                        provable_height: latest_height,
+                        event: self.batch,
+                    }],
+                }),
+            )));
+        }
+
+        if self.checks + 1 >= BATCH_COMMIT_MAX_CHECKS {
+            warn!(
+                %batch_hash,
+                checks = self.checks + 1,
+                "batch has not been committed, relaying the events individually"
+            );
+
+            // the individual commitments of the events are still provable at any height >= the
+            // latest height
+            let (_, op) = mk_ready_ops(
+                self.client_id,
+                vec![(
+                    vec![],
+                    events
+                        .into_iter()
+                        .map(|event| BatchableEvent {
+                            first_seen_at,
+                            provable_height: latest_height,
+                            event,
+                        })
+                        .collect(),
+                )],
+                module,
+                voyager_client,
+            )
+            .await?;
+
+            return Ok(op);
+        }
+
+        debug!(%batch_hash, %latest_height, checks = self.checks + 1, "batch has not yet been committed");
+
+        Ok(seq([
+            defer(now() + BATCH_COMMIT_CHECK_INTERVAL.as_secs()),
+            call(PluginMessage::new(
+                module.plugin_name(),
+                ModuleCall::from(CheckBatchCommitted {
+                    checks: self.checks + 1,
+                    ..self
+                }),
+            )),
+        ]))
+    }
+}
@@ -1,9 +1,15 @@
+use alloy::sol_types::SolValue;
 use enumorph::Enumorph;
 use ibc_classic_spec::IbcClassic;
-use ibc_union_spec::IbcUnion;
+use ibc_solidity::Packet;
+use ibc_union_spec::{event::PacketMetadata, IbcUnion};
 use macros::model;
 use subset_of::SubsetOf;
-use unionlabs::ibc::core::client::height::Height;
+use unionlabs::{
+    ethereum::keccak256,
+    ibc::core::client::height::Height,
+    primitives::{Bytes, H256},
+};
 
 use crate::IbcSpecExt;
 
@@ -70,7 +76,6 @@ impl TryFrom<ibc_classic_spec::FullEvent> for EventClassic {
     }
 }
 
-// REVIEW: We probably want to add a way to have "a packet timed out" be a variant here as well
 /// A subset of [`FullEvent`], containing only events that cause an action on the counterparty chain.
 #[model]
 #[derive(Enumorph)]
@@ -83,8 +88,88 @@ pub enum EventUnion {
     ChannelOpenTry(ibc_union_spec::event::ChannelOpenTry),
     ChannelOpenAck(ibc_union_spec::event::ChannelOpenAck),
 
+    ChannelCloseInit(ibc_union_spec::event::ChannelCloseInit),
+
     PacketSend(ibc_union_spec::event::PacketSend),
     WriteAck(ibc_union_spec::event::WriteAck),
+
+    PacketTimeout(PacketTimeout),
+
+    BatchSend(BatchSend),
+    BatchAcks(BatchAcks),
+}
+
+/// A packet that was sent from this chain and has timed out on the counterparty chain without
+/// being received. This is not emitted on chain, it is produced by [`CheckPacketTimeout`] on the
+/// counterparty chain.
+///
+/// [`CheckPacketTimeout`]: crate::call::CheckPacketTimeout
+#[model]
+pub struct PacketTimeout {
+    /// The original send event of the packet.
+    pub packet_send: ibc_union_spec::event::PacketSend,
+}
+
+/// Packets sent from the counterparty chain over the same channel, that have been committed to as a
+/// whole on the counterparty chain with [`MsgBatchSend`]. All of the packets can then be received
+/// on this chain with a single proof.
+///
+/// [`MsgBatchSend`]: ibc_union_spec::datagram::MsgBatchSend
+#[model]
+pub struct BatchSend {
+    pub packet_sends: Vec<ibc_union_spec::event::PacketSend>,
+}
+
+/// Acknowledgements written on the counterparty chain for packets received over the same channel,
+/// that have been committed to as a whole on the counterparty chain with [`MsgBatchAcks`]. All of
+/// the acknowledgements can then be relayed to this chain with a single proof.
+///
+/// [`MsgBatchAcks`]: ibc_union_spec::datagram::MsgBatchAcks
+#[model]
+pub struct BatchAcks {
+    pub write_acks: Vec<ibc_union_spec::event::WriteAck>,
+}
+
+impl BatchSend {
+    pub fn packets(&self) -> Vec<Packet> {
+        self.packet_sends
+            .iter()
+            .map(|e| mk_packet(&e.packet_data, &e.packet))
+            .collect()
+    }
+
+    /// The hash the batch is committed under in [`BatchPacketsPath`].
+    ///
+    /// [`BatchPacketsPath`]: ibc_union_spec::path::BatchPacketsPath
+    pub fn batch_hash(&self) -> H256 {
+        keccak256(self.packets().abi_encode())
+    }
+}
+
+impl BatchAcks {
+    pub fn packets(&self) -> Vec<Packet> {
+        self.write_acks
+            .iter()
+            .map(|e| mk_packet(&e.packet_data, &e.packet))
+            .collect()
+    }
+
+    /// The hash the batch is committed under in [`BatchReceiptsPath`].
+    ///
+    /// [`BatchReceiptsPath`]: ibc_union_spec::path::BatchReceiptsPath
+    pub fn batch_hash(&self) -> H256 {
+        keccak256(self.packets().abi_encode())
+    }
+}
+
+fn mk_packet(packet_data: &Bytes, packet: &PacketMetadata) -> Packet {
+    Packet {
+        source_channel_id: packet.source_channel.channel_id,
+        destination_channel_id: packet.destination_channel.channel_id,
+        data: packet_data.clone().into(),
+        timeout_height: packet.timeout_height,
+        timeout_timestamp: packet.timeout_timestamp,
+    }
 }
 
 impl TryFrom<ibc_union_spec::event::FullEvent> for EventUnion {
@@ -104,6 +189,7 @@ impl TryFrom<ibc_union_spec::event::FullEvent> for EventUnion {
             ibc_union_spec::event::FullEvent::ChannelOpenInit(e) => Ok(Self::ChannelOpenInit(e)),
             ibc_union_spec::event::FullEvent::ChannelOpenTry(e) => Ok(Self::ChannelOpenTry(e)),
             ibc_union_spec::event::FullEvent::ChannelOpenAck(e) => Ok(Self::ChannelOpenAck(e)),
+            ibc_union_spec::event::FullEvent::ChannelCloseInit(e) => Ok(Self::ChannelCloseInit(e)),
             ibc_union_spec::event::FullEvent::PacketSend(e) => Ok(Self::PacketSend(e)),
             ibc_union_spec::event::FullEvent::WriteAck(e) => Ok(Self::WriteAck(e)),
             _ => Err(()),
@@ -12,6 +12,7 @@ use futures::{stream::FuturesOrdered, StreamExt, TryStreamExt};
 use ibc_classic_spec::IbcClassic;
 use ibc_solidity::Packet;
 use ibc_union_spec::{
+    event::FullEvent,
     types::{Channel, ChannelState},
     IbcUnion,
 };
@@ -38,19 +39,25 @@ use unionlabs::{
     DELAY_PERIOD,
 };
 use voyager_message::{
-    call::WaitForHeight,
-    core::{ChainId, IbcSpec, QueryHeight},
+    call::{SubmitTx, WaitForHeight},
+    core::{ChainId, IbcSpec, QueryHeight, Timestamp},
     data::{ChainEvent, Data, IbcDatagram},
     module::{PluginInfo, PluginServer},
     DefaultCmd, ExtensionsExt, Plugin, PluginMessage, RawClientId, VoyagerClient, VoyagerMessage,
     FATAL_JSONRPC_ERROR_CODE,
 };
-use voyager_vm::{call, data, pass::PassResult, seq, BoxDynError, Op};
+use voyager_vm::{call, conc, data, defer, noop, now, pass::PassResult, seq, BoxDynError, Op};
 
 use crate::{
-    call::{MakeMsg, MakeTransactionBatchesWithUpdate, ModuleCall},
+    call::{
+        CheckBatchCommitted, CheckPacketTimeout, MakeMsg, MakeTransactionBatchesWithUpdate,
+        ModuleCall,
+    },
     callback::ModuleCallback,
-    data::{BatchableEvent, EventBatch, EventClassic, EventUnion, ModuleData},
+    data::{
+        BatchAcks, BatchSend, BatchableEvent, EventBatch, EventClassic, EventUnion, ModuleData,
+        PacketTimeout,
+    },
 };
 
 pub mod call;
@@ -66,6 +73,8 @@ async fn main() {
 pub struct Module {
     pub chain_id: ChainId,
     pub client_configs: ClientConfigs,
+    pub packet_timeout_check_interval: Duration,
+    pub commit_packet_batches: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -79,6 +88,19 @@ pub enum ClientConfigs {
 pub struct Config {
     pub chain_id: ChainId,
     pub client_configs: ClientConfigsSerde,
+    /// How often to check whether packets sent to this chain have timed out without being
+    /// received.
+    #[serde(default = "default_packet_timeout_check_interval")]
+    pub packet_timeout_check_interval: Duration,
+    /// Commit to batches of packets and acknowledgements on the counterparty chain (with
+    /// `MsgBatchSend` and `MsgBatchAcks`) before relaying them to this chain, so that each batch
+    /// only needs a single proof. This is worth it when verifying proofs on this chain is expensive.
+    #[serde(default)]
+    pub commit_packet_batches: bool,
+}
+
+fn default_packet_timeout_check_interval() -> Duration {
+    Duration::from_secs(60)
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -164,8 +186,12 @@ impl IbcSpecExt for IbcUnion {
             EventUnion::ChannelOpenInit(_) => "channel_open_init",
             EventUnion::ChannelOpenTry(_) => "channel_open_try",
             EventUnion::ChannelOpenAck(_) => "channel_open_ack",
+            EventUnion::ChannelCloseInit(_) => "channel_close_init",
             EventUnion::PacketSend(_) => "packet_send",
             EventUnion::WriteAck(_) => "write_ack",
+            EventUnion::PacketTimeout(_) => "packet_timeout",
+            EventUnion::BatchSend(_) => "batch_send",
+            EventUnion::BatchAcks(_) => "batch_acks",
         }
     }
 }
@@ -280,6 +306,9 @@ if ."@type" == "data" then
         ) or (
             $event_type == "channel_open_ack"
             and ($event_data.connection.counterparty_client_id as $client_id | {clients_filter})
+        ) or (
+            $event_type == "channel_close_init"
+            and ($event_data.connection.counterparty_client_id as $client_id | {clients_filter})
         ) or (
             $event_type == "packet_send"
             and ($event_data.packet.destination_channel.connection.client_id as $client_id | {clients_filter})
@@ -289,9 +318,15 @@ if ."@type" == "data" then
         ) or ($data."@type" == "plugin"
             and $data."@value".plugin == "{plugin_name}"
             and $data."@value".message."@type" == "event_batch")
+    # events handed off from the transaction batch plugins of other chains (i.e. packet timeouts)
+    elif $data."@type" == "plugin" and $data."@value".plugin == "{plugin_name}" then
+        true
     else
         false
     end
+# batches of events ready to be relayed are time sensitive (i.e. packet timeouts)
+elif ."@type" == "call" and ."@value"."@type" == "plugin" and ."@value"."@value".plugin == "{plugin_name}" then
+    {{"priority": 10}}
 else
     false
 end
@@ -312,6 +347,13 @@ end
 
 pub const PLUGIN_NAME: &str = env!("CARGO_PKG_NAME");
 
+/// How often to check whether a packet batch has been committed on the counterparty chain.
+pub const BATCH_COMMIT_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How many times to check whether a packet batch has been committed on the counterparty chain
+/// before relaying the packets in the batch individually.
+pub const BATCH_COMMIT_MAX_CHECKS: u32 = 30;
+
 impl Module {
     fn plugin_name(&self) -> String {
         format!("{PLUGIN_NAME}/{}", self.chain_id)
@@ -324,6 +366,8 @@ impl Module {
         Self {
             chain_id: config.chain_id,
             client_configs: ClientConfigs::new(config.client_configs),
+            packet_timeout_check_interval: config.packet_timeout_check_interval,
+            commit_packet_batches: config.commit_packet_batches,
         }
     }
 }
@@ -352,6 +396,12 @@ impl PluginServer<ModuleCall, ModuleCallback> for Module {
             ModuleCall::MakeMsgUnion(make_msg_union) => {
                 do_make_msg_union(voyager_client, make_msg_union).await
             }
+            ModuleCall::CheckPacketTimeout(check_packet_timeout) => {
+                check_packet_timeout.call(self, voyager_client).await
+            }
+            ModuleCall::CheckBatchCommitted(check_batch_committed) => {
+                check_batch_committed.call(self, voyager_client).await
+            }
         }
     }
 
@@ -757,6 +807,40 @@ async fn do_make_msg_union(
             )))
         }
 
+        EventUnion::ChannelCloseInit(event) => {
+            let proof_init = voyager_client
+                .query_ibc_proof(
+                    origin_chain_id,
+                    QueryHeight::Specific(origin_chain_proof_height),
+                    ibc_union_spec::path::ChannelPath {
+                        channel_id: event.channel_id,
+                    },
+                )
+                .await?;
+
+            let client_info = voyager_client
+                .client_info::<IbcUnion>(target_chain_id, event.connection.counterparty_client_id)
+                .await?;
+
+            let encoded_proof_init = voyager_client
+                .encode_proof::<IbcUnion>(
+                    client_info.client_type,
+                    client_info.ibc_interface,
+                    proof_init.proof,
+                )
+                .await?;
+
+            Ok(data(IbcDatagram::new::<IbcUnion>(
+                ibc_union_spec::datagram::Datagram::from(
+                    ibc_union_spec::datagram::MsgChannelCloseConfirm {
+                        channel_id: event.counterparty_channel_id,
+                        proof_init: encoded_proof_init,
+                        proof_height: origin_chain_proof_height.height(),
+                    },
+                ),
+            )))
+        }
+
         EventUnion::PacketSend(event) => {
             let packet = Packet {
                 source_channel_id: event.packet.source_channel.channel_id,
@@ -765,12 +849,110 @@ async fn do_make_msg_union(
                 timeout_height: event.packet.timeout_height,
                 timeout_timestamp: event.packet.timeout_timestamp,
             };
+
+            // receiving a timed out packet will fail, causing the entire batch to fail. the timeout
+            // will instead be relayed back to the origin chain by `CheckPacketTimeout`.
+            let (target_latest_height, target_latest_timestamp) =
+                query_latest_height_and_timestamp(voyager_client, &target_chain_id).await?;
+
+            if is_timed_out(&packet, target_latest_height, &target_latest_timestamp) {
+                info!(
+                    %target_latest_height,
+                    %target_latest_timestamp,
+                    timeout_height = packet.timeout_height,
+                    timeout_timestamp = packet.timeout_timestamp,
+                    "packet has timed out on the target chain, not relaying"
+                );
+
+                return Ok(noop());
+            }
+
+            mk_packet_recv(
+                voyager_client,
+                origin_chain_id,
+                origin_chain_proof_height,
+                target_chain_id,
+                event.packet.destination_channel.connection.client_id,
+                vec![packet],
+            )
+            .await
+            .map(data)
+        }
+
+        EventUnion::BatchSend(batch_send) => {
+            let client_id = batch_send.packet_sends[0]
+                .packet
+                .destination_channel
+                .connection
+                .client_id;
+
+            let packets = batch_send.packets();
+
+            let (target_latest_height, target_latest_timestamp) =
+                query_latest_height_and_timestamp(voyager_client, &target_chain_id).await?;
+
+            let (timed_out, packets): (Vec<_>, Vec<_>) = packets.into_iter().partition(|packet| {
+                is_timed_out(packet, target_latest_height, &target_latest_timestamp)
+            });
+
+            if timed_out.is_empty() {
+                return mk_packet_recv(
+                    voyager_client,
+                    origin_chain_id,
+                    origin_chain_proof_height,
+                    target_chain_id,
+                    client_id,
+                    packets,
+                )
+                .await
+                .map(data);
+            }
+
+            // the batch commitment can only be used to receive all of the packets in the batch at
+            // once, so fall back to proving the commitments of the remaining packets individually
+            info!(
+                %target_latest_height,
+                %target_latest_timestamp,
+                timed_out = timed_out.len(),
+                remaining = packets.len(),
+                "packets in batch have timed out on the target chain, relaying the \
+                remaining packets individually"
+            );
+
+            Ok(conc(
+                packets
+                    .into_iter()
+                    .map(|packet| {
+                        mk_packet_recv(
+                            voyager_client,
+                            origin_chain_id.clone(),
+                            origin_chain_proof_height,
+                            target_chain_id.clone(),
+                            client_id,
+                            vec![packet],
+                        )
+                    })
+                    .collect::<FuturesOrdered<_>>()
+                    .map_ok(data)
+                    .try_collect::<Vec<_>>()
+                    .await?,
+            ))
+        }
+
+        EventUnion::WriteAck(event) => {
+            let packet = Packet {
+                source_channel_id: event.packet.source_channel.channel_id,
+                destination_channel_id: event.packet.destination_channel.channel_id,
+                data: event.packet_data.into(),
+                timeout_height: event.packet.timeout_height,
+                timeout_timestamp: event.packet.timeout_timestamp,
+            };
             let proof_try = voyager_client
                 .query_ibc_proof(
                     origin_chain_id,
                     QueryHeight::Specific(origin_chain_proof_height),
-                    ibc_union_spec::path::BatchPacketsPath {
-                        channel_id: event.packet.source_channel.channel_id,
+                    ibc_union_spec::path::BatchReceiptsPath {
+                        channel_id: event.packet.destination_channel.channel_id,
                         batch_hash: keccak256(packet.abi_encode()),
                     },
                 )
@@ -779,7 +961,7 @@ async fn do_make_msg_union(
             let client_info = voyager_client
                 .client_info::<IbcUnion>(
                     target_chain_id,
-                    event.packet.destination_channel.connection.client_id,
+                    event.packet.source_channel.connection.client_id,
                 )
                 .await?;
 
@@ -792,16 +974,63 @@ async fn do_make_msg_union(
                 .await?;
 
             Ok(data(IbcDatagram::new::<IbcUnion>(
-                ibc_union_spec::datagram::Datagram::from(ibc_union_spec::datagram::MsgPacketRecv {
-                    packets: vec![packet.into()],
-                    relayer_msgs: vec![vec![].into()],
-                    proof: encoded_proof_commitment,
-                    proof_height: origin_chain_proof_height.height(),
-                }),
+                ibc_union_spec::datagram::Datagram::from(
+                    ibc_union_spec::datagram::MsgPacketAcknowledgement {
+                        packets: vec![packet.into()],
+                        acknowledgements: vec![event.acknowledgement],
+                        proof: encoded_proof_commitment,
+                        proof_height: origin_chain_proof_height.height(),
+                    },
+                ),
             )))
         }
 
-        EventUnion::WriteAck(event) => {
+        EventUnion::BatchAcks(batch_acks) => {
+            let write_ack = &batch_acks.write_acks[0];
+
+            let proof_acks = voyager_client
+                .query_ibc_proof(
+                    origin_chain_id,
+                    QueryHeight::Specific(origin_chain_proof_height),
+                    ibc_union_spec::path::BatchReceiptsPath {
+                        channel_id: write_ack.packet.destination_channel.channel_id,
+                        batch_hash: batch_acks.batch_hash(),
+                    },
+                )
+                .await?;
+
+            let client_info = voyager_client
+                .client_info::<IbcUnion>(
+                    target_chain_id,
+                    write_ack.packet.source_channel.connection.client_id,
+                )
+                .await?;
+
+            let encoded_proof_acks = voyager_client
+                .encode_proof::<IbcUnion>(
+                    client_info.client_type,
+                    client_info.ibc_interface,
+                    proof_acks.proof,
+                )
+                .await?;
+
+            Ok(data(IbcDatagram::new::<IbcUnion>(
+                ibc_union_spec::datagram::Datagram::from(
+                    ibc_union_spec::datagram::MsgPacketAcknowledgement {
+                        packets: batch_acks.packets().into_iter().map(Into::into).collect(),
+                        acknowledgements: batch_acks
+                            .write_acks
+                            .into_iter()
+                            .map(|write_ack| write_ack.acknowledgement)
+                            .collect(),
+                        proof: encoded_proof_acks,
+                        proof_height: origin_chain_proof_height.height(),
+                    },
+                ),
+            )))
+        }
+
+        EventUnion::PacketTimeout(PacketTimeout { packet_send: event }) => {
             let packet = Packet {
                 source_channel_id: event.packet.source_channel.channel_id,
                 destination_channel_id: event.packet.destination_channel.channel_id,
@@ -809,7 +1038,10 @@ async fn do_make_msg_union(
                 timeout_height: event.packet.timeout_height,
                 timeout_timestamp: event.packet.timeout_timestamp,
             };
-            let proof_try = voyager_client
+
+            // the origin chain here is the destination of the packet, so this is a proof of the
+            // absence of the receipt
+            let proof_unreceived = voyager_client
                 .query_ibc_proof(
                     origin_chain_id,
                     QueryHeight::Specific(origin_chain_proof_height),
@@ -827,20 +1059,19 @@ async fn do_make_msg_union(
                 )
                 .await?;
 
-            let encoded_proof_commitment = voyager_client
+            let encoded_proof_unreceived = voyager_client
                 .encode_proof::<IbcUnion>(
                     client_info.client_type,
                     client_info.ibc_interface,
-                    proof_try.proof,
+                    proof_unreceived.proof,
                 )
                 .await?;
 
             Ok(data(IbcDatagram::new::<IbcUnion>(
                 ibc_union_spec::datagram::Datagram::from(
-                    ibc_union_spec::datagram::MsgPacketAcknowledgement {
-                        packets: vec![packet.into()],
-                        acknowledgements: vec![event.acknowledgement],
-                        proof: encoded_proof_commitment,
+                    ibc_union_spec::datagram::MsgPacketTimeout {
+                        packet: packet.into(),
+                        proof: encoded_proof_unreceived,
                         proof_height: origin_chain_proof_height.height(),
                     },
                 ),
@@ -849,6 +1080,102 @@ async fn do_make_msg_union(
     }
 }
 
+/// Builds a [`MsgPacketRecv`] for `packets`, proving their commitment on the origin chain. A single
+/// packet is proven by its own commitment, multiple packets by the commitment of the batch.
+///
+/// [`MsgPacketRecv`]: ibc_union_spec::datagram::MsgPacketRecv
+async fn mk_packet_recv(
+    voyager_client: &VoyagerClient,
+    origin_chain_id: ChainId,
+    origin_chain_proof_height: Height,
+    target_chain_id: ChainId,
+    client_id: ibc_union_spec::types::ClientId,
+    packets: Vec<Packet>,
+) -> RpcResult<IbcDatagram> {
+    let batch_hash = match &*packets {
+        [packet] => keccak256(packet.abi_encode()),
+        packets => keccak256(packets.abi_encode()),
+    };
+
+    let proof_commitment = voyager_client
+        .query_ibc_proof(
+            origin_chain_id,
+            QueryHeight::Specific(origin_chain_proof_height),
+            ibc_union_spec::path::BatchPacketsPath {
+                channel_id: packets[0].source_channel_id,
+                batch_hash,
+            },
+        )
+        .await?;
+
+    let client_info = voyager_client
+        .client_info::<IbcUnion>(target_chain_id, client_id)
+        .await?;
+
+    let encoded_proof_commitment = voyager_client
+        .encode_proof::<IbcUnion>(
+            client_info.client_type,
+            client_info.ibc_interface,
+            proof_commitment.proof,
+        )
+        .await?;
+
+    Ok(IbcDatagram::new::<IbcUnion>(
+        ibc_union_spec::datagram::Datagram::from(ibc_union_spec::datagram::MsgPacketRecv {
+            relayer_msgs: vec![vec![].into(); packets.len()],
+            packets: packets.into_iter().map(Into::into).collect(),
+            proof: encoded_proof_commitment,
+            proof_height: origin_chain_proof_height.height(),
+        }),
+    ))
+}
+
+async fn query_latest_height_and_timestamp(
+    voyager_client: &VoyagerClient,
+    chain_id: &ChainId,
+) -> RpcResult<(Height, Timestamp)> {
+    let latest_timestamp = voyager_client
+        .query_latest_timestamp(chain_id.clone(), false)
+        .await?;
+    let latest_height = voyager_client
+        .query_latest_height(chain_id.clone(), false)
+        .await?;
+
+    Ok((latest_height, latest_timestamp))
+}
+
+/// Whether each of the non-zero timeouts of `packet` has been reached on the target chain.
+fn reached_timeouts(
+    packet: &Packet,
+    latest_height: Height,
+    latest_timestamp: &Timestamp,
+) -> impl Iterator<Item = bool> {
+    [
+        (packet.timeout_height != 0).then(|| latest_height.height() >= packet.timeout_height),
+        (packet.timeout_timestamp != 0)
+            .then(|| latest_timestamp.as_nanos() >= packet.timeout_timestamp),
+    ]
+    .into_iter()
+    .flatten()
+}
+
+/// Whether `packet` can no longer be received on the target chain, i.e. any of its timeouts has
+/// been reached.
+fn is_timed_out(packet: &Packet, latest_height: Height, latest_timestamp: &Timestamp) -> bool {
+    reached_timeouts(packet, latest_height, latest_timestamp).any(|reached| reached)
+}
+
+/// Whether the timeout of `packet` can be proven on the origin chain. The core contracts only
+/// accept a timeout once every non-zero timeout has been reached.
+fn is_timeout_provable(
+    packet: &Packet,
+    latest_height: Height,
+    latest_timestamp: &Timestamp,
+) -> bool {
+    let mut reached_timeouts = reached_timeouts(packet, latest_height, latest_timestamp).peekable();
+    reached_timeouts.peek().is_some() && reached_timeouts.all(|reached| reached)
+}
+
 async fn do_make_msg_v1(
     voyager_client: &VoyagerClient,
     MakeMsg {
@@ -1153,6 +1480,7 @@ impl Module {
             let mut batchers_v1 =
                 HashMap::<ClientId, Vec<(usize, BatchableEvent<IbcClassic>)>>::new();
             let mut batchers_union = HashMap::<u32, Vec<(usize, BatchableEvent<IbcUnion>)>>::new();
+            let mut packet_timeout_checks = vec![];
 
             for (idx, msg) in msgs.into_iter().enumerate() {
                 let Op::Data(msg) = msg else {
@@ -1201,6 +1529,23 @@ impl Module {
 
                             trace!(%client_id, "batching event");
 
+                            // start watching for a timeout of the packet on this chain
+                            if let FullEvent::PacketSend(packet_send) = &full_ibc_event {
+                                packet_timeout_checks.push((
+                                    vec![idx],
+                                    seq([
+                                        defer(now() + self.packet_timeout_check_interval.as_secs()),
+                                        call(PluginMessage::new(
+                                            self.plugin_name(),
+                                            ModuleCall::from(CheckPacketTimeout {
+                                                origin_chain_id: chain_event.chain_id.clone(),
+                                                packet_send: packet_send.clone(),
+                                            }),
+                                        )),
+                                    ]),
+                                ));
+                            }
+
                             batchers_union.entry(client_id).or_default().push((
                                 idx,
                                 BatchableEvent {
@@ -1256,6 +1601,30 @@ impl Module {
                 .flat_map(|(client_id, events)| split_ready(client_id, events, self))
                 .partition_map::<Vec<_>, Vec<_>, _, _, _>(convert::identity);
 
+            // packet sends and write acks from the same channel are first committed to as a batch on
+            // the counterparty chain, and then relayed with a single proof once the commitment
+            // exists (see `CheckBatchCommitted`)
+            let (ready_union, packet_batches) = if self.commit_packet_batches {
+                ready_union.into_iter().fold(
+                    (vec![], vec![]),
+                    |(mut ready, mut packet_batches), (client_id, (idxs, events))| {
+                        let (batches, events) = split_packet_batches(events);
+
+                        if !batches.is_empty() {
+                            packet_batches.push((client_id, idxs.clone(), batches));
+                        }
+
+                        if !events.is_empty() {
+                            ready.push((client_id, (idxs, events)));
+                        }
+
+                        (ready, packet_batches)
+                    },
+                )
+            } else {
+                (ready_union, vec![])
+            };
+
             let voyager_client = e.try_get::<VoyagerClient>()?;
 
             let ready_v1 = ready_v1
@@ -1272,12 +1641,27 @@ impl Module {
                 .map(|(client_id, events)| mk_ready_ops(client_id, events, self, voyager_client))
                 .collect::<FuturesOrdered<_>>();
 
+            let packet_batches = packet_batches
+                .into_iter()
+                .map(|(client_id, idxs, batches)| {
+                    mk_commit_batch_ops(client_id, idxs, batches, self, voyager_client)
+                })
+                .collect::<FuturesOrdered<_>>();
+
             Ok(PassResult {
                 optimize_further: optimize_further_v1
                     .into_iter()
                     .chain(optimize_further_union)
                     .collect(),
-                ready: ready_v1.chain(ready_union).map(|x| x).try_collect().await?,
+                ready: ready_v1
+                    .chain(ready_union)
+                    .chain(packet_batches)
+                    .map(|x| x)
+                    .try_collect::<Vec<_>>()
+                    .await?
+                    .into_iter()
+                    .chain(packet_timeout_checks)
+                    .collect(),
             })
         })
     }
@@ -1521,6 +1905,135 @@ where
     ))
 }
 
+/// Groups the packet sends and write acks in `events` by channel. Groups of more than one event are
+/// combined into a [`BatchSend`] or [`BatchAcks`], along with the datagram that commits to the batch
+/// on the chain the events were emitted on. All other events are returned as is.
+#[allow(clippy::type_complexity)]
+fn split_packet_batches(
+    events: Vec<BatchableEvent<IbcUnion>>,
+) -> (
+    Vec<(ibc_union_spec::datagram::Datagram, EventUnion)>,
+    Vec<BatchableEvent<IbcUnion>>,
+) {
+    let mut packet_sends = HashMap::<_, Vec<_>>::new();
+    let mut write_acks = HashMap::<_, Vec<_>>::new();
+    let mut rest = vec![];
+
+    for event in events {
+        match event.event {
+            EventUnion::PacketSend(packet_send) => packet_sends
+                .entry(packet_send.packet.source_channel.channel_id)
+                .or_default()
+                .push((event.first_seen_at, event.provable_height, packet_send)),
+            EventUnion::WriteAck(write_ack) => write_acks
+                .entry(write_ack.packet.destination_channel.channel_id)
+                .or_default()
+                .push((event.first_seen_at, event.provable_height, write_ack)),
+            other => rest.push(BatchableEvent {
+                event: other,
+                ..event
+            }),
+        }
+    }
+
+    let mut batches = vec![];
+
+    for (source_channel, packet_sends) in packet_sends {
+        if let [(first_seen_at, provable_height, packet_send)] = &*packet_sends {
+            rest.push(BatchableEvent {
+                first_seen_at: *first_seen_at,
+                provable_height: *provable_height,
+                event: packet_send.clone().into(),
+            });
+        } else {
+            let batch_send = BatchSend {
+                packet_sends: packet_sends.into_iter().map(|(_, _, e)| e).collect(),
+            };
+
+            batches.push((
+                ibc_union_spec::datagram::MsgBatchSend {
+                    source_channel,
+                    packets: batch_send.packets().into_iter().map(Into::into).collect(),
+                }
+                .into(),
+                batch_send.into(),
+            ));
+        }
+    }
+
+    for (source_channel, write_acks) in write_acks {
+        if let [(first_seen_at, provable_height, write_ack)] = &*write_acks {
+            rest.push(BatchableEvent {
+                first_seen_at: *first_seen_at,
+                provable_height: *provable_height,
+                event: write_ack.clone().into(),
+            });
+        } else {
+            let batch_acks = BatchAcks {
+                write_acks: write_acks.into_iter().map(|(_, _, e)| e).collect(),
+            };
+
+            batches.push((
+                ibc_union_spec::datagram::MsgBatchAcks {
+                    source_channel,
+                    packets: batch_acks.packets().into_iter().map(Into::into).collect(),
+                    acks: batch_acks
+                        .write_acks
+                        .iter()
+                        .map(|write_ack| write_ack.acknowledgement.clone())
+                        .collect(),
+                }
+                .into(),
+                batch_acks.into(),
+            ));
+        }
+    }
+
+    (batches, rest)
+}
+
+/// Submits the commitments for `batches` to the counterparty chain, and then waits for them to be
+/// committed before relaying them to this chain.
+async fn mk_commit_batch_ops(
+    client_id: ibc_union_spec::types::ClientId,
+    idxs: Vec<usize>,
+    batches: Vec<(ibc_union_spec::datagram::Datagram, EventUnion)>,
+    module: &Module,
+    voyager_client: &VoyagerClient,
+) -> RpcResult<(Vec<usize>, Op<VoyagerMessage>)> {
+    let client_meta = voyager_client
+        .client_meta::<IbcUnion>(module.chain_id.clone(), QueryHeight::Latest, client_id)
+        .await?;
+
+    info!(
+        %client_id,
+        counterparty_chain_id = %client_meta.chain_id,
+        batches = batches.len(),
+        "committing packet batches"
+    );
+
+    Ok((
+        idxs,
+        conc(batches.into_iter().map(|(datagram, batch)| {
+            seq([
+                call(SubmitTx {
+                    chain_id: client_meta.chain_id.clone(),
+                    datagrams: vec![IbcDatagram::new::<IbcUnion>(datagram)],
+                }),
+                call(PluginMessage::new(
+                    module.plugin_name(),
+                    ModuleCall::from(CheckBatchCommitted {
+                        origin_chain_id: client_meta.chain_id.clone(),
+                        client_id,
+                        batch,
+                        checks: 0,
+                    }),
+                )),
+            ])
+        })),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use serde_json::json;
@@ -1543,4 +2056,288 @@ mod tests {
 
         let _config = serde_json::from_value::<Config>(config_json).unwrap();
     }
+
+    fn packet_metadata(
+        source_channel_id: u32,
+        destination_channel_id: u32,
+        timeout_timestamp: u64,
+    ) -> ibc_union_spec::event::PacketMetadata {
+        ibc_union_spec::event::PacketMetadata {
+            source_channel: ibc_union_spec::event::ChannelMetadata {
+                channel_id: source_channel_id,
+                version: "version".into(),
+                connection: ibc_union_spec::event::ConnectionMetadata {
+                    client_id: 1,
+                    connection_id: 1,
+                },
+            },
+            destination_channel: ibc_union_spec::event::ChannelMetadata {
+                channel_id: destination_channel_id,
+                version: "version".into(),
+                connection: ibc_union_spec::event::ConnectionMetadata {
+                    client_id: 2,
+                    connection_id: 2,
+                },
+            },
+            timeout_height: 0,
+            timeout_timestamp,
+        }
+    }
+
+    fn packet_send(source_channel_id: u32, timeout_timestamp: u64) -> BatchableEvent<IbcUnion> {
+        BatchableEvent {
+            first_seen_at: timeout_timestamp,
+            provable_height: Height::new(10),
+            event: ibc_union_spec::event::PacketSend {
+                packet_data: b"data".into(),
+                packet: packet_metadata(source_channel_id, 100, timeout_timestamp),
+            }
+            .into(),
+        }
+    }
+
+    fn write_ack(destination_channel_id: u32, timeout_timestamp: u64) -> BatchableEvent<IbcUnion> {
+        BatchableEvent {
+            first_seen_at: timeout_timestamp,
+            provable_height: Height::new(10),
+            event: ibc_union_spec::event::WriteAck {
+                packet_data: b"data".into(),
+                packet: packet_metadata(100, destination_channel_id, timeout_timestamp),
+                acknowledgement: timeout_timestamp.to_be_bytes().into(),
+            }
+            .into(),
+        }
+    }
+
+    #[test]
+    fn split_packet_batches_empty() {
+        let (batches, rest) = split_packet_batches(vec![]);
+
+        assert!(batches.is_empty());
+        assert!(rest.is_empty());
+    }
+
+    #[test]
+    fn split_packet_batches_single_events_are_not_batched() {
+        let events = vec![packet_send(1, 1), write_ack(2, 2), packet_send(3, 3)];
+
+        let (batches, mut rest) = split_packet_batches(events.clone());
+
+        assert!(batches.is_empty());
+
+        rest.sort_by_key(|event| event.first_seen_at);
+        assert_eq!(rest, events);
+    }
+
+    #[test]
+    fn split_packet_batches_other_events_are_passed_through() {
+        let timeout = BatchableEvent::<IbcUnion> {
+            first_seen_at: 1,
+            provable_height: Height::new(10),
+            event: PacketTimeout {
+                packet_send: ibc_union_spec::event::PacketSend {
+                    packet_data: b"data".into(),
+                    packet: packet_metadata(1, 100, 1),
+                },
+            }
+            .into(),
+        };
+
+        let (batches, rest) = split_packet_batches(vec![timeout.clone()]);
+
+        assert!(batches.is_empty());
+        assert_eq!(rest, vec![timeout]);
+    }
+
+    #[test]
+    fn split_packet_batches_packet_sends() {
+        let events = vec![
+            packet_send(1, 1),
+            packet_send(1, 2),
+            packet_send(2, 3),
+            packet_send(1, 4),
+        ];
+
+        let (batches, rest) = split_packet_batches(events.clone());
+
+        assert_eq!(rest, vec![events[2].clone()]);
+
+        let [(datagram, batch)] = &*batches else {
+            panic!("expected exactly one batch, found {batches:?}");
+        };
+
+        let ibc_union_spec::datagram::Datagram::BatchSend(msg) = datagram else {
+            panic!("expected a batch send, found {datagram:?}");
+        };
+        assert_eq!(msg.source_channel, 1);
+        assert_eq!(
+            msg.packets
+                .iter()
+                .map(|packet| packet.timeout_timestamp)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 4]
+        );
+
+        let EventUnion::BatchSend(batch_send) = batch else {
+            panic!("expected a batch send event, found {batch:?}");
+        };
+        assert_eq!(batch_send.packet_sends.len(), 3);
+    }
+
+    #[test]
+    fn split_packet_batches_write_acks() {
+        let events = vec![
+            write_ack(5, 1),
+            packet_send(5, 2),
+            write_ack(5, 3),
+            write_ack(6, 4),
+            write_ack(6, 5),
+        ];
+
+        let (mut batches, rest) = split_packet_batches(events.clone());
+
+        // a single packet send on channel 5 is not batched with the write acks on channel 5
+        assert_eq!(rest, vec![events[1].clone()]);
+
+        batches.sort_by_key(|(datagram, _)| match datagram {
+            ibc_union_spec::datagram::Datagram::BatchAcks(msg) => msg.source_channel,
+            _ => panic!("expected batch acks, found {datagram:?}"),
+        });
+
+        let acks = batches
+            .iter()
+            .map(|(datagram, _)| match datagram {
+                ibc_union_spec::datagram::Datagram::BatchAcks(msg) => {
+                    assert_eq!(msg.packets.len(), msg.acks.len());
+                    (msg.source_channel, msg.acks.clone())
+                }
+                _ => unreachable!(),
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            acks,
+            vec![
+                (
+                    5,
+                    vec![1_u64.to_be_bytes().into(), 3_u64.to_be_bytes().into()]
+                ),
+                (
+                    6,
+                    vec![4_u64.to_be_bytes().into(), 5_u64.to_be_bytes().into()]
+                ),
+            ]
+        );
+
+        assert!(batches.iter().all(
+            |(_, batch)| matches!(batch, EventUnion::BatchAcks(b) if b.write_acks.len() == 2)
+        ));
+    }
+
+    fn packet(timeout_height: u64, timeout_timestamp: u64) -> Packet {
+        Packet {
+            source_channel_id: 1,
+            destination_channel_id: 2,
+            data: Default::default(),
+            timeout_height,
+            timeout_timestamp,
+        }
+    }
+
+    #[test]
+    fn timeout_height_only() {
+        let packet = packet(10, 0);
+        let timestamp = Timestamp::from_nanos(u64::MAX);
+
+        assert!(!is_timed_out(&packet, Height::new(9), &timestamp));
+        assert!(!is_timeout_provable(&packet, Height::new(9), &timestamp));
+
+        assert!(is_timed_out(&packet, Height::new(10), &timestamp));
+        assert!(is_timeout_provable(&packet, Height::new(10), &timestamp));
+    }
+
+    #[test]
+    fn timeout_timestamp_only() {
+        let packet = packet(0, 100);
+        let height = Height::new(u64::MAX);
+
+        assert!(!is_timed_out(&packet, height, &Timestamp::from_nanos(99)));
+        assert!(!is_timeout_provable(
+            &packet,
+            height,
+            &Timestamp::from_nanos(99)
+        ));
+
+        assert!(is_timed_out(&packet, height, &Timestamp::from_nanos(100)));
+        assert!(is_timeout_provable(
+            &packet,
+            height,
+            &Timestamp::from_nanos(100)
+        ));
+    }
+
+    #[test]
+    fn timeout_height_and_timestamp() {
+        let packet = packet(10, 100);
+
+        assert!(!is_timed_out(
+            &packet,
+            Height::new(9),
+            &Timestamp::from_nanos(99)
+        ));
+        assert!(!is_timeout_provable(
+            &packet,
+            Height::new(9),
+            &Timestamp::from_nanos(99)
+        ));
+
+        // the packet can't be received anymore, but the timeout can't be proven yet either
+        assert!(is_timed_out(
+            &packet,
+            Height::new(10),
+            &Timestamp::from_nanos(99)
+        ));
+        assert!(!is_timeout_provable(
+            &packet,
+            Height::new(10),
+            &Timestamp::from_nanos(99)
+        ));
+        assert!(is_timed_out(
+            &packet,
+            Height::new(9),
+            &Timestamp::from_nanos(100)
+        ));
+        assert!(!is_timeout_provable(
+            &packet,
+            Height::new(9),
+            &Timestamp::from_nanos(100)
+        ));
+
+        assert!(is_timed_out(
+            &packet,
+            Height::new(10),
+            &Timestamp::from_nanos(100)
+        ));
+        assert!(is_timeout_provable(
+            &packet,
+            Height::new(10),
+            &Timestamp::from_nanos(100)
+        ));
+    }
+
+    #[test]
+    fn timeout_not_set() {
+        let packet = packet(0, 0);
+
+        assert!(!is_timed_out(
+            &packet,
+            Height::new(u64::MAX),
+            &Timestamp::from_nanos(u64::MAX)
+        ));
+        assert!(!is_timeout_provable(
+            &packet,
+            Height::new(u64::MAX),
+            &Timestamp::from_nanos(u64::MAX)
+        ));
+    }
 }
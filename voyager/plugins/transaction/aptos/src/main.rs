@@ -7,13 +7,13 @@ use aptos_types::{
     transaction::{EntryFunction, RawTransaction},
 };
 use chain_utils::{
-    keyring::{ConcurrentKeyring, KeyringConfig, KeyringEntry},
+    keyring::{ConcurrentKeyring, KeyType, KeyringConfig, KeyringEntry},
     BoxDynError,
 };
 use ibc_union_spec::{datagram::Datagram, IbcUnion};
 use jsonrpsee::{
     core::{async_trait, RpcResult},
-    Extensions,
+    Extensions, RpcModule,
 };
 use move_core_types::{
     identifier::Identifier,
@@ -27,10 +27,10 @@ use voyager_message::{
     core::ChainId,
     data::Data,
     hook::SubmitTxHook,
-    module::{PluginInfo, PluginServer},
-    DefaultCmd, Plugin, PluginMessage, VoyagerMessage,
+    module::{PluginInfo, PluginServer, SignerBalance, SignerBalances, SignerCmd, SignerServer},
+    Plugin, PluginMessage, VoyagerMessage,
 };
-use voyager_vm::{call, noop, pass::PassResult, Op, Visit};
+use voyager_vm::{call, filter::Priority, noop, pass::PassResult, Op, Visit};
 
 use crate::{call::ModuleCall, callback::ModuleCallback};
 
@@ -59,7 +59,7 @@ impl Plugin for Module {
     type Callback = ModuleCallback;
 
     type Config = Config;
-    type Cmd = DefaultCmd;
+    type Cmd = SignerCmd;
 
     async fn new(config: Self::Config) -> Result<Self, BoxDynError> {
         let aptos_client = aptos_rest_client::Client::new(config.rpc_url.parse().unwrap());
@@ -70,11 +70,11 @@ impl Plugin for Module {
             chain_id: ChainId::new(chain_id.to_string()),
             ibc_handler_address: config.ibc_handler_address,
             aptos_client,
-            keyring: ConcurrentKeyring::new(
-                config.keyring.name,
-                config.keyring.keys.into_iter().map(|config| {
-                    let pk = aptos_crypto::ed25519::Ed25519PrivateKey::try_from(&*config.value())
-                        .unwrap();
+            keyring: ConcurrentKeyring::from_config(
+                config.keyring,
+                KeyType::Ed25519,
+                |name, key| {
+                    let pk = aptos_crypto::ed25519::Ed25519PrivateKey::try_from(&*key)?;
 
                     let address = (*<H256>::from(
                         sha3::Sha3_256::new()
@@ -85,25 +85,33 @@ impl Plugin for Module {
                     .get())
                     .into();
 
-                    KeyringEntry {
-                        name: config.name(),
+                    Ok::<_, aptos_crypto::CryptoMaterialError>(KeyringEntry {
+                        name,
                         address,
                         signer: Arc::new(pk),
-                    }
-                }),
-            ),
+                    })
+                },
+            )?,
         })
     }
 
     fn info(config: Self::Config) -> PluginInfo {
         PluginInfo {
             name: plugin_name(&config.chain_id),
-            interest_filter: SubmitTxHook::filter(&config.chain_id),
+            interest_filter: SubmitTxHook::filter(&config.chain_id, config.priority),
         }
     }
 
-    async fn cmd(_config: Self::Config, cmd: Self::Cmd) {
-        match cmd {}
+    async fn cmd(config: Self::Config, cmd: Self::Cmd) {
+        cmd.run(Self::new(config).await.unwrap()).await
+    }
+
+    fn into_rpc_module(self) -> RpcModule<Self> {
+        let mut module = PluginServer::into_rpc(self.clone());
+        module
+            .merge(SignerServer::into_rpc(self))
+            .expect("no conflicting methods");
+        module
     }
 }
 
@@ -115,6 +123,12 @@ pub struct Config {
     pub ibc_handler_address: Address,
 
     pub keyring: KeyringConfig,
+
+    /// The priority of the calls to this plugin (i.e. the calls that submit the transactions) in
+    /// the queue. Calls with a higher priority are processed first. Defaults to 0, the same
+    /// priority as everything else.
+    #[serde(default)]
+    pub priority: Priority,
 }
 
 impl aptos_move_ibc::ibc::ClientExt for Module {
@@ -141,6 +155,12 @@ impl aptos_move_ibc::channel_handshake::ClientExt for Module {
     }
 }
 
+impl aptos_move_ibc::timeout_packet::ClientExt for Module {
+    fn client(&self) -> &aptos_rest_client::Client {
+        &self.aptos_client
+    }
+}
+
 fn plugin_name(chain_id: &ChainId) -> String {
     pub const PLUGIN_NAME: &str = env!("CARGO_PKG_NAME");
 
@@ -153,6 +173,36 @@ impl Module {
     }
 }
 
+impl SignerBalances for Module {
+    /// Fetch the APT balance of every key in the keyring, taking any keys below the minimum
+    /// balance out of rotation.
+    #[instrument(skip_all, fields(chain_id = %self.chain_id))]
+    async fn signer_balances(&self) -> Result<Vec<SignerBalance>, BoxDynError> {
+        let balances = self
+            .keyring
+            .fetch_balances("octa", |address| {
+                let address = *address;
+                async move {
+                    self.aptos_client
+                        .get_account_balance(address)
+                        .await
+                        .map(|balance| balance.into_inner().get().into())
+                }
+            })
+            .await?;
+
+        Ok(balances
+            .into_iter()
+            .map(|balance| SignerBalance {
+                key_name: balance.key_name,
+                address: balance.address.to_hex_literal(),
+                balance: balance.balance,
+                denom: balance.denom,
+            })
+            .collect())
+    }
+}
+
 #[async_trait]
 impl PluginServer<ModuleCall, ModuleCallback> for Module {
     async fn run_pass(
@@ -288,7 +338,8 @@ async fn process_msgs<
     T: aptos_move_ibc::ibc::ClientExt
         + aptos_move_ibc::recv_packet::ClientExt
         + aptos_move_ibc::acknowledge_packet::ClientExt
-        + aptos_move_ibc::channel_handshake::ClientExt,
+        + aptos_move_ibc::channel_handshake::ClientExt
+        + aptos_move_ibc::timeout_packet::ClientExt,
 >(
     ibc_handler_address: AccountAddress,
     client: &T,
@@ -315,6 +366,13 @@ async fn process_msgs<
                     (data.client_id, data.client_message.into_vec()),
                 ),
             ),
+            Datagram::Misbehaviour(data) => (
+                msg,
+                client.submit_misbehaviour(
+                    ibc_handler_address,
+                    (data.client_id, data.client_message.into_vec()),
+                ),
+            ),
             Datagram::ConnectionOpenInit(data) => (
                 msg,
                 client.connection_open_init(
@@ -501,6 +559,32 @@ async fn process_msgs<
                     ),
                 )
             }
+            Datagram::PacketTimeout(data) => {
+                let port_id = client
+                    .get_module(ibc_handler_address, None, (data.packet.source_channel_id,))
+                    .await
+                    .unwrap();
+
+                (
+                    msg,
+                    client.timeout_packet(
+                        ibc_handler_address,
+                        (
+                            port_id.into(),
+                            data.packet.source_channel_id,
+                            data.packet.destination_channel_id,
+                            data.packet.data.into_vec(),
+                            data.packet.timeout_height,
+                            data.packet.timeout_timestamp,
+                            data.proof.into_vec(),
+                            data.proof_height,
+                            // next_sequence_recv, unused for union packets
+                            0,
+                        ),
+                        (ibc_app_witness(port_id.into()),),
+                    ),
+                )
+            }
             _ => todo!(),
         };
         data.push(item);
@@ -7,13 +7,13 @@ use chain_utils::{
         cosmos_sdk_error::{ChannelError, ClientError, CosmosSdkError, IbcWasmError, SdkError},
         CosmosKeyring, GasConfig,
     },
-    keyring::{KeyringConfig, KeyringEntry},
+    keyring::{KeyType, KeyringConfig, KeyringEntry},
     BoxDynError,
 };
 use jsonrpsee::{
     core::{async_trait, RpcResult},
     types::ErrorObject,
-    Extensions,
+    Extensions, RpcModule,
 };
 use prost::Message;
 use serde::{Deserialize, Serialize};
@@ -42,10 +42,10 @@ use voyager_message::{
     core::ChainId,
     data::Data,
     hook::SubmitTxHook,
-    module::{PluginInfo, PluginServer},
-    DefaultCmd, Plugin, PluginMessage, VoyagerMessage, FATAL_JSONRPC_ERROR_CODE,
+    module::{PluginInfo, PluginServer, SignerBalance, SignerBalances, SignerCmd, SignerServer},
+    Plugin, PluginMessage, VoyagerMessage, FATAL_JSONRPC_ERROR_CODE,
 };
-use voyager_vm::{call, conc, noop, pass::PassResult, Op, Visit};
+use voyager_vm::{call, conc, filter::Priority, noop, pass::PassResult, Op, Visit};
 
 use crate::{
     call::{IbcMessage, ModuleCall},
@@ -81,6 +81,12 @@ pub struct Config {
     pub rpc_url: String,
     pub grpc_url: String,
     pub gas_config: GasConfig,
+
+    /// The priority of the calls to this plugin (i.e. the calls that submit the transactions) in
+    /// the queue. Calls with a higher priority are processed first. Defaults to 0, the same
+    /// priority as everything else.
+    #[serde(default)]
+    pub priority: Priority,
 }
 
 impl Plugin for Module {
@@ -88,7 +94,7 @@ impl Plugin for Module {
     type Callback = ModuleCallback;
 
     type Config = Config;
-    type Cmd = DefaultCmd;
+    type Cmd = SignerCmd;
 
     async fn new(config: Self::Config) -> Result<Self, BoxDynError> {
         let tm_client = cometbft_rpc::Client::new(config.rpc_url).await?;
@@ -106,24 +112,22 @@ impl Plugin for Module {
 
         Ok(Self {
             ibc_host_contract_address: config.ibc_host_contract_address,
-            keyring: CosmosKeyring::new(
-                config.keyring.name,
-                config.keyring.keys.into_iter().map(|entry| {
+            keyring: CosmosKeyring::from_config(
+                config.keyring,
+                KeyType::Secp256k1,
+                |name, key| {
                     let signer = CosmosSigner::new(
-                        bip32::secp256k1::ecdsa::SigningKey::from_bytes(
-                            entry.value().as_slice().into(),
-                        )
-                        .expect("invalid private key"),
+                        bip32::secp256k1::ecdsa::SigningKey::from_slice(&key)?,
                         bech32_prefix.clone(),
                     );
 
-                    KeyringEntry {
-                        name: entry.name(),
+                    Ok::<_, bip32::secp256k1::ecdsa::Error>(KeyringEntry {
+                        name,
                         address: signer.to_string(),
                         signer,
-                    }
-                }),
-            ),
+                    })
+                },
+            )?,
             comtbft_client: tm_client,
             chain_id: ChainId::new(chain_id),
             grpc_url: config.grpc_url,
@@ -135,12 +139,20 @@ impl Plugin for Module {
     fn info(config: Self::Config) -> PluginInfo {
         PluginInfo {
             name: plugin_name(&config.chain_id),
-            interest_filter: SubmitTxHook::filter(&config.chain_id),
+            interest_filter: SubmitTxHook::filter(&config.chain_id, config.priority),
         }
     }
 
-    async fn cmd(_config: Self::Config, cmd: Self::Cmd) {
-        match cmd {}
+    async fn cmd(config: Self::Config, cmd: Self::Cmd) {
+        cmd.run(Self::new(config).await.unwrap()).await
+    }
+
+    fn into_rpc_module(self) -> RpcModule<Self> {
+        let mut module = PluginServer::into_rpc(self.clone());
+        module
+            .merge(SignerServer::into_rpc(self))
+            .expect("no conflicting methods");
+        module
     }
 }
 
@@ -596,6 +608,52 @@ pub enum BroadcastTxCommitError {
     OutOfGas,
 }
 
+impl SignerBalances for Module {
+    /// Fetch the balance of the gas denom of every key in the keyring, taking any keys below the
+    /// minimum balance out of rotation.
+    #[instrument(skip_all, fields(chain_id = %self.chain_id))]
+    async fn signer_balances(&self) -> Result<Vec<SignerBalance>, BoxDynError> {
+        let query_client = protos::cosmos::bank::v1beta1::query_client::QueryClient::connect(
+            self.grpc_url.clone(),
+        )
+        .await?;
+
+        let balances = self
+            .keyring
+            .fetch_balances(&self.gas_config.gas_denom, |address| {
+                let mut query_client = query_client.clone();
+                let request = protos::cosmos::bank::v1beta1::QueryBalanceRequest {
+                    address: address.clone(),
+                    denom: self.gas_config.gas_denom.clone(),
+                };
+
+                async move {
+                    Ok::<_, BoxDynError>(
+                        query_client
+                            .balance(request)
+                            .await?
+                            .into_inner()
+                            .balance
+                            .map(|coin| coin.amount.parse::<u128>())
+                            .transpose()?
+                            .unwrap_or_default(),
+                    )
+                }
+            })
+            .await?;
+
+        Ok(balances
+            .into_iter()
+            .map(|balance| SignerBalance {
+                key_name: balance.key_name,
+                address: balance.address,
+                balance: balance.balance,
+                denom: balance.denom,
+            })
+            .collect())
+    }
+}
+
 #[async_trait]
 impl PluginServer<ModuleCall, ModuleCallback> for Module {
     #[instrument(skip_all)]
@@ -890,6 +948,21 @@ fn process_msgs(
                             funds: vec![],
                         })
                     }
+                    ibc_union_spec::datagram::Datagram::Misbehaviour(msg_misbehaviour) => {
+                        mk_any(&protos::cosmwasm::wasm::v1::MsgExecuteContract {
+                            sender: signer.to_string(),
+                            contract: ibc_host_contract_address.to_string(),
+                            msg: serde_json::to_vec(&ibc_union_msg::msg::ExecuteMsg::Misbehaviour(
+                                ibc_union_msg::msg::MsgMisbehaviour {
+                                    client_id: msg_misbehaviour.client_id,
+                                    client_message: msg_misbehaviour.client_message,
+                                    relayer: signer.to_string(),
+                                },
+                            ))
+                            .unwrap(),
+                            funds: vec![],
+                        })
+                    }
                     ibc_union_spec::datagram::Datagram::ConnectionOpenInit(
                         msg_connection_open_init,
                     ) => mk_any(&protos::cosmwasm::wasm::v1::MsgExecuteContract {
@@ -1048,12 +1121,41 @@ fn process_msgs(
                         })
                     }
                     ibc_union_spec::datagram::Datagram::ChannelCloseInit(
-                        _msg_channel_close_init,
-                    ) => todo!(),
+                        msg_channel_close_init,
+                    ) => {
+                        let channel_close_init = ibc_union_msg::msg::ExecuteMsg::ChannelCloseInit(
+                            ibc_union_msg::msg::MsgChannelCloseInit {
+                                channel_id: msg_channel_close_init.channel_id,
+                                relayer: signer.to_string(),
+                            },
+                        );
+
+                        mk_any(&protos::cosmwasm::wasm::v1::MsgExecuteContract {
+                            sender: signer.to_string(),
+                            contract: ibc_host_contract_address.to_string(),
+                            msg: serde_json::to_vec(&channel_close_init).unwrap(),
+                            funds: vec![],
+                        })
+                    }
                     ibc_union_spec::datagram::Datagram::ChannelCloseConfirm(
-                        _msg_channel_close_confirm,
+                        msg_channel_close_confirm,
                     ) => {
-                        todo!()
+                        let channel_close_confirm =
+                            ibc_union_msg::msg::ExecuteMsg::ChannelCloseConfirm(
+                                ibc_union_msg::msg::MsgChannelCloseConfirm {
+                                    channel_id: msg_channel_close_confirm.channel_id,
+                                    proof_init: msg_channel_close_confirm.proof_init,
+                                    proof_height: msg_channel_close_confirm.proof_height,
+                                    relayer: signer.to_string(),
+                                },
+                            );
+
+                        mk_any(&protos::cosmwasm::wasm::v1::MsgExecuteContract {
+                            sender: signer.to_string(),
+                            contract: ibc_host_contract_address.to_string(),
+                            msg: serde_json::to_vec(&channel_close_confirm).unwrap(),
+                            funds: vec![],
+                        })
                     }
                     ibc_union_spec::datagram::Datagram::PacketRecv(msg_packet_recv) => {
                         let packet_recv = ibc_union_msg::msg::ExecuteMsg::PacketRecv(
@@ -1101,14 +1203,80 @@ fn process_msgs(
                             funds: vec![],
                         })
                     }
-                    ibc_union_spec::datagram::Datagram::PacketTimeout(_msg_packet_timeout) => {
-                        todo!()
+                    ibc_union_spec::datagram::Datagram::PacketTimeout(msg_packet_timeout) => {
+                        let packet_timeout = ibc_union_msg::msg::ExecuteMsg::PacketTimeout(
+                            ibc_union_msg::msg::MsgPacketTimeout {
+                                packet: msg_packet_timeout.packet,
+                                proof: msg_packet_timeout.proof,
+                                proof_height: msg_packet_timeout.proof_height,
+                                relayer: signer.to_string(),
+                            },
+                        );
+
+                        mk_any(&protos::cosmwasm::wasm::v1::MsgExecuteContract {
+                            sender: signer.to_string(),
+                            contract: ibc_host_contract_address.to_string(),
+                            msg: serde_json::to_vec(&packet_timeout).unwrap(),
+                            funds: vec![],
+                        })
                     }
                     ibc_union_spec::datagram::Datagram::IntentPacketRecv(
-                        _msg_intent_packet_recv,
-                    ) => todo!(),
-                    ibc_union_spec::datagram::Datagram::BatchSend(_msg_batch_send) => todo!(),
-                    ibc_union_spec::datagram::Datagram::BatchAcks(_msg_batch_acks) => todo!(),
+                        msg_intent_packet_recv,
+                    ) => {
+                        let intent_packet_recv = ibc_union_msg::msg::ExecuteMsg::IntentPacketRecv(
+                            ibc_union_msg::msg::MsgIntentPacketRecv {
+                                packets: msg_intent_packet_recv
+                                    .packets
+                                    .into_iter()
+                                    .map(Into::into)
+                                    .collect(),
+                                market_maker_msgs: msg_intent_packet_recv.market_maker_msgs,
+                                market_maker: String::from_utf8(
+                                    msg_intent_packet_recv.market_maker.to_vec(),
+                                )
+                                .unwrap(),
+                                empty_proof: msg_intent_packet_recv.empty_proof,
+                            },
+                        );
+
+                        mk_any(&protos::cosmwasm::wasm::v1::MsgExecuteContract {
+                            sender: signer.to_string(),
+                            contract: ibc_host_contract_address.to_string(),
+                            msg: serde_json::to_vec(&intent_packet_recv).unwrap(),
+                            funds: vec![],
+                        })
+                    }
+                    ibc_union_spec::datagram::Datagram::BatchSend(msg_batch_send) => {
+                        let batch_send = ibc_union_msg::msg::ExecuteMsg::BatchSend(
+                            ibc_union_msg::msg::MsgBatchSend {
+                                source_channel: msg_batch_send.source_channel,
+                                packets: msg_batch_send.packets,
+                            },
+                        );
+
+                        mk_any(&protos::cosmwasm::wasm::v1::MsgExecuteContract {
+                            sender: signer.to_string(),
+                            contract: ibc_host_contract_address.to_string(),
+                            msg: serde_json::to_vec(&batch_send).unwrap(),
+                            funds: vec![],
+                        })
+                    }
+                    ibc_union_spec::datagram::Datagram::BatchAcks(msg_batch_acks) => {
+                        let batch_acks = ibc_union_msg::msg::ExecuteMsg::BatchAcks(
+                            ibc_union_msg::msg::MsgBatchAcks {
+                                source_channel: msg_batch_acks.source_channel,
+                                packets: msg_batch_acks.packets,
+                                acks: msg_batch_acks.acks,
+                            },
+                        );
+
+                        mk_any(&protos::cosmwasm::wasm::v1::MsgExecuteContract {
+                            sender: signer.to_string(),
+                            contract: ibc_host_contract_address.to_string(),
+                            msg: serde_json::to_vec(&batch_acks).unwrap(),
+                            funds: vec![],
+                        })
+                    }
                 },
             };
 
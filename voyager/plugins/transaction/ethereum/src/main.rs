@@ -1,16 +1,22 @@
-use std::collections::VecDeque;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
 
 use alloy::{
     contract::{Error, RawCallBuilder},
-    network::{AnyNetwork, EthereumWallet},
+    network::{AnyNetwork, EthereumWallet, Network, TransactionBuilder},
+    primitives::{Address, TxHash, U256},
     providers::{PendingTransactionError, Provider, ProviderBuilder, RootProvider},
+    rpc::types::TransactionRequest,
+    serde::WithOtherFields,
     signers::local::LocalSigner,
     sol_types::{SolEvent, SolInterface},
     transports::{BoxTransport, Transport, TransportError},
 };
 use bip32::secp256k1::ecdsa::{self, SigningKey};
 use chain_utils::{
-    keyring::{ConcurrentKeyring, KeyringConfig, KeyringEntry},
+    keyring::{ConcurrentKeyring, KeyType, KeyringConfig, KeyringEntry},
     BoxDynError,
 };
 use ibc_solidity::Ibc::{self, IbcErrors};
@@ -18,7 +24,7 @@ use ibc_union_spec::{datagram::Datagram, IbcUnion};
 use jsonrpsee::{
     core::{async_trait, RpcResult},
     types::{ErrorObject, ErrorObjectOwned},
-    Extensions,
+    Extensions, RpcModule,
 };
 use serde::{Deserialize, Serialize};
 use tracing::{debug, error, info, info_span, instrument, trace, warn, Instrument};
@@ -30,20 +36,22 @@ use voyager_message::{
     core::ChainId,
     data::Data,
     hook::SubmitTxHook,
-    module::{PluginInfo, PluginServer},
-    DefaultCmd, Plugin, PluginMessage, VoyagerMessage,
+    module::{PluginInfo, PluginServer, SignerBalance, SignerBalances, SignerCmd, SignerServer},
+    Plugin, PluginMessage, VoyagerMessage,
 };
-use voyager_vm::{call, defer, now, pass::PassResult, seq, Op, Visit};
+use voyager_vm::{call, defer, filter::Priority, now, pass::PassResult, seq, Op, Visit};
 
 use crate::{
     call::ModuleCall,
     callback::ModuleCallback,
     multicall::{Call3, Multicall, MulticallResult},
+    replacement::{Fees, ReplacementClient, TxReplacer},
 };
 
 pub mod call;
 pub mod callback;
 pub mod data;
+pub mod replacement;
 
 #[tokio::main(flavor = "multi_thread")]
 async fn main() {
@@ -67,6 +75,12 @@ pub struct Module {
     pub fixed_gas_price: Option<u128>,
 
     pub legacy: bool,
+
+    pub tx_replacement: TxReplacementConfig,
+
+    /// The next nonce to use for each key in the keyring, as of the last transaction from that
+    /// key that was included.
+    pub nonces: Arc<Mutex<HashMap<Address, u64>>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -92,6 +106,49 @@ pub struct Config {
 
     #[serde(default)]
     pub legacy: bool,
+
+    #[serde(default)]
+    pub tx_replacement: TxReplacementConfig,
+
+    /// The priority of the calls to this plugin (i.e. the calls that submit the transactions) in
+    /// the queue. Calls with a higher priority are processed first. Defaults to 0, the same
+    /// priority as everything else.
+    #[serde(default)]
+    pub priority: Priority,
+}
+
+/// Replacement of transactions that are not included in a block in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TxReplacementConfig {
+    /// How long to wait for a transaction to be included before replacing it with a transaction
+    /// with higher fees.
+    pub timeout_seconds: u64,
+    /// How much to increase the fees by on each replacement, in percent. Most nodes require an
+    /// increase of at least 10% to accept a replacement.
+    pub fee_bump_percent: u64,
+    /// How many times to replace a transaction before giving up on it. Transactions that were
+    /// given up on stay in the mempool, blocking all further transactions from their key until
+    /// they are included, unless `cancel_pending_transactions` is set.
+    pub max_replacements: u32,
+    /// Cancel all pending transactions from a key before using it, by replacing them with empty
+    /// transfers.
+    ///
+    /// NOTE: This cancels *every* pending transaction from the key, not only the ones sent by
+    /// this plugin. Only enable this if the keys in the keyring are not used by anything else
+    /// (including other instances of this plugin).
+    pub cancel_pending_transactions: bool,
+}
+
+impl Default for TxReplacementConfig {
+    fn default() -> Self {
+        Self {
+            timeout_seconds: 60,
+            fee_bump_percent: 20,
+            max_replacements: 5,
+            cancel_pending_transactions: false,
+        }
+    }
 }
 
 impl Plugin for Module {
@@ -99,7 +156,7 @@ impl Plugin for Module {
     type Callback = ModuleCallback;
 
     type Config = Config;
-    type Cmd = DefaultCmd;
+    type Cmd = SignerCmd;
 
     async fn new(config: Self::Config) -> Result<Self, BoxDynError> {
         let provider = ProviderBuilder::new()
@@ -123,38 +180,46 @@ impl Plugin for Module {
             ibc_handler_address: config.ibc_handler_address,
             multicall_address: config.multicall_address,
             provider,
-            keyring: ConcurrentKeyring::new(
-                config.keyring.name,
-                config.keyring.keys.into_iter().map(|config| {
-                    let signing_key = <ecdsa::SigningKey as bip32::PrivateKey>::from_bytes(
-                        &config.value().as_slice().try_into().unwrap(),
-                    )
-                    .unwrap();
+            keyring: ConcurrentKeyring::from_config(
+                config.keyring,
+                KeyType::Secp256k1,
+                |name, key| {
+                    let signing_key = ecdsa::SigningKey::from_slice(&key)?;
 
                     let signer = LocalSigner::from_signing_key(signing_key);
 
-                    KeyringEntry {
-                        name: config.name(),
+                    Ok::<_, ecdsa::Error>(KeyringEntry {
+                        name,
                         address: signer.address(),
                         signer,
-                    }
-                }),
-            ),
+                    })
+                },
+            )?,
             max_gas_price: config.max_gas_price,
             fixed_gas_price: config.fixed_gas_price,
             legacy: config.legacy,
+            tx_replacement: config.tx_replacement,
+            nonces: Arc::default(),
         })
     }
 
     fn info(config: Self::Config) -> PluginInfo {
         PluginInfo {
             name: plugin_name(&config.chain_id),
-            interest_filter: SubmitTxHook::filter(&config.chain_id),
+            interest_filter: SubmitTxHook::filter(&config.chain_id, config.priority),
         }
     }
 
-    async fn cmd(_config: Self::Config, cmd: Self::Cmd) {
-        match cmd {}
+    async fn cmd(config: Self::Config, cmd: Self::Cmd) {
+        cmd.run(Self::new(config).await.unwrap()).await
+    }
+
+    fn into_rpc_module(self) -> RpcModule<Self> {
+        let mut module = PluginServer::into_rpc(self.clone());
+        module
+            .merge(SignerServer::into_rpc(self))
+            .expect("no conflicting methods");
+        module
     }
 }
 
@@ -168,6 +233,15 @@ impl Module {
     fn plugin_name(&self) -> String {
         plugin_name(&self.chain_id)
     }
+
+    fn replacer(&self) -> TxReplacer<'_, Self> {
+        TxReplacer {
+            client: self,
+            config: &self.tx_replacement,
+            max_gas_price: self.max_gas_price,
+            nonces: &self.nonces,
+        }
+    }
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -186,6 +260,81 @@ pub enum TxSubmitError {
     GasPriceTooHigh { max: u128, price: u128 },
     #[error("rpc error (this is just the IbcDatagram conversion functions but i need to make those errors better)")]
     RpcError(#[from] ErrorObjectOwned),
+    #[error("error fetching nonce")]
+    Nonce(#[source] TransportError),
+    #[error("error fetching fees")]
+    Fees(#[source] TransportError),
+    #[error("transaction with nonce {nonce} was not included after {replacements} replacements")]
+    Stuck { nonce: u64, replacements: u32 },
+    #[error("nonce {nonce} has already been used")]
+    NonceTooLow { nonce: u64 },
+}
+
+impl ReplacementClient for Module {
+    type Receipt = <AnyNetwork as Network>::ReceiptResponse;
+
+    async fn fees(&self) -> Result<Fees, TxSubmitError> {
+        if let Some(gas_price) = self.fixed_gas_price {
+            Ok(Fees::Legacy { gas_price })
+        } else if self.legacy {
+            Ok(Fees::Legacy {
+                gas_price: self
+                    .provider
+                    .get_gas_price()
+                    .await
+                    .map_err(TxSubmitError::Fees)?,
+            })
+        } else {
+            let estimate = self
+                .provider
+                .estimate_eip1559_fees(None)
+                .await
+                .map_err(TxSubmitError::Fees)?;
+
+            Ok(Fees::Eip1559 {
+                max_fee_per_gas: estimate.max_fee_per_gas,
+                max_priority_fee_per_gas: estimate.max_priority_fee_per_gas,
+            })
+        }
+    }
+
+    async fn receipt(&self, tx_hash: TxHash) -> Result<Option<Self::Receipt>, TxSubmitError> {
+        Ok(self
+            .provider
+            .get_transaction_receipt(tx_hash)
+            .await
+            .map_err(Error::TransportError)?)
+    }
+}
+
+impl SignerBalances for Module {
+    /// Fetch the balance of every key in the keyring, taking any keys below the minimum balance
+    /// out of rotation.
+    #[instrument(skip_all, fields(chain_id = %self.chain_id))]
+    async fn signer_balances(&self) -> Result<Vec<SignerBalance>, BoxDynError> {
+        let balances = self
+            .keyring
+            .fetch_balances("wei", |address| {
+                let address = *address;
+                async move {
+                    self.provider
+                        .get_balance(address)
+                        .await
+                        .map(|balance| balance.try_into().unwrap_or(u128::MAX))
+                }
+            })
+            .await?;
+
+        Ok(balances
+            .into_iter()
+            .map(|balance| SignerBalance {
+                key_name: balance.key_name,
+                address: balance.address.to_string(),
+                balance: balance.balance,
+                denom: balance.denom,
+            })
+            .collect())
+    }
 }
 
 #[async_trait]
@@ -251,6 +400,9 @@ impl PluginServer<ModuleCall, ModuleCallback> for Module {
                     Some(Err(TxSubmitError::OutOfGas)) => {
                         Ok(seq([defer(now() + 12), call(rewrap_msg())]))
                     }
+                    Some(Err(TxSubmitError::Stuck { .. } | TxSubmitError::NonceTooLow { .. })) => {
+                        Ok(seq([defer(now() + 6), call(rewrap_msg())]))
+                    }
                     Some(Err(TxSubmitError::EmptyRevert(msgs))) => Ok(seq([
                         defer(now() + 12),
                         call(PluginMessage::new(
@@ -325,7 +477,7 @@ impl Module {
             .map(|x| (x.0.clone(), x.0.name()))
             .collect::<Vec<_>>();
 
-        let mut call = multicall.multicall(
+        let call = multicall.multicall(
             msgs.into_iter()
                 .map(|(_, call)| Call3 {
                     target: self.ibc_handler_address.into(),
@@ -349,99 +501,187 @@ impl Module {
 
         info!(gas_estimate, gas_to_use, "gas estimatation successful");
 
-        if let Some(fixed_gas_price) = self.fixed_gas_price {
-            call = call.gas_price(fixed_gas_price);
-        }
+        let address = wallet.address();
+
+        let nonce = self.next_nonce(&signer, address).await?;
+
+        let receipt = self
+            .replacer()
+            .send(address, nonce, |fees| {
+                let call = match fees {
+                    Fees::Legacy { gas_price } => call.clone().gas_price(gas_price),
+                    Fees::Eip1559 {
+                        max_fee_per_gas,
+                        max_priority_fee_per_gas,
+                    } => call
+                        .clone()
+                        .max_fee_per_gas(max_fee_per_gas)
+                        .max_priority_fee_per_gas(max_priority_fee_per_gas),
+                }
+                .nonce(nonce)
+                .gas(gas_to_use);
 
-        match call.gas(gas_to_use).send().await {
-            Ok(ok) => {
-                let tx_hash = <H256>::from(*ok.tx_hash());
-                async move {
-                    let receipt = ok.get_receipt().await?;
-
-                    info!(%tx_hash, "tx included");
-
-                    let result = MulticallResult::decode_log_data(
-                        receipt
-                            .inner
-                            .inner
-                            .logs()
-                            .last()
-                            .expect("multicall event should be last log")
-                            .data(),
-                        true,
-                    )
-                    .expect("unable to decode multicall result log");
-
-                    info!(
-                        gas_used = %receipt.gas_used,
-                        batch.size = msg_names.len(),
-                        "submitted batched evm messages"
-                    );
+                async move { call.send().await }
+            })
+            .await?;
 
-                    for (idx, (result, (msg, msg_name))) in
-                        result._0.into_iter().zip(msg_names).enumerate()
-                    {
-                        if result.success {
-                            info_span!(
-                                "evm tx",
-                                msg = msg_name,
-                                %idx,
-                                data = %serde_json::to_string(&msg).unwrap(),
-                            );
-                        } else if let Ok(known_revert) =
-                            IbcErrors::abi_decode(&result.returnData, true)
-                        {
-                            error!(
-                                msg = %msg_name,
-                                %idx,
-                                revert = ?known_revert,
-                                well_known = true,
-                                data = %serde_json::to_string(&msg).unwrap(),
-                                "evm message failed",
-                            );
-                        } else if result.returnData.is_empty() {
-                            error!(
-                                msg = %msg_name,
-                                %idx,
-                                revert = %result.returnData,
-                                well_known = false,
-                                data = %serde_json::to_string(&msg).unwrap(),
-                                "evm message failed with 0x revert, likely an ABI issue",
-                            );
-                        } else {
-                            error!(
-                                msg = %msg_name,
-                                %idx,
-                                revert = %result.returnData,
-                                well_known = false,
-                                data = %serde_json::to_string(&msg).unwrap(),
-                                "evm message failed",
-                            );
-                        }
-                    }
+        let tx_hash = <H256>::from(receipt.transaction_hash);
+
+        async move {
+            info!(%tx_hash, "tx included");
+
+            let result = MulticallResult::decode_log_data(
+                receipt
+                    .inner
+                    .inner
+                    .logs()
+                    .last()
+                    .expect("multicall event should be last log")
+                    .data(),
+                true,
+            )
+            .expect("unable to decode multicall result log");
+
+            info!(
+                gas_used = %receipt.gas_used,
+                batch.size = msg_names.len(),
+                "submitted batched evm messages"
+            );
 
-                    Ok(())
+            for (idx, (result, (msg, msg_name))) in result._0.into_iter().zip(msg_names).enumerate()
+            {
+                if result.success {
+                    info_span!(
+                        "evm tx",
+                        msg = msg_name,
+                        %idx,
+                        data = %serde_json::to_string(&msg).unwrap(),
+                    );
+                } else if let Ok(known_revert) = IbcErrors::abi_decode(&result.returnData, true) {
+                    error!(
+                        msg = %msg_name,
+                        %idx,
+                        revert = ?known_revert,
+                        well_known = true,
+                        data = %serde_json::to_string(&msg).unwrap(),
+                        "evm message failed",
+                    );
+                } else if result.returnData.is_empty() {
+                    error!(
+                        msg = %msg_name,
+                        %idx,
+                        revert = %result.returnData,
+                        well_known = false,
+                        data = %serde_json::to_string(&msg).unwrap(),
+                        "evm message failed with 0x revert, likely an ABI issue",
+                    );
+                } else {
+                    error!(
+                        msg = %msg_name,
+                        %idx,
+                        revert = %result.returnData,
+                        well_known = false,
+                        data = %serde_json::to_string(&msg).unwrap(),
+                        "evm message failed",
+                    );
                 }
-                .instrument(info_span!(
-                    "evm tx",
-                    %tx_hash,
-                ))
-                .await
             }
-            Err(
-                Error::PendingTransactionError(PendingTransactionError::TransportError(
-                    TransportError::ErrorResp(e),
-                ))
-                | Error::TransportError(TransportError::ErrorResp(e)),
-            ) if e
-                .message
-                .contains("insufficient funds for gas * price + value") =>
-            {
-                error!("out of gas");
-                Err(TxSubmitError::OutOfGas)
+
+            Ok(())
+        }
+        .instrument(info_span!(
+            "evm tx",
+            %tx_hash,
+        ))
+        .await
+    }
+
+    /// The nonce to use for the next transaction from `address`. If
+    /// [`TxReplacementConfig::cancel_pending_transactions`] is set, all transactions from
+    /// `address` that are still pending are cancelled first; otherwise, the next transaction is
+    /// queued behind them.
+    async fn next_nonce<P: Provider<BoxTransport, AnyNetwork>>(
+        &self,
+        signer: &P,
+        address: Address,
+    ) -> Result<u64, TxSubmitError> {
+        let pending = self
+            .provider
+            .get_transaction_count(address)
+            .pending()
+            .await
+            .map_err(TxSubmitError::Nonce)?;
+
+        if self.tx_replacement.cancel_pending_transactions {
+            let mined = self
+                .provider
+                .get_transaction_count(address)
+                .latest()
+                .await
+                .map_err(TxSubmitError::Nonce)?;
+
+            // keys are only used for one submission at a time, and submissions wait for their
+            // transaction to be included (or give up on it), so anything that is still pending is
+            // stuck and will block all further transactions from this key
+            for nonce in mined..pending {
+                warn!(%address, nonce, "cancelling stuck transaction");
+
+                self.cancel_nonce(signer, address, nonce).await?;
             }
-            Err(err) => Err(TxSubmitError::Error(err)),
+        }
+
+        let local = self
+            .nonces
+            .lock()
+            .expect("mutex is poisoned")
+            .get(&address)
+            .copied()
+            .unwrap_or_default();
+
+        // the local nonce is only ahead of the chain if the rpc is lagging behind
+        Ok(local.max(pending))
+    }
+
+    /// Replace the transaction with `nonce` with an empty transfer to `address`.
+    async fn cancel_nonce<P: Provider<BoxTransport, AnyNetwork>>(
+        &self,
+        signer: &P,
+        address: Address,
+        nonce: u64,
+    ) -> Result<(), TxSubmitError> {
+        let res = self
+            .replacer()
+            .send(address, nonce, |fees| {
+                let tx = TransactionRequest::default()
+                    .with_from(address)
+                    .with_to(address)
+                    .with_value(U256::ZERO)
+                    .with_nonce(nonce)
+                    .with_gas_limit(21_000);
+
+                let tx = WithOtherFields::new(match fees {
+                    Fees::Legacy { gas_price } => tx.with_gas_price(gas_price),
+                    Fees::Eip1559 {
+                        max_fee_per_gas,
+                        max_priority_fee_per_gas,
+                    } => tx
+                        .with_max_fee_per_gas(max_fee_per_gas)
+                        .with_max_priority_fee_per_gas(max_priority_fee_per_gas),
+                });
+
+                async move {
+                    signer
+                        .send_transaction(tx)
+                        .await
+                        .map_err(Error::TransportError)
+                }
+            })
+            .await;
+
+        match res {
+            // the stuck transaction was included in the meantime
+            Ok(_) | Err(TxSubmitError::NonceTooLow { .. }) => Ok(()),
+            Err(err) => Err(err),
         }
     }
 }
@@ -479,6 +719,15 @@ fn process_msgs<T: Transport + Clone, P: Provider<T, AnyNetwork>>(
                         })
                         .clear_decoder(),
                 ),
+                Datagram::Misbehaviour(data) => (
+                    msg,
+                    ibc_handler
+                        .misbehaviour(ibc_solidity::MsgMisbehaviour {
+                            client_id: data.client_id,
+                            client_message: data.client_message.into(),
+                        })
+                        .clear_decoder(),
+                ),
                 Datagram::ConnectionOpenInit(data) => (
                     msg,
                     ibc_handler
@@ -574,6 +823,26 @@ fn process_msgs<T: Transport + Clone, P: Provider<T, AnyNetwork>>(
                         })
                         .clear_decoder(),
                 ),
+                Datagram::ChannelCloseInit(data) => (
+                    msg,
+                    ibc_handler
+                        .channelCloseInit(ibc_solidity::MsgChannelCloseInit {
+                            channel_id: data.channel_id,
+                            relayer: relayer.into(),
+                        })
+                        .clear_decoder(),
+                ),
+                Datagram::ChannelCloseConfirm(data) => (
+                    msg,
+                    ibc_handler
+                        .channelCloseConfirm(ibc_solidity::MsgChannelCloseConfirm {
+                            channel_id: data.channel_id,
+                            proof_init: data.proof_init.into(),
+                            proof_height: data.proof_height,
+                            relayer: relayer.into(),
+                        })
+                        .clear_decoder(),
+                ),
                 Datagram::PacketRecv(data) => (
                     msg,
                     ibc_handler
@@ -602,18 +871,51 @@ fn process_msgs<T: Transport + Clone, P: Provider<T, AnyNetwork>>(
                         })
                         .clear_decoder(),
                 ),
-                // Datagram::TimeoutPacket(data) => (
-                //     msg,
-                //     ibc_handler
-                //         .timeoutPacket(MsgPacketTimeout {
-                //             packet: convert_packet(data.packet)?,
-                //             proof: data.proof_unreceived.into(),
-                //             proofHeight: data.proof_height.height(),
-                //             relayer: relayer.into(),
-                //         })
-                //         .clear_decoder(),
-                // ),
-                _ => todo!(),
+                Datagram::PacketTimeout(data) => (
+                    msg,
+                    ibc_handler
+                        .timeoutPacket(ibc_solidity::MsgPacketTimeout {
+                            packet: data.packet.into(),
+                            proof: data.proof.into(),
+                            proof_height: data.proof_height,
+                            relayer: relayer.into(),
+                        })
+                        .clear_decoder(),
+                ),
+                Datagram::IntentPacketRecv(data) => (
+                    msg,
+                    ibc_handler
+                        .recvIntentPacket(ibc_solidity::MsgIntentPacketRecv {
+                            packets: data.packets.into_iter().map(Into::into).collect(),
+                            market_maker_msgs: data
+                                .market_maker_msgs
+                                .into_iter()
+                                .map(Into::into)
+                                .collect(),
+                            market_maker: data.market_maker.try_into().unwrap(),
+                            emptyProof: data.empty_proof.into(),
+                        })
+                        .clear_decoder(),
+                ),
+                Datagram::BatchSend(data) => (
+                    msg,
+                    ibc_handler
+                        .batchSend(ibc_solidity::MsgBatchSend {
+                            source_channel: data.source_channel,
+                            packets: data.packets.into_iter().map(Into::into).collect(),
+                        })
+                        .clear_decoder(),
+                ),
+                Datagram::BatchAcks(data) => (
+                    msg,
+                    ibc_handler
+                        .batchAcks(ibc_solidity::MsgBatchAcks {
+                            source_channel: data.source_channel,
+                            packets: data.packets.into_iter().map(Into::into).collect(),
+                            acks: data.acks.into_iter().map(Into::into).collect(),
+                        })
+                        .clear_decoder(),
+                ),
             })
         })
         .collect()
@@ -651,6 +953,7 @@ mod tests {
     use alloy::{
         hex,
         primitives::{fixed_bytes, LogData},
+        sol_types::SolCall,
     };
 
     use super::*;
@@ -675,6 +978,52 @@ mod tests {
         dbg!(result);
     }
 
+    #[test]
+    fn intent_packet_recv_encode() {
+        let provider = ProviderBuilder::new()
+            .network::<AnyNetwork>()
+            .on_http("http://localhost:8545".parse().unwrap());
+        let ibc = Ibc::new(Address::ZERO, &provider);
+
+        let packet = ibc_union_spec::types::Packet {
+            source_channel_id: 1,
+            destination_channel_id: 2,
+            data: b"data".into(),
+            timeout_height: 0,
+            timeout_timestamp: 1,
+        };
+        let market_maker = H160::new([1; 20]);
+
+        let msgs = process_msgs(
+            &ibc,
+            vec![Datagram::IntentPacketRecv(
+                ibc_union_spec::datagram::MsgIntentPacketRecv {
+                    packets: vec![packet.clone()],
+                    market_maker_msgs: vec![b"msg".into()],
+                    market_maker: market_maker.get().into(),
+                    empty_proof: b"proof".into(),
+                },
+            )],
+            H160::new([2; 20]),
+        )
+        .unwrap();
+
+        assert_eq!(
+            msgs[0].1.calldata(),
+            &alloy::primitives::Bytes::from(
+                Ibc::recvIntentPacketCall {
+                    msg_: ibc_solidity::MsgIntentPacketRecv {
+                        packets: vec![packet.into()],
+                        market_maker_msgs: vec![b"msg".into()],
+                        market_maker: market_maker.into(),
+                        emptyProof: b"proof".into(),
+                    },
+                }
+                .abi_encode()
+            )
+        );
+    }
+
     // TODO: rename of the event broke the test indeed
     // #[test]
     // fn create_client_decode() {
@@ -0,0 +1,688 @@
+//! Replacement of transactions that are not included in a block in time.
+
+use std::{collections::HashMap, future::Future, sync::Mutex, time::Duration};
+
+use alloy::{
+    contract::Error,
+    network::{AnyNetwork, Network},
+    primitives::{Address, TxHash},
+    providers::{PendingTransactionBuilder, PendingTransactionError, WatchTxError},
+    transports::{BoxTransport, TransportError},
+};
+use tracing::{debug, error, info, warn};
+
+use crate::{TxReplacementConfig, TxSubmitError};
+
+/// The fees to submit a transaction with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fees {
+    Legacy {
+        gas_price: u128,
+    },
+    Eip1559 {
+        max_fee_per_gas: u128,
+        max_priority_fee_per_gas: u128,
+    },
+}
+
+impl Fees {
+    /// Increase the fees by `percent`, rounding up.
+    pub fn bump(self, percent: u64) -> Self {
+        let bump = |x: u128| x.saturating_add(x.saturating_mul(percent.into()).div_ceil(100));
+
+        match self {
+            Fees::Legacy { gas_price } => Fees::Legacy {
+                gas_price: bump(gas_price),
+            },
+            Fees::Eip1559 {
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+            } => Fees::Eip1559 {
+                max_fee_per_gas: bump(max_fee_per_gas),
+                max_priority_fee_per_gas: bump(max_priority_fee_per_gas),
+            },
+        }
+    }
+
+    /// Take the higher of each fee. If the kinds of fees don't match, `self` is returned.
+    pub fn max(self, other: Self) -> Self {
+        match (self, other) {
+            (Fees::Legacy { gas_price: a }, Fees::Legacy { gas_price: b }) => Fees::Legacy {
+                gas_price: a.max(b),
+            },
+            (
+                Fees::Eip1559 {
+                    max_fee_per_gas: a_max,
+                    max_priority_fee_per_gas: a_priority,
+                },
+                Fees::Eip1559 {
+                    max_fee_per_gas: b_max,
+                    max_priority_fee_per_gas: b_priority,
+                },
+            ) => Fees::Eip1559 {
+                max_fee_per_gas: a_max.max(b_max),
+                max_priority_fee_per_gas: a_priority.max(b_priority),
+            },
+            _ => self,
+        }
+    }
+
+    /// The maximum price per unit of gas that will be paid with these fees.
+    pub fn max_gas_price(self) -> u128 {
+        match self {
+            Fees::Legacy { gas_price } => gas_price,
+            Fees::Eip1559 {
+                max_fee_per_gas, ..
+            } => max_fee_per_gas,
+        }
+    }
+}
+
+/// The queries to the chain that are required to replace transactions.
+pub trait ReplacementClient {
+    type Receipt;
+
+    /// The fees to submit a new transaction with.
+    fn fees(&self) -> impl Future<Output = Result<Fees, TxSubmitError>> + Send;
+
+    /// The receipt of the transaction `tx_hash`, if it has been included.
+    fn receipt(
+        &self,
+        tx_hash: TxHash,
+    ) -> impl Future<Output = Result<Option<Self::Receipt>, TxSubmitError>> + Send;
+}
+
+/// A transaction that has been sent to the chain.
+pub trait SentTx {
+    type Receipt;
+
+    fn tx_hash(&self) -> TxHash;
+
+    /// Wait for the transaction to be included for at most `timeout`, returning `None` if it was
+    /// not included in time.
+    fn receipt(
+        self,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<Option<Self::Receipt>, TxSubmitError>> + Send;
+}
+
+impl SentTx for PendingTransactionBuilder<BoxTransport, AnyNetwork> {
+    type Receipt = <AnyNetwork as Network>::ReceiptResponse;
+
+    fn tx_hash(&self) -> TxHash {
+        *PendingTransactionBuilder::tx_hash(self)
+    }
+
+    async fn receipt(self, timeout: Duration) -> Result<Option<Self::Receipt>, TxSubmitError> {
+        match self.with_timeout(Some(timeout)).get_receipt().await {
+            Ok(receipt) => Ok(Some(receipt)),
+            Err(PendingTransactionError::TxWatcher(WatchTxError::Timeout)) => Ok(None),
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// Sends transactions, replacing them with transactions with higher fees every time they are not
+/// included within the configured timeout.
+pub struct TxReplacer<'a, C> {
+    pub client: &'a C,
+    pub config: &'a TxReplacementConfig,
+    pub max_gas_price: Option<u128>,
+    /// The next nonce to use for each key, as of the last transaction from that key that was
+    /// included.
+    pub nonces: &'a Mutex<HashMap<Address, u64>>,
+}
+
+impl<C: ReplacementClient + Sync> TxReplacer<'_, C> {
+    /// Send the transaction built by `send` with `nonce`, replacing it with one with higher fees
+    /// every time it is not included within the configured timeout.
+    pub async fn send<Fut, T>(
+        &self,
+        address: Address,
+        nonce: u64,
+        mut send: impl FnMut(Fees) -> Fut,
+    ) -> Result<C::Receipt, TxSubmitError>
+    where
+        Fut: Future<Output = Result<T, Error>>,
+        T: SentTx<Receipt = C::Receipt>,
+    {
+        let timeout = Duration::from_secs(self.config.timeout_seconds);
+
+        let mut fees = self.client.fees().await?;
+        let mut sent = vec![];
+
+        for attempt in 0..=self.config.max_replacements {
+            if attempt > 0 {
+                // a previous transaction may have been included while waiting on the last one
+                if let Some(receipt) = self.find_receipt(address, nonce, &sent).await? {
+                    return Ok(receipt);
+                }
+
+                fees = fees
+                    .bump(self.config.fee_bump_percent)
+                    .max(self.client.fees().await?);
+
+                if let Some(max_gas_price) = self.max_gas_price {
+                    if fees.max_gas_price() > max_gas_price {
+                        warn!(
+                            %address,
+                            nonce,
+                            %max_gas_price,
+                            ?fees,
+                            "unable to replace transaction, gas price is too high"
+                        );
+
+                        break;
+                    }
+                }
+
+                warn!(%address, nonce, attempt, ?fees, "replacing transaction");
+            }
+
+            let pending = match send(fees).await {
+                Ok(pending) => pending,
+                Err(err) => match error_message(&err) {
+                    Some(message)
+                        if message.contains("insufficient funds for gas * price + value") =>
+                    {
+                        error!("out of gas");
+                        return Err(TxSubmitError::OutOfGas);
+                    }
+                    Some(message) if message.contains("replacement transaction underpriced") => {
+                        debug!(%address, nonce, ?fees, "replacement transaction underpriced");
+                        continue;
+                    }
+                    Some(message) if message.contains("nonce too low") => {
+                        return self
+                            .find_receipt(address, nonce, &sent)
+                            .await?
+                            .ok_or(TxSubmitError::NonceTooLow { nonce });
+                    }
+                    _ => return Err(TxSubmitError::Error(err)),
+                },
+            };
+
+            let tx_hash = pending.tx_hash();
+
+            debug!(%address, nonce, %tx_hash, ?fees, "sent transaction");
+
+            sent.push(tx_hash);
+
+            match pending.receipt(timeout).await? {
+                Some(receipt) => {
+                    self.record_nonce(address, nonce);
+                    return Ok(receipt);
+                }
+                None => {
+                    warn!(%address, nonce, %tx_hash, "transaction was not included in time");
+                }
+            }
+        }
+
+        if let Some(receipt) = self.find_receipt(address, nonce, &sent).await? {
+            return Ok(receipt);
+        }
+
+        Err(TxSubmitError::Stuck {
+            nonce,
+            replacements: self.config.max_replacements,
+        })
+    }
+
+    /// Find the receipt of whichever of the `sent` transactions with `nonce` was included, if any.
+    async fn find_receipt(
+        &self,
+        address: Address,
+        nonce: u64,
+        sent: &[TxHash],
+    ) -> Result<Option<C::Receipt>, TxSubmitError> {
+        for tx_hash in sent {
+            if let Some(receipt) = self.client.receipt(*tx_hash).await? {
+                info!(%address, nonce, %tx_hash, "found receipt of previously sent transaction");
+
+                self.record_nonce(address, nonce);
+
+                return Ok(Some(receipt));
+            }
+        }
+
+        Ok(None)
+    }
+
+    fn record_nonce(&self, address: Address, nonce: u64) {
+        let mut nonces = self.nonces.lock().expect("mutex is poisoned");
+        let next = nonces.entry(address).or_default();
+        *next = (*next).max(nonce + 1);
+    }
+}
+
+/// The message of the json-rpc error response contained in `err`, if any.
+fn error_message(err: &Error) -> Option<&str> {
+    match err {
+        Error::PendingTransactionError(PendingTransactionError::TransportError(
+            TransportError::ErrorResp(e),
+        ))
+        | Error::TransportError(TransportError::ErrorResp(e)) => Some(&e.message),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    const LEGACY: Fees = Fees::Legacy { gas_price: 100 };
+
+    #[test]
+    fn bump() {
+        assert_eq!(LEGACY.bump(20), Fees::Legacy { gas_price: 120 });
+        assert_eq!(LEGACY.bump(0), LEGACY);
+
+        assert_eq!(
+            Fees::Eip1559 {
+                max_fee_per_gas: 1000,
+                max_priority_fee_per_gas: 10,
+            }
+            .bump(10),
+            Fees::Eip1559 {
+                max_fee_per_gas: 1100,
+                max_priority_fee_per_gas: 11,
+            }
+        );
+    }
+
+    #[test]
+    fn bump_rounds_up() {
+        // 101 * 10% = 10.1
+        assert_eq!(
+            Fees::Legacy { gas_price: 101 }.bump(10),
+            Fees::Legacy { gas_price: 112 }
+        );
+        // small fees are always increased
+        assert_eq!(
+            Fees::Legacy { gas_price: 1 }.bump(10),
+            Fees::Legacy { gas_price: 2 }
+        );
+        assert_eq!(
+            Fees::Eip1559 {
+                max_fee_per_gas: 3,
+                max_priority_fee_per_gas: 1,
+            }
+            .bump(1),
+            Fees::Eip1559 {
+                max_fee_per_gas: 4,
+                max_priority_fee_per_gas: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn bump_saturates() {
+        assert_eq!(
+            Fees::Legacy {
+                gas_price: u128::MAX
+            }
+            .bump(20),
+            Fees::Legacy {
+                gas_price: u128::MAX
+            }
+        );
+        assert_eq!(
+            Fees::Eip1559 {
+                max_fee_per_gas: u128::MAX / 2,
+                max_priority_fee_per_gas: u128::MAX - 1,
+            }
+            .bump(u64::MAX),
+            Fees::Eip1559 {
+                max_fee_per_gas: u128::MAX / 2 + (u128::MAX / 100 + 1),
+                max_priority_fee_per_gas: u128::MAX,
+            }
+        );
+    }
+
+    #[test]
+    fn bump_zero_fees() {
+        assert_eq!(
+            Fees::Legacy { gas_price: 0 }.bump(20),
+            Fees::Legacy { gas_price: 0 }
+        );
+        assert_eq!(
+            Fees::Eip1559 {
+                max_fee_per_gas: 0,
+                max_priority_fee_per_gas: 0,
+            }
+            .bump(20),
+            Fees::Eip1559 {
+                max_fee_per_gas: 0,
+                max_priority_fee_per_gas: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn max() {
+        assert_eq!(
+            LEGACY.max(Fees::Legacy { gas_price: 50 }),
+            Fees::Legacy { gas_price: 100 }
+        );
+        assert_eq!(
+            LEGACY.max(Fees::Legacy { gas_price: 150 }),
+            Fees::Legacy { gas_price: 150 }
+        );
+
+        // each fee is compared separately
+        assert_eq!(
+            Fees::Eip1559 {
+                max_fee_per_gas: 100,
+                max_priority_fee_per_gas: 1,
+            }
+            .max(Fees::Eip1559 {
+                max_fee_per_gas: 50,
+                max_priority_fee_per_gas: 2,
+            }),
+            Fees::Eip1559 {
+                max_fee_per_gas: 100,
+                max_priority_fee_per_gas: 2,
+            }
+        );
+
+        // mismatched kinds of fees
+        let eip1559 = Fees::Eip1559 {
+            max_fee_per_gas: 1000,
+            max_priority_fee_per_gas: 1000,
+        };
+        assert_eq!(LEGACY.max(eip1559), LEGACY);
+        assert_eq!(eip1559.max(LEGACY), eip1559);
+    }
+
+    #[test]
+    fn max_gas_price() {
+        assert_eq!(LEGACY.max_gas_price(), 100);
+        assert_eq!(
+            Fees::Eip1559 {
+                max_fee_per_gas: 1000,
+                max_priority_fee_per_gas: 10,
+            }
+            .max_gas_price(),
+            1000
+        );
+    }
+
+    /// A chain where the current fees are `fees`, and only the transactions in `included` have
+    /// been included. The receipt of a transaction is its hash.
+    struct MockClient {
+        fees: Mutex<Fees>,
+        included: Mutex<HashSet<TxHash>>,
+    }
+
+    impl MockClient {
+        fn new(fees: Fees) -> Self {
+            Self {
+                fees: Mutex::new(fees),
+                included: Mutex::default(),
+            }
+        }
+
+        fn set_fees(&self, fees: Fees) {
+            *self.fees.lock().unwrap() = fees;
+        }
+
+        fn include(&self, tx_hash: TxHash) {
+            self.included.lock().unwrap().insert(tx_hash);
+        }
+
+        fn is_included(&self, tx_hash: TxHash) -> bool {
+            self.included.lock().unwrap().contains(&tx_hash)
+        }
+    }
+
+    impl ReplacementClient for MockClient {
+        type Receipt = TxHash;
+
+        async fn fees(&self) -> Result<Fees, TxSubmitError> {
+            Ok(*self.fees.lock().unwrap())
+        }
+
+        async fn receipt(&self, tx_hash: TxHash) -> Result<Option<TxHash>, TxSubmitError> {
+            Ok(self.is_included(tx_hash).then_some(tx_hash))
+        }
+    }
+
+    /// A sent transaction, which is included immediately if `included` is set and is otherwise
+    /// never included within the timeout.
+    struct MockSentTx {
+        tx_hash: TxHash,
+        included: bool,
+    }
+
+    impl SentTx for MockSentTx {
+        type Receipt = TxHash;
+
+        fn tx_hash(&self) -> TxHash {
+            self.tx_hash
+        }
+
+        async fn receipt(self, _: Duration) -> Result<Option<TxHash>, TxSubmitError> {
+            Ok(self.included.then_some(self.tx_hash))
+        }
+    }
+
+    fn sent(n: u8, included: bool) -> Result<MockSentTx, Error> {
+        Ok(MockSentTx {
+            tx_hash: TxHash::with_last_byte(n),
+            included,
+        })
+    }
+
+    fn rpc_error(message: &str) -> Result<MockSentTx, Error> {
+        Err(Error::TransportError(TransportError::ErrorResp(
+            serde_json::from_value(serde_json::json!({
+                "code": -32000,
+                "message": message,
+            }))
+            .unwrap(),
+        )))
+    }
+
+    fn config(max_replacements: u32) -> TxReplacementConfig {
+        TxReplacementConfig {
+            timeout_seconds: 0,
+            fee_bump_percent: 20,
+            max_replacements,
+            cancel_pending_transactions: false,
+        }
+    }
+
+    const ADDRESS: Address = Address::ZERO;
+
+    /// Send a transaction with nonce 7, with the responses to every attempt to send it returned by
+    /// `responses`. Returns the result, the fees of every attempt and the recorded nonces.
+    async fn send(
+        client: &MockClient,
+        config: &TxReplacementConfig,
+        max_gas_price: Option<u128>,
+        mut responses: impl FnMut(usize) -> Result<MockSentTx, Error>,
+    ) -> (
+        Result<TxHash, TxSubmitError>,
+        Vec<Fees>,
+        HashMap<Address, u64>,
+    ) {
+        let nonces = Mutex::default();
+
+        let replacer = TxReplacer {
+            client,
+            config,
+            max_gas_price,
+            nonces: &nonces,
+        };
+
+        let mut attempts = vec![];
+
+        let res = replacer
+            .send(ADDRESS, 7, |fees| {
+                attempts.push(fees);
+                std::future::ready(responses(attempts.len() - 1))
+            })
+            .await;
+
+        (res, attempts, nonces.into_inner().unwrap())
+    }
+
+    #[tokio::test]
+    async fn included() {
+        let client = MockClient::new(LEGACY);
+
+        let (res, attempts, nonces) = send(&client, &config(5), None, |_| sent(1, true)).await;
+
+        assert_eq!(res.unwrap(), TxHash::with_last_byte(1));
+        assert_eq!(attempts, [LEGACY]);
+        assert_eq!(nonces, HashMap::from([(ADDRESS, 8)]));
+    }
+
+    #[tokio::test]
+    async fn underpriced_replacement() {
+        let client = MockClient::new(LEGACY);
+
+        let (res, attempts, nonces) = send(&client, &config(5), None, |attempt| match attempt {
+            0 => sent(1, false),
+            1 => rpc_error("replacement transaction underpriced"),
+            _ => sent(3, true),
+        })
+        .await;
+
+        // the fees are bumped again after the replacement was rejected
+        assert_eq!(res.unwrap(), TxHash::with_last_byte(3));
+        assert_eq!(
+            attempts,
+            [
+                LEGACY,
+                Fees::Legacy { gas_price: 120 },
+                Fees::Legacy { gas_price: 144 }
+            ]
+        );
+        assert_eq!(nonces, HashMap::from([(ADDRESS, 8)]));
+    }
+
+    #[tokio::test]
+    async fn replacement_uses_current_fees() {
+        let client = MockClient::new(LEGACY);
+
+        let (res, attempts, _) = send(&client, &config(5), None, |attempt| {
+            if attempt == 0 {
+                client.set_fees(Fees::Legacy { gas_price: 500 });
+            }
+            sent(attempt as u8, attempt == 1)
+        })
+        .await;
+
+        // the fees rose above the bumped fees while waiting for the first transaction
+        assert_eq!(res.unwrap(), TxHash::with_last_byte(1));
+        assert_eq!(attempts, [LEGACY, Fees::Legacy { gas_price: 500 }]);
+    }
+
+    #[tokio::test]
+    async fn nonce_too_low_resolved_by_receipt() {
+        let client = MockClient::new(LEGACY);
+
+        let (res, attempts, nonces) = send(&client, &config(5), None, |attempt| match attempt {
+            0 => sent(1, false),
+            _ => {
+                // the first transaction was included after it was checked for, but before the
+                // replacement was sent
+                client.include(TxHash::with_last_byte(1));
+                rpc_error("nonce too low")
+            }
+        })
+        .await;
+
+        assert_eq!(res.unwrap(), TxHash::with_last_byte(1));
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(nonces, HashMap::from([(ADDRESS, 8)]));
+    }
+
+    #[tokio::test]
+    async fn nonce_too_low_without_receipt() {
+        let client = MockClient::new(LEGACY);
+
+        // the nonce was used by a transaction that was not sent by this replacer
+        let (res, attempts, nonces) =
+            send(&client, &config(5), None, |_| rpc_error("nonce too low")).await;
+
+        assert!(matches!(res, Err(TxSubmitError::NonceTooLow { nonce: 7 })));
+        assert_eq!(attempts, [LEGACY]);
+        assert!(nonces.is_empty());
+    }
+
+    #[tokio::test]
+    async fn previous_transaction_included() {
+        let client = MockClient::new(LEGACY);
+
+        let (res, attempts, nonces) = send(&client, &config(5), None, |attempt| {
+            if attempt == 1 {
+                client.include(TxHash::with_last_byte(0));
+            }
+            sent(attempt as u8, false)
+        })
+        .await;
+
+        // the receipt of the first transaction is found before sending a third one
+        assert_eq!(res.unwrap(), TxHash::with_last_byte(0));
+        assert_eq!(attempts.len(), 2);
+        assert_eq!(nonces, HashMap::from([(ADDRESS, 8)]));
+    }
+
+    #[tokio::test]
+    async fn stuck_after_max_replacements() {
+        let client = MockClient::new(LEGACY);
+
+        let (res, attempts, nonces) = send(&client, &config(2), None, |attempt| {
+            sent(attempt as u8, false)
+        })
+        .await;
+
+        assert!(matches!(
+            res,
+            Err(TxSubmitError::Stuck {
+                nonce: 7,
+                replacements: 2
+            })
+        ));
+        assert_eq!(
+            attempts,
+            [
+                LEGACY,
+                Fees::Legacy { gas_price: 120 },
+                Fees::Legacy { gas_price: 144 }
+            ]
+        );
+        assert!(nonces.is_empty());
+    }
+
+    #[tokio::test]
+    async fn stuck_at_max_gas_price() {
+        let client = MockClient::new(LEGACY);
+
+        let (res, attempts, _) = send(&client, &config(5), Some(130), |attempt| {
+            sent(attempt as u8, false)
+        })
+        .await;
+
+        // 144 is above the max gas price, so the transaction is not replaced a second time
+        assert!(matches!(res, Err(TxSubmitError::Stuck { nonce: 7, .. })));
+        assert_eq!(attempts, [LEGACY, Fees::Legacy { gas_price: 120 }]);
+    }
+
+    #[tokio::test]
+    async fn out_of_gas() {
+        let client = MockClient::new(LEGACY);
+
+        let (res, _, _) = send(&client, &config(5), None, |_| {
+            rpc_error("insufficient funds for gas * price + value: balance 0")
+        })
+        .await;
+
+        assert!(matches!(res, Err(TxSubmitError::OutOfGas)));
+    }
+}
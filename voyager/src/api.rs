@@ -1,4 +1,8 @@
-use std::net::SocketAddr;
+use std::{
+    collections::BTreeMap,
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
 
 use axum::{
     extract::State,
@@ -12,23 +16,29 @@ use futures::{
 use prometheus::TextEncoder;
 use reqwest::StatusCode;
 use tracing::error;
-use voyager_message::VoyagerMessage;
+use voyager_message::{module::SignerBalance, VoyagerMessage};
 use voyager_vm::Op;
 
-pub fn run(laddr: &SocketAddr) -> UnboundedReceiver<Op<VoyagerMessage>> {
+/// The latest known signer balances of each transaction plugin, keyed by plugin name.
+pub type SignerBalances = Arc<Mutex<BTreeMap<String, Vec<SignerBalance>>>>;
+
+pub fn run(
+    laddr: &SocketAddr,
+    signer_balances: SignerBalances,
+) -> UnboundedReceiver<Op<VoyagerMessage>> {
     let (queue_tx, queue_rx) = unbounded::<Op<VoyagerMessage>>();
 
     let app = axum::Router::new()
         .route("/enqueue", post(enqueue))
         .route("/health", get(|| async move { StatusCode::OK }))
         .route("/metrics", get(metrics))
-        // .route(
-        //     "/signer/balances",
-        //     get({
-        //         let chains = self.chains.clone();
-        //         || async move { Json(signer_balances(&chains).await) }
-        //     }),
-        // )
+        .route(
+            "/signer/balances",
+            get(move || {
+                let signer_balances = signer_balances.lock().expect("mutex is poisoned").clone();
+                async move { Json(signer_balances) }
+            }),
+        )
         .with_state(queue_tx.clone());
 
     tokio::spawn(axum::Server::bind(laddr).serve(app.into_make_service()));
@@ -1,7 +1,12 @@
-use std::{ffi::OsString, str::FromStr};
+use std::{ffi::OsString, path::PathBuf, str::FromStr, time::Duration};
 
 use clap::{self, Parser, Subcommand};
-use unionlabs::{self, bounded::BoundedI64, ibc::core::client::height::Height, result_unwrap};
+use unionlabs::{
+    self,
+    bounded::{BoundedI32, BoundedI64},
+    ibc::core::client::height::Height,
+    result_unwrap,
+};
 use voyager_message::{
     core::{ChainId, ClientType, IbcInterface, IbcSpecId, QueryHeight},
     module::{ClientModuleInfo, ConsensusModuleInfo, ProofModuleInfo, StateModuleInfo},
@@ -125,6 +130,7 @@ pub enum ConfigCmd {
 }
 
 type Pg64 = BoundedI64<1, { i64::MAX }>;
+type Pg32 = BoundedI32<0, { i32::MAX }>;
 
 #[derive(Debug, Subcommand)]
 pub enum QueueCmd {
@@ -144,11 +150,25 @@ pub enum QueueCmd {
         rest_url: String,
     },
 
-    // History {
-    //     id: PgId,
-    //     #[arg(long, default_value_t = result_unwrap!(Pg32::new(10)))]
-    //     max_depth: Pg32,
-    // },
+    /// Query the history of an op, i.e. the op itself and all of the ops that it was produced from.
+    ///
+    /// The ops are returned as a tree rooted at the queried op, where every op links to the ops it
+    /// was produced from, along with the table it is currently in.
+    History {
+        id: Pg64,
+        #[arg(long, default_value_t = result_unwrap!(Pg32::new_const(10)))]
+        max_depth: Pg32,
+    },
+    /// Query the descendants of an op, i.e. the op itself and all of the ops that were produced
+    /// from it.
+    ///
+    /// The ops are returned as a tree rooted at the queried op, where every op links to the ops
+    /// that were produced from it.
+    Descendants {
+        id: Pg64,
+        #[arg(long, default_value_t = result_unwrap!(Pg32::new_const(10)))]
+        max_depth: Pg32,
+    },
     /// Query all failed messages.
     QueryFailed {
         #[arg(long, default_value_t = result_unwrap!(Pg64::new_const(1)))]
@@ -175,12 +195,82 @@ pub enum QueueCmd {
         #[arg(long = "message-filter", short = 'm')]
         message_filters: Vec<String>,
     },
+    /// Requeue all failed messages matching the filters.
+    ///
+    /// The failed messages are moved to the archive, and the requeued messages will have the
+    /// failed message they were requeued from as their parent.
+    RequeueFailed {
+        /// SQL filters for the item. See `query-failed` for more information.
+        #[arg(long = "item-filter", short = 'i')]
+        item_filters: Vec<String>,
+        /// SQL filters for failure message.
+        #[arg(long = "message-filter", short = 'm')]
+        message_filters: Vec<String>,
+    },
+    /// Archive all failed messages older than the specified age.
+    ArchiveFailed {
+        /// How old the messages must be, i.e. `1 day`, `12 hours` or `1h 30m`.
+        #[arg(long, value_parser(parse_duration))]
+        older_than: Duration,
+        /// Delete the failed messages instead of archiving them.
+        #[arg(long)]
+        delete: bool,
+    },
+    /// Export all failed messages matching the filters to a JSONL file, one message per line.
+    ExportFailed {
+        path: PathBuf,
+        /// SQL filters for the item. See `query-failed` for more information.
+        #[arg(long = "item-filter", short = 'i')]
+        item_filters: Vec<String>,
+        /// SQL filters for failure message.
+        #[arg(long = "message-filter", short = 'm')]
+        message_filters: Vec<String>,
+    },
+    /// Import failed messages from a JSONL file created with `export-failed`.
+    ///
+    /// The messages are inserted as failed messages with new IDs, unless `--requeue` is set, in
+    /// which case they are enqueued directly.
+    ImportFailed {
+        path: PathBuf,
+        #[arg(long, short = 'e')]
+        requeue: bool,
+    },
+    /// Query all messages that have failed with a retryable error and are waiting to be retried,
+    /// along with their attempt count and the last error they returned.
+    QueryRetrying {
+        #[arg(long, default_value_t = result_unwrap!(Pg64::new_const(1)))]
+        page: Pg64,
+        #[arg(long, default_value_t = result_unwrap!(Pg64::new_const(1)))]
+        per_page: Pg64,
+    },
     /// Query a failed message by it's ID.
     QueryFailedById {
         id: Pg64,
         #[arg(long, short = 'e')]
         requeue: bool,
     },
+    /// Write all pending messages (the messages that are ready to be processed and the messages
+    /// waiting to be optimized) to a JSON file, to be replayed with `replay`.
+    Snapshot { path: PathBuf },
+    /// Replay a snapshot created with `snapshot` against an in-memory queue, processing the
+    /// messages one at a time.
+    ///
+    /// The requests to the modules are served from a recording of their responses (see
+    /// `voyager.module_recording` in the config), the modules are not started. The messages are
+    /// processed with the plugins in the config, except for the plugins that handle transaction
+    /// submission: these are not loaded, and any transaction submissions fail instead.
+    ///
+    /// Every step is printed as a JSON line, followed by the failed and remaining messages once
+    /// the replay has finished.
+    Replay {
+        path: PathBuf,
+        /// The recorded module responses to serve.
+        #[arg(long)]
+        recording: PathBuf,
+        /// Stop after processing this many messages.
+        #[arg(long)]
+        max_steps: Option<u64>,
+    },
 }
 
 #[derive(Debug, Subcommand)]
@@ -323,3 +413,92 @@ pub enum MsgCmd {
 fn ok<T>(t: T) -> Result<T, BoxDynError> {
     Ok(t)
 }
+
+/// Parse a duration made up of one or more `<amount> <unit>` pairs, i.e. `1 day`, `12 hours` or
+/// `1h 30m`. The space between the amount and the unit is optional.
+fn parse_duration(s: &str) -> Result<Duration, BoxDynError> {
+    let mut tokens = s.split_whitespace();
+    let mut duration = None::<Duration>;
+
+    while let Some(token) = tokens.next() {
+        let (amount, unit) = token.split_at(
+            token
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(token.len()),
+        );
+
+        let unit = match unit {
+            "" => tokens
+                .next()
+                .ok_or_else(|| format!("missing unit after `{amount}`"))?,
+            unit => unit,
+        };
+
+        let amount = amount
+            .parse::<u64>()
+            .map_err(|_| format!("invalid amount in `{token}`"))?;
+
+        let unit_secs = match unit {
+            "s" | "sec" | "secs" | "second" | "seconds" => 1,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60,
+            "h" | "hour" | "hours" => 60 * 60,
+            "d" | "day" | "days" => 24 * 60 * 60,
+            "w" | "week" | "weeks" => 7 * 24 * 60 * 60,
+            _ => return Err(format!("unknown unit `{unit}`").into()),
+        };
+
+        duration = Some(
+            amount
+                .checked_mul(unit_secs)
+                .map(Duration::from_secs)
+                .and_then(|d| d.checked_add(duration.unwrap_or_default()))
+                .ok_or("duration is too large")?,
+        );
+    }
+
+    duration.ok_or_else(|| "duration must not be empty".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_units() {
+        assert_eq!(
+            parse_duration("1 day").unwrap(),
+            Duration::from_secs(24 * 60 * 60)
+        );
+        assert_eq!(
+            parse_duration("12 hours").unwrap(),
+            Duration::from_secs(12 * 60 * 60)
+        );
+        assert_eq!(parse_duration("30s").unwrap(), Duration::from_secs(30));
+        assert_eq!(
+            parse_duration("2 weeks").unwrap(),
+            Duration::from_secs(14 * 24 * 60 * 60)
+        );
+    }
+
+    #[test]
+    fn parse_duration_sums_parts() {
+        assert_eq!(
+            parse_duration("1h 30m").unwrap(),
+            Duration::from_secs(90 * 60)
+        );
+        assert_eq!(
+            parse_duration("1 day 1 min").unwrap(),
+            Duration::from_secs(24 * 60 * 60 + 60)
+        );
+    }
+
+    #[test]
+    fn parse_duration_invalid() {
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("1").is_err());
+        assert!(parse_duration("1 fortnight").is_err());
+        assert!(parse_duration("day").is_err());
+        assert!(parse_duration("-1 day").is_err());
+        assert!(parse_duration(&format!("{} weeks", u64::MAX)).is_err());
+    }
+}
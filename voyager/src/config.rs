@@ -1,10 +1,14 @@
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    path::PathBuf,
+};
 
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use voyager_message::context::{
     equivalent_chain_ids::EquivalentChainIds, ModulesConfig, PluginConfig,
 };
+use voyager_vm::engine::RetryPolicy;
 
 use crate::queue::QueueConfig;
 
@@ -33,6 +37,13 @@ pub struct VoyagerConfig {
     // TODO: Specify per plugin
     #[serde(default = "default_optimizer_delay_milliseconds")]
     pub optimizer_delay_milliseconds: u64,
+    /// How ops that fail with a retryable error are retried.
+    #[serde(default)]
+    pub retry_policy: RetryPolicy,
+    /// If set, the responses of the modules are appended to this file, to be served when
+    /// replaying a snapshot of the queue with `queue replay`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub module_recording: Option<PathBuf>,
 }
 
 #[must_use]
@@ -18,7 +18,7 @@ use anyhow::{anyhow, Context as _};
 use clap::Parser;
 use ibc_classic_spec::IbcClassic;
 use ibc_union_spec::IbcUnion;
-use pg_queue::PgQueueConfig;
+use pg_queue::{FailedRecord, PgQueueConfig};
 use schemars::gen::{SchemaGenerator, SchemaSettings};
 use serde::Serialize;
 use tikv_jemallocator::Jemalloc;
@@ -28,15 +28,18 @@ use voyager_message::{
     call::{FetchBlocks, FetchUpdateHeaders},
     callback::AggregateMsgUpdateClientsFromOrderedHeaders,
     context::{
-        equivalent_chain_ids::EquivalentChainIds, get_plugin_info,
-        ibc_spec_handler::IbcSpecHandler, Context, ModulesConfig,
+        equivalent_chain_ids::EquivalentChainIds,
+        get_plugin_info,
+        ibc_spec_handler::IbcSpecHandler,
+        recording::{ModuleResponses, Recording},
+        Context, ModulesConfig,
     },
     core::{IbcSpec, QueryHeight},
     filter::{make_filter, run_filter, JaqInterestFilter},
     rpc::{IbcState, VoyagerRpcClient},
     VoyagerMessage,
 };
-use voyager_vm::{call, filter::FilterResult, promise, Op, Queue};
+use voyager_vm::{call, engine::RetryPolicy, filter::FilterResult, promise, ItemTree, Op, Queue};
 
 #[global_allocator]
 static GLOBAL: Jemalloc = Jemalloc;
@@ -57,6 +60,7 @@ compile_error!(
 pub mod api;
 pub mod cli;
 pub mod config;
+pub mod metrics;
 pub mod queue;
 
 fn main() -> ExitCode {
@@ -162,6 +166,7 @@ async fn do_main(args: cli::AppArgs) -> anyhow::Result<()> {
                         max_lifetime: None,
                     }),
                     optimizer_delay_milliseconds: 100,
+                    retry_policy: RetryPolicy::default(),
                 },
             }),
             ConfigCmd::Schema => print_json(
@@ -202,7 +207,9 @@ async fn do_main(args: cli::AppArgs) -> anyhow::Result<()> {
 
                 match result {
                     Ok(FilterResult::Interest(tag)) => println!("interest ({tag})"),
-                    Ok(FilterResult::NoInterest) => println!("no interest"),
+                    Ok(FilterResult::NoInterest(priority)) => {
+                        println!("no interest (priority {priority})");
+                    }
                     Err(()) => println!("failed"),
                 }
             }
@@ -252,54 +259,118 @@ async fn do_main(args: cli::AppArgs) -> anyhow::Result<()> {
             ModuleCmd::Client(_) => todo!(),
         },
         Command::Queue(cli_msg) => {
-            let db = || {
-                Ok(match get_voyager_config()?.voyager.queue {
-                    QueueConfig::PgQueue(cfg) => pg_queue::PgQueue::<VoyagerMessage>::new(cfg),
-                    QueueConfig::InMemory => {
-                        return Err(anyhow!(
-                            "no database set in config, queue commands \
-                            require the `pg-queue` database backend"
-                        ))
+            // the queue commands operate on the database directly, and are supported by all of
+            // the persistent queue backends
+            macro_rules! with_db {
+                (|$q:ident| $body:expr) => {
+                    match get_voyager_config()?.voyager.queue {
+                        QueueConfig::PgQueue(cfg) => {
+                            let $q = pg_queue::PgQueue::<VoyagerMessage>::new(cfg).await?;
+                            $body
+                        }
+                        QueueConfig::SqliteQueue(cfg) => {
+                            let $q = sqlite_queue::SqliteQueue::<VoyagerMessage>::new(cfg).await?;
+                            $body
+                        }
+                        QueueConfig::InMemory => {
+                            return Err(anyhow!(
+                                "no database set in config, queue commands require \
+                                either the `pg-queue` or `sqlite-queue` backend"
+                            ))
+                        }
                     }
-                })
-            };
+                };
+            }
 
             match cli_msg {
                 QueueCmd::Enqueue { op, rest_url } => {
                     send_enqueue(&rest_url, op).await?;
                 }
-                // NOTE: Temporarily disabled until i figure out a better way to implement this with the new queue design
-                // cli::QueueCmd::History { id, max_depth } => {
-                //     // let results = query_as!(
-                //     //     Record,
-                //     //     r#"SELECT id as "id!", parent, item as "item!: Item" FROM get_list($1, $2) ORDER BY id ASC"#,
-                //     //     id.inner(),
-                //     //     max_depth.inner()
-                //     // )
-                //     // .fetch_all(&db)
-                //     // .await
-                //     // .unwrap();
-
-                //     // println!("{}", serde_json::to_string_pretty(&results).unwrap());
-
-                //     todo!();
-                // }
+                QueueCmd::History { id, max_depth } => with_db!(|q| {
+                    let records = q.query_ancestors(id.inner(), max_depth.inner()).await?;
+
+                    print_json(&ItemTree::ancestors(records));
+                }),
+                QueueCmd::Descendants { id, max_depth } => with_db!(|q| {
+                    let records = q.query_descendants(id.inner(), max_depth.inner()).await?;
+
+                    print_json(&ItemTree::descendants(records));
+                }),
                 QueueCmd::QueryFailed {
                     page,
                     per_page,
                     item_filters,
                     message_filters,
-                } => {
-                    let record = db()?
-                        .await?
+                } => with_db!(|q| {
+                    let record = q
                         .query_failed(page.into(), per_page.into(), item_filters, message_filters)
                         .await?;
 
                     print_json(&record);
+                }),
+                QueueCmd::RequeueFailed {
+                    item_filters,
+                    message_filters,
+                } => with_db!(|q| {
+                    let ids = q.requeue_failed(item_filters, message_filters).await?;
+
+                    print_json(&ids);
+                }),
+                QueueCmd::ArchiveFailed { older_than, delete } => with_db!(|q| {
+                    let count = q.archive_failed(older_than, delete).await?;
+
+                    print_json(&count);
+                }),
+                QueueCmd::ExportFailed {
+                    path,
+                    item_filters,
+                    message_filters,
+                } => with_db!(|q| {
+                    let records = q.export_failed(item_filters, message_filters).await?;
+
+                    let mut out = String::new();
+                    for record in &records {
+                        writeln!(out, "{}", serde_json::to_string(record)?)?;
+                    }
+
+                    std::fs::write(&path, out).with_context(|| {
+                        format!("unable to write to `{}`", path.to_string_lossy())
+                    })?;
+
+                    print_json(&records.len());
+                }),
+                QueueCmd::ImportFailed { path, requeue } => {
+                    // the exported records have the same format for all of the queue backends
+                    let lines = read_to_string(&path)
+                        .with_context(|| format!("unable to read `{}`", path.to_string_lossy()))?;
+                    let lines = lines.lines().filter(|line| !line.trim().is_empty());
+
+                    with_db!(|q| if requeue {
+                        let filter =
+                            JaqInterestFilter::new(vec![]).expect("empty filter can be built");
+
+                        let mut results = vec![];
+                        for line in lines {
+                            let record =
+                                serde_json::from_str::<FailedRecord<VoyagerMessage>>(line)?;
+                            results.push(q.enqueue(record.item.0, &filter).await?);
+                        }
+
+                        print_json(&results);
+                    } else {
+                        let records = lines
+                            .map(serde_json::from_str)
+                            .collect::<Result<Vec<_>, _>>()?;
+
+                        print_json(&q.import_failed(records).await?);
+                    });
                 }
-                QueueCmd::QueryFailedById { id, requeue } => {
-                    let q = db()?.await?;
+                QueueCmd::QueryRetrying { page, per_page } => with_db!(|q| {
+                    let record = q.query_retrying(page.into(), per_page.into()).await?;
 
+                    print_json(&record);
+                }),
+                QueueCmd::QueryFailedById { id, requeue } => with_db!(|q| {
                     let record = q.query_failed_by_id(id.inner()).await?;
 
                     if requeue {
@@ -316,6 +387,44 @@ async fn do_main(args: cli::AppArgs) -> anyhow::Result<()> {
                     } else {
                         print_json(&record);
                     }
+                }),
+                QueueCmd::Snapshot { path } => with_db!(|q| {
+                    let snapshot = q.snapshot().await?;
+
+                    std::fs::write(&path, serde_json::to_string(&snapshot)?).with_context(
+                        || format!("unable to write to `{}`", path.to_string_lossy()),
+                    )?;
+
+                    print_json(&serde_json::json!({
+                        "queue": snapshot.queue.len(),
+                        "optimize": snapshot.optimize.values().map(Vec::len).sum::<usize>(),
+                    }));
+                }),
+                QueueCmd::Replay {
+                    path,
+                    recording,
+                    max_steps,
+                } => {
+                    let snapshot =
+                        serde_json::from_str(&read_to_string(&path).with_context(|| {
+                            format!("unable to read `{}`", path.to_string_lossy())
+                        })?)
+                        .with_context(|| format!("unable to parse `{}`", path.to_string_lossy()))?;
+
+                    let recording = Recording::read(&recording)?;
+
+                    let result = queue::replay(
+                        get_voyager_config()?,
+                        snapshot,
+                        recording,
+                        max_steps,
+                        |step| {
+                            print_json(&step);
+                        },
+                    )
+                    .await?;
+
+                    print_json(&result);
                 }
             }
         }
@@ -502,6 +611,7 @@ async fn do_main(args: cli::AppArgs) -> anyhow::Result<()> {
                         h.register::<IbcClassic>();
                         h.register::<IbcUnion>();
                     },
+                    ModuleResponses::Live,
                 )
                 .await?;
 
@@ -545,6 +655,7 @@ async fn do_main(args: cli::AppArgs) -> anyhow::Result<()> {
                         h.register::<IbcClassic>();
                         h.register::<IbcUnion>();
                     },
+                    ModuleResponses::Live,
                 )
                 .await?;
 
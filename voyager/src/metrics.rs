@@ -0,0 +1,127 @@
+use std::sync::LazyLock;
+
+use prometheus::{
+    register_gauge_vec, register_int_gauge, register_int_gauge_vec, GaugeVec, IntGauge, IntGaugeVec,
+};
+use voyager_message::module::SignerBalance;
+use voyager_vm::QueueDepth;
+
+pub static QUEUE_READY_ITEMS: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!(
+        "voyager_queue_ready_items",
+        "The amount of items in the queue that are ready to be processed.",
+    )
+    .unwrap()
+});
+
+pub static QUEUE_RETRYING_ITEMS: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!(
+        "voyager_queue_retrying_items",
+        "The amount of items in the queue that are waiting to be retried.",
+    )
+    .unwrap()
+});
+
+pub static QUEUE_FAILED_ITEMS: LazyLock<IntGauge> = LazyLock::new(|| {
+    register_int_gauge!(
+        "voyager_queue_failed_items",
+        "The amount of items in the queue that have failed.",
+    )
+    .unwrap()
+});
+
+pub static QUEUE_OPTIMIZE_ITEMS: LazyLock<IntGaugeVec> = LazyLock::new(|| {
+    register_int_gauge_vec!(
+        "voyager_queue_optimize_items",
+        "The amount of items in the queue waiting to be optimized, by tag.",
+        &["tag"],
+    )
+    .unwrap()
+});
+
+pub static SIGNER_BALANCE: LazyLock<GaugeVec> = LazyLock::new(|| {
+    register_gauge_vec!(
+        "voyager_signer_balance",
+        "The balance of each key of the transaction plugins, in the smallest unit of the denom.",
+        &["plugin", "key_name", "address", "denom"],
+    )
+    .unwrap()
+});
+
+pub fn set_queue_depth(depth: &QueueDepth) {
+    let as_i64 = |n: u64| i64::try_from(n).unwrap_or(i64::MAX);
+
+    QUEUE_READY_ITEMS.set(as_i64(depth.ready));
+    QUEUE_RETRYING_ITEMS.set(as_i64(depth.retrying));
+    QUEUE_FAILED_ITEMS.set(as_i64(depth.failed));
+
+    // tags with no items are not returned, so clear the previous values first
+    QUEUE_OPTIMIZE_ITEMS.reset();
+    for (tag, count) in &depth.optimize {
+        QUEUE_OPTIMIZE_ITEMS
+            .with_label_values(&[tag])
+            .set(as_i64(*count));
+    }
+}
+
+pub fn set_signer_balances(plugin: &str, balances: &[SignerBalance]) {
+    for SignerBalance {
+        key_name,
+        address,
+        balance,
+        denom,
+    } in balances
+    {
+        SIGNER_BALANCE
+            .with_label_values(&[plugin, key_name, address, denom])
+            .set(*balance as f64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use prometheus::core::Collector;
+
+    use super::*;
+
+    #[test]
+    fn queue_depth_is_published() {
+        let depth = |optimize: &[(&str, u64)]| QueueDepth {
+            ready: 3,
+            retrying: 1,
+            optimize: optimize
+                .iter()
+                .map(|(tag, count)| ((*tag).to_owned(), *count))
+                .collect::<BTreeMap<_, _>>(),
+            failed: u64::MAX,
+        };
+
+        set_queue_depth(&depth(&[("a", 2), ("b", 5)]));
+
+        assert_eq!(QUEUE_READY_ITEMS.get(), 3);
+        assert_eq!(QUEUE_RETRYING_ITEMS.get(), 1);
+        // saturated instead of wrapping around
+        assert_eq!(QUEUE_FAILED_ITEMS.get(), i64::MAX);
+        assert_eq!(QUEUE_OPTIMIZE_ITEMS.with_label_values(&["a"]).get(), 2);
+        assert_eq!(QUEUE_OPTIMIZE_ITEMS.with_label_values(&["b"]).get(), 5);
+
+        // tags that no longer have any items are cleared
+        set_queue_depth(&depth(&[("b", 1)]));
+
+        let tags = QUEUE_OPTIMIZE_ITEMS
+            .collect()
+            .iter()
+            .flat_map(|family| family.get_metric())
+            .map(|metric| {
+                (
+                    metric.get_label()[0].get_value().to_owned(),
+                    metric.get_gauge().get_value(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(tags, [("b".to_owned(), 1.0)]);
+    }
+}
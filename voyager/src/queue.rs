@@ -1,27 +1,52 @@
 #![allow(clippy::type_complexity)]
 
-use std::{fmt::Debug, net::SocketAddr, panic::AssertUnwindSafe};
+use std::{fmt::Debug, net::SocketAddr, panic::AssertUnwindSafe, sync::Arc, time::Duration};
 
-use anyhow::{bail, Context as _};
+use anyhow::{anyhow, bail, Context as _};
 use futures::{future::BoxFuture, stream::FuturesUnordered, Future, FutureExt, StreamExt};
 use ibc_classic_spec::IbcClassic;
 use ibc_union_spec::IbcUnion;
+use jsonrpsee::{
+    core::{async_trait, RpcResult},
+    proc_macros::rpc,
+    types::{error::METHOD_NOT_FOUND_CODE, ErrorObject},
+};
 use pg_queue::{PgQueue, PgQueueConfig};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use sqlite_queue::{SqliteQueue, SqliteQueueConfig};
 use tracing::{debug, error, info, info_span, trace, trace_span};
 use tracing_futures::Instrument;
 use unionlabs::ErrorReporter;
 use voyager_message::{
-    context::Context, filter::JaqInterestFilter, into_value, module::PluginInfo,
-    pass::PluginOptPass, rpc::VoyagerRpcServer, VoyagerMessage,
+    context::{
+        recording::{ModuleResponses, Recorder, Recording},
+        Context,
+    },
+    data::Data,
+    filter::JaqInterestFilter,
+    into_value,
+    module::{PluginInfo, SignerClient},
+    pass::PluginOptPass,
+    rpc::VoyagerRpcServer,
+    VoyagerMessage,
 };
 use voyager_vm::{
-    engine::Engine, in_memory::InMemoryQueue, pass::Pass, BoxDynError, Captures, EnqueueResult,
-    ItemId, Op, Queue,
+    engine::{Engine, RetryPolicy},
+    in_memory::InMemoryQueue,
+    pass::Pass,
+    BoxDynError, Captures, EnqueueResult, ItemId, ItemRecord, ItemTree, Op, ProcessResult, Queue,
+    QueueDepth, QueueSnapshot,
 };
 
-use crate::{api, config::Config};
+use crate::{api, config::Config, metrics};
+
+/// How often the depth of the queue is published to the metrics.
+const QUEUE_DEPTH_INTERVAL: Duration = Duration::from_secs(10);
+
+/// How often the balances of the keys of the transaction plugins are fetched. This also takes any
+/// keys below the configured minimum balance of their keyring out of rotation.
+const SIGNER_BALANCES_INTERVAL: Duration = Duration::from_secs(60);
 
 #[derive(Debug)]
 pub struct Voyager {
@@ -32,6 +57,7 @@ pub struct Voyager {
     rpc_laddr: SocketAddr,
     queue: QueueImpl,
     optimizer_delay_milliseconds: u64,
+    retry_policy: RetryPolicy,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
@@ -39,12 +65,14 @@ pub struct Voyager {
 pub enum QueueConfig {
     InMemory,
     PgQueue(PgQueueConfig),
+    SqliteQueue(SqliteQueueConfig),
 }
 
 #[derive(Debug, Clone)]
 pub enum QueueImpl {
     InMemory(InMemoryQueue<VoyagerMessage>),
     PgQueue(PgQueue<VoyagerMessage>),
+    SqliteQueue(SqliteQueue<VoyagerMessage>),
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -52,6 +80,94 @@ pub enum QueueImpl {
 pub enum AnyQueueError {
     InMemory(std::convert::Infallible),
     PgQueue(sqlx::Error),
+    SqliteQueue(sqlx::Error),
+}
+
+/// Methods for inspecting the queue of a running voyager instance, served alongside the voyager
+/// RPC.
+#[rpc(client, server, namespace = "queue")]
+pub trait QueueRpc {
+    /// Returns the ancestors and descendants of the item with the specified id, up to `max_depth`
+    /// levels deep in either direction.
+    #[method(name = "lineage")]
+    async fn lineage(&self, id: ItemId, max_depth: u32) -> RpcResult<ItemLineage>;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ItemLineage {
+    /// The item and all of the items it was produced from. This is `None` if the item does not
+    /// exist.
+    pub ancestors: Option<ItemTree<VoyagerMessage>>,
+    /// The item and all of the items that were produced from it. This is `None` if the item does
+    /// not exist.
+    pub descendants: Option<ItemTree<VoyagerMessage>>,
+}
+
+impl QueueImpl {
+    pub async fn depth(&self) -> Result<QueueDepth, AnyQueueError> {
+        match self {
+            QueueImpl::InMemory(queue) => Ok(queue.depth()),
+            QueueImpl::PgQueue(queue) => queue.depth().await.map_err(AnyQueueError::PgQueue),
+            QueueImpl::SqliteQueue(queue) => {
+                queue.depth().await.map_err(AnyQueueError::SqliteQueue)
+            }
+        }
+    }
+
+    pub async fn ancestors(
+        &self,
+        id: ItemId,
+        max_depth: u32,
+    ) -> Result<Vec<ItemRecord<VoyagerMessage>>, AnyQueueError> {
+        match self {
+            QueueImpl::InMemory(queue) => Ok(queue.ancestors(id, max_depth)),
+            QueueImpl::PgQueue(queue) => queue
+                .query_ancestors(id.raw(), max_depth.try_into().unwrap_or(i32::MAX))
+                .await
+                .map_err(AnyQueueError::PgQueue),
+            QueueImpl::SqliteQueue(queue) => queue
+                .query_ancestors(id.raw(), max_depth.try_into().unwrap_or(i32::MAX))
+                .await
+                .map_err(AnyQueueError::SqliteQueue),
+        }
+    }
+
+    pub async fn descendants(
+        &self,
+        id: ItemId,
+        max_depth: u32,
+    ) -> Result<Vec<ItemRecord<VoyagerMessage>>, AnyQueueError> {
+        match self {
+            QueueImpl::InMemory(queue) => Ok(queue.descendants(id, max_depth)),
+            QueueImpl::PgQueue(queue) => queue
+                .query_descendants(id.raw(), max_depth.try_into().unwrap_or(i32::MAX))
+                .await
+                .map_err(AnyQueueError::PgQueue),
+            QueueImpl::SqliteQueue(queue) => queue
+                .query_descendants(id.raw(), max_depth.try_into().unwrap_or(i32::MAX))
+                .await
+                .map_err(AnyQueueError::SqliteQueue),
+        }
+    }
+}
+
+#[async_trait]
+impl QueueRpcServer for QueueImpl {
+    async fn lineage(&self, id: ItemId, max_depth: u32) -> RpcResult<ItemLineage> {
+        let err = |e: AnyQueueError| {
+            ErrorObject::owned(
+                -1,
+                format!("error querying lineage: {}", ErrorReporter(e)),
+                None::<()>,
+            )
+        };
+
+        Ok(ItemLineage {
+            ancestors: ItemTree::ancestors(self.ancestors(id, max_depth).await.map_err(err)?),
+            descendants: ItemTree::descendants(self.descendants(id, max_depth).await.map_err(err)?),
+        })
+    }
 }
 
 impl Queue<VoyagerMessage> for QueueImpl {
@@ -69,6 +185,11 @@ impl Queue<VoyagerMessage> for QueueImpl {
                 QueueConfig::PgQueue(cfg) => {
                     Self::PgQueue(PgQueue::new(cfg).await.map_err(AnyQueueError::PgQueue)?)
                 }
+                QueueConfig::SqliteQueue(cfg) => Self::SqliteQueue(
+                    SqliteQueue::new(cfg)
+                        .await
+                        .map_err(AnyQueueError::SqliteQueue)?,
+                ),
             })
         }
     }
@@ -88,6 +209,10 @@ impl Queue<VoyagerMessage> for QueueImpl {
                     .enqueue(item, filter)
                     .await
                     .map_err(AnyQueueError::PgQueue)?,
+                QueueImpl::SqliteQueue(queue) => queue
+                    .enqueue(item, filter)
+                    .await
+                    .map_err(AnyQueueError::SqliteQueue)?,
             };
 
             trace!("queued");
@@ -102,8 +227,8 @@ impl Queue<VoyagerMessage> for QueueImpl {
         f: F,
     ) -> impl Future<Output = Result<Option<R>, Self::Error>> + Send + Captures<'a>
     where
-        F: (FnOnce(Op<VoyagerMessage>, ItemId) -> Fut) + Send + Captures<'a>,
-        Fut: Future<Output = (R, Result<Vec<Op<VoyagerMessage>>, String>)> + Send + Captures<'a>,
+        F: (FnOnce(Op<VoyagerMessage>, ItemId, u32) -> Fut) + Send + Captures<'a>,
+        Fut: Future<Output = (R, ProcessResult<VoyagerMessage>)> + Send + Captures<'a>,
         R: Send + Sync + 'static,
     {
         async move {
@@ -116,6 +241,10 @@ impl Queue<VoyagerMessage> for QueueImpl {
                     .process(filter, f)
                     .await
                     .map_err(AnyQueueError::PgQueue),
+                QueueImpl::SqliteQueue(queue) => queue
+                    .process(filter, f)
+                    .await
+                    .map_err(AnyQueueError::SqliteQueue),
             };
 
             trace!("processed");
@@ -128,16 +257,21 @@ impl Queue<VoyagerMessage> for QueueImpl {
         &'a self,
         tag: &'a str,
         optimizer: &'a O,
+        filter: &'a JaqInterestFilter,
     ) -> Result<(), sqlx::Either<Self::Error, O::Error>> {
         match self {
             QueueImpl::InMemory(queue) => queue
-                .optimize(tag, optimizer)
+                .optimize(tag, optimizer, filter)
                 .await
                 .map_err(|e| e.map_left(AnyQueueError::InMemory)),
             QueueImpl::PgQueue(queue) => queue
-                .optimize(tag, optimizer)
+                .optimize(tag, optimizer, filter)
                 .await
                 .map_err(|e| e.map_left(AnyQueueError::PgQueue)),
+            QueueImpl::SqliteQueue(queue) => queue
+                .optimize(tag, optimizer, filter)
+                .await
+                .map_err(|e| e.map_left(AnyQueueError::SqliteQueue)),
         }
     }
 }
@@ -148,41 +282,29 @@ impl Voyager {
             .await
             .context("error initializing queue")?;
 
+        let module_responses = match &config.voyager.module_recording {
+            Some(path) => ModuleResponses::Record(Recorder::open(path)?),
+            None => ModuleResponses::Live,
+        };
+
         Ok(Self {
-            context: Context::new(
-                config.plugins,
-                config.modules,
-                config.equivalent_chain_ids,
-                |h| {
-                    h.register::<IbcClassic>();
-                    h.register::<IbcUnion>();
-                },
-            )
-            .await
-            .context("error initializing plugins")?,
+            context: make_context(config.clone(), module_responses).await?,
             num_workers: config.voyager.num_workers,
             rest_laddr: config.voyager.rest_laddr,
             rpc_laddr: config.voyager.rpc_laddr,
             queue,
             optimizer_delay_milliseconds: config.voyager.optimizer_delay_milliseconds,
+            retry_policy: config.voyager.retry_policy,
         })
     }
 
     #[allow(clippy::too_many_lines)]
     pub async fn run(self) -> anyhow::Result<()> {
-        let interest_filter = JaqInterestFilter::new(
-            self.context
-                .interest_filters()
-                .clone()
-                .into_iter()
-                .map(|(name, interest_filter)| PluginInfo {
-                    name,
-                    interest_filter,
-                })
-                .collect(),
-        )?;
+        let interest_filter = make_interest_filter(&self.context)?;
+
+        let signer_balances = api::SignerBalances::default();
 
-        let queue_rx = api::run(&self.rest_laddr);
+        let queue_rx = api::run(&self.rest_laddr, signer_balances.clone());
 
         {
             let mut tasks =
@@ -194,7 +316,9 @@ impl Voyager {
                         .build(&self.rpc_laddr)
                         .await?;
                     let addr = server.local_addr()?;
-                    let handle = server.start(self.context.rpc_server.clone().into_rpc());
+                    let mut rpc = self.context.rpc_server.clone().into_rpc();
+                    rpc.merge(self.queue.clone().into_rpc())?;
+                    let handle = server.start(rpc);
                     info!("rpc listening on {addr}");
                     handle
                         .stopped()
@@ -222,6 +346,57 @@ impl Voyager {
                 .catch_unwind(),
             ));
 
+            tasks.push(Box::pin(
+                AssertUnwindSafe(async {
+                    loop {
+                        match self.queue.depth().await {
+                            Ok(depth) => metrics::set_queue_depth(&depth),
+                            Err(error) => {
+                                error!(
+                                    error = %ErrorReporter(error),
+                                    "error querying queue depth"
+                                );
+                            }
+                        }
+
+                        tokio::time::sleep(QUEUE_DEPTH_INTERVAL).await;
+                    }
+                })
+                .catch_unwind(),
+            ));
+
+            tasks.push(Box::pin(
+                AssertUnwindSafe(async {
+                    loop {
+                        for (plugin_name, client) in self.context.plugins() {
+                            match SignerClient::balances(client).await {
+                                Ok(balances) => {
+                                    metrics::set_signer_balances(plugin_name, &balances);
+
+                                    signer_balances
+                                        .lock()
+                                        .expect("mutex is poisoned")
+                                        .insert(plugin_name.to_owned(), balances);
+                                }
+                                // only transaction plugins serve the signer methods
+                                Err(jsonrpsee::core::client::Error::Call(error))
+                                    if error.code() == METHOD_NOT_FOUND_CODE => {}
+                                Err(error) => {
+                                    error!(
+                                        %plugin_name,
+                                        error = %ErrorReporter(error),
+                                        "error fetching signer balances"
+                                    );
+                                }
+                            }
+                        }
+
+                        tokio::time::sleep(SIGNER_BALANCES_INTERVAL).await;
+                    }
+                })
+                .catch_unwind(),
+            ));
+
             info!("spawning {} workers", self.num_workers);
 
             for id in 0..self.num_workers {
@@ -229,26 +404,31 @@ impl Voyager {
 
                 tasks.push(Box::pin(
                     AssertUnwindSafe(
-                        Engine::new(&self.context, &self.queue, &interest_filter)
-                            .run()
-                            .for_each(|res| async move {
-                                match res {
-                                    Ok(data) => {
-                                        info!(
-                                            "received data outside of an aggregation: {}",
-                                            into_value(&data)
-                                        );
-                                    }
-                                    Err(error) => {
-                                        error!(
-                                            error = %ErrorReporter(&*error),
-                                            "error processing message"
-                                        );
-                                    }
+                        Engine::new(
+                            &self.context,
+                            &self.queue,
+                            &interest_filter,
+                            &self.retry_policy,
+                        )
+                        .run()
+                        .for_each(|res| async move {
+                            match res {
+                                Ok(data) => {
+                                    info!(
+                                        "received data outside of an aggregation: {}",
+                                        into_value(&data)
+                                    );
                                 }
-                            })
-                            .map(Ok)
-                            .instrument(trace_span!("engine task", %id)),
+                                Err(error) => {
+                                    error!(
+                                        error = %ErrorReporter(&*error),
+                                        "error processing message"
+                                    );
+                                }
+                            }
+                        })
+                        .map(Ok)
+                        .instrument(trace_span!("engine task", %id)),
                     )
                     .catch_unwind(),
                 ));
@@ -265,15 +445,17 @@ impl Voyager {
                             let pass = PluginOptPass::new(
                                 self.context
                                     .plugin_client_raw(&plugin_name)
-                                    .expect("plugin exists")
-                                    .client(),
+                                    .expect("plugin exists"),
                             );
 
                             loop {
                                 trace!("optimizing");
 
-                                let res =
-                                    self.queue.optimize(&plugin_name, &pass).await.map_err(|e| {
+                                let res = self
+                                    .queue
+                                    .optimize(&plugin_name, &pass, &interest_filter)
+                                    .await
+                                    .map_err(|e| {
                                         e.map_either::<_, _, BoxDynError, BoxDynError>(
                                             |x| Box::new(x),
                                             |x| Box::new(x),
@@ -336,3 +518,171 @@ impl Voyager {
         self.context.shutdown().await;
     }
 }
+
+async fn make_context(
+    config: Config,
+    module_responses: ModuleResponses,
+) -> anyhow::Result<Context> {
+    Context::new(
+        config.plugins,
+        config.modules,
+        config.equivalent_chain_ids,
+        |h| {
+            h.register::<IbcClassic>();
+            h.register::<IbcUnion>();
+        },
+        module_responses,
+    )
+    .await
+    .context("error initializing plugins")
+}
+
+fn make_interest_filter(context: &Context) -> anyhow::Result<JaqInterestFilter> {
+    JaqInterestFilter::new(
+        context
+            .interest_filters()
+            .clone()
+            .into_iter()
+            .map(|(name, interest_filter)| PluginInfo {
+                name,
+                interest_filter,
+            })
+            .collect(),
+    )
+}
+
+/// A single step of [`replay`].
+#[derive(Debug, Serialize)]
+pub struct ReplayStep {
+    pub step: u64,
+    /// The data produced by the processed op that was not used by any other op, if any.
+    pub data: Option<Data>,
+    /// The amount of ops that are ready to be processed after this step.
+    pub queue: usize,
+    /// The amount of ops that are waiting to be optimized after this step.
+    pub optimize: usize,
+}
+
+/// The state of the queue at the end of [`replay`].
+#[derive(Debug, Serialize)]
+pub struct ReplayResult {
+    pub steps: u64,
+    /// The ops that failed during the replay.
+    pub failed: Vec<ReplayFailedItem>,
+    /// The ops that were still pending when the replay ended.
+    pub remaining: QueueSnapshot<VoyagerMessage>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplayFailedItem {
+    pub id: ItemId,
+    pub item: Op<VoyagerMessage>,
+    pub error: String,
+}
+
+/// Replay `snapshot` against an in-memory queue, stepping through the ops one at a time.
+///
+/// Each step runs an optimization pass for all of the tags that have items waiting to be
+/// optimized (in order of their tag), and then processes the next op in the queue. Ops that fail
+/// with a retryable error are retried immediately, so the order in which the ops are processed
+/// only depends on the snapshot and the responses of the plugins and modules.
+///
+/// The requests to the modules configured in `config` are served from `recording` (see
+/// [`VoyagerConfig::module_recording`]), the modules themselves are not started. The plugins are
+/// run as configured, except for the plugins that handle transaction submission: these are not
+/// loaded, and any transaction submissions fail instead. Any ops waiting to be optimized by such a
+/// plugin are queued to be processed directly, failing the same way.
+///
+/// Stops once there are no more ops to process, or after `max_steps` steps.
+///
+/// [`VoyagerConfig::module_recording`]: crate::config::VoyagerConfig::module_recording
+pub async fn replay(
+    config: Config,
+    mut snapshot: QueueSnapshot<VoyagerMessage>,
+    recording: Recording,
+    max_steps: Option<u64>,
+    mut on_step: impl FnMut(ReplayStep),
+) -> anyhow::Result<ReplayResult> {
+    let retry_policy = RetryPolicy {
+        base_delay_milliseconds: 0,
+        max_delay_milliseconds: 0,
+        ..config.voyager.retry_policy.clone()
+    };
+
+    let context = make_context(config, ModuleResponses::Replay(Arc::new(recording))).await?;
+
+    let res = async {
+        // the plugins that handle transaction submission are not loaded when replaying, process the
+        // items waiting on them directly instead
+        let unhandled_tags = snapshot
+            .optimize
+            .keys()
+            .filter(|tag| !context.interest_filters().contains_key(*tag))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for tag in unhandled_tags {
+            let items = snapshot.optimize.remove(&tag).unwrap_or_default();
+
+            info!(%tag, "queueing {} items waiting on a plugin that is not loaded", items.len());
+
+            snapshot.queue.extend(items);
+        }
+
+        snapshot.queue.sort_by_key(|item| item.id);
+
+        let queue = InMemoryQueue::from_snapshot(snapshot).context("invalid snapshot")?;
+
+        let interest_filter = make_interest_filter(&context)?;
+
+        let engine = Engine::new(&context, &queue, &interest_filter, &retry_policy);
+
+        let mut step = 0;
+
+        while max_steps.is_none_or(|max_steps| step < max_steps) {
+            for tag in queue.snapshot().optimize.into_keys() {
+                let pass = PluginOptPass::new(
+                    context
+                        .plugin_client_raw(&tag)
+                        .with_context(|| format!("unable to optimize items tagged `{tag}`"))?,
+                );
+
+                queue
+                    .optimize(&tag, &pass, &interest_filter)
+                    .await
+                    .map_err(|e| e.either(|never| match never {}, anyhow::Error::from))
+                    .with_context(|| format!("error optimizing items tagged `{tag}`"))?;
+            }
+
+            let Some(data) = engine.step().await.map_err(|e| anyhow!(e))? else {
+                break;
+            };
+
+            step += 1;
+
+            let pending = queue.snapshot();
+
+            on_step(ReplayStep {
+                step,
+                data,
+                queue: pending.queue.len(),
+                optimize: pending.optimize.values().map(Vec::len).sum(),
+            });
+        }
+
+        Ok(ReplayResult {
+            steps: step,
+            failed: queue
+                .failed()
+                .into_iter()
+                .map(|(id, item, error)| ReplayFailedItem { id, item, error })
+                .collect(),
+            remaining: queue.snapshot(),
+        })
+    }
+    .await;
+
+    context.shutdown().await;
+
+    res
+}